@@ -0,0 +1,382 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+use anyhow::{
+    Context,
+    Result,
+    bail,
+};
+use graph_lib::prelude::{DefaultGraphIdType, Graph, GraphError};
+use crate::render::{self, BfsLine, RenderOptions};
+
+type AnalysisGraph = Graph<String, String>;
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 50;
+const DEFAULT_RANK_TOP: usize = 10;
+const STDIN_INPUT: &str = "-";
+
+/// Предварительная обработка графа сразу после чтения. Шаги выполняются в порядке полей:
+/// удаление вершин со степенью меньше `--min-degree K`, затем `--drop-isolated`, затем `--largest-component`
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Preprocess {
+    min_degree: Option<usize>,
+    drop_isolated: bool,
+    largest_component: bool,
+}
+
+impl Preprocess {
+    fn is_empty(&self) -> bool {
+        *self == Preprocess::default()
+    }
+
+    /// Применяет шаги к графу и возвращает строку о том, сколько вершин и рёбер удалено
+    fn apply(&self, graph: &mut AnalysisGraph) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let (vertex_count, edge_count) = (graph.vertex_count(), graph.edge_count());
+        if let Some(min_degree) = self.min_degree {
+            graph.drop_low_degree_vertices(min_degree);
+        }
+        if self.drop_isolated {
+            graph.drop_isolated_vertices();
+        }
+        if self.largest_component {
+            graph.keep_largest_component();
+        }
+        Some(format!(
+            "note: pre-processing removed {} vertices and {} edges",
+            vertex_count - graph.vertex_count(),
+            edge_count - graph.edge_count(),
+        ))
+    }
+}
+
+/// Аргументы команд анализа `<command> input.tgf [operands] [--option value] [pre-processing flags]`.
+/// Вместо имени файла можно указать `-`, тогда граф читается из стандартного ввода
+#[derive(Debug, PartialEq, Eq)]
+struct AnalysisArgs {
+    input: String,
+    operands: Vec<String>,
+    /// Параметры команд: `--start`, `--from`, `--to`, `--top`
+    options: Vec<(String, String)>,
+    preprocess: Preprocess,
+    /// `--no-color`: не выделять вывод цветом даже в терминале
+    no_color: bool,
+}
+
+impl AnalysisArgs {
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.iter()
+            .rev()
+            .find(|(option_name, _)| option_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+const OPTIONS_WITH_VALUE: [&str; 4] = ["--start", "--from", "--to", "--top"];
+
+/// Команды `bfs`, `stats`, `rank`, `components`, `shortest-path` (`path`), `convert`, `diff` и `validate`
+pub fn run(command: &str, args: &[String]) -> Result<()> {
+    let args = parse_args(args)?;
+    if command == "validate" {
+        return validate(&args.input);
+    }
+    let mut graph = Graph::deserialize(open_input(&args.input)?)
+        .with_context(|| format!("error loading graph from \"{}\"", args.input))?;
+    if let Some(note) = args.preprocess.apply(&mut graph) {
+        eprintln!("{}", note);
+    }
+    print!("{}", analyze(command, &graph, &args, &RenderOptions::detect(args.no_color))?);
+    Ok(())
+}
+
+/// Файл или, если имя файла `-`, стандартный ввод
+pub fn open_input(input: &str) -> Result<Box<dyn BufRead>> {
+    if input == STDIN_INPUT {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    let file = File::open(input)
+        .with_context(|| format!("error reading file \"{}\"", input))?;
+    Ok(Box::new(BufReader::new(file)))
+}
+
+fn parse_args(args: &[String]) -> Result<AnalysisArgs> {
+    let mut input = None;
+    let mut operands = Vec::new();
+    let mut options = Vec::new();
+    let mut preprocess = Preprocess::default();
+    let mut no_color = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--min-degree" => {
+                let min_degree = args.next().context("--min-degree requires a value")?;
+                preprocess.min_degree = Some(min_degree.parse()
+                    .with_context(|| format!("wrong --min-degree value \"{}\"", min_degree))?);
+            }
+            "--drop-isolated" => preprocess.drop_isolated = true,
+            "--largest-component" => preprocess.largest_component = true,
+            "--no-color" => no_color = true,
+            option if OPTIONS_WITH_VALUE.contains(&option) => {
+                let value = args.next().with_context(|| format!("{} requires a value", option))?;
+                options.push((option.to_owned(), value.clone()));
+            }
+            flag if flag.starts_with("--") => bail!("unknown flag \"{}\"", flag),
+            _ if input.is_none() => input = Some(arg.clone()),
+            _ => operands.push(arg.clone()),
+        }
+    }
+
+    Ok(AnalysisArgs {
+        input: input.context("input file not set, use - to read from standard input")?,
+        operands,
+        options,
+        preprocess,
+        no_color,
+    })
+}
+
+fn analyze(command: &str, graph: &AnalysisGraph, args: &AnalysisArgs, options: &RenderOptions) -> Result<String> {
+    match command {
+        "bfs" => render_bfs(graph, args, options),
+        "stats" => Ok(render::render_stats(&graph.stats(), options)),
+        "rank" => render_rank(graph, args, options),
+        "components" => Ok(render_components(graph, options)),
+        "shortest-path" | "path" => render_path(graph, args),
+        "convert" => convert(graph, args),
+        "diff" => diff(graph, args),
+        _ => bail!("unknown command \"{}\"", command),
+    }
+}
+
+fn parse_id(operand: &str) -> Result<DefaultGraphIdType> {
+    operand.parse()
+        .with_context(|| format!("wrong vertex id \"{}\"", operand))
+}
+
+/// Обход в ширину из `--start` (или первого операнда) либо, если начало не задано, из случайной вершины
+fn render_bfs(graph: &AnalysisGraph, args: &AnalysisArgs, options: &RenderOptions) -> Result<String> {
+    let start_id = match (args.option("--start"), args.operands.as_slice()) {
+        (Some(start_id), []) => Some(start_id),
+        (None, [start_id]) => Some(start_id.as_str()),
+        (None, []) => None,
+        _ => bail!("bfs takes at most one start vertex"),
+    };
+    let bfs_result = match start_id {
+        Some(start_id) => graph.bfs(parse_id(start_id)?)?,
+        None => graph.bfs_random_start()?,
+    };
+    let lines: Vec<_> = bfs_result.into_iter()
+        .map(|(id, value, neighbours)| BfsLine { id, value: value.cloned(), neighbours })
+        .collect();
+    Ok(render::render_bfs(&lines, options))
+}
+
+fn render_rank(graph: &AnalysisGraph, args: &AnalysisArgs, options: &RenderOptions) -> Result<String> {
+    let top = match args.option("--top") {
+        Some(top) => top.parse()
+            .with_context(|| format!("wrong --top value \"{}\"", top))?,
+        None => DEFAULT_RANK_TOP,
+    };
+    let mut ranks: Vec<_> = graph.pagerank(PAGERANK_DAMPING, PAGERANK_ITERATIONS)
+        .into_iter()
+        .collect();
+    ranks.sort_by(|(first_id, first_rank), (second_id, second_rank)| second_rank.total_cmp(first_rank)
+        .then(first_id.cmp(second_id)));
+    ranks.truncate(top);
+    Ok(render::render_rank(&ranks, options))
+}
+
+fn render_components(graph: &AnalysisGraph, options: &RenderOptions) -> String {
+    let components: Vec<_> = graph.connected_components()
+        .into_iter()
+        .map(|mut component| {
+            component.sort_unstable();
+            component
+        })
+        .collect();
+    render::render_components(&components, options)
+}
+
+/// Кратчайший путь `--from A --to B` (или двумя операндами `A B`)
+fn render_path(graph: &AnalysisGraph, args: &AnalysisArgs) -> Result<String> {
+    let (from_id, to_id) = match (args.option("--from"), args.option("--to"), args.operands.as_slice()) {
+        (Some(from_id), Some(to_id), []) => (from_id, to_id),
+        (None, None, [from_id, to_id]) => (from_id.as_str(), to_id.as_str()),
+        _ => bail!("shortest-path requires --from and --to vertices"),
+    };
+    match graph.shortest_path(parse_id(from_id)?, parse_id(to_id)?)? {
+        Some(path) => {
+            let ids: Vec<_> = path.iter().map(ToString::to_string).collect();
+            Ok(format!("{}\n", ids.join(" ")))
+        }
+        None => Ok("no path\n".to_owned()),
+    }
+}
+
+fn convert(graph: &AnalysisGraph, args: &AnalysisArgs) -> Result<String> {
+    match args.option("--to") {
+        Some("dot") => Ok(graph.to_dot()),
+        Some("json") => Ok(format!("{}\n", graph.to_json())),
+        Some(format) => bail!("unknown output format \"{}\", expected dot or json", format),
+        None => bail!("output format not set, use --to dot or --to json"),
+    }
+}
+
+/// Изменения второго графа (единственный операнд) относительно первого. Предобработка применяется к обоим графам
+fn diff(graph: &AnalysisGraph, args: &AnalysisArgs) -> Result<String> {
+    let [other_input] = args.operands.as_slice() else {
+        bail!("diff requires the second graph file");
+    };
+    let mut other = Graph::deserialize(open_input(other_input)?)
+        .with_context(|| format!("error loading graph from \"{}\"", other_input))?;
+    args.preprocess.apply(&mut other);
+    render_diff(graph, &other)
+}
+
+fn render_diff(graph: &AnalysisGraph, other: &AnalysisGraph) -> Result<String> {
+    let changes = graph.diff(other)?;
+    if changes.is_empty() {
+        return Ok("no changes\n".to_owned());
+    }
+    Ok(changes.to_string())
+}
+
+/// Проверяет файл, печатая каждую ошибку с номером строки. Файл с ошибками - ошибка команды
+fn validate(input: &str) -> Result<()> {
+    let (graph, errors) = Graph::deserialize_diagnostics(open_input(input)?);
+    print!("{}", render_diagnostics(graph.as_ref(), &errors));
+    if !errors.is_empty() {
+        bail!("\"{}\" has {} errors", input, errors.len());
+    }
+    Ok(())
+}
+
+fn render_diagnostics(graph: Option<&AnalysisGraph>, errors: &[(usize, GraphError)]) -> String {
+    let mut rendered: String = errors.iter()
+        .map(|(line_no, error)| match error.hint() {
+            Some(hint) => format!("line {}: {} ({})\n", line_no, error, hint),
+            None => format!("line {}: {}\n", line_no, error),
+        })
+        .collect();
+    if let (Some(graph), true) = (graph, errors.is_empty()) {
+        rendered.push_str(&format!("ok: {} vertices, {} edges\n", graph.vertex_count(), graph.edge_count()));
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Треугольник с хвостом, отдельное ребро, вершина с петлёй и изолированная вершина
+    const COMPONENTS_TGF: &str = "1 a\n2 b\n3 c\n4 d\n5 e\n6 f\n7 g\n8 h\n#\n1 2\n2 3\n3 1\n3 4\n5 6\n7 7\n";
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn run_on_fixture(command: &str, flags: &[&str]) -> Result<(String, Option<String>)> {
+        let args = parse_args(&args(flags))?;
+        let mut graph = Graph::deserialize(COMPONENTS_TGF.as_bytes())?;
+        let note = args.preprocess.apply(&mut graph);
+        Ok((analyze(command, &graph, &args, &RenderOptions::plain())?, note))
+    }
+
+    fn output(command: &str, flags: &[&str]) -> Result<String> {
+        Ok(run_on_fixture(command, flags)?.0)
+    }
+
+    #[test]
+    fn parse_preprocess_flags() -> Result<()> {
+        let parsed = parse_args(&args(&["--largest-component", "-", "1", "--to", "4", "--min-degree", "2"]))?;
+        assert_eq!(parsed, AnalysisArgs {
+            input: "-".to_owned(),
+            operands: args(&["1"]),
+            options: vec![("--to".to_owned(), "4".to_owned())],
+            preprocess: Preprocess {
+                min_degree: Some(2),
+                drop_isolated: false,
+                largest_component: true,
+            },
+            no_color: false,
+        });
+        assert!(parse_args(&args(&["in.tgf", "--no-color"]))?.no_color);
+        assert!(parse_args(&args(&["in.tgf", "--min-degree", "two"])).is_err());
+        assert!(parse_args(&args(&["in.tgf", "--unknown"])).is_err());
+        assert!(parse_args(&args(&["in.tgf", "--from"])).is_err());
+        assert!(parse_args(&args(&[])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn stats_with_preprocessing() -> Result<()> {
+        assert_eq!(
+            run_on_fixture("stats", &["in.tgf"])?,
+            (
+                "vertices:          8\nedges:             6\nself-loops:        1\nisolated vertices: 1\ndegree:            min 0, max 3, average 1.38\n\
+                    components:        4\nlargest component: 4\n".to_owned(),
+                None,
+            ),
+        );
+        assert_eq!(
+            run_on_fixture("stats", &["in.tgf", "--drop-isolated"])?,
+            (
+                "vertices:          7\nedges:             6\nself-loops:        1\nisolated vertices: 0\ndegree:            min 1, max 3, average 1.57\n\
+                    components:        3\nlargest component: 4\n".to_owned(),
+                Some("note: pre-processing removed 1 vertices and 0 edges".to_owned()),
+            ),
+        );
+        assert_eq!(
+            run_on_fixture("stats", &["in.tgf", "--largest-component", "--min-degree", "2"])?,
+            (
+                "vertices:          3\nedges:             3\nself-loops:        0\nisolated vertices: 0\ndegree:            min 2, max 2, average 2.00\n\
+                    components:        1\nlargest component: 3\n".to_owned(),
+                Some("note: pre-processing removed 5 vertices and 3 edges".to_owned()),
+            ),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn other_commands() -> Result<()> {
+        assert_eq!(output("shortest-path", &["in.tgf", "--from", "1", "--to", "4"])?, "1 3 4\n");
+        assert_eq!(output("path", &["in.tgf", "1", "5"])?, "no path\n");
+        assert!(output("shortest-path", &["in.tgf", "--from", "1"]).is_err());
+        assert_eq!(output("components", &["in.tgf"])?.lines().next(), Some("4: 1 2 3 4"));
+        assert_eq!(output("rank", &["in.tgf", "--top", "1"])?.lines().count(), 1);
+        assert!(output("bfs", &["in.tgf", "--start", "1"])?.starts_with("1 a "));
+        assert_eq!(output("bfs", &["in.tgf", "5"])?, "5 e [6]\n6 f [5]\n");
+        assert!(output("convert", &["in.tgf", "--to", "dot"])?.starts_with("graph {\n"));
+        assert!(output("convert", &["in.tgf", "--to", "json"])?.starts_with("{\"directed\":false,"));
+        assert!(output("convert", &["in.tgf", "--to", "xml"]).is_err());
+        assert!(output("diff", &["in.tgf"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn diff_between_graphs() -> Result<()> {
+        let graph = Graph::deserialize(COMPONENTS_TGF.as_bytes())?;
+        let mut other = Graph::deserialize(COMPONENTS_TGF.as_bytes())?;
+        assert_eq!(render_diff(&graph, &other)?, "no changes\n");
+        other.delete_vertex(8)?;
+        other.add_edge(4, 5, Some("bridge".to_owned()))?;
+        assert_eq!(render_diff(&graph, &other)?, "- vertex 8 h\n+ edge 4 5 bridge\n");
+        Ok(())
+    }
+
+    #[test]
+    fn diagnostics_with_line_numbers() {
+        let (graph, errors) = Graph::deserialize_diagnostics("1 a\n2 b\n#\n1 2\n1 3\n".as_bytes());
+        assert_eq!(
+            render_diagnostics(graph.as_ref(), &errors),
+            "line 5: vertex id \"3\" not found in graph (declare vertex 3 before the '#' line)\n",
+        );
+        let (graph, errors) = Graph::deserialize_diagnostics(COMPONENTS_TGF.as_bytes());
+        assert_eq!(render_diagnostics(graph.as_ref(), &errors), "ok: 8 vertices, 6 edges\n");
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
+use anyhow::{
+    Context,
+    Result,
+    bail,
+};
+use graph_lib::prelude::Graph;
+
+type BenchGraph = Graph<(), ()>;
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BenchOp {
+    Bfs,
+    Components,
+    Pagerank,
+    Dijkstra,
+}
+
+const ALL_OPS: [BenchOp; 4] = [BenchOp::Bfs, BenchOp::Components, BenchOp::Pagerank, BenchOp::Dijkstra];
+
+impl BenchOp {
+    fn parse(name: &str) -> Result<BenchOp> {
+        ALL_OPS.into_iter()
+            .find(|op| op.name() == name)
+            .with_context(|| format!("unknown bench operation \"{}\", expected one of bfs, components, pagerank, dijkstra", name))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            BenchOp::Bfs => "bfs",
+            BenchOp::Components => "components",
+            BenchOp::Pagerank => "pagerank",
+            BenchOp::Dijkstra => "dijkstra",
+        }
+    }
+
+    fn run(self, graph: &BenchGraph, vertex_count: u32) -> Result<()> {
+        match self {
+            BenchOp::Bfs => {
+                graph.bfs(0)?;
+            }
+            BenchOp::Components => {
+                graph.connected_components();
+            }
+            BenchOp::Pagerank => {
+                graph.pagerank(PAGERANK_DAMPING, PAGERANK_ITERATIONS);
+            }
+            // A* без эвристики - алгоритм Дейкстры
+            BenchOp::Dijkstra => {
+                graph.astar(0, vertex_count - 1, |_| 1.0, |_| 0.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Аргументы команды `bench --vertices N --edges M --seed S [--ops bfs,components,pagerank,dijkstra] [--json]`
+#[derive(Debug, PartialEq)]
+struct BenchArgs {
+    vertices: u32,
+    edges: usize,
+    seed: u64,
+    ops: Vec<BenchOp>,
+    json: bool,
+}
+
+struct BenchReport {
+    args: BenchArgs,
+    memory_estimate: usize,
+    generate_duration: Duration,
+    durations: Vec<(BenchOp, Duration)>,
+}
+
+pub fn run(args: &[String]) -> Result<()> {
+    let report = bench(parse_args(args)?)?;
+    if report.args.json {
+        println!("{}", render_json(&report));
+    } else {
+        print!("{}", render_table(&report));
+    }
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<BenchArgs> {
+    let mut vertices = None;
+    let mut edges = None;
+    let mut seed = 1;
+    let mut ops = ALL_OPS.to_vec();
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().with_context(|| format!("{} requires a value", arg));
+        match arg.as_str() {
+            "--vertices" => vertices = Some(value()?.parse().context("--vertices must be a number")?),
+            "--edges" => edges = Some(value()?.parse().context("--edges must be a number")?),
+            "--seed" => seed = value()?.parse().context("--seed must be a number")?,
+            "--ops" => ops = value()?.split(',').map(BenchOp::parse).collect::<Result<_>>()?,
+            "--json" => json = true,
+            _ => bail!("unexpected argument \"{}\"", arg),
+        }
+    }
+
+    let vertices = vertices.context("--vertices not set")?;
+    if vertices == 0 {
+        bail!("--vertices must be positive");
+    }
+    Ok(BenchArgs {
+        vertices,
+        edges: edges.context("--edges not set")?,
+        seed,
+        ops,
+        json,
+    })
+}
+
+fn bench(args: BenchArgs) -> Result<BenchReport> {
+    let start = Instant::now();
+    let graph = BenchGraph::gnm_random(args.vertices, args.edges, args.seed)?;
+    let generate_duration = start.elapsed();
+
+    let mut durations = Vec::new();
+    for &op in &args.ops {
+        let start = Instant::now();
+        op.run(&graph, args.vertices)?;
+        durations.push((op, start.elapsed()));
+    }
+
+    Ok(BenchReport {
+        memory_estimate: graph.memory_estimate().total(),
+        args,
+        generate_duration,
+        durations,
+    })
+}
+
+fn ops_per_second(duration: Duration) -> f64 {
+    1.0 / duration.as_secs_f64().max(f64::MIN_POSITIVE)
+}
+
+fn render_table(report: &BenchReport) -> String {
+    let mut table = format!(
+        "graph: {} vertices, {} edges, seed {}, memory estimate {:.1} MiB, generated in {:.3} ms\n",
+        report.args.vertices,
+        report.args.edges,
+        report.args.seed,
+        report.memory_estimate as f64 / (1024.0 * 1024.0),
+        report.generate_duration.as_secs_f64() * 1000.0,
+    );
+    table.push_str(&format!("{:<12} {:>12} {:>14}\n", "operation", "time, ms", "ops per second"));
+    for (op, duration) in &report.durations {
+        table.push_str(&format!(
+            "{:<12} {:>12.3} {:>14.2}\n",
+            op.name(),
+            duration.as_secs_f64() * 1000.0,
+            ops_per_second(*duration),
+        ));
+    }
+    table
+}
+
+fn render_json(report: &BenchReport) -> String {
+    let mut json = String::new();
+    let _ = write!(
+        json,
+        "{{\"vertices\":{},\"edges\":{},\"seed\":{},\"memory_estimate_bytes\":{},\"generate_seconds\":{},\"results\":[",
+        report.args.vertices,
+        report.args.edges,
+        report.args.seed,
+        report.memory_estimate,
+        report.generate_duration.as_secs_f64(),
+    );
+    for (index, (op, duration)) in report.durations.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        let _ = write!(
+            json,
+            "{{\"op\":\"{}\",\"seconds\":{},\"ops_per_second\":{}}}",
+            op.name(),
+            duration.as_secs_f64(),
+            ops_per_second(*duration),
+        );
+    }
+    json.push_str("]}");
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// Значение числового поля `"name":` из первого объекта JSON после `from`
+    fn json_number(json: &str, from: usize, name: &str) -> Option<f64> {
+        let field = format!("\"{}\":", name);
+        let start = from + json[from..].find(&field)? + field.len();
+        let end = start + json[start..].find([',', '}'])?;
+        json[start..end].parse().ok()
+    }
+
+    #[test]
+    fn parse_bench_args() -> Result<()> {
+        let parsed = parse_args(&args(&["--vertices", "10", "--edges", "20", "--ops", "bfs,pagerank", "--json"]))?;
+        assert_eq!(parsed, BenchArgs { vertices: 10, edges: 20, seed: 1, ops: vec![BenchOp::Bfs, BenchOp::Pagerank], json: true });
+        assert!(parse_args(&args(&["--vertices", "10", "--edges", "20", "--ops", "sort"])).is_err());
+        assert!(parse_args(&args(&["--edges", "20"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn json_has_every_requested_op() -> Result<()> {
+        let report = bench(parse_args(&args(&["--vertices", "50", "--edges", "120", "--seed", "3", "--json"]))?)?;
+        let json = render_json(&report);
+        assert_eq!(json_number(&json, 0, "vertices"), Some(50.0));
+        assert!(json_number(&json, 0, "memory_estimate_bytes").is_some_and(|bytes| bytes > 0.0));
+        for op in ALL_OPS {
+            let position = json.find(&format!("\"op\":\"{}\"", op.name()))
+                .with_context(|| format!("{} missing in {}", op.name(), json))?;
+            assert!(json_number(&json, position, "seconds").is_some_and(|seconds| seconds > 0.0));
+            assert!(json_number(&json, position, "ops_per_second").is_some_and(|ops| ops > 0.0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn table_lists_requested_ops() -> Result<()> {
+        let report = bench(parse_args(&args(&["--vertices", "20", "--edges", "30", "--ops", "components,dijkstra"]))?)?;
+        let table = render_table(&report);
+        assert_eq!(table.lines().count(), 4);
+        assert!(table.contains("components") && table.contains("dijkstra") && !table.contains("pagerank"));
+        Ok(())
+    }
+}
@@ -1,33 +1,113 @@
 use std::{
-    fs::File,
-    io::BufReader,
     env,
+    process,
 };
-use anyhow::{
-    Context,
-    Result,
-};
-use graph_lib::Graph;
+use anyhow::anyhow;
+use graph_lib::errors::GraphError;
+
+mod analysis;
+mod bench;
+mod relabel;
+mod render;
+
+const USAGE: &str = "usage: graph-bin <command> <file.tgf|-> [options]
+commands:
+  bfs <file> [--start ID]
+  components <file>
+  shortest-path <file> --from A --to B
+  convert <file> --to dot|json
+  diff <file> <other-file>
+  validate <file>
+  stats <file>
+  rank <file> [--top N]
+  relabel-values <file> --dict mapping.csv [-o out.tgf] [--vertices|--edges|--both] [--report]
+  bench --vertices N --edges M [--seed S] [--ops bfs,components,pagerank,dijkstra] [--json]
+analysis commands accept --min-degree K, --drop-isolated, --largest-component and --no-color
+(colors are also disabled when output is not a terminal or NO_COLOR is set)";
 
 fn main() {
-    let file_name = env::args().nth(1).expect("filename with \"Trivial Graph Format\" not set");
-    graph_processing(&file_name).unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(error) = run(&args) {
+        eprint!("{}", render_error(&error));
+        process::exit(1);
+    }
 }
 
-fn graph_processing(file_name: &str) -> Result<()> {
-    let file = File::open(file_name)
-        .with_context(|| format!("error reading file \"{}\"", file_name))?;
-
-    let graph = Graph::deserialize(BufReader::new(file))?;
-    let bfs_result = graph.bfs_random_start()?;
-    for (id, value, neighbours) in bfs_result {
-        if let Some(value) = value {
-            println!("{} {} {:?}", id, value, neighbours);
+/// Выполняет команду из первого аргумента
+fn run(args: &[String]) -> anyhow::Result<()> {
+    match args.first().map(String::as_str) {
+        Some("relabel-values") => relabel::run(&args[1..]),
+        Some("bench") => bench::run(&args[1..]),
+        Some(command @ ("bfs" | "stats" | "rank" | "components" | "path" | "shortest-path" | "convert" | "diff" | "validate")) => {
+            analysis::run(command, &args[1..])
         }
-        else {
-            println!("{} {:?}", id, neighbours);
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            Ok(())
         }
+        Some(_) => Err(anyhow!("unknown command \"{}\"\n{}", args[0], USAGE)),
+        None => Err(anyhow!("command not set\n{}", USAGE)),
+    }
+}
+
+/// Ошибка со всей цепочкой причин и подсказкой, если причиной стала ошибка графа
+fn render_error(error: &anyhow::Error) -> String {
+    let mut rendered = format!("error: {}\n", error);
+    for cause in error.chain().skip(1) {
+        rendered.push_str(&format!("  caused by: {}\n", cause));
+    }
+    let hint = error.chain()
+        .find_map(|cause| cause.downcast_ref::<GraphError>())
+        .and_then(GraphError::hint);
+    if let Some(hint) = hint {
+        rendered.push_str(&format!("hint: {}\n", hint));
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        fs,
+        io,
     };
 
-    Ok(())
+    #[test]
+    fn diff_command_dispatched() -> anyhow::Result<()> {
+        let directory = env::temp_dir().join(format!("graph-bin-diff-{}", process::id()));
+        fs::create_dir_all(&directory)?;
+        let (first, second) = (directory.join("first.tgf"), directory.join("second.tgf"));
+        fs::write(&first, "1 a\n2 b\n#\n1 2\n")?;
+        fs::write(&second, "1 a\n2 b\n3 c\n#\n1 2\n")?;
+        let args = |command: &str| -> Vec<String> {
+            vec![command.to_owned(), first.display().to_string(), second.display().to_string()]
+        };
+        let diff_result = run(&args("diff"));
+        let unknown_result = run(&args("difference"));
+        fs::remove_dir_all(&directory)?;
+        diff_result?;
+        assert!(unknown_result.is_err_and(|error| error.to_string().starts_with("unknown command \"difference\"")));
+        Ok(())
+    }
+
+    #[test]
+    fn render_error_with_hint() {
+        let error = anyhow::Error::from(GraphError::VertexNotFound("9".to_owned()))
+            .context("error loading graph");
+        assert_eq!(
+            render_error(&error),
+            "error: error loading graph\n  caused by: vertex id \"9\" not found in graph\nhint: declare vertex 9 before the '#' line\n"
+        );
+    }
+
+    #[test]
+    fn render_error_without_hint() {
+        let error = anyhow::Error::from(io::Error::new(io::ErrorKind::NotFound, "no such file"))
+            .context("error reading file \"missing.tgf\"");
+        assert_eq!(
+            render_error(&error),
+            "error: error reading file \"missing.tgf\"\n  caused by: no such file\n"
+        );
+    }
 }
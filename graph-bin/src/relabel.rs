@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter},
+};
+use anyhow::{
+    Context,
+    Result,
+    bail,
+};
+use graph_lib::{
+    algo::{RewriteReport, RewriteTargets},
+    prelude::Graph,
+};
+
+const DICTIONARY_DELIMITER: char = ',';
+const DICTIONARY_QUOTE: char = '"';
+
+/// Аргументы команды `relabel-values input.tgf --dict mapping.csv [-o out.tgf] [--edges|--vertices|--both] [--report]`
+#[derive(Debug, PartialEq, Eq)]
+struct RelabelArgs {
+    input: String,
+    dictionary: String,
+    output: Option<String>,
+    targets: RewriteTargets,
+    report: bool,
+}
+
+pub fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args)?;
+
+    let dictionary_file = File::open(&args.dictionary)
+        .with_context(|| format!("error reading file \"{}\"", args.dictionary))?;
+    let dictionary = parse_dictionary(BufReader::new(dictionary_file))?;
+    let mut graph = Graph::deserialize(crate::analysis::open_input(&args.input)?)?;
+
+    let report = graph.rewrite_values(&dictionary, args.targets);
+
+    match &args.output {
+        Some(output) => {
+            let output_file = File::create(output)
+                .with_context(|| format!("error writing file \"{}\"", output))?;
+            graph.serialize(&mut BufWriter::new(output_file))?;
+        }
+        None => graph.serialize(&mut BufWriter::new(io::stdout()))?,
+    }
+    if args.report {
+        eprint!("{}", render_report(&report));
+    }
+
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<RelabelArgs> {
+    let mut input = None;
+    let mut dictionary = None;
+    let mut output = None;
+    let mut targets = RewriteTargets::Both;
+    let mut report = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dict" => dictionary = Some(args.next().context("--dict requires a file name")?.clone()),
+            "-o" | "--output" => output = Some(args.next().context("-o requires a file name")?.clone()),
+            "--vertices" => targets = RewriteTargets::Vertices,
+            "--edges" => targets = RewriteTargets::Edges,
+            "--both" => targets = RewriteTargets::Both,
+            "--report" => report = true,
+            flag if flag.starts_with('-') && flag != "-" => bail!("unknown flag \"{}\"", flag),
+            _ if input.is_none() => input = Some(arg.clone()),
+            _ => bail!("unexpected argument \"{}\"", arg),
+        }
+    }
+
+    Ok(RelabelArgs {
+        input: input.context("input file not set")?,
+        dictionary: dictionary.context("dictionary file not set, use --dict mapping.csv")?,
+        output,
+        targets,
+        report,
+    })
+}
+
+/// Словарь в формате CSV: `old,new` в каждой строке. Значения с запятой или кавычками заключаются в кавычки,
+/// кавычка внутри значения удваивается
+fn parse_dictionary<BR: BufRead>(reader: BR) -> Result<HashMap<String, String>> {
+    let mut dictionary = HashMap::new();
+    for (line_index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = parse_csv_line(&line)
+            .with_context(|| format!("wrong dictionary line {}: \"{}\"", line_index + 1, line))?;
+        match <[String; 2]>::try_from(fields) {
+            Ok([old_value, new_value]) => {
+                dictionary.insert(old_value, new_value);
+            }
+            Err(fields) => bail!("dictionary line {} must have 2 fields, found {}", line_index + 1, fields.len()),
+        }
+    }
+    Ok(dictionary)
+}
+
+fn parse_csv_line(line: &str) -> Result<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            DICTIONARY_QUOTE if quoted && chars.peek() == Some(&DICTIONARY_QUOTE) => {
+                chars.next();
+                field.push(DICTIONARY_QUOTE);
+            }
+            DICTIONARY_QUOTE if quoted => quoted = false,
+            DICTIONARY_QUOTE if field.is_empty() => quoted = true,
+            DICTIONARY_DELIMITER if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(char),
+        }
+    }
+    if quoted {
+        bail!("unterminated quote");
+    }
+    fields.push(field);
+
+    Ok(fields)
+}
+
+fn render_report(report: &RewriteReport) -> String {
+    let mut rendered = format!(
+        "rewritten {} vertex values and {} edge values\n",
+        report.rewritten_vertex_values,
+        report.rewritten_edge_values,
+    );
+    if !report.unmapped_values.is_empty() {
+        let unmapped_values: Vec<_> = report.unmapped_values.iter().map(String::as_str).collect();
+        rendered.push_str(&format!("values missing from dictionary: {}\n", unmapped_values.join(", ")));
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_relabel_args() -> Result<()> {
+        let parsed = parse_args(&args(&["in.tgf", "--dict", "map.csv", "-o", "out.tgf", "--vertices", "--report"]))?;
+        assert_eq!(parsed, RelabelArgs {
+            input: "in.tgf".to_owned(),
+            dictionary: "map.csv".to_owned(),
+            output: Some("out.tgf".to_owned()),
+            targets: RewriteTargets::Vertices,
+            report: true,
+        });
+        assert!(parse_args(&args(&["in.tgf"])).is_err());
+        assert!(parse_args(&args(&["in.tgf", "--dict", "map.csv", "--unknown"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_quoted_dictionary() -> Result<()> {
+        let csv = "January,Январь\n\"Happy, New Year!\",\"Праздник \"\"Новый год\"\"\"\n\n";
+        let dictionary = parse_dictionary(csv.as_bytes())?;
+        assert_eq!(dictionary.len(), 2);
+        assert_eq!(dictionary["January"], "Январь");
+        assert_eq!(dictionary["Happy, New Year!"], "Праздник \"Новый год\"");
+        assert!(parse_dictionary("a,b,c".as_bytes()).is_err());
+        assert!(parse_dictionary("\"a,b".as_bytes()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn relabel_with_quoted_values() -> Result<()> {
+        let tgf = "1 January\n2 May\n#\n1 2 Happy, New Year!\n";
+        let csv = "January,Январь\n\"Happy, New Year!\",Праздник\n";
+        let mut graph = Graph::deserialize(tgf.as_bytes())?;
+        let report = graph.rewrite_values(&parse_dictionary(csv.as_bytes())?, RewriteTargets::Both);
+        assert_eq!(report.rewritten_vertex_values, 1);
+        assert_eq!(report.rewritten_edge_values, 1);
+        assert_eq!(report.unmapped_values, BTreeSet::from(["May".to_owned()]));
+        assert_eq!(
+            render_report(&report),
+            "rewritten 1 vertex values and 1 edge values\nvalues missing from dictionary: May\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    borrow::Cow,
+    env,
+    fmt::Write,
+    io::{self, IsTerminal},
+};
+use graph_lib::{metrics::GraphStats, prelude::DefaultGraphIdType};
+
+/// Ширина терминала, если переменная окружения `COLUMNS` не задана
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+/// Столбец значений не сужается меньше этой ширины, даже если строка не поместится в терминал
+const MIN_VALUE_WIDTH: usize = 8;
+const ELLIPSIS: char = '…';
+
+/// Параметры вывода: ширина строки в символах и цвет (ANSI-последовательности)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub width: usize,
+    pub color: bool,
+}
+
+impl RenderOptions {
+    /// Вывод без цвета и без ограничения ширины, как при перенаправлении в файл или канал
+    pub fn plain() -> RenderOptions {
+        RenderOptions { width: usize::MAX, color: false }
+    }
+
+    /// Параметры для стандартного вывода. В терминале ширина берётся из `COLUMNS` (по умолчанию 80), а цвет
+    /// включён, если не задан `--no-color` и непустая переменная `NO_COLOR`. При выводе не в терминал
+    /// ширина не ограничена, а цвет выключен
+    pub fn detect(no_color: bool) -> RenderOptions {
+        if !io::stdout().is_terminal() {
+            return RenderOptions::plain();
+        }
+        let width = env::var("COLUMNS").ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(DEFAULT_TERMINAL_WIDTH);
+        let color = !no_color && env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty());
+        RenderOptions { width, color }
+    }
+
+    fn paint<'a>(&self, text: &'a str, style: Style) -> Cow<'a, str> {
+        if self.color {
+            Cow::Owned(format!("\x1b[{}m{}\x1b[0m", style.code(), text))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Style {
+    Id,
+    Value,
+    Number,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Id => "36",
+            Style::Value => "32",
+            Style::Number => "33",
+        }
+    }
+}
+
+/// Вершина, посещённая обходом: идентификатор, значение и соседи
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BfsLine {
+    pub id: DefaultGraphIdType,
+    pub value: Option<String>,
+    pub neighbours: Vec<DefaultGraphIdType>,
+}
+
+/// Строки обхода в столбцах: идентификаторы выровнены вправо, значения - влево и обрезаются многоточием,
+/// чтобы строка помещалась в ширину вывода
+pub fn render_bfs(lines: &[BfsLine], options: &RenderOptions) -> String {
+    let neighbours: Vec<_> = lines.iter().map(|line| format!("{:?}", line.neighbours)).collect();
+    let id_width = lines.iter().map(|line| line.id.to_string().len()).max().unwrap_or(0);
+    let neighbours_width = neighbours.iter().map(String::len).max().unwrap_or(0);
+    let longest_value = lines.iter()
+        .filter_map(|line| line.value.as_ref())
+        .map(|value| value.chars().count())
+        .max();
+    let value_width = longest_value.map(|longest_value| {
+        let available = options.width.saturating_sub(id_width + neighbours_width + 2);
+        longest_value.min(available.max(MIN_VALUE_WIDTH))
+    });
+
+    let mut rendered = String::new();
+    for (line, neighbours) in lines.iter().zip(neighbours) {
+        let id = format!("{:>width$}", line.id, width = id_width);
+        rendered.push_str(&options.paint(&id, Style::Id));
+        if let Some(value_width) = value_width {
+            let value = truncate(line.value.as_deref().unwrap_or_default(), value_width);
+            let padding = " ".repeat(value_width - value.chars().count());
+            write!(rendered, " {}{}", options.paint(&value, Style::Value), padding).expect("write to String");
+        }
+        writeln!(rendered, " {}", neighbours).expect("write to String");
+    }
+    rendered
+}
+
+/// Сводка по графу: названия выровнены по самому длинному, числа выделены цветом
+pub fn render_stats(stats: &GraphStats, options: &RenderOptions) -> String {
+    let number = |value: &dyn ToString| options.paint(&value.to_string(), Style::Number).into_owned();
+    let mut rows = vec![
+        ("vertices", number(&stats.vertices)),
+        ("edges", number(&stats.edges)),
+        ("self-loops", number(&stats.self_loops)),
+        ("isolated vertices", number(&stats.isolated_vertices)),
+        ("degree", format!(
+            "min {}, max {}, average {}",
+            number(&stats.min_degree),
+            number(&stats.max_degree),
+            number(&format!("{:.2}", stats.average_degree)),
+        )),
+        ("components", number(&stats.components)),
+        ("largest component", number(&stats.largest_component)),
+    ];
+    if let Some(acyclic) = stats.acyclic {
+        rows.push(("dag", if acyclic { "yes" } else { "no" }.to_owned()));
+    }
+
+    let label_width = rows.iter().map(|(label, _)| label.len() + 1).max().unwrap_or(0);
+    rows.into_iter()
+        .map(|(label, value)| format!("{:<width$} {}\n", format!("{}:", label), value, width = label_width))
+        .collect()
+}
+
+/// Вершины с наибольшим рангом: идентификаторы выровнены вправо
+pub fn render_rank(ranks: &[(DefaultGraphIdType, f64)], options: &RenderOptions) -> String {
+    let id_width = ranks.iter().map(|(id, _)| id.to_string().len()).max().unwrap_or(0);
+    ranks.iter()
+        .map(|(id, rank)| format!(
+            "{} {}\n",
+            options.paint(&format!("{:>width$}", id, width = id_width), Style::Id),
+            options.paint(&format!("{:.6}", rank), Style::Number),
+        ))
+        .collect()
+}
+
+/// Компоненты связности: размер, выровненный вправо, и вершины, обрезанные по ширине вывода
+pub fn render_components(components: &[Vec<DefaultGraphIdType>], options: &RenderOptions) -> String {
+    let size_width = components.iter().map(|component| component.len().to_string().len()).max().unwrap_or(0);
+    let ids_width = options.width.saturating_sub(size_width + 2);
+    components.iter()
+        .map(|component| {
+            let ids: Vec<_> = component.iter().map(ToString::to_string).collect();
+            format!(
+                "{}: {}\n",
+                options.paint(&format!("{:>width$}", component.len(), width = size_width), Style::Number),
+                truncate(&ids.join(" "), ids_width),
+            )
+        })
+        .collect()
+}
+
+/// Текст не длиннее `width` символов: лишние символы заменяются многоточием
+fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.chars().count() <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    if width > 0 {
+        truncated.push(ELLIPSIS);
+    }
+    Cow::Owned(truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bfs_lines() -> Vec<BfsLine> {
+        vec![
+            BfsLine { id: 7, value: Some("September".to_owned()), neighbours: vec![105, 6] },
+            BfsLine { id: 105, value: Some("the longest month name".to_owned()), neighbours: vec![7] },
+            BfsLine { id: 6, value: None, neighbours: vec![7] },
+        ]
+    }
+
+    #[test]
+    fn bfs_columns_aligned() {
+        assert_eq!(
+            render_bfs(&bfs_lines(), &RenderOptions::plain()),
+            "  7 September              [105, 6]\n105 the longest month name [7]\n  6                        [7]\n",
+        );
+    }
+
+    #[test]
+    fn values_truncated_at_narrow_width() {
+        let options = RenderOptions { width: 28, color: false };
+        assert_eq!(
+            render_bfs(&bfs_lines(), &options),
+            "  7 September       [105, 6]\n105 the longest mo… [7]\n  6                 [7]\n",
+        );
+        let narrow = RenderOptions { width: 10, color: false };
+        assert!(render_bfs(&bfs_lines(), &narrow).contains(" Septemb… "));
+        assert_eq!(render_components(&[vec![1, 2, 3, 10, 11]], &narrow), "5: 1 2 3 …\n");
+        assert_eq!(truncate("abc", 0), "");
+    }
+
+    #[test]
+    fn color_on_and_off() {
+        let ranks = [(3, 0.5), (12, 0.25)];
+        let colored = RenderOptions { width: 80, color: true };
+        assert_eq!(render_rank(&ranks, &RenderOptions::plain()), " 3 0.500000\n12 0.250000\n");
+        assert_eq!(
+            render_rank(&ranks, &colored),
+            "\x1b[36m 3\x1b[0m \x1b[33m0.500000\x1b[0m\n\x1b[36m12\x1b[0m \x1b[33m0.250000\x1b[0m\n",
+        );
+        let lines = [BfsLine { id: 1, value: Some("a".to_owned()), neighbours: vec![] }];
+        assert_eq!(render_bfs(&lines, &colored), "\x1b[36m1\x1b[0m \x1b[32ma\x1b[0m []\n");
+    }
+
+    #[test]
+    fn stats_labels_aligned() {
+        let stats = GraphStats {
+            vertices: 12,
+            edges: 3,
+            self_loops: 0,
+            isolated_vertices: 1,
+            min_degree: 0,
+            max_degree: 2,
+            average_degree: 0.5,
+            components: 10,
+            largest_component: 3,
+            acyclic: Some(true),
+        };
+        assert_eq!(
+            render_stats(&stats, &RenderOptions::plain()),
+            "vertices:          12\nedges:             3\nself-loops:        0\nisolated vertices: 1\n\
+                degree:            min 0, max 2, average 0.50\ncomponents:        10\nlargest component: 3\ndag:               yes\n",
+        );
+        assert!(render_stats(&stats, &RenderOptions { width: 80, color: true }).starts_with("vertices:          \x1b[33m12\x1b[0m\n"));
+    }
+}
@@ -0,0 +1,121 @@
+//! Анализ небольшой социальной сети от начала до конца: чтение TGF, сводка, компоненты связности,
+//! PageRank, кратчайший путь между людьми по именам и экспорт в DOT и JSON.
+//!
+//! `cargo run --example social [file.tgf]` - без аргумента используется встроенный набор данных `data/social.tgf`
+
+use std::{cmp::Reverse, env, fs::File, io::BufReader};
+use anyhow::{Context, Result};
+use graph_lib::{metrics::GraphStats, prelude::Graph};
+
+/// Друзья: две компании, связанные знакомством Дейва и Эрин, и отдельная семья
+const SOCIAL_TGF: &str = include_str!("data/social.tgf");
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 50;
+const TOP_RANKED: usize = 5;
+const RANK_PRECISION: f64 = 1e-9;
+const PATH_FROM: &str = "Alice";
+const PATH_TO: &str = "Ivan";
+
+type SocialGraph = Graph<String, String>;
+
+/// Результаты анализа, которые печатает пример и проверяют тесты
+#[derive(Debug)]
+struct AnalysisSummary {
+    stats: GraphStats,
+    /// Размеры компонент связности от большей к меньшей
+    component_sizes: Vec<usize>,
+    /// Имена с наибольшим PageRank, при равном ранге - по алфавиту
+    top_ranked: Vec<(String, f64)>,
+    /// Цепочка знакомств от `PATH_FROM` до `PATH_TO`
+    path: Option<Vec<String>>,
+    dot: String,
+    json: String,
+}
+
+fn name(graph: &SocialGraph, id: u32) -> String {
+    graph.vertex_value(id).cloned().unwrap_or_else(|| id.to_string())
+}
+
+fn run(graph: &SocialGraph) -> Result<AnalysisSummary> {
+    let component_sizes = graph.connected_components()
+        .iter()
+        .map(Vec::len)
+        .collect();
+
+    let mut ranks: Vec<_> = graph.pagerank(PAGERANK_DAMPING, PAGERANK_ITERATIONS)
+        .into_iter()
+        .map(|(id, rank)| (name(graph, id), rank))
+        .collect();
+    // Симметричные вершины получают ранги, различающиеся лишь ошибкой округления, поэтому сравниваются округлённые ранги
+    ranks.sort_by_key(|(name, rank)| (Reverse((rank / RANK_PRECISION).round() as i64), name.clone()));
+    ranks.truncate(TOP_RANKED);
+
+    let from_id = graph.id_of(PATH_FROM).with_context(|| format!("{} not found", PATH_FROM))?;
+    let to_id = graph.id_of(PATH_TO).with_context(|| format!("{} not found", PATH_TO))?;
+    let path = graph.shortest_path(from_id, to_id)?
+        .map(|path| path.into_iter().map(|id| name(graph, id)).collect());
+
+    Ok(AnalysisSummary {
+        stats: graph.stats(),
+        component_sizes,
+        top_ranked: ranks,
+        path,
+        dot: graph.to_dot(),
+        json: graph.to_json(),
+    })
+}
+
+fn load(path: Option<&str>) -> Result<SocialGraph> {
+    let graph = match path {
+        Some(path) => {
+            let file = File::open(path).with_context(|| format!("error reading file \"{}\"", path))?;
+            Graph::deserialize_with_value_ids(BufReader::new(file))?
+        }
+        None => Graph::deserialize_with_value_ids(SOCIAL_TGF.as_bytes())?,
+    };
+    Ok(graph)
+}
+
+fn main() -> Result<()> {
+    let path = env::args().nth(1);
+    let summary = run(&load(path.as_deref())?)?;
+
+    println!("{}", summary.stats);
+    println!("component sizes: {:?}", summary.component_sizes);
+    println!("\ntop {} by pagerank:", TOP_RANKED);
+    for (name, rank) in &summary.top_ranked {
+        println!("  {} {:.4}", name, rank);
+    }
+    match &summary.path {
+        Some(path) => println!("\n{} to {}: {}", PATH_FROM, PATH_TO, path.join(" -> ")),
+        None => println!("\n{} and {} are not connected", PATH_FROM, PATH_TO),
+    }
+    println!("\n{}", summary.dot);
+    println!("{}", summary.json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_dataset_summary() -> Result<()> {
+        let summary = run(&load(None)?)?;
+        let top_names: Vec<_> = summary.top_ranked.iter().map(|(name, _)| name.as_str()).collect();
+
+        assert_eq!(summary.stats.vertices, 12);
+        assert_eq!(summary.stats.edges, 14);
+        assert_eq!((summary.stats.min_degree, summary.stats.max_degree), (1, 3));
+        assert_eq!(summary.stats.isolated_vertices, 0);
+        assert_eq!(summary.component_sizes, vec![9, 3]);
+        assert_eq!(top_names, vec!["Mallory", "Frank", "Grace", "Erin", "Alice"]);
+        assert_eq!(summary.path, Some(vec!["Alice", "Dave", "Erin", "Frank", "Ivan"].into_iter().map(String::from).collect()));
+        assert!(summary.dot.starts_with("graph {\n"));
+        assert!(summary.dot.contains("climbing"));
+        assert!(summary.json.starts_with("{\"directed\":false,"));
+        assert!(summary.json.contains("\"value\":\"Mallory\""));
+        Ok(())
+    }
+}
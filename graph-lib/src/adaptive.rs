@@ -0,0 +1,259 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{BuildHasher, BuildHasherDefault},
+};
+use super::*;
+
+/// Число направлений рёбер вершины, начиная с которого у неё строится `TargetIndex`
+pub(crate) const DEFAULT_ADAPTIVE_THRESHOLD: usize = 1024;
+
+/// Порог по умолчанию для новых графов. В тестах задаётся переменной окружения `GRAPH_ADAPTIVE_THRESHOLD`,
+/// чтобы прогнать все тесты с индексом у каждой вершины (`1`) и без индексов (`18446744073709551615`)
+#[cfg(not(test))]
+pub(crate) fn default_adaptive_threshold() -> usize {
+    DEFAULT_ADAPTIVE_THRESHOLD
+}
+
+#[cfg(test)]
+pub(crate) fn default_adaptive_threshold() -> usize {
+    std::env::var("GRAPH_ADAPTIVE_THRESHOLD")
+        .ok()
+        .and_then(|threshold| threshold.parse().ok())
+        .unwrap_or(DEFAULT_ADAPTIVE_THRESHOLD)
+}
+
+/// Концы направлений рёбер вершины, отсортированные по хешу идентификатора: проверка наличия ребра
+/// двоичным поиском вместо просмотра всех направлений. Тип идентификатора не обязан быть упорядоченным,
+/// поэтому сортировка идёт по хешу, а вершины с одинаковым хешем лежат рядом.
+/// Параллельные рёбра мультиграфа дают повторяющиеся записи
+#[derive(Debug, Clone)]
+pub(crate) struct TargetIndex<ID> {
+    entries: Vec<(u64, ID)>,
+}
+
+fn target_hash<ID: Hash>(id: &ID) -> u64 {
+    BuildHasherDefault::<DefaultHasher>::default().hash_one(id)
+}
+
+impl<ID: GraphId> TargetIndex<ID> {
+    fn build<ET>(edge_directions: &[EdgeDirection<ET, ID>]) -> TargetIndex<ID> {
+        let mut entries: Vec<_> = edge_directions.iter()
+            .map(|edge_direction| (target_hash(&edge_direction.to_vertex_id), edge_direction.to_vertex_id.clone()))
+            .collect();
+        entries.sort_unstable_by_key(|(hash, _)| *hash);
+        TargetIndex { entries }
+    }
+
+    /// Записи с тем же хешем, что у `id`
+    fn hash_range(&self, id: &ID) -> (u64, std::ops::Range<usize>) {
+        let hash = target_hash(id);
+        let start = self.entries.partition_point(|(entry_hash, _)| *entry_hash < hash);
+        let end = self.entries.partition_point(|(entry_hash, _)| *entry_hash <= hash);
+        (hash, start..end)
+    }
+
+    fn contains(&self, id: &ID) -> bool {
+        let (_, range) = self.hash_range(id);
+        self.entries[range].iter().any(|(_, entry_id)| entry_id == id)
+    }
+
+    fn insert(&mut self, id: ID) {
+        let (hash, range) = self.hash_range(&id);
+        self.entries.insert(range.end, (hash, id));
+    }
+
+    fn remove_one(&mut self, id: &ID) {
+        let (_, range) = self.hash_range(id);
+        if let Some(position) = self.entries[range.clone()].iter().position(|(_, entry_id)| entry_id == id) {
+            self.entries.remove(range.start + position);
+        }
+    }
+
+    fn remove_all(&mut self, id: &ID) {
+        let (_, range) = self.hash_range(id);
+        let mut position = range.start;
+        for _ in range {
+            if self.entries[position].1 == *id {
+                self.entries.remove(position);
+            } else {
+                position += 1;
+            }
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Направления рёбер вершины меняются только через эти методы, чтобы индекс оставался согласованным с ними.
+/// Индекс строится, когда число направлений достигает `threshold`, и удаляется, когда оно становится меньше
+/// половины порога, чтобы вершина на границе порога не перестраивала индекс при каждом изменении
+impl<VT, ET, ID: GraphId> Vertex<VT, ET, ID> {
+    /// Есть ли у вершины направление ребра в `to_id`
+    pub(crate) fn has_edge_direction_to(&self, to_id: &ID) -> bool {
+        match &self.target_index {
+            Some(target_index) => target_index.contains(to_id),
+            None => self.edge_directions.iter().any(|edge_direction| edge_direction.to_vertex_id == *to_id),
+        }
+    }
+
+    pub(crate) fn push_edge_direction(&mut self, edge_direction: EdgeDirection<ET, ID>, threshold: usize) {
+        if let Some(target_index) = self.target_index.as_mut() {
+            target_index.insert(edge_direction.to_vertex_id.clone());
+        }
+        self.edge_directions.push(edge_direction);
+        if self.target_index.is_none() && self.edge_directions.len() >= threshold {
+            self.target_index = Some(TargetIndex::build(&self.edge_directions));
+        }
+    }
+
+    /// Удаляет все направления рёбер в `to_id`, возвращая удалённые
+    pub(crate) fn remove_edge_directions_to(&mut self, to_id: &ID, threshold: usize) -> Vec<EdgeDirection<ET, ID>> {
+        if !self.has_edge_direction_to(to_id) {
+            return Vec::new();
+        }
+        let removed;
+        (removed, self.edge_directions) = std::mem::take(&mut self.edge_directions)
+            .into_iter()
+            .partition(|edge_direction| edge_direction.to_vertex_id == *to_id);
+        if let Some(target_index) = self.target_index.as_mut() {
+            target_index.remove_all(to_id);
+        }
+        self.shrink_target_index(threshold);
+        removed
+    }
+
+    /// Удаляет первое направление ребра, для которого `predicate` вернул `true`
+    pub(crate) fn remove_edge_direction_where<P: FnMut(&EdgeDirection<ET, ID>) -> bool>(
+        &mut self,
+        predicate: P,
+        threshold: usize,
+    ) -> Option<EdgeDirection<ET, ID>> {
+        let position = self.edge_directions.iter().position(predicate)?;
+        let edge_direction = self.edge_directions.remove(position);
+        if let Some(target_index) = self.target_index.as_mut() {
+            target_index.remove_one(&edge_direction.to_vertex_id);
+        }
+        self.shrink_target_index(threshold);
+        Some(edge_direction)
+    }
+
+    /// Заново строит или удаляет индекс, например после замены идентификаторов вершин или порога
+    pub(crate) fn rebuild_target_index(&mut self, threshold: usize) {
+        self.target_index = (self.edge_directions.len() >= threshold)
+            .then(|| TargetIndex::build(&self.edge_directions));
+    }
+
+    fn shrink_target_index(&mut self, threshold: usize) {
+        if self.edge_directions.len() < threshold / 2 {
+            self.target_index = None;
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Задаёт число направлений рёбер вершины (исходящих и, в неориентированном графе, зеркальных),
+    /// начиная с которого наличие ребра из вершины проверяется двоичным поиском по отсортированному индексу
+    /// соседей, а не просмотром всех рёбер. Индекс вершины удаляется, когда число её рёбер становится
+    /// меньше половины порога. На результаты методов графа порог не влияет
+    pub fn set_adaptive_threshold(&mut self, threshold: usize) {
+        self.adaptive_threshold = threshold;
+        for vertex in self.vertices.values_mut() {
+            vertex.rebuild_target_index(threshold);
+        }
+    }
+
+    pub fn adaptive_threshold(&self) -> usize {
+        self.adaptive_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Хеш, одинаковый для всех идентификаторов, проверяет поиск среди записей с равным хешем
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct CollidingId(u32);
+
+    impl Hash for CollidingId {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            state.write_u8(0);
+        }
+    }
+
+    impl Display for CollidingId {
+        fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(formatter, "{}", self.0)
+        }
+    }
+
+    #[test]
+    fn hub_membership_matches_oracle() -> Result<()> {
+        const EDGE_COUNT: u32 = 50_000;
+        let mut graph = Graph::<(), ()>::new_multigraph(GraphType::Undirected);
+        graph.set_adaptive_threshold(64);
+        for id in 0..=EDGE_COUNT {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        let mut oracle = HashSet::new();
+        for id in 1..=EDGE_COUNT {
+            graph.add_edge(0, id, None)?;
+            oracle.insert(id);
+        }
+        graph.add_edge(0, 7, None)?;
+        graph.delete_edge_where(0, 7, |_| true)?;
+        for id in (1..=EDGE_COUNT).filter(|id| id % 97 == 0) {
+            graph.delete_edge(id, 0)?;
+            oracle.remove(&id);
+        }
+        for id in (1..=EDGE_COUNT).filter(|id| id % 89 == 0) {
+            graph.delete_vertex(id)?;
+            oracle.remove(&id);
+        }
+        let hub_indexed = graph.vertices[&0].target_index.as_ref().map(TargetIndex::len);
+        let mismatch = (0..=EDGE_COUNT + 1).find(|&id| graph.contains_edge_direction(&0, &id).unwrap_or(false) != oracle.contains(&id));
+
+        // Индекс удаляется, только когда рёбер становится меньше половины порога
+        let mut small = Graph::<(), ()>::new(GraphType::Directed);
+        small.set_adaptive_threshold(4);
+        for id in 0..=4 {
+            small.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..=4 {
+            small.add_edge(0, id, None)?;
+        }
+        small.delete_edge(0, 4)?;
+        small.delete_edge(0, 3)?;
+        let kept_at_half = small.vertices[&0].target_index.is_some();
+        small.delete_edge(0, 2)?;
+
+        let mut colliding = Graph::<(), (), CollidingId>::new(GraphType::Directed);
+        colliding.set_adaptive_threshold(1);
+        for id in 0..4 {
+            colliding.add_vertex(Vertex::new(CollidingId(id), None))?;
+        }
+        colliding.add_edge(CollidingId(0), CollidingId(1), None)?;
+        colliding.add_edge(CollidingId(0), CollidingId(3), None)?;
+        colliding.delete_edge(CollidingId(0), CollidingId(1))?;
+
+        if mismatch.is_none()
+            && hub_indexed == Some(oracle.len())
+            && graph.edge_count() == oracle.len()
+            && kept_at_half
+            && small.vertices[&0].target_index.is_none()
+            && small.contains_edge_direction(&0, &1)?
+            && colliding.contains_edge_direction(&CollidingId(0), &CollidingId(3))?
+            && !colliding.contains_edge_direction(&CollidingId(0), &CollidingId(1))?
+            && !colliding.contains_edge_direction(&CollidingId(0), &CollidingId(2))? {
+            Ok(())
+        } else {
+            bail!("hub membership differs from oracle at {:?}, index size {:?}", mismatch, hub_indexed)
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! Типы результатов и параметров алгоритмов: обходы, кратчайшие пути, остовные деревья, поиск по образцу,
+//! сопоставление и различия графов, планы удаления вершин и изменения значений
+
+pub use crate::{BfsResult, BfsVertexLinks, TakenVertex};
+pub use crate::align::{AlignmentReport, normalized_levenshtein};
+pub use crate::astar::AstarPath;
+pub use crate::bipartite::Bipartition;
+pub use crate::chains::CollapsedChains;
+pub use crate::coloring::Coloring;
+pub use crate::flow::{FlowAlgorithm, MinCut};
+pub use crate::distances::ShortestPaths;
+pub use crate::distribution::DegreeStats;
+pub use crate::changes::{ChangedEdge, ChangedVertex, EdgeEntry, GraphChanges, VertexEntry};
+pub use crate::incremental::{GraphDiff, TraversalCache};
+pub use crate::merge::MergePolicy;
+pub use crate::multi_source::NearestSource;
+pub use crate::neighbour::{BfsEdgesResult, Neighbour};
+pub use crate::pattern::{Pattern, PatternMatches, PatternVar};
+pub use crate::plan::MutationPlan;
+pub use crate::reachability::ReachabilityIndex;
+pub use crate::reachability_cache::ReachabilityCache;
+pub use crate::rewrite::{RewriteReport, RewriteTargets};
+pub use crate::scc::StronglyConnectedComponents;
+pub use crate::roles::{RoleViolation, VertexRole};
+pub use crate::spanning_tree::WeightedEdge;
+pub use crate::traversal::BfsIter;
+pub use crate::traversal_options::{EdgeFilter, NeighbourOrder, TraversalOptions, TraversalResult};
+pub use crate::weight::EdgeWeight;
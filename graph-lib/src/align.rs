@@ -0,0 +1,193 @@
+use super::*;
+
+/// Результат сопоставления вершин двух графов по значениям, см. `Graph::align_by_value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignmentReport<ID = DefaultGraphIdType> {
+    /// Сопоставленные вершины: идентификатор в первом графе, во втором и сходство значений
+    pub matches: Vec<(ID, ID, f64)>,
+    /// Несопоставленные вершины первого и второго графа, включая вершины без значения
+    pub unmatched: Vec<ID>,
+    pub unmatched_other: Vec<ID>,
+    /// Доля рёбер первого графа, концы которых сопоставлены с вершинами, смежными во втором графе.
+    /// Для графа без рёбер - 1.0
+    pub agreement: f64,
+}
+
+/// Сходство строк от 0.0 до 1.0: единица минус расстояние Левенштейна по символам, делённое на длину
+/// более длинной строки. Две пустые строки совпадают
+pub fn normalized_levenshtein(first: &str, second: &str) -> f64 {
+    let first: Vec<_> = first.chars().collect();
+    let second: Vec<_> = second.chars().collect();
+    let max_len = first.len().max(second.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+    // Одна строка матрицы расстояний: расстояния от префикса `first` до всех префиксов `second`
+    let mut distances: Vec<_> = (0..=second.len()).collect();
+    for (first_index, first_char) in first.iter().enumerate() {
+        let mut diagonal = distances[0];
+        distances[0] = first_index + 1;
+        for (second_index, second_char) in second.iter().enumerate() {
+            let substitution = diagonal + usize::from(first_char != second_char);
+            diagonal = distances[second_index + 1];
+            distances[second_index + 1] = substitution
+                .min(distances[second_index] + 1)
+                .min(diagonal + 1);
+        }
+    }
+    1.0 - distances[second.len()] as f64 / max_len as f64
+}
+
+impl<ET, ID: GraphId + Ord> Graph<String, ET, ID> {
+    /// Жадное сопоставление вершин с графом `other` по значениям: сначала вершины с равными значениями,
+    /// затем пары с наибольшим сходством `similarity` не меньше `threshold`. Каждая вершина входит не более
+    /// чем в одну пару, при равном сходстве предпочитаются меньшие идентификаторы.
+    /// Для сходства без внешних зависимостей подходит `normalized_levenshtein`
+    pub fn align_by_value<OET, F: Fn(&str, &str) -> f64>(
+        &self,
+        other: &Graph<String, OET, ID>,
+        similarity: F,
+        threshold: f64,
+    ) -> AlignmentReport<ID> {
+        let mut unmatched = valued_vertices(self);
+        let mut unmatched_other = valued_vertices(other);
+        let mut matches = Vec::new();
+
+        let mut other_by_value: HashMap<&str, VecDeque<&ID>> = HashMap::new();
+        for &(id, value) in &unmatched_other {
+            other_by_value.entry(value).or_default().push_back(id);
+        }
+        unmatched.retain(|&(id, value)| {
+            match other_by_value.get_mut(value).and_then(VecDeque::pop_front) {
+                Some(other_id) => {
+                    matches.push((id.clone(), other_id.clone(), 1.0));
+                    false
+                }
+                None => true,
+            }
+        });
+        let exactly_matched: HashSet<_> = matches.iter().map(|(_, other_id, _)| other_id).collect();
+        unmatched_other.retain(|(id, _)| !exactly_matched.contains(id));
+
+        let mut candidates = Vec::new();
+        for (index, &(_, value)) in unmatched.iter().enumerate() {
+            for (other_index, &(_, other_value)) in unmatched_other.iter().enumerate() {
+                let score = similarity(value, other_value);
+                if score >= threshold {
+                    candidates.push((score, index, other_index));
+                }
+            }
+        }
+        candidates.sort_by(|first, second| second.0.total_cmp(&first.0)
+            .then_with(|| (first.1, first.2).cmp(&(second.1, second.2))));
+        let mut used = vec![false; unmatched.len()];
+        let mut other_used = vec![false; unmatched_other.len()];
+        for (score, index, other_index) in candidates {
+            if !used[index] && !other_used[other_index] {
+                used[index] = true;
+                other_used[other_index] = true;
+                matches.push((unmatched[index].0.clone(), unmatched_other[other_index].0.clone(), score));
+            }
+        }
+
+        let mapping: HashMap<_, _> = matches.iter()
+            .map(|(id, other_id, _)| (id, other_id))
+            .collect();
+        let agreed_edges = self.logical_edges()
+            .filter(|(from_id, edge_direction)| {
+                match (mapping.get(from_id), mapping.get(&edge_direction.to_vertex_id)) {
+                    (Some(&other_from_id), Some(&other_to_id)) => other.has_edge(other_from_id.clone(), other_to_id.clone()),
+                    _ => false,
+                }
+            })
+            .count();
+        let agreement = if self.edge_count == 0 {
+            1.0
+        } else {
+            agreed_edges as f64 / self.edge_count as f64
+        };
+
+        let mut unmatched: Vec<_> = self.vertices.keys()
+            .filter(|&id| !mapping.contains_key(id))
+            .cloned()
+            .collect();
+        unmatched.sort_unstable();
+        let matched_other: HashSet<_> = mapping.into_values().collect();
+        let mut unmatched_other: Vec<_> = other.vertices.keys()
+            .filter(|id| !matched_other.contains(id))
+            .cloned()
+            .collect();
+        unmatched_other.sort_unstable();
+        matches.sort_by(|first, second| first.0.cmp(&second.0));
+
+        AlignmentReport { matches, unmatched, unmatched_other, agreement }
+    }
+}
+
+/// Вершины со значениями в порядке идентификаторов
+fn valued_vertices<ET, ID: GraphId + Ord>(graph: &Graph<String, ET, ID>) -> Vec<(&ID, &str)> {
+    let mut vertices: Vec<_> = graph.vertices.values()
+        .filter_map(|vertex| vertex.value.as_deref().map(|value| (&vertex.id, value)))
+        .collect();
+    vertices.sort_unstable_by(|first, second| first.0.cmp(second.0));
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const VENDOR_TGF: &str = "1 Moscow\n2 Saint Petersburg\n3 Kazan\n4 Novosibirsk\n#\n1 2\n1 3\n3 4";
+
+    #[test]
+    fn identical_graphs_align_perfectly() -> Result<()> {
+        let graph = Graph::deserialize(VENDOR_TGF.as_bytes())?;
+        let report = graph.align_by_value(&graph, normalized_levenshtein, 0.8);
+        if report.matches == vec![(1, 1, 1.0), (2, 2, 1.0), (3, 3, 1.0), (4, 4, 1.0)]
+            && report.unmatched.is_empty()
+            && report.unmatched_other.is_empty()
+            && report.agreement == 1.0 {
+            Ok(())
+        } else {
+            bail!("identical graphs misaligned: {:?}", report)
+        }
+    }
+
+    #[test]
+    fn renamed_vertex_matched_by_similarity() -> Result<()> {
+        let graph = Graph::deserialize(VENDOR_TGF.as_bytes())?;
+        let other = Graph::deserialize("10 Kazan\n20 Moskow\n30 St. Petersburg\n40 Novosibirsk\n50 Omsk\n#\n20 30\n10 20\n10 40".as_bytes())?;
+        let report = graph.align_by_value(&other, normalized_levenshtein, 0.8);
+        if report.matches == vec![(1, 20, 5.0 / 6.0), (3, 10, 1.0), (4, 40, 1.0)]
+            && report.unmatched == vec![2]
+            && report.unmatched_other == vec![30, 50]
+            && report.agreement == 2.0 / 3.0
+            && graph.align_by_value(&other, normalized_levenshtein, 0.5).unmatched.is_empty() {
+            Ok(())
+        } else {
+            bail!("renamed vertices misaligned: {:?}", report)
+        }
+    }
+
+    #[test]
+    fn missing_edge_lowers_agreement() -> Result<()> {
+        let graph = Graph::deserialize(VENDOR_TGF.as_bytes())?;
+        let mut other = graph.clone();
+        other.delete_edge(4, 3)?;
+        let report = graph.align_by_value(&other, normalized_levenshtein, 0.8);
+        let reverse_report = other.align_by_value(&graph, normalized_levenshtein, 0.8);
+        if report.matches.len() == 4
+            && report.agreement == 2.0 / 3.0
+            && reverse_report.agreement == 1.0
+            && normalized_levenshtein("", "") == 1.0
+            && normalized_levenshtein("kitten", "sitting") == 1.0 - 3.0 / 7.0 {
+            Ok(())
+        } else {
+            bail!("wrong agreement {}", report.agreement)
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+use super::*;
+
+/// Путь, найденный `astar`: суммарная стоимость и вершины от начальной до конечной включительно
+pub type AstarPath<ID = DefaultGraphIdType> = (f64, Vec<ID>);
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Поиск пути минимальной стоимости алгоритмом A*. `cost` - стоимость ребра по его значению,
+    /// `heuristic` - нижняя оценка стоимости пути от вершины до `to_id`. Рёбра без значения стоят 1.0
+    pub fn astar<C, H>(&self, from_id: ID, to_id: ID, cost: C, heuristic: H) -> Result<Option<AstarPath<ID>>, GraphError>
+    where
+        C: Fn(&ET) -> f64,
+        H: Fn(ID) -> f64,
+    {
+        self.astar_with_default_cost(from_id, to_id, DEFAULT_EDGE_COST, cost, heuristic)
+    }
+
+    /// То же, что `astar`, но рёбра без значения стоят `default_cost`
+    pub fn astar_with_default_cost<C, H>(
+        &self,
+        from_id: ID,
+        to_id: ID,
+        default_cost: f64,
+        cost: C,
+        heuristic: H,
+    ) -> Result<Option<AstarPath<ID>>, GraphError>
+    where
+        C: Fn(&ET) -> f64,
+        H: Fn(ID) -> f64,
+    {
+        if !self.contains_vertex(&from_id) {
+            return Err(GraphError::VertexNotFound(from_id.to_string()));
+        }
+        if !self.contains_vertex(&to_id) {
+            return Err(GraphError::VertexNotFound(to_id.to_string()));
+        }
+
+        let mut costs = HashMap::from([(&from_id, 0.0)]);
+        let mut parents: HashMap<&ID, &ID> = HashMap::new();
+        let mut queue = BinaryHeap::from([QueueEntry { estimate: heuristic(from_id.clone()), cost: 0.0, id: &from_id }]);
+
+        while let Some(QueueEntry { cost: current_cost, id, .. }) = queue.pop() {
+            if *id == to_id {
+                let mut path = vec![id.clone()];
+                while let Some(&parent_id) = parents.get(&path[path.len() - 1]) {
+                    path.push(parent_id.clone());
+                }
+                path.reverse();
+                return Ok(Some((current_cost, path)));
+            }
+            // Устаревшая запись: вершина уже извлечена с меньшей стоимостью
+            if costs.get(id).is_some_and(|&known_cost| known_cost < current_cost) {
+                continue;
+            }
+
+            for edge_direction in &self.vertices[id].edge_directions {
+                let edge_cost = edge_direction.value.as_ref().as_ref().map_or(default_cost, &cost);
+                let neighbour_cost = current_cost + edge_cost;
+                let neighbour_id = &edge_direction.to_vertex_id;
+                if costs.get(neighbour_id).is_none_or(|&known_cost| neighbour_cost < known_cost) {
+                    costs.insert(neighbour_id, neighbour_cost);
+                    parents.insert(neighbour_id, id);
+                    queue.push(QueueEntry {
+                        estimate: neighbour_cost + heuristic(neighbour_id.clone()),
+                        cost: neighbour_cost,
+                        id: neighbour_id,
+                    });
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Элемент очереди с приоритетом. Порядок обратный, чтобы `BinaryHeap` выдавал наименьшую оценку
+struct QueueEntry<'a, ID> {
+    estimate: f64,
+    cost: f64,
+    id: &'a ID,
+}
+
+impl<ID> PartialEq for QueueEntry<'_, ID> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<ID> Eq for QueueEntry<'_, ID> {}
+
+impl<ID> PartialOrd for QueueEntry<'_, ID> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<ID> Ord for QueueEntry<'_, ID> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+            // При равной оценке раньше извлекается вершина, дальше продвинувшаяся от начала
+            .then_with(|| self.cost.total_cmp(&other.cost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const ROWS: u32 = 20;
+    const COLS: u32 = 20;
+
+    /// Решётка, в которой значение вершины - её координаты, а стоимость ребра (не меньше 1.0) зависит от строки
+    fn weighted_grid() -> Result<Graph<(u32, u32), f64>> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                graph.add_vertex(Vertex::new(row * COLS + col, Some((row, col))))?;
+            }
+        }
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                let id = row * COLS + col;
+                let cost = 1.0 + f64::from(row % 3) / 10.0;
+                if col + 1 < COLS {
+                    graph.add_edge(id, id + 1, Some(cost))?;
+                }
+                if row + 1 < ROWS {
+                    graph.add_edge(id, id + COLS, Some(cost))?;
+                }
+            }
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn astar_expands_fewer_vertices_than_dijkstra() -> Result<()> {
+        let graph = weighted_grid()?;
+        let (from_id, to_id) = (2 * COLS + 2, 8 * COLS + 8);
+        let (to_row, to_col) = *graph.vertex_value(to_id).unwrap_or(&(0, 0));
+
+        // Эвристика вызывается для каждой вершины, добавленной в очередь, поэтому число вызовов
+        // показывает, сколько вершин раскрыл поиск
+        let astar_calls = Cell::new(0);
+        let manhattan = |id| {
+            astar_calls.set(astar_calls.get() + 1);
+            let (row, col) = *graph.vertex_value(id).unwrap_or(&(0, 0));
+            f64::from(to_row.abs_diff(row) + to_col.abs_diff(col))
+        };
+        let astar_path = graph.astar(from_id, to_id, |&cost| cost, manhattan)?;
+
+        let dijkstra_calls = Cell::new(0);
+        let zero = |_| {
+            dijkstra_calls.set(dijkstra_calls.get() + 1);
+            0.0
+        };
+        let dijkstra_path = graph.astar(from_id, to_id, |&cost| cost, zero)?;
+
+        match (astar_path, dijkstra_path) {
+            (Some((astar_cost, path)), Some((dijkstra_cost, _)))
+                if astar_cost == dijkstra_cost
+                    && path.first() == Some(&from_id)
+                    && path.last() == Some(&to_id)
+                    && astar_calls.get() < dijkstra_calls.get() => Ok(()),
+            result => bail!(
+                "A* expanded {} vertices, Dijkstra {}: {:?}",
+                astar_calls.get(),
+                dijkstra_calls.get(),
+                result,
+            ),
+        }
+    }
+
+    #[test]
+    fn astar_default_cost_and_unreachable() -> Result<()> {
+        let mut graph = Graph::<(), f64>::new(GraphType::Directed);
+        for id in 1..=4 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(2, 3, None)?;
+        graph.add_edge(1, 3, Some(5.0))?;
+
+        let default_path = graph.astar(1, 3, |&cost| cost, |_| 0.0)?;
+        let expensive_default_path = graph.astar_with_default_cost(1, 3, 3.0, |&cost| cost, |_| 0.0)?;
+        let unreachable = graph.astar(3, 1, |&cost| cost, |_| 0.0)?;
+        let missing = graph.astar(1, 9, |&cost| cost, |_| 0.0);
+        if default_path == Some((2.0, vec![1, 2, 3]))
+            && expensive_default_path == Some((5.0, vec![1, 3]))
+            && unreachable.is_none()
+            && graph.astar(4, 1, |&cost| cost, |_| 0.0)?.is_none()
+            && matches!(missing, Err(GraphError::VertexNotFound(id)) if id == "9") {
+            Ok(())
+        } else {
+            bail!("wrong A* result")
+        }
+    }
+}
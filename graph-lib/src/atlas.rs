@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use super::*;
+
+/// Рёбра графа клуба карате Закари (вершины нумеруются с 1)
+const KARATE_CLUB_EDGES: [(DefaultGraphIdType, DefaultGraphIdType); 78] = [
+    (2, 1), (3, 1), (3, 2), (4, 1), (4, 2), (4, 3), (5, 1), (6, 1), (7, 1), (7, 5),
+    (7, 6), (8, 1), (8, 2), (8, 3), (8, 4), (9, 1), (9, 3), (10, 3), (11, 1), (11, 5),
+    (11, 6), (12, 1), (13, 1), (13, 4), (14, 1), (14, 2), (14, 3), (14, 4), (17, 6), (17, 7),
+    (18, 1), (18, 2), (20, 1), (20, 2), (22, 1), (22, 2), (26, 24), (26, 25), (28, 3), (28, 24),
+    (28, 25), (29, 3), (30, 24), (30, 27), (31, 2), (31, 9), (32, 1), (32, 25), (32, 26), (32, 29),
+    (33, 3), (33, 9), (33, 15), (33, 16), (33, 19), (33, 21), (33, 23), (33, 24), (33, 30), (33, 31),
+    (33, 32), (34, 9), (34, 10), (34, 14), (34, 15), (34, 16), (34, 19), (34, 20), (34, 21), (34, 23),
+    (34, 24), (34, 27), (34, 28), (34, 29), (34, 30), (34, 31), (34, 32), (34, 33),
+];
+
+/// Идентификаторы вершин решётки по позициям (строка, столбец), см. `Graph::grid`
+pub type GridPositions = HashMap<(u32, u32), DefaultGraphIdType>;
+
+/// Вершины, оставшиеся после раскола клуба с инструктором (группа "0"), остальные ушли с администратором (группа "1")
+const KARATE_CLUB_INSTRUCTOR_FACTION: [DefaultGraphIdType; 17] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 11, 12, 13, 14, 17, 18, 20, 22];
+
+impl<VT, ET> Graph<VT, ET> {
+    /// Граф Петерсена: внешний цикл 0..5, внутренняя пентаграмма 5..10 и спицы между ними
+    pub fn petersen() -> Graph<VT, ET> {
+        let mut graph = Graph::with_vertices(0..10, GraphType::Undirected);
+        for i in 0..5 {
+            graph.add_atlas_edge(i, (i + 1) % 5);
+            graph.add_atlas_edge(i + 5, (i + 2) % 5 + 5);
+            graph.add_atlas_edge(i, i + 5);
+        }
+        graph
+    }
+
+    /// Полный граф на вершинах 0..n: рёбра между всеми парами различных вершин, в ориентированном графе - в обе стороны
+    pub fn complete(n: u32, r#type: GraphType) -> Graph<VT, ET> {
+        let mut graph = Graph::with_vertices(0..n, r#type);
+        for from_id in 0..n {
+            for to_id in 0..n {
+                let is_new_pair = match r#type {
+                    GraphType::Directed => from_id != to_id,
+                    GraphType::Undirected => from_id < to_id,
+                };
+                if is_new_pair {
+                    graph.add_atlas_edge(from_id, to_id);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Неориентированный путь 0 - 1 - ... - (n - 1)
+    pub fn path(n: u32) -> Graph<VT, ET> {
+        let mut graph = Graph::with_vertices(0..n, GraphType::Undirected);
+        for id in 1..n {
+            graph.add_atlas_edge(id - 1, id);
+        }
+        graph
+    }
+
+    /// Решётка rows x cols. Вершина (row, col) получает идентификатор row * cols + col.
+    /// Если идентификаторов вершин не хватает (rows * cols больше `u32::MAX`) - ошибка `LimitExceeded`
+    pub fn grid(rows: u32, cols: u32) -> Result<(Graph<VT, ET>, GridPositions), GraphError> {
+        let vertex_count = rows.checked_mul(cols).ok_or_else(too_many_vertices)?;
+        // Дальше идентификаторы и их суммы не превышают vertex_count
+        let mut graph = Graph::with_vertices(0..vertex_count, GraphType::Undirected);
+        let mut positions = HashMap::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                let id = row * cols + col;
+                positions.insert((row, col), id);
+                if col + 1 < cols {
+                    graph.add_atlas_edge(id, id + 1);
+                }
+                if row + 1 < rows {
+                    graph.add_atlas_edge(id, id + cols);
+                }
+            }
+        }
+        Ok((graph, positions))
+    }
+
+    /// Гиперкуб размерности d: вершины смежны, если их идентификаторы отличаются ровно одним битом.
+    /// При d от 32 идентификаторов вершин не хватает - ошибка `LimitExceeded`
+    pub fn hypercube(d: u32) -> Result<Graph<VT, ET>, GraphError> {
+        let vertex_count = 1u32.checked_shl(d).ok_or_else(too_many_vertices)?;
+        let mut graph = Graph::with_vertices(0..vertex_count, GraphType::Undirected);
+        for id in 0..vertex_count {
+            for bit in 0..d {
+                let neighbour_id = id ^ (1 << bit);
+                if id < neighbour_id {
+                    graph.add_atlas_edge(id, neighbour_id);
+                }
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Граф без рёбер с вершинами без значений, добавленными через `add_vertex`
+    pub(crate) fn with_vertices(ids: impl Iterator<Item = DefaultGraphIdType>, r#type: GraphType) -> Graph<VT, ET> {
+        let mut graph = Graph::new(r#type);
+        for id in ids {
+            graph.add_vertex(Vertex::new(id, None))
+                .expect("atlas graph vertex ids are distinct");
+        }
+        graph
+    }
+
+    fn add_atlas_edge(&mut self, from_id: DefaultGraphIdType, to_id: DefaultGraphIdType) {
+        self.add_edge(from_id, to_id, None)
+            .expect("atlas graph edges reference existing vertices");
+    }
+}
+
+/// Вершин у графа атласа больше, чем идентификаторов `DefaultGraphIdType`
+fn too_many_vertices() -> GraphError {
+    GraphError::LimitExceeded {
+        which: LimitKind::Vertices,
+        limit: DefaultGraphIdType::MAX as usize,
+        at_line: None,
+    }
+}
+
+impl<ET> Graph<String, ET> {
+    /// Граф клуба карате Закари: 34 вершины, 78 рёбер.
+    /// Значение вершины - группа ("0" или "1"), к которой вершина отошла после раскола клуба
+    pub fn karate_club() -> Graph<String, ET> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for id in 1..=34 {
+            let faction = if KARATE_CLUB_INSTRUCTOR_FACTION.contains(&id) { "0" } else { "1" };
+            graph.add_vertex(Vertex::new(id, Some(faction.to_owned())))
+                .expect("karate club vertex ids are distinct");
+        }
+        for (from_id, to_id) in KARATE_CLUB_EDGES {
+            graph.add_atlas_edge(from_id, to_id);
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn edge_count<VT, ET>(graph: &Graph<VT, ET>) -> usize {
+        graph.vertices.values()
+            .flat_map(Vertex::own_edge_directions)
+            .count()
+    }
+
+    fn degree<VT, ET>(graph: &Graph<VT, ET>, id: DefaultGraphIdType) -> usize {
+        graph.vertices[&id].edge_directions.len()
+    }
+
+    /// Длина кратчайшего цикла, найденная обходом в ширину из каждой вершины
+    fn girth<VT, ET>(graph: &Graph<VT, ET>) -> Option<usize> {
+        let mut girth = None;
+        for &start_id in graph.vertices.keys() {
+            let mut distances = HashMap::from([(start_id, 0)]);
+            let mut parents = HashMap::new();
+            let mut queue = VecDeque::from([start_id]);
+            while let Some(id) = queue.pop_front() {
+                for edge_direction in &graph.vertices[&id].edge_directions {
+                    let neighbour_id = edge_direction.to_vertex_id;
+                    match distances.get(&neighbour_id) {
+                        None => {
+                            distances.insert(neighbour_id, distances[&id] + 1);
+                            parents.insert(neighbour_id, id);
+                            queue.push_back(neighbour_id);
+                        }
+                        Some(&distance) if parents.get(&id) != Some(&neighbour_id) => {
+                            let cycle_length = distance + distances[&id] + 1;
+                            girth = Some(girth.map_or(cycle_length, |girth: usize| girth.min(cycle_length)));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        girth
+    }
+
+    #[test]
+    fn petersen() -> Result<()> {
+        let graph = Graph::<(), ()>::petersen();
+        if graph.vertices.len() == 10
+            && edge_count(&graph) == 15
+            && graph.vertices.keys().all(|&id| degree(&graph, id) == 3)
+            && girth(&graph) == Some(5) {
+            Ok(())
+        } else {
+            bail!("wrong petersen graph")
+        }
+    }
+
+    #[test]
+    fn karate_club() -> Result<()> {
+        let graph = Graph::<String, ()>::karate_club();
+        let faction_sizes = graph.vertices.values()
+            .fold((0, 0), |(first, second), vertex| match vertex.value.as_deref() {
+                Some("0") => (first + 1, second),
+                _ => (first, second + 1),
+            });
+        if graph.vertices.len() == 34
+            && edge_count(&graph) == 78
+            && degree(&graph, 1) == 16
+            && degree(&graph, 34) == 17
+            && faction_sizes == (17, 17) {
+            Ok(())
+        } else {
+            bail!("wrong karate club graph")
+        }
+    }
+
+    #[test]
+    fn complete_and_path() -> Result<()> {
+        let complete = Graph::<(), ()>::complete(5, GraphType::Undirected);
+        let complete_directed = Graph::<(), ()>::complete(5, GraphType::Directed);
+        let path = Graph::<(), ()>::path(4);
+        if edge_count(&complete) == 10
+            && complete.vertices.keys().all(|&id| degree(&complete, id) == 4)
+            && girth(&complete) == Some(3)
+            && complete_directed.edge_count() == 20
+            && complete_directed.degree(0)? == 8
+            && edge_count(&path) == 3
+            && girth(&path).is_none()
+            && path.shortest_path(0, 3)? == Some(vec![0, 1, 2, 3])
+            && Graph::<(), ()>::path(0).vertex_count() == 0 {
+            Ok(())
+        } else {
+            bail!("wrong complete graph or path")
+        }
+    }
+
+    #[test]
+    fn grid() -> Result<()> {
+        let (graph, positions) = Graph::<(), ()>::grid(3, 4)?;
+        let corner = positions[&(0, 0)];
+        let center = positions[&(1, 1)];
+        if graph.vertices.len() == 12
+            && edge_count(&graph) == 3 * 3 + 4 * 2
+            && positions.len() == 12
+            && degree(&graph, corner) == 2
+            && degree(&graph, center) == 4
+            && positions[&(2, 3)] == 11
+            && matches!(Graph::<(), ()>::grid(1 << 16, 1 << 16), Err(GraphError::LimitExceeded { which: LimitKind::Vertices, .. })) {
+            Ok(())
+        } else {
+            bail!("wrong grid graph")
+        }
+    }
+
+    #[test]
+    fn hypercube() -> Result<()> {
+        const DIMENSION: u32 = 4;
+        let graph = Graph::<(), ()>::hypercube(DIMENSION)?;
+        if graph.vertices.len() == 16
+            && edge_count(&graph) == 32
+            && graph.vertices.keys().all(|&id| degree(&graph, id) == DIMENSION as usize)
+            && girth(&graph) == Some(4)
+            && matches!(Graph::<(), ()>::hypercube(32), Err(GraphError::LimitExceeded { which: LimitKind::Vertices, .. })) {
+            Ok(())
+        } else {
+            bail!("wrong hypercube graph")
+        }
+    }
+}
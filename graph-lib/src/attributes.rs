@@ -0,0 +1,259 @@
+use std::{
+    mem,
+    sync::LazyLock,
+};
+use super::*;
+
+/// Атрибуты вершины или ребра: имя - значение
+pub type AttributeMap = HashMap<String, String>;
+
+static NO_ATTRIBUTES: LazyLock<AttributeMap> = LazyLock::new(HashMap::new);
+
+/// Именованные строковые атрибуты вершин и рёбер в дополнение к значениям.
+/// Атрибуты ребра неориентированного графа хранятся один раз под парой, с которой их впервые записали
+#[derive(Debug, Clone)]
+pub(crate) struct Attributes<ID> {
+    vertices: HashMap<ID, AttributeMap>,
+    edges: HashMap<(ID, ID), AttributeMap>,
+}
+
+impl<ID> Default for Attributes<ID> {
+    fn default() -> Attributes<ID> {
+        Attributes { vertices: HashMap::new(), edges: HashMap::new() }
+    }
+}
+
+impl<ID: GraphId> Attributes<ID> {
+    /// Удаляет атрибуты вершины и всех рёбер, в которых она участвует
+    pub(crate) fn remove_vertex(&mut self, vertex_id: &ID) {
+        self.vertices.remove(vertex_id);
+        if !self.edges.is_empty() {
+            self.edges.retain(|(from_id, to_id), _| from_id != vertex_id && to_id != vertex_id);
+        }
+    }
+
+    /// Атрибуты с заменёнными идентификаторами, см. `Graph::relabel`
+    pub(crate) fn relabel(&mut self, new_ids: &HashMap<ID, ID>) {
+        self.vertices = mem::take(&mut self.vertices)
+            .into_iter()
+            .map(|(id, attributes)| (new_ids[&id].clone(), attributes))
+            .collect();
+        self.edges = mem::take(&mut self.edges)
+            .into_iter()
+            .map(|((from_id, to_id), attributes)| ((new_ids[&from_id].clone(), new_ids[&to_id].clone()), attributes))
+            .collect();
+    }
+
+    /// Атрибуты вершин и рёбер, оставшихся в `graph`, для копии графа. Вызывается после добавления рёбер копии
+    pub(crate) fn retained_in<VT, ET>(&self, graph: &Graph<VT, ET, ID>) -> Attributes<ID> {
+        Attributes {
+            vertices: self.vertices.iter()
+                .filter(|(id, _)| graph.contains_vertex(id))
+                .map(|(id, attributes)| (id.clone(), attributes.clone()))
+                .collect(),
+            edges: self.edges.iter()
+                .filter(|((from_id, to_id), _)| graph.has_edge(from_id.clone(), to_id.clone()))
+                .map(|(ends, attributes)| (ends.clone(), attributes.clone()))
+                .collect(),
+        }
+    }
+
+    /// Извлекает атрибуты вершины, см. `Graph::merge_with`
+    pub(crate) fn take_vertex(&mut self, vertex_id: &ID) -> Option<AttributeMap> {
+        self.vertices.remove(vertex_id)
+    }
+
+    /// Извлекает атрибуты ребра, для неориентированного графа - записанные под парой в любом порядке концов
+    pub(crate) fn take_edge(&mut self, from_id: &ID, to_id: &ID, r#type: GraphType) -> Option<AttributeMap> {
+        self.edges.remove(&(from_id.clone(), to_id.clone())).or_else(|| match r#type {
+            GraphType::Undirected => self.edges.remove(&(to_id.clone(), from_id.clone())),
+            GraphType::Directed => None,
+        })
+    }
+
+    /// Атрибуты с обращёнными рёбрами, см. `Graph::transpose`
+    pub(crate) fn reversed(&self) -> Attributes<ID> {
+        Attributes {
+            vertices: self.vertices.clone(),
+            edges: self.edges.iter()
+                .map(|((from_id, to_id), attributes)| ((to_id.clone(), from_id.clone()), attributes.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Записывает атрибут вершины, возвращая прежнее значение. Атрибуты удаляются вместе с вершиной,
+    /// не входят в TGF и выводятся только в `to_dot` и `to_json`
+    pub fn set_vertex_attr(&mut self, vertex_id: ID, key: &str, value: String) -> Result<Option<String>, GraphError> {
+        if !self.contains_vertex(&vertex_id) {
+            return Err(GraphError::VertexNotFound(vertex_id.to_string()));
+        }
+        Ok(self.attributes.vertices.entry(vertex_id).or_default().insert(key.to_owned(), value))
+    }
+
+    pub fn vertex_attr(&self, vertex_id: ID, key: &str) -> Option<&str> {
+        self.vertex_attrs(vertex_id).get(key).map(String::as_str)
+    }
+
+    /// Все атрибуты вершины. У вершины без атрибутов и у отсутствующей вершины - пустой набор
+    pub fn vertex_attrs(&self, vertex_id: ID) -> &AttributeMap {
+        self.attributes.vertices.get(&vertex_id).unwrap_or(&NO_ATTRIBUTES)
+    }
+
+    /// Удаляет атрибут вершины, возвращая его значение
+    pub fn remove_vertex_attr(&mut self, vertex_id: ID, key: &str) -> Option<String> {
+        let attributes = self.attributes.vertices.get_mut(&vertex_id)?;
+        let value = attributes.remove(key);
+        if attributes.is_empty() {
+            self.attributes.vertices.remove(&vertex_id);
+        }
+        value
+    }
+
+    /// Записывает атрибут ребра, возвращая прежнее значение. У неориентированного графа атрибуты общие
+    /// для обоих направлений ребра, у мультиграфа - для всех параллельных рёбер между вершинами.
+    /// Атрибуты удаляются вместе с последним ребром между вершинами или с одной из вершин
+    pub fn set_edge_attr(&mut self, from_id: ID, to_id: ID, key: &str, value: String) -> Result<Option<String>, GraphError> {
+        if !self.contains_vertex(&to_id) {
+            return Err(GraphError::VertexNotFound(to_id.to_string()));
+        }
+        if !self.contains_edge_direction(&from_id, &to_id)? {
+            return Err(GraphError::EdgeNotFound(from_id.to_string(), to_id.to_string()));
+        }
+        let ends = self.edge_attrs_key(from_id, to_id);
+        Ok(self.attributes.edges.entry(ends).or_default().insert(key.to_owned(), value))
+    }
+
+    pub fn edge_attr(&self, from_id: ID, to_id: ID, key: &str) -> Option<&str> {
+        self.edge_attrs(from_id, to_id).get(key).map(String::as_str)
+    }
+
+    /// Все атрибуты ребра. У ребра без атрибутов и у отсутствующего ребра - пустой набор
+    pub fn edge_attrs(&self, from_id: ID, to_id: ID) -> &AttributeMap {
+        let ends = self.edge_attrs_key(from_id, to_id);
+        self.attributes.edges.get(&ends).unwrap_or(&NO_ATTRIBUTES)
+    }
+
+    /// Удаляет атрибут ребра, возвращая его значение
+    pub fn remove_edge_attr(&mut self, from_id: ID, to_id: ID, key: &str) -> Option<String> {
+        let ends = self.edge_attrs_key(from_id, to_id);
+        let attributes = self.attributes.edges.get_mut(&ends)?;
+        let value = attributes.remove(key);
+        if attributes.is_empty() {
+            self.attributes.edges.remove(&ends);
+        }
+        value
+    }
+
+    /// Дописывает атрибуты к атрибутам вершины, заменяя совпавшие
+    pub(crate) fn extend_vertex_attrs(&mut self, vertex_id: ID, attributes: Option<AttributeMap>) {
+        if let Some(attributes) = attributes {
+            self.attributes.vertices.entry(vertex_id).or_default().extend(attributes);
+        }
+    }
+
+    /// Дописывает атрибуты к атрибутам ребра, заменяя совпавшие
+    pub(crate) fn extend_edge_attrs(&mut self, from_id: ID, to_id: ID, attributes: Option<AttributeMap>) {
+        if let Some(attributes) = attributes {
+            let ends = self.edge_attrs_key(from_id, to_id);
+            self.attributes.edges.entry(ends).or_default().extend(attributes);
+        }
+    }
+
+    /// Удаляет атрибуты ребра после удаления последнего ребра между вершинами
+    pub(crate) fn remove_edge_attrs(&mut self, from_id: ID, to_id: ID) {
+        let ends = self.edge_attrs_key(from_id, to_id);
+        self.attributes.edges.remove(&ends);
+    }
+
+    /// Пара, под которой хранятся атрибуты ребра: для неориентированного графа - уже записанная
+    /// пара в любом порядке концов
+    fn edge_attrs_key(&self, from_id: ID, to_id: ID) -> (ID, ID) {
+        match self.r#type {
+            GraphType::Undirected if self.attributes.edges.contains_key(&(to_id.clone(), from_id.clone())) => (to_id, from_id),
+            _ => (from_id, to_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn path(graph_type: GraphType) -> Result<Graph<String, String>> {
+        let mut graph = Graph::new(graph_type);
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, Some(format!("v{}", id))))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(2, 3, Some("b".to_owned()))?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn undirected_edge_attributes_shared() -> Result<()> {
+        let mut graph = path(GraphType::Undirected)?;
+        graph.set_vertex_attr(1, "color", "red".to_owned())?;
+        let previous = graph.set_vertex_attr(1, "color", "blue".to_owned())?;
+        graph.set_edge_attr(2, 1, "weight", "5".to_owned())?;
+        graph.set_edge_attr(1, 2, "style", "dashed".to_owned())?;
+        if previous.as_deref() == Some("red")
+            && graph.vertex_attr(1, "color") == Some("blue")
+            && graph.vertex_attr(2, "color").is_none()
+            && graph.edge_attr(1, 2, "weight") == Some("5")
+            && graph.edge_attrs(2, 1).len() == 2
+            && graph.remove_edge_attr(1, 2, "weight").as_deref() == Some("5")
+            && graph.edge_attrs(2, 1).len() == 1
+            && graph.set_edge_attr(1, 3, "weight", "1".to_owned()) == Err(GraphError::EdgeNotFound("1".to_owned(), "3".to_owned()))
+            && graph.set_vertex_attr(4, "color", "red".to_owned()) == Err(GraphError::VertexNotFound("4".to_owned())) {
+            Ok(())
+        } else {
+            bail!("wrong attributes: {:?}", graph.edge_attrs(1, 2))
+        }
+    }
+
+    #[test]
+    fn deletion_removes_attributes() -> Result<()> {
+        let mut graph = path(GraphType::Directed)?;
+        graph.set_vertex_attr(2, "label", "middle".to_owned())?;
+        graph.set_edge_attr(1, 2, "weight", "1".to_owned())?;
+        graph.set_edge_attr(2, 3, "weight", "2".to_owned())?;
+        graph.delete_edge(2, 3)?;
+        let edge_cleared = graph.edge_attrs(2, 3).is_empty() && graph.edge_attr(1, 2, "weight") == Some("1");
+        graph.add_edge(2, 3, None)?;
+        graph.delete_vertex(2)?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, None)?;
+        if edge_cleared
+            && graph.edge_attrs(2, 3).is_empty()
+            && graph.vertex_attrs(2).is_empty()
+            && graph.edge_attrs(1, 2).is_empty() {
+            Ok(())
+        } else {
+            bail!("attributes survived deletion: {:?}", graph.edge_attrs(1, 2))
+        }
+    }
+
+    #[test]
+    fn parallel_edges_keep_attributes_until_last() -> Result<()> {
+        let mut graph = Graph::<(), u32>::new_multigraph(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, None))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, Some(1))?;
+        graph.add_edge(2, 1, Some(2))?;
+        graph.set_edge_attr(1, 2, "color", "green".to_owned())?;
+        graph.delete_edge_where(2, 1, |value| value == Some(&1))?;
+        let kept = graph.edge_attr(2, 1, "color") == Some("green");
+        graph.delete_edge_where(1, 2, |_| true)?;
+        if kept && graph.edge_attrs(1, 2).is_empty() {
+            Ok(())
+        } else {
+            bail!("attributes of parallel edges removed too early or kept too long")
+        }
+    }
+}
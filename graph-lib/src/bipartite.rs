@@ -0,0 +1,194 @@
+use super::*;
+
+/// Результат `Graph::bipartition`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bipartition<ID = DefaultGraphIdType> {
+    /// Доли графа по возрастанию идентификатора. Наименьшая вершина каждой компоненты связности - в первой доле
+    Parts(Vec<ID>, Vec<ID>),
+    /// Нечётный цикл, доказывающий, что графа нельзя разбить на две доли: вершины по порядку обхода,
+    /// последняя соединена с первой. Петля - цикл из одной вершины
+    OddCycle(Vec<ID>),
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Можно ли разбить вершины на две доли так, чтобы каждое ребро соединяло разные доли
+    pub fn is_bipartite(&self) -> bool {
+        self.two_coloring().is_some()
+    }
+
+    /// Раскраска вершин в два цвета по чётности слоя обхода в ширину, отдельно в каждой компоненте связности.
+    /// Рёбра ориентированного графа считаются неориентированными. Нечётный цикл или петля дают `None`
+    pub fn two_coloring(&self) -> Option<HashMap<ID, bool>> {
+        self.two_coloring_from(self.vertices.keys()).ok()
+    }
+
+    /// Раскраска обходом в ширину из вершин `start_ids` по очереди, начальная вершина компоненты получает `false`.
+    /// Ошибка - нечётный цикл, на котором обход нашёл ребро между вершинами одного цвета
+    fn two_coloring_from<'a>(&'a self, start_ids: impl Iterator<Item = &'a ID>) -> Result<HashMap<ID, bool>, Vec<ID>> {
+        let mut colors = HashMap::with_capacity(self.vertices.len());
+        // Вершина, из которой обход пришёл в вершину, для восстановления нечётного цикла
+        let mut parents: HashMap<ID, ID> = HashMap::new();
+        let mut pending_ids = VecDeque::new();
+
+        for start_id in start_ids {
+            if colors.contains_key(start_id) {
+                continue;
+            }
+            colors.insert(start_id.clone(), false);
+            pending_ids.push_back(start_id.clone());
+
+            while let Some(id) = pending_ids.pop_front() {
+                let color = colors[&id];
+                let successor_ids = self.vertices[&id].edge_directions
+                    .iter()
+                    .map(|edge_direction| edge_direction.to_vertex_id.clone());
+                let predecessor_ids = match self.r#type {
+                    GraphType::Directed => self.predecessor_ids(&id),
+                    GraphType::Undirected => Vec::new(),
+                };
+                for neighbour_id in successor_ids.chain(predecessor_ids) {
+                    match colors.get(&neighbour_id) {
+                        Some(&neighbour_color) if neighbour_color == color => return Err(odd_cycle(&parents, id, neighbour_id)),
+                        Some(_) => {}
+                        None => {
+                            colors.insert(neighbour_id.clone(), !color);
+                            parents.insert(neighbour_id.clone(), id.clone());
+                            pending_ids.push_back(neighbour_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(colors)
+    }
+}
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Доли графа или нечётный цикл, если графа нельзя разбить на две доли, см. `two_coloring`.
+    /// Доли не зависят от порядка добавления вершин и рёбер
+    pub fn bipartition(&self) -> Bipartition<ID> {
+        let mut start_ids: Vec<_> = self.vertices.keys().collect();
+        start_ids.sort_unstable();
+        match self.two_coloring_from(start_ids.into_iter()) {
+            Ok(colors) => {
+                let (mut first_part, mut second_part): (Vec<_>, Vec<_>) = colors.into_iter().partition(|&(_, color)| !color);
+                first_part.sort_unstable();
+                second_part.sort_unstable();
+                Bipartition::Parts(
+                    first_part.into_iter().map(|(id, _)| id).collect(),
+                    second_part.into_iter().map(|(id, _)| id).collect(),
+                )
+            }
+            Err(cycle) => Bipartition::OddCycle(cycle),
+        }
+    }
+}
+
+/// Цикл из ребра `first_id - second_id` между вершинами одного слоя по чётности и путей от них
+/// по дереву обхода до ближайшего общего предка
+fn odd_cycle<ID: GraphId>(parents: &HashMap<ID, ID>, first_id: ID, second_id: ID) -> Vec<ID> {
+    let ancestors = |id: ID| {
+        let mut path = vec![id];
+        while let Some(parent_id) = parents.get(&path[path.len() - 1]) {
+            path.push(parent_id.clone());
+        }
+        path
+    };
+    let mut first_path = ancestors(first_id);
+    let mut second_path = ancestors(second_id);
+    // Обе вершины в одной компоненте, поэтому пути заканчиваются в одной начальной вершине
+    let mut common_id = None;
+    while first_path.last().is_some() && first_path.last() == second_path.last() {
+        common_id = first_path.pop();
+        second_path.pop();
+    }
+    first_path.extend(common_id);
+    first_path.extend(second_path.into_iter().rev());
+    first_path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Пользователи 1..=3 подписаны на ресурсы 10..=12
+    fn follows() -> Result<Graph<String, ()>> {
+        let mut graph = Graph::new(GraphType::Directed);
+        for (id, kind) in [(1, "user"), (2, "user"), (3, "user"), (10, "resource"), (11, "resource"), (12, "resource")] {
+            graph.add_vertex(Vertex::new(id, Some(kind.to_owned())))?;
+        }
+        for (user_id, resource_id) in [(1, 10), (1, 11), (2, 11), (3, 12)] {
+            graph.add_edge(user_id, resource_id, None)?;
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn user_resource_graph() -> Result<()> {
+        let mut graph = follows()?;
+        let coloring = graph.two_coloring();
+        let sides_match_kinds = coloring.as_ref().is_some_and(|colors| colors[&1] == colors[&2]
+            && colors[&1] != colors[&10]
+            && colors[&1] != colors[&11]
+            && colors[&3] != colors[&12]);
+        // Ребро между пользователями, подписанными на один ресурс, даёт нечётный цикл 1 - 2 - 11 - 1
+        graph.add_edge(2, 1, None)?;
+        if sides_match_kinds && !graph.is_bipartite() {
+            Ok(())
+        } else {
+            bail!("user-user edge not caught: {:?}", coloring)
+        }
+    }
+
+    /// Соседние вершины цикла, включая последнюю и первую, соединены рёбрами, и длина цикла нечётна
+    fn is_odd_cycle<VT, ET>(graph: &Graph<VT, ET>, cycle: &[u32]) -> bool {
+        cycle.len() % 2 == 1
+            && (0..cycle.len()).all(|position| {
+                let (id, next_id) = (cycle[position], cycle[(position + 1) % cycle.len()]);
+                graph.has_edge(id, next_id) || graph.has_edge(next_id, id)
+            })
+    }
+
+    #[test]
+    fn parts_and_odd_cycle_witness() -> Result<()> {
+        let mut graph = follows()?;
+        let parts = graph.bipartition();
+        graph.add_edge(2, 1, None)?;
+        let cycle = graph.bipartition();
+        let pentagon = Graph::deserialize("1\n2\n3\n4\n5\n6\n#\n1 2\n2 3\n3 4\n4 5\n5 1\n5 6".as_bytes())?;
+        let self_loop = Graph::deserialize("1\n2\n#\n1 2\n2 2".as_bytes())?;
+        let witnesses_valid = match (&cycle, pentagon.bipartition()) {
+            (Bipartition::OddCycle(cycle), Bipartition::OddCycle(pentagon_cycle)) => {
+                is_odd_cycle(&graph, cycle) && pentagon_cycle.len() == 5 && is_odd_cycle(&pentagon, &pentagon_cycle)
+            }
+            _ => false,
+        };
+        if parts == Bipartition::Parts(vec![1, 2, 3], vec![10, 11, 12])
+            && witnesses_valid
+            && self_loop.bipartition() == Bipartition::OddCycle(vec![2]) {
+            Ok(())
+        } else {
+            bail!("wrong bipartition {:?}, witness {:?}", parts, cycle)
+        }
+    }
+
+    #[test]
+    fn odd_cycles_and_loops() -> Result<()> {
+        let even_cycle = Graph::deserialize("1\n2\n3\n4\n5\n#\n1 2\n2 3\n3 4\n4 1".as_bytes())?;
+        let odd_cycle = Graph::deserialize("1\n2\n3\n#\n1 2\n2 3\n3 1".as_bytes())?;
+        let self_loop = Graph::deserialize("1\n2\n#\n1 2\n2 2".as_bytes())?;
+        let coloring = even_cycle.two_coloring();
+        if coloring.as_ref().is_some_and(|colors| colors.len() == 5 && colors[&1] == colors[&3] && colors[&1] != colors[&4])
+            && odd_cycle.two_coloring().is_none()
+            && !self_loop.is_bipartite() {
+            Ok(())
+        } else {
+            bail!("wrong two-coloring: {:?}", coloring)
+        }
+    }
+}
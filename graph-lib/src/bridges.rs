@@ -0,0 +1,111 @@
+use super::*;
+
+const UNVISITED: usize = usize::MAX;
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Мосты неориентированного графа: рёбра, удаление которых увеличивает число компонент связности
+    /// (обход в глубину без рекурсии с нижними метками, O(n + m)). Ребро - пара концов, меньший конец первым,
+    /// рёбра - по возрастанию. Кратные рёбра и петли мостами не бывают. Ориентированный граф - ошибка `WrongGraphType`
+    pub fn bridges(&self) -> Result<Vec<(ID, ID)>, GraphError> {
+        if self.r#type == GraphType::Directed {
+            return Err(GraphError::WrongGraphType { expected: GraphType::Undirected.name(), actual: self.r#type.name() });
+        }
+        let mut ids: Vec<_> = self.vertices.keys().collect();
+        ids.sort_unstable();
+        let adjacency = self.index_adjacency(&ids);
+
+        let mut order = vec![UNVISITED; ids.len()];
+        let mut low_links = vec![0; ids.len()];
+        let mut visit_count = 0;
+        let mut bridges = Vec::new();
+        // Вершина, родитель, номер следующего просматриваемого ребра и пропущено ли уже ребро к родителю
+        let mut call_stack = Vec::new();
+        for root in 0..ids.len() {
+            if order[root] != UNVISITED {
+                continue;
+            }
+            order[root] = visit_count;
+            low_links[root] = visit_count;
+            visit_count += 1;
+            call_stack.push((root, None, 0, false));
+            while let Some(&mut (index, parent, ref mut edge_position, ref mut parent_edge_skipped)) = call_stack.last_mut() {
+                if let Some(&neighbour_index) = adjacency[index].get(*edge_position) {
+                    *edge_position += 1;
+                    // Ребро, по которому пришли, пропускается один раз: кратное ему ребро - обратное
+                    if Some(neighbour_index) == parent && !*parent_edge_skipped {
+                        *parent_edge_skipped = true;
+                    } else if order[neighbour_index] == UNVISITED {
+                        order[neighbour_index] = visit_count;
+                        low_links[neighbour_index] = visit_count;
+                        visit_count += 1;
+                        call_stack.push((neighbour_index, Some(index), 0, false));
+                    } else {
+                        low_links[index] = low_links[index].min(order[neighbour_index]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some(parent_index) = parent {
+                    low_links[parent_index] = low_links[parent_index].min(low_links[index]);
+                    if low_links[index] > order[parent_index] {
+                        let (from_index, to_index) = (parent_index.min(index), parent_index.max(index));
+                        bridges.push((ids[from_index].clone(), ids[to_index].clone()));
+                    }
+                }
+            }
+        }
+        bridges.sort_unstable();
+        Ok(bridges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn triangles_joined_by_bridge() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new_multigraph(GraphType::Undirected);
+        for id in 1..=8 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 6), (6, 4), (6, 7), (7, 8), (7, 8), (8, 8)] {
+            graph.add_edge(from_id, to_id, None)?;
+        }
+        let directed = Graph::<(), ()>::new(GraphType::Directed);
+        if graph.bridges()? == vec![(3, 4), (6, 7)]
+            && Graph::<(), ()>::path(4).bridges()? == vec![(0, 1), (1, 2), (2, 3)]
+            && Graph::<(), ()>::petersen().bridges()?.is_empty()
+            && directed.bridges() == Err(GraphError::WrongGraphType { expected: "undirected", actual: "directed" }) {
+            Ok(())
+        } else {
+            bail!("wrong bridges {:?}", graph.bridges())
+        }
+    }
+
+    #[test]
+    fn bridges_disconnect_random_graph() -> Result<()> {
+        let graph = Graph::<(), ()>::gnm_random(40, 45, 11)?;
+        let component_count = graph.connected_components().len();
+        let mut expected = Vec::new();
+        for (from_id, to_id, _) in graph.edges() {
+            let mut without_edge = graph.clone();
+            without_edge.delete_edge(from_id, to_id)?;
+            if without_edge.connected_components().len() > component_count {
+                expected.push((from_id.min(to_id), from_id.max(to_id)));
+            }
+        }
+        expected.sort_unstable();
+        let bridges = graph.bridges()?;
+        if bridges == expected && !bridges.is_empty() {
+            Ok(())
+        } else {
+            bail!("bridges {:?}, expected {:?}", bridges, expected)
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use super::*;
+
+/// Построение графа цепочкой вызовов `vertex` и `edge`. Ошибки не возвращаются по одной, а собираются в `build`
+#[derive(Debug)]
+pub struct GraphBuilder<VT, ET, ID = DefaultGraphIdType> {
+    r#type: GraphType,
+    auto_create_vertices: bool,
+    vertices: Vec<(ID, Option<VT>)>,
+    edges: Vec<(ID, ID, Option<ET>)>,
+}
+
+impl<VT, ET, ID> Default for GraphBuilder<VT, ET, ID> {
+    fn default() -> Self {
+        GraphBuilder {
+            r#type: GraphType::Undirected,
+            auto_create_vertices: false,
+            vertices: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> GraphBuilder<VT, ET, ID> {
+    /// Построитель неориентированного графа
+    pub fn new() -> GraphBuilder<VT, ET, ID> {
+        GraphBuilder::default()
+    }
+
+    pub fn directed(mut self) -> GraphBuilder<VT, ET, ID> {
+        self.r#type = GraphType::Directed;
+        self
+    }
+
+    pub fn undirected(mut self) -> GraphBuilder<VT, ET, ID> {
+        self.r#type = GraphType::Undirected;
+        self
+    }
+
+    /// Концы рёбер, не добавленные через `vertex`, создаются без значения
+    pub fn auto_create_vertices(mut self) -> GraphBuilder<VT, ET, ID> {
+        self.auto_create_vertices = true;
+        self
+    }
+
+    pub fn vertex(mut self, id: ID, value: Option<VT>) -> GraphBuilder<VT, ET, ID> {
+        self.vertices.push((id, value));
+        self
+    }
+
+    /// Ребро добавляется после всех вершин, поэтому может ссылаться на вершины, объявленные позже
+    pub fn edge(mut self, from_id: ID, to_id: ID, value: Option<ET>) -> GraphBuilder<VT, ET, ID> {
+        self.edges.push((from_id, to_id, value));
+        self
+    }
+
+    /// Строит граф. Если есть повторяющиеся вершины или рёбра либо рёбра с необъявленными концами,
+    /// возвращается `InvalidGraph` со всеми такими ошибками в порядке объявления
+    pub fn build(self) -> Result<Graph<VT, ET, ID>, GraphError> {
+        let mut graph = Graph::new(self.r#type);
+        let mut errors = Vec::new();
+        for (id, value) in self.vertices {
+            if let Err(error) = graph.add_vertex(Vertex::new(id, value)) {
+                errors.push(error);
+            }
+        }
+        for (from_id, to_id, value) in self.edges {
+            let mut missing_ids = Vec::new();
+            for id in [&from_id, &to_id] {
+                if graph.contains_vertex(id) || missing_ids.contains(&id) {
+                    continue;
+                }
+                if self.auto_create_vertices {
+                    graph.add_vertex(Vertex::new(id.clone(), None))?;
+                } else {
+                    missing_ids.push(id);
+                }
+            }
+            if !missing_ids.is_empty() {
+                errors.extend(missing_ids.into_iter().map(|id| GraphError::VertexNotFound(id.to_string())));
+                continue;
+            }
+            if let Err(error) = graph.add_edge(from_id, to_id, value) {
+                errors.push(error);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(graph)
+        } else {
+            Err(GraphError::InvalidGraph(errors))
+        }
+    }
+}
+
+/// Неориентированный граф из списка рёбер, вершины создаются по концам рёбер. Повторные рёбра пропускаются
+impl FromIterator<(DefaultGraphIdType, DefaultGraphIdType)> for Graph<(), ()> {
+    fn from_iter<I: IntoIterator<Item = (DefaultGraphIdType, DefaultGraphIdType)>>(edges: I) -> Self {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for (from_id, to_id) in edges {
+            for id in [from_id, to_id] {
+                if !graph.contains_vertex(&id) {
+                    graph.add_vertex(Vertex::new(id, None))
+                        .expect("vertex is added once");
+                }
+            }
+            if !graph.contains_edge_direction(&from_id, &to_id).expect("edge endpoints were added") {
+                graph.add_edge(from_id, to_id, None)
+                    .expect("edge is new and its endpoints exist");
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn build_graph() -> Result<()> {
+        let graph = GraphBuilder::new()
+            .directed()
+            .vertex(1, Some("January".to_owned()))
+            .edge(1, 2, Some("next".to_owned()))
+            .vertex(2, Some("February".to_owned()))
+            .build()?;
+        let auto_created = GraphBuilder::<(), ()>::new()
+            .auto_create_vertices()
+            .edge(1, 2, None)
+            .edge(2, 3, None)
+            .build()?;
+        if graph.r#type == GraphType::Directed
+            && graph.vertex_value(2).map(String::as_str) == Some("February")
+            && graph.edges() == vec![(1, 2, Some(&"next".to_owned()))]
+            && auto_created.r#type == GraphType::Undirected
+            && auto_created.vertex_count() == 3
+            && auto_created.shortest_path(3, 1)? == Some(vec![3, 2, 1]) {
+            Ok(())
+        } else {
+            bail!("wrong built graph")
+        }
+    }
+
+    #[test]
+    fn all_errors_collected() -> Result<()> {
+        let result = GraphBuilder::<(), ()>::new()
+            .vertex(1, None)
+            .vertex(1, None)
+            .vertex(2, None)
+            .edge(1, 2, None)
+            .edge(2, 1, None)
+            .edge(3, 4, None)
+            .edge(2, 5, None)
+            .build();
+        let Err(GraphError::InvalidGraph(errors)) = result else {
+            bail!("invalid graph built: {:?}", result)
+        };
+        let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        if messages == vec![
+            "vertex with id \"1\" already exists in the graph",
+            "edge from \"2\" to \"1\" already exists in the graph",
+            "vertex id \"3\" not found in graph",
+            "vertex id \"4\" not found in graph",
+            "vertex id \"5\" not found in graph",
+        ] {
+            Ok(())
+        } else {
+            bail!("wrong collected errors {:?}", messages)
+        }
+    }
+
+    #[test]
+    fn from_edge_list() -> Result<()> {
+        let graph: Graph<(), ()> = [(1, 2), (2, 3), (3, 1), (2, 1), (4, 4)].into_iter().collect();
+        if graph.vertex_count() == 4
+            && graph.edge_count() == 4
+            && graph.neighbours(4)? == vec![4] {
+            Ok(())
+        } else {
+            bail!("wrong graph from edge list: {:?}", graph.edges())
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use super::*;
+
+/// Ребро для пакетной вставки: начальная вершина, конечная вершина и значение
+pub(crate) type NewEdge<ET, ID> = (ID, ID, Option<ET>);
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Добавляет вершины пакетом. Повтор идентификатора (среди вершин графа или внутри пакета) и превышение
+    /// ограничения проверяются до изменения графа: при ошибке не добавляется ни одна вершина
+    pub fn add_vertices<I: IntoIterator<Item = Vertex<VT, ET, ID>>>(&mut self, vertices: I) -> Result<(), GraphError> {
+        let vertices: Vec<_> = vertices.into_iter().collect();
+        let mut new_ids = HashSet::with_capacity(vertices.len());
+        for vertex in &vertices {
+            if self.contains_vertex(&vertex.id) || !new_ids.insert(&vertex.id) {
+                return Err(GraphError::VertexAlreadyExist(vertex.id.to_string()));
+            }
+        }
+        DeserializeLimits::check(self.limits.max_vertices, self.vertices.len() + vertices.len(), LimitKind::Vertices, None)?;
+
+        self.vertices.reserve(vertices.len());
+        for vertex in vertices {
+            self.mark_changed(&vertex.id);
+            self.vertices.insert(vertex.id.clone(), vertex);
+        }
+        Ok(())
+    }
+
+    /// Добавляет рёбра пакетом. Концы рёбер, повторы рёбер простого графа, ограничение числа рёбер и циклы
+    /// ациклического графа проверяются до изменения графа: при ошибке не добавляется ни одно ребро.
+    /// В отличие от `add_edge` в цикле, повтор ребра ищется по множеству рёбер пакета, а не просмотром
+    /// растущего списка рёбер вершины, и память под рёбра выделяется заранее
+    pub fn add_edges<I: IntoIterator<Item = NewEdge<ET, ID>>>(&mut self, edges: I) -> Result<(), GraphError> {
+        self.insert_edges(edges.into_iter().collect()).map_err(|(_, error)| error)
+    }
+
+    /// `add_edges` с номером ребра пакета, на котором обнаружена ошибка
+    pub(crate) fn insert_edges(&mut self, edges: Vec<NewEdge<ET, ID>>) -> Result<(), (usize, GraphError)> {
+        for (index, (from_id, to_id, _)) in edges.iter().enumerate() {
+            if let Some(missing_id) = [from_id, to_id].into_iter().find(|id| !self.contains_vertex(id)) {
+                return Err((index, GraphError::VertexNotFound(missing_id.to_string())));
+            }
+        }
+        // Ошибка относится к первому ребру сверх ограничения
+        DeserializeLimits::check(self.limits.max_edges, self.edge_count + edges.len(), LimitKind::Edges, None)
+            .map_err(|error| (self.limits.max_edges.map_or(0, |max_edges| max_edges.saturating_sub(self.edge_count)), error))?;
+        if !self.multigraph {
+            self.check_new_edges_unique(&edges)?;
+        }
+        if self.dag {
+            self.check_acyclic_edges(&edges)?;
+        }
+
+        // Направления добавляются в конец списков в том же порядке, что и при вызовах `add_edge`
+        let mut new_direction_counts: HashMap<ID, usize> = HashMap::new();
+        for (from_id, to_id, _) in &edges {
+            *new_direction_counts.entry(from_id.clone()).or_default() += 1;
+            if self.r#type == GraphType::Undirected && from_id != to_id {
+                *new_direction_counts.entry(to_id.clone()).or_default() += 1;
+            }
+        }
+        for (id, &count) in &new_direction_counts {
+            self.vertices.get_mut(id).expect("edge ends are checked").edge_directions.reserve(count);
+        }
+        self.edge_count += edges.len();
+        for (from_id, to_id, value) in edges {
+            let value = Arc::new(value);
+            match self.r#type {
+                GraphType::Undirected => {
+                    if from_id != to_id {
+                        self.vertices.get_mut(&to_id).expect("edge ends are checked").edge_directions
+                            .push(EdgeDirection::new_weak(from_id.clone(), Arc::clone(&value)));
+                    }
+                }
+                GraphType::Directed => {
+                    self.predecessors.entry(to_id.clone()).or_default().insert(from_id.clone());
+                }
+            }
+            self.vertices.get_mut(&from_id).expect("edge ends are checked").edge_directions
+                .push(EdgeDirection::new(to_id, value));
+        }
+        // Индекс соседей строится один раз после вставки, а не обновляется на каждом ребре
+        let threshold = self.adaptive_threshold;
+        for id in new_direction_counts.keys() {
+            self.vertices.get_mut(id).expect("edge ends are checked").rebuild_target_index(threshold);
+            self.touch_vertex(id);
+        }
+        Ok(())
+    }
+
+    /// Повтор ребра простого графа: среди рёбер пакета по множеству пар концов, с рёбрами графа -
+    /// по спискам рёбер вершин до вставки
+    fn check_new_edges_unique(&self, edges: &[NewEdge<ET, ID>]) -> Result<(), (usize, GraphError)> {
+        let mut new_ends = HashSet::with_capacity(edges.len());
+        for (index, (from_id, to_id, _)) in edges.iter().enumerate() {
+            let reversed_exists = self.r#type == GraphType::Undirected && new_ends.contains(&(to_id, from_id));
+            if reversed_exists || !new_ends.insert((from_id, to_id)) || self.vertices[from_id].has_edge_direction_to(to_id) {
+                return Err((index, GraphError::EdgeAlreadyExist(from_id.to_string(), to_id.to_string())));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn vertices(count: u32) -> impl Iterator<Item = Vertex<(), u32>> {
+        (1..=count).map(|id| Vertex::with_edge_capacity(id, None, 2))
+    }
+
+    #[test]
+    fn bulk_insert_matches_loop() -> Result<()> {
+        let edges = [(1, 2, Some(12)), (2, 3, None), (3, 3, Some(33)), (4, 1, Some(41))];
+        for graph_type in [GraphType::Directed, GraphType::Undirected] {
+            let mut bulk = Graph::with_capacity(graph_type, 4);
+            bulk.add_vertices(vertices(4))?;
+            bulk.add_edges(edges)?;
+            let mut looped = Graph::new(graph_type);
+            for vertex in vertices(4) {
+                looped.add_vertex(vertex)?;
+            }
+            for (from_id, to_id, value) in edges {
+                looped.add_edge(from_id, to_id, value)?;
+            }
+            if bulk != looped || bulk.edge_count() != 4 || bulk.neighbours(1)? != looped.neighbours(1)? {
+                bail!("bulk {:?} graph differs from loop", graph_type)
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_batch_leaves_graph_unchanged() -> Result<()> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        graph.add_vertices(vertices(3))?;
+        graph.add_edge(1, 2, None)?;
+        let duplicate_vertex = graph.add_vertices([Vertex::new(4, None), Vertex::new(4, None)]);
+        let missing_end = graph.add_edges([(2, 3, None), (3, 5, None)]);
+        let reversed_in_batch = graph.add_edges([(2, 3, None), (3, 2, None)]);
+        let existing = graph.add_edges([(2, 3, None), (2, 1, None)]);
+        let mut dag = Graph::<(), u32>::new_dag();
+        dag.add_vertices(vertices(3))?;
+        dag.add_edge(1, 2, None)?;
+        let cycle = dag.add_edges([(2, 3, None), (3, 1, None)]);
+        if duplicate_vertex == Err(GraphError::VertexAlreadyExist("4".to_owned()))
+            && missing_end == Err(GraphError::VertexNotFound("5".to_owned()))
+            && reversed_in_batch == Err(GraphError::EdgeAlreadyExist("3".to_owned(), "2".to_owned()))
+            && existing == Err(GraphError::EdgeAlreadyExist("2".to_owned(), "1".to_owned()))
+            && matches!(cycle, Err(GraphError::WouldCreateCycle { witness_path, .. }) if witness_path == ["1", "2", "3"])
+            && graph.vertex_count() == 3
+            && graph.edge_count() == 1
+            && dag.edge_count() == 1 {
+            Ok(())
+        } else {
+            bail!("invalid batch accepted: {:?} {:?} {:?} {:?}", duplicate_vertex, missing_end, reversed_in_batch, existing)
+        }
+    }
+
+    #[test]
+    fn deserialize_reports_edge_line() -> Result<()> {
+        let result = Graph::deserialize("1\n2\n3\n#\n1 2\n2 3\n2 1\n".as_bytes());
+        let expected = GraphError::ParseLine { line_no: 7, source: Box::new(GraphError::EdgeAlreadyExist("2".to_owned(), "1".to_owned())) };
+        let with_roles = Graph::deserialize("1\n2\n#\n1 2\n!roles\n1 source\n".as_bytes())?;
+        match result {
+            Err(error) if error == expected && with_roles.edge_count() == 1 && with_roles.role(1) == Some(VertexRole::Source) => Ok(()),
+            _ => bail!("wrong duplicate edge error: {:?}", result.map(|graph| graph.edge_count())),
+        }
+    }
+
+    /// Время построения звезды: все рёбра из одной вершины
+    fn star_build_time(edge_count: u32, bulk: bool) -> Result<Duration> {
+        let mut graph = Graph::<(), u32>::with_capacity(GraphType::Directed, edge_count as usize + 1);
+        graph.add_vertices(vertices(edge_count + 1))?;
+        let start = Instant::now();
+        let edges = (2..=edge_count + 1).map(|to_id| (1, to_id, Some(to_id)));
+        if bulk {
+            graph.add_edges(edges)?;
+        } else {
+            for (from_id, to_id, value) in edges {
+                graph.add_edge(from_id, to_id, value)?;
+            }
+        }
+        Ok(start.elapsed())
+    }
+
+    #[test]
+    #[ignore = "замер времени, запускается через --ignored"]
+    fn bulk_insert_faster_than_loop() -> Result<()> {
+        let looped = star_build_time(100_000, false)?;
+        let bulk = star_build_time(100_000, true)?;
+        if bulk * 3 < looped {
+            Ok(())
+        } else {
+            bail!("bulk insert {:?} is not substantially faster than loop {:?}", bulk, looped)
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{BuildHasher, BuildHasherDefault},
+};
+use super::*;
+
+/// Разные метки для вершин и рёбер, чтобы вершина не совпала по хешу с ребром
+const VERTEX_TAG: u8 = 0;
+const EDGE_TAG: u8 = 1;
+
+fn hash_one<T: Hash>(item: T) -> u64 {
+    BuildHasherDefault::<DefaultHasher>::default().hash_one(item)
+}
+
+impl<VT: Hash, ET: Hash, ID: GraphId> Graph<VT, ET, ID> {
+    /// Хеш графа для поиска дубликатов: тип графа, вершины со значениями и рёбра со значениями.
+    /// Не зависит от порядка добавления вершин и рёбер, ребро неориентированного графа - неупорядоченная пара.
+    /// Равные графы имеют равные хеши, обратное верно лишь с высокой вероятностью. В отличие от `fingerprint`,
+    /// значения хешируются через `Hash`, а не через `Display`, и результат не записывается в файлы
+    pub fn structural_hash(&self) -> u64 {
+        self.combined_hash(|value| hash_one(value), |value| hash_one(value))
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Хеш только топологии графа: тип графа, идентификаторы вершин и рёбра без значений, см. `structural_hash`
+    pub fn structural_hash_ignoring_values(&self) -> u64 {
+        self.combined_hash(|_| 0, |_| 0)
+    }
+
+    /// Сумма хешей элементов графа: от порядка обхода хеш-таблицы сумма не зависит.
+    /// Значения вершин и рёбер хешируются переданными функциями
+    fn combined_hash<FV, FE>(&self, vertex_value_hash: FV, edge_value_hash: FE) -> u64
+    where
+        FV: Fn(&Option<VT>) -> u64,
+        FE: Fn(&Option<ET>) -> u64,
+    {
+        let mut hash = hash_one((self.r#type.name(), self.vertex_count(), self.edge_count()));
+        for vertex in self.vertices.values() {
+            hash = hash.wrapping_add(hash_one((VERTEX_TAG, &vertex.id, vertex_value_hash(&vertex.value))));
+        }
+        for (from_id, edge_direction) in self.logical_edges() {
+            let (mut from_hash, mut to_hash) = (hash_one(from_id), hash_one(&edge_direction.to_vertex_id));
+            if self.r#type == GraphType::Undirected && to_hash < from_hash {
+                std::mem::swap(&mut from_hash, &mut to_hash);
+            }
+            let value_hash = edge_value_hash(&edge_direction.value);
+            hash = hash.wrapping_add(hash_one((EDGE_TAG, from_hash, to_hash, value_hash)));
+        }
+        hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn build(graph_type: GraphType, ids: &[u32], edges: &[(u32, u32, &str)]) -> Result<Graph<String, String>> {
+        let mut graph = Graph::new(graph_type);
+        for &id in ids {
+            graph.add_vertex(Vertex::new(id, Some(format!("v{}", id))))?;
+        }
+        for &(from_id, to_id, value) in edges {
+            graph.add_edge(from_id, to_id, Some(value.to_owned()))?;
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn insertion_order_does_not_matter() -> Result<()> {
+        let graph = build(GraphType::Undirected, &[1, 2, 3, 4], &[(1, 2, "a"), (2, 3, "b"), (4, 3, "c")])?;
+        let reordered = build(GraphType::Undirected, &[4, 3, 2, 1], &[(3, 4, "c"), (2, 1, "a"), (3, 2, "b")])?;
+        if graph.structural_hash() == reordered.structural_hash()
+            && graph.structural_hash_ignoring_values() == reordered.structural_hash_ignoring_values() {
+            Ok(())
+        } else {
+            bail!("hash depends on insertion order")
+        }
+    }
+
+    #[test]
+    fn edges_type_and_values_change_hash() -> Result<()> {
+        let edges = [(1, 2, "a"), (2, 3, "b")];
+        let graph = build(GraphType::Directed, &[1, 2, 3], &edges)?;
+        let mut extended = build(GraphType::Directed, &[1, 2, 3], &edges)?;
+        extended.add_edge(3, 1, Some("c".to_owned()))?;
+        let undirected = build(GraphType::Undirected, &[1, 2, 3], &edges)?;
+        let reversed = build(GraphType::Directed, &[1, 2, 3], &[(2, 1, "a"), (2, 3, "b")])?;
+        let renamed = build(GraphType::Directed, &[1, 2, 3], &[(1, 2, "x"), (2, 3, "b")])?;
+        if graph.structural_hash() != extended.structural_hash()
+            && graph.structural_hash() != undirected.structural_hash()
+            && graph.structural_hash_ignoring_values() != undirected.structural_hash_ignoring_values()
+            && graph.structural_hash() != reversed.structural_hash()
+            && graph.structural_hash() != renamed.structural_hash()
+            && graph.structural_hash_ignoring_values() == renamed.structural_hash_ignoring_values() {
+            Ok(())
+        } else {
+            bail!("different graphs have equal hashes")
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::distribution::DegreeKind;
+use super::*;
+
+/// Наибольшее число итераций `Graph::pagerank_until`: защита от остановки сходимости на ошибках округления
+const MAX_PAGERANK_ITERATIONS: usize = 10_000;
+
+/// Проверяет, что `damping` в `[0, 1)`, а `epsilon` больше 0 (NaN не проходит ни одну из проверок)
+fn check_pagerank_parameters(damping: f64, epsilon: f64) -> Result<(), GraphError> {
+    if !(0.0..1.0).contains(&damping) {
+        return Err(GraphError::InvalidParameter { name: "damping", value: damping.to_string(), expected: "in [0, 1)" });
+    }
+    if epsilon.is_nan() || epsilon <= 0.0 {
+        return Err(GraphError::InvalidParameter { name: "epsilon", value: epsilon.to_string(), expected: "greater than 0" });
+    }
+    Ok(())
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// PageRank степенным методом за `iterations` итераций. Ранг вершин без исходящих рёбер
+    /// распределяется поровну между всеми вершинами. Ребро неориентированного графа ведёт в обе стороны.
+    /// Сумма рангов равна 1
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> HashMap<ID, f64> {
+        self.power_iteration(damping, |iteration, _| iteration == iterations)
+    }
+
+    /// PageRank, как `pagerank`, с итерациями до тех пор, пока суммарное изменение рангов за итерацию
+    /// не станет меньше `epsilon`. Изменение убывает не медленнее, чем в `damping` раз за итерацию.
+    /// `damping` должен быть в `[0, 1)`, `epsilon` - больше 0, иначе возвращается `GraphError::InvalidParameter`
+    pub fn pagerank_until(&self, damping: f64, epsilon: f64) -> Result<HashMap<ID, f64>, GraphError> {
+        check_pagerank_parameters(damping, epsilon)?;
+        Ok(self.power_iteration(damping, |iteration, change| change < epsilon || iteration == MAX_PAGERANK_ITERATIONS))
+    }
+
+    /// Степенной метод PageRank. `is_done` получает число выполненных итераций и суммарное изменение рангов
+    /// за последнюю из них (до первой итерации - бесконечность)
+    fn power_iteration<F: Fn(usize, f64) -> bool>(&self, damping: f64, is_done: F) -> HashMap<ID, f64> {
+        let ids: Vec<_> = self.vertices.keys().collect();
+        let vertex_count = ids.len();
+        if vertex_count == 0 {
+            return HashMap::new();
+        }
+        let indices: HashMap<_, _> = ids.iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        let out_neighbours: Vec<Vec<usize>> = ids.iter()
+            .map(|&id| self.vertices[id].edge_directions
+                .iter()
+                .map(|edge_direction| indices[&edge_direction.to_vertex_id])
+                .collect())
+            .collect();
+
+        let mut ranks = vec![1.0 / vertex_count as f64; vertex_count];
+        let mut next_ranks = vec![0.0; vertex_count];
+        let mut iteration = 0;
+        let mut change = f64::INFINITY;
+        while !is_done(iteration, change) {
+            let dangling_rank: f64 = out_neighbours.iter()
+                .zip(&ranks)
+                .filter(|(neighbours, _)| neighbours.is_empty())
+                .map(|(_, rank)| rank)
+                .sum();
+            let base_rank = (1.0 - damping + damping * dangling_rank) / vertex_count as f64;
+            next_ranks.fill(base_rank);
+            for (neighbours, rank) in out_neighbours.iter().zip(&ranks) {
+                let share = damping * rank / neighbours.len() as f64;
+                for &neighbour in neighbours {
+                    next_ranks[neighbour] += share;
+                }
+            }
+            change = ranks.iter()
+                .zip(&next_ranks)
+                .map(|(rank, next_rank)| (rank - next_rank).abs())
+                .sum();
+            std::mem::swap(&mut ranks, &mut next_ranks);
+            iteration += 1;
+        }
+
+        // Ошибки округления накапливаются за итерации, поэтому сумма рангов приводится к 1
+        let rank_sum: f64 = ranks.iter().sum();
+        ids.into_iter()
+            .cloned()
+            .zip(ranks.into_iter().map(|rank| rank / rank_sum))
+            .collect()
+    }
+
+    /// Степень вершины (как в `degree`), делённая на число остальных вершин. У графа из одной вершины -
+    /// степень вершины
+    pub fn degree_centrality(&self) -> HashMap<ID, f64> {
+        let other_vertex_count = self.vertices.len().saturating_sub(1).max(1) as f64;
+        self.degrees(DegreeKind::Undirected)
+            .into_iter()
+            .map(|(id, degree)| (id.clone(), degree as f64 / other_vertex_count))
+            .collect()
+    }
+
+    /// Промежуточность вершин (алгоритм Брандеса, O(n · m)): сумма по парам остальных вершин доли кратчайших путей
+    /// между ними, проходящих через вершину. Длина пути - число рёбер, кратные рёбра и петли не учитываются.
+    /// В неориентированном графе каждая пара считается один раз. Значения не нормированы: для сравнения графов
+    /// разного размера их можно разделить на число пар остальных вершин
+    pub fn betweenness_centrality(&self) -> HashMap<ID, f64> {
+        let ids: Vec<_> = self.vertices.keys().collect();
+        let mut adjacency = self.index_adjacency(&ids);
+        for (index, neighbours) in adjacency.iter_mut().enumerate() {
+            neighbours.retain(|&neighbour_index| neighbour_index != index);
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+
+        let mut centrality = vec![0.0; ids.len()];
+        let mut distances = vec![usize::MAX; ids.len()];
+        let mut path_counts = vec![0.0; ids.len()];
+        let mut dependencies = vec![0.0; ids.len()];
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+        let mut visit_order: Vec<usize> = Vec::with_capacity(ids.len());
+        let mut queue = VecDeque::new();
+        for source in 0..ids.len() {
+            for &index in &visit_order {
+                distances[index] = usize::MAX;
+                path_counts[index] = 0.0;
+                dependencies[index] = 0.0;
+                predecessors[index].clear();
+            }
+            visit_order.clear();
+
+            // Обход в ширину считает число кратчайших путей из `source` до каждой вершины
+            distances[source] = 0;
+            path_counts[source] = 1.0;
+            queue.push_back(source);
+            while let Some(index) = queue.pop_front() {
+                visit_order.push(index);
+                for &neighbour_index in &adjacency[index] {
+                    if distances[neighbour_index] == usize::MAX {
+                        distances[neighbour_index] = distances[index] + 1;
+                        queue.push_back(neighbour_index);
+                    }
+                    if distances[neighbour_index] == distances[index] + 1 {
+                        path_counts[neighbour_index] += path_counts[index];
+                        predecessors[neighbour_index].push(index);
+                    }
+                }
+            }
+
+            // Зависимости накапливаются от дальних вершин к ближним
+            for &index in visit_order.iter().rev() {
+                for &predecessor_index in &predecessors[index] {
+                    dependencies[predecessor_index] += path_counts[predecessor_index] / path_counts[index] * (1.0 + dependencies[index]);
+                }
+                if index != source {
+                    centrality[index] += dependencies[index];
+                }
+            }
+        }
+
+        let pair_factor = match self.r#type {
+            GraphType::Directed => 1.0,
+            GraphType::Undirected => 0.5,
+        };
+        ids.into_iter()
+            .cloned()
+            .zip(centrality.into_iter().map(|value| value * pair_factor))
+            .collect()
+    }
+
+    /// Приближённый персонализированный PageRank относительно множества `seeds` методом проталкивания
+    /// (Andersen, Chung, Lang): остаток вершины проталкивается, пока он не меньше `epsilon` на исходящее ребро,
+    /// поэтому обходится только окрестность начальных вершин, а не весь граф.
+    /// Остаток вершин без исходящих рёбер возвращается начальным вершинам.
+    /// Сумма рангов не больше 1 и отличается от 1 меньше чем на `epsilon`, умноженный на число направлений рёбер затронутых вершин.
+    /// Вершины с нулевым рангом в результат не входят. Ограничения на `damping` и `epsilon` - как в `pagerank_until`
+    pub fn personalized_pagerank(&self, seeds: &[ID], damping: f64, epsilon: f64) -> Result<HashMap<ID, f64>, GraphError> {
+        check_pagerank_parameters(damping, epsilon)?;
+        let missing_seeds: Vec<_> = seeds.iter()
+            .filter(|id| !self.contains_vertex(id))
+            .map(ToString::to_string)
+            .collect();
+        if !missing_seeds.is_empty() {
+            return Err(GraphError::VerticesNotFound(missing_seeds));
+        }
+
+        let seed_share = 1.0 / seeds.len() as f64;
+        let mut ranks = HashMap::new();
+        let mut residuals: HashMap<ID, f64> = HashMap::new();
+        for seed in seeds {
+            *residuals.entry(seed.clone()).or_default() += seed_share;
+        }
+        let mut queue: VecDeque<_> = residuals.keys().cloned().collect();
+        let mut queued: HashSet<_> = queue.iter().cloned().collect();
+        // Вершина без исходящих рёбер проталкивает остаток, как будто у неё одно ребро
+        let push_threshold = |edge_directions: &[EdgeDirection<ET, ID>]| epsilon * edge_directions.len().max(1) as f64;
+
+        while let Some(id) = queue.pop_front() {
+            queued.remove(&id);
+            let residual = residuals.insert(id.clone(), 0.0).unwrap_or_default();
+            *ranks.entry(id.clone()).or_default() += (1.0 - damping) * residual;
+
+            let edge_directions = &self.vertices[&id].edge_directions;
+            let targets: Vec<(&ID, f64)> = if edge_directions.is_empty() {
+                seeds.iter().map(|seed| (seed, seed_share)).collect()
+            } else {
+                let share = 1.0 / edge_directions.len() as f64;
+                edge_directions.iter().map(|edge_direction| (&edge_direction.to_vertex_id, share)).collect()
+            };
+            for (target_id, share) in targets {
+                let target_residual = residuals.entry(target_id.clone()).or_default();
+                *target_residual += damping * residual * share;
+                if *target_residual >= push_threshold(&self.vertices[target_id].edge_directions) && queued.insert(target_id.clone()) {
+                    queue.push_back(target_id.clone());
+                }
+            }
+        }
+
+        Ok(ranks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn pagerank_star() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 0..5 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..5 {
+            graph.add_edge(id, 0, None)?;
+        }
+        let ranks = graph.pagerank(0.85, 50);
+        let rank_sum: f64 = ranks.values().sum();
+        if (rank_sum - 1.0).abs() < 1e-9
+            && (1..5).all(|id| ranks[&0] > ranks[&id])
+            && (ranks[&1] - ranks[&4]).abs() < 1e-12 {
+            Ok(())
+        } else {
+            bail!("wrong pagerank {:?}", ranks)
+        }
+    }
+
+    #[test]
+    fn pagerank_undirected_and_dangling() -> Result<()> {
+        let mut triangle = Graph::<(), ()>::new(GraphType::Undirected);
+        // Вершины 3 и 4 без рёбер: их ранг распределяется между всеми вершинами
+        let mut dangling = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 0..5 {
+            triangle.add_vertex(Vertex::new(id, None))?;
+            dangling.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(0, 1), (1, 2), (2, 0), (2, 3)] {
+            triangle.add_edge(from_id, to_id, None)?;
+            dangling.add_edge(from_id, to_id, None)?;
+        }
+        let triangle_ranks = triangle.pagerank_until(0.85, 1e-12)?;
+        let converged = dangling.pagerank_until(0.85, 1e-12)?;
+        let iterated = dangling.pagerank(0.85, 300);
+        let sums_to_one = |ranks: &HashMap<u32, f64>| (ranks.values().sum::<f64>() - 1.0).abs() < 1e-12;
+        if sums_to_one(&triangle_ranks)
+            && sums_to_one(&converged)
+            && (triangle_ranks[&0] - triangle_ranks[&1]).abs() < 1e-9
+            && triangle_ranks[&2] > triangle_ranks[&0]
+            && triangle_ranks[&3] > triangle_ranks[&4]
+            && (0..5).all(|id| (converged[&id] - iterated[&id]).abs() < 1e-10)
+            && (converged[&3] - converged[&4]).abs() > 1e-3 {
+            Ok(())
+        } else {
+            bail!("wrong pagerank {:?}", converged)
+        }
+    }
+
+    #[test]
+    fn degree_centrality_star() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 0..5 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..5 {
+            graph.add_edge(0, id, None)?;
+        }
+        let centrality = graph.degree_centrality();
+        let mut single = Graph::<(), ()>::new(GraphType::Undirected);
+        single.add_vertex(Vertex::new(0, None))?;
+        if centrality[&0] == 1.0
+            && (1..5).all(|id| centrality[&id] == 0.25)
+            && single.degree_centrality()[&0] == 0.0
+            && Graph::<(), ()>::new(GraphType::Undirected).degree_centrality().is_empty() {
+            Ok(())
+        } else {
+            bail!("wrong degree centrality {:?}", centrality)
+        }
+    }
+
+    fn cycle_with_tail() -> Result<Graph<(), ()>> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for id in 0..10 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 0..6 {
+            graph.add_edge(id, (id + 1) % 6, None)?;
+        }
+        graph.add_edge(3, 6, None)?;
+        graph.add_edge(6, 7, None)?;
+        graph.add_edge(8, 9, None)?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn betweenness_path_and_karate_club() -> Result<()> {
+        let path = Graph::<(), ()>::path(5).betweenness_centrality();
+        let mut directed = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=4 {
+            directed.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(1, 2), (2, 3), (1, 4), (4, 3), (3, 3)] {
+            directed.add_edge(from_id, to_id, None)?;
+        }
+        let diamond = directed.betweenness_centrality();
+        let karate_club = Graph::<String, ()>::karate_club().betweenness_centrality();
+        // Нормированная промежуточность инструктора клуба - 0.43763528, нормирующий множитель - 33 · 32 / 2 пар
+        if path == HashMap::from([(0, 0.0), (1, 3.0), (2, 4.0), (3, 3.0), (4, 0.0)])
+            && diamond == HashMap::from([(1, 0.0), (2, 0.5), (3, 0.0), (4, 0.5)])
+            && (karate_club[&1] / 528.0 - 0.43763528).abs() < 1e-8
+            && karate_club[&12] == 0.0 {
+            Ok(())
+        } else {
+            bail!("wrong betweenness {:?}, instructor {}", path, karate_club[&1])
+        }
+    }
+
+    #[test]
+    fn personalized_pagerank_mass() -> Result<()> {
+        let epsilon = 1e-4;
+        let graph = cycle_with_tail()?;
+        let random = Graph::<(), ()>::gnm_random(50, 200, 7)?;
+        // Вершина 2 без исходящих рёбер
+        let mut directed = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 0..3 {
+            directed.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(0, 1), (1, 2), (0, 2)] {
+            directed.add_edge(from_id, to_id, None)?;
+        }
+        for (graph, seeds) in [(&graph, vec![0, 4]), (&random, vec![1]), (&directed, vec![0])] {
+            let ranks = graph.personalized_pagerank(&seeds, 0.85, epsilon)?;
+            let rank_sum: f64 = ranks.values().sum();
+            let tolerance = epsilon * (2 * graph.edge_count() + graph.vertex_count()) as f64;
+            if rank_sum > 1.0 + 1e-12 || rank_sum < 1.0 - tolerance {
+                bail!("rank mass {} lost for seeds {:?}", rank_sum, seeds)
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn personalized_pagerank_locality() -> Result<()> {
+        let graph = cycle_with_tail()?;
+        let ranks = graph.personalized_pagerank(&[0], 0.85, 1e-10)?;
+        let top_id = ranks.iter()
+            .max_by(|(_, first_rank), (_, second_rank)| first_rank.total_cmp(second_rank))
+            .map(|(&id, _)| id);
+        let missing = graph.personalized_pagerank(&[0, 10, 11], 0.85, 1e-4);
+        if top_id == Some(0)
+            && ranks[&1] > ranks[&3]
+            && ranks[&5] > ranks[&7]
+            && !ranks.contains_key(&8)
+            && !ranks.contains_key(&9)
+            && matches!(missing, Err(GraphError::VerticesNotFound(ids)) if ids == vec!["10", "11"]) {
+            Ok(())
+        } else {
+            bail!("wrong personalized pagerank {:?}", ranks)
+        }
+    }
+
+    #[test]
+    fn pagerank_invalid_parameters() -> Result<()> {
+        let graph = cycle_with_tail()?;
+        let damping_error = |value: &str| GraphError::InvalidParameter { name: "damping", value: value.to_owned(), expected: "in [0, 1)" };
+        let epsilon_error = |value: &str| GraphError::InvalidParameter { name: "epsilon", value: value.to_owned(), expected: "greater than 0" };
+        let results = [
+            (graph.pagerank_until(1.0, 1e-6).err(), Some(damping_error("1"))),
+            (graph.pagerank_until(-0.1, 1e-6).err(), Some(damping_error("-0.1"))),
+            (graph.pagerank_until(0.85, 0.0).err(), Some(epsilon_error("0"))),
+            (graph.pagerank_until(0.85, f64::NAN).err(), Some(epsilon_error("NaN"))),
+            (graph.personalized_pagerank(&[0], f64::NAN, 1e-6).err(), Some(damping_error("NaN"))),
+            (graph.personalized_pagerank(&[0], 0.85, -1e-6).err(), Some(epsilon_error("-0.000001"))),
+            (graph.pagerank_until(0.0, 1e-6).err(), None),
+        ];
+        for (error, expected_error) in results {
+            if error != expected_error {
+                bail!("expected {:?}, got {:?}", expected_error, error)
+            }
+        }
+        Ok(())
+    }
+}
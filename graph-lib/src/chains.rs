@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+use crate::distribution::DegreeKind;
+use super::*;
+
+/// Вершины, удалённые при схлопывании цепочки, в порядке от начала нового ребра к его концу
+pub type CollapsedChains<ID = DefaultGraphIdType> = HashMap<(ID, ID), Vec<ID>>;
+
+impl<VT: Clone, ET: Clone, ID: GraphId> Graph<VT, ET, ID> {
+    /// Заменяет каждую максимальную цепочку проходных вершин одним ребром. Проходная вершина неориентированного
+    /// графа имеет ровно двух различных соседей, ориентированного - одно входящее и одно исходящее ребро.
+    /// Значение нового ребра - свёртка значений рёбер цепочки функцией `combine`.
+    /// Концы цепочек сохраняются всегда, от цикла из одних проходных вершин остаётся одна вершина с петлёй.
+    /// Если между концами цепочки уже есть ребро, цепочка не схлопывается, чтобы не создавать параллельных рёбер.
+    /// Возвращает упрощённый граф и удалённые вершины для каждого нового ребра
+    pub fn collapse_chains<C>(&self, combine: C) -> (Graph<VT, ET, ID>, CollapsedChains<ID>)
+    where
+        C: Fn(Option<&ET>, Option<&ET>) -> Option<ET>,
+    {
+        let pass_through_ids = self.pass_through_ids();
+        let mut graph = Graph {
+            multigraph: self.multigraph,
+            dag: self.dag,
+            limits: self.limits,
+            ..Graph::new(self.r#type)
+        };
+        let mut chains = HashMap::new();
+
+        for (id, vertex) in &self.vertices {
+            if !pass_through_ids.contains(id) {
+                graph.vertices.insert(id.clone(), Vertex::new(id.clone(), vertex.value.clone()));
+            }
+        }
+        for (from_id, edge_direction) in self.logical_edges() {
+            if !pass_through_ids.contains(from_id) && !pass_through_ids.contains(&edge_direction.to_vertex_id) {
+                graph.add_simplified_edge(from_id.clone(), edge_direction.to_vertex_id.clone(), edge_direction.value.as_ref().clone());
+            }
+        }
+
+        let mut visited_ids = HashSet::new();
+        let end_ids: Vec<_> = graph.vertices.keys().cloned().collect();
+        for end_id in end_ids {
+            for edge_direction in &self.vertices[&end_id].edge_directions {
+                let next_id = &edge_direction.to_vertex_id;
+                if pass_through_ids.contains(next_id) && !visited_ids.contains(next_id) {
+                    self.collapse_chain(&mut graph, &mut chains, &mut visited_ids, &pass_through_ids, &end_id, edge_direction, &combine);
+                }
+            }
+        }
+        // Остались только циклы из проходных вершин
+        for id in &pass_through_ids {
+            if visited_ids.insert(id.clone()) {
+                graph.vertices.insert(id.clone(), Vertex::new(id.clone(), self.vertices[id].value.clone()));
+                let edge_direction = &self.vertices[id].edge_directions[0];
+                self.collapse_chain(&mut graph, &mut chains, &mut visited_ids, &pass_through_ids, id, edge_direction, &combine);
+            }
+        }
+
+        (graph, chains)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn collapse_chain<C>(
+        &self,
+        graph: &mut Graph<VT, ET, ID>,
+        chains: &mut CollapsedChains<ID>,
+        visited_ids: &mut HashSet<ID>,
+        pass_through_ids: &HashSet<ID>,
+        start_id: &ID,
+        first_edge_direction: &EdgeDirection<ET, ID>,
+        combine: &C,
+    )
+    where
+        C: Fn(Option<&ET>, Option<&ET>) -> Option<ET>,
+    {
+        let mut chain_edges = vec![(start_id, first_edge_direction)];
+        let mut value = first_edge_direction.value.as_ref().clone();
+        let mut interior_ids = Vec::new();
+        let mut previous_id = start_id;
+        let mut current_id = &first_edge_direction.to_vertex_id;
+        while pass_through_ids.contains(current_id) && current_id != start_id {
+            visited_ids.insert(current_id.clone());
+            interior_ids.push(current_id.clone());
+            let next_edge_direction = self.vertices[current_id].edge_directions
+                .iter()
+                .find(|edge_direction| match self.r#type {
+                    GraphType::Directed => true,
+                    GraphType::Undirected => edge_direction.to_vertex_id != *previous_id,
+                })
+                .expect("pass-through vertex has an edge leading further");
+            chain_edges.push((current_id, next_edge_direction));
+            value = combine(value.as_ref(), next_edge_direction.value.as_ref().as_ref());
+            previous_id = current_id;
+            current_id = &next_edge_direction.to_vertex_id;
+        }
+
+        let end_id = current_id;
+        let parallel_edge_exists = graph.contains_vertex(end_id)
+            && (graph.contains_edge_direction(start_id, end_id).unwrap_or(false)
+                || graph.contains_edge_direction(end_id, start_id).unwrap_or(false));
+        if parallel_edge_exists {
+            for interior_id in &interior_ids {
+                graph.vertices.insert(interior_id.clone(), Vertex::new(interior_id.clone(), self.vertices[interior_id].value.clone()));
+            }
+            for (from_id, edge_direction) in chain_edges {
+                graph.add_simplified_edge(from_id.clone(), edge_direction.to_vertex_id.clone(), edge_direction.value.as_ref().clone());
+            }
+        } else {
+            graph.add_simplified_edge(start_id.clone(), end_id.clone(), value);
+            chains.insert((start_id.clone(), end_id.clone()), interior_ids);
+        }
+    }
+
+    fn pass_through_ids(&self) -> HashSet<ID> {
+        let in_degrees = match self.r#type {
+            GraphType::Directed => self.degrees(DegreeKind::In),
+            GraphType::Undirected => HashMap::new(),
+        };
+        self.vertices.iter()
+            .filter(|(id, vertex)| match (self.r#type, vertex.edge_directions.as_slice()) {
+                (GraphType::Undirected, [first, second]) => first.to_vertex_id != second.to_vertex_id
+                    && first.to_vertex_id != **id
+                    && second.to_vertex_id != **id,
+                (GraphType::Directed, [out_edge]) => out_edge.to_vertex_id != **id && in_degrees[id] == 1,
+                _ => false,
+            })
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    fn add_simplified_edge(&mut self, from_id: ID, to_id: ID, value: Option<ET>) {
+        self.add_edge(from_id, to_id, value)
+            .expect("simplified graph edges connect kept vertices without exceeding source limits");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn path_graph(r#type: GraphType, edges: &[(u32, u32, u32)]) -> Result<Graph<(), u32>> {
+        let mut graph = Graph::new(r#type);
+        for &(from_id, to_id, _) in edges {
+            for id in [from_id, to_id] {
+                if !graph.contains_vertex(&id) {
+                    graph.add_vertex(Vertex::new(id, None))?;
+                }
+            }
+        }
+        for &(from_id, to_id, weight) in edges {
+            graph.add_edge(from_id, to_id, Some(weight))?;
+        }
+        Ok(graph)
+    }
+
+    fn sum(first: Option<&u32>, second: Option<&u32>) -> Option<u32> {
+        Some(first.copied().unwrap_or(0) + second.copied().unwrap_or(0))
+    }
+
+    /// Полный путь исходного графа по пути упрощённого графа
+    fn expand_path(path: &[u32], chains: &CollapsedChains) -> Vec<u32> {
+        let mut full_path = vec![path[0]];
+        for pair in path.windows(2) {
+            match (chains.get(&(pair[0], pair[1])), chains.get(&(pair[1], pair[0]))) {
+                (Some(interior_ids), _) => full_path.extend(interior_ids),
+                (None, Some(interior_ids)) => full_path.extend(interior_ids.iter().rev()),
+                (None, None) => {}
+            }
+            full_path.push(pair[1]);
+        }
+        full_path
+    }
+
+    #[test]
+    fn collapse_path() -> Result<()> {
+        let edges: Vec<_> = (1..10).map(|id| (id, id + 1, id)).collect();
+        let graph = path_graph(GraphType::Undirected, &edges)?;
+        let (simplified, chains) = graph.collapse_chains(sum);
+        let interior_ids: Vec<_> = (2..10).collect();
+        let chain_recorded = chains.get(&(1, 10)) == Some(&interior_ids)
+            || chains.get(&(10, 1)).is_some_and(|ids| ids.iter().rev().eq(interior_ids.iter()));
+        if simplified.vertex_count() == 2
+            && simplified.edges().iter().map(|&(_, _, value)| value.copied()).eq([Some(45)])
+            && chains.len() == 1
+            && chain_recorded {
+            Ok(())
+        } else {
+            bail!("path not collapsed: {:?}", chains)
+        }
+    }
+
+    #[test]
+    fn expanded_shortest_paths() -> Result<()> {
+        // Две развилки 1 и 2, соединённые тремя цепочками разной длины, и тупиковая ветка 2 - 9 - 10 - 11
+        let graph = path_graph(GraphType::Undirected, &[
+            (1, 3, 1), (3, 4, 1), (4, 2, 1),
+            (1, 5, 1), (5, 2, 1),
+            (1, 6, 2), (6, 7, 2), (7, 8, 2), (8, 2, 2),
+            (2, 9, 1), (9, 10, 1), (10, 11, 1),
+        ])?;
+        let (simplified, chains) = graph.collapse_chains(sum);
+        for (from_id, to_id) in [(1, 11), (11, 1), (1, 2)] {
+            let expected = graph.astar(from_id, to_id, |&weight| f64::from(weight), |_| 0.0)?;
+            let simplified_path = simplified.astar(from_id, to_id, |&weight| f64::from(weight), |_| 0.0)?
+                .map(|(cost, path)| (cost, expand_path(&path, &chains)));
+            if simplified_path != expected {
+                bail!("expanded path {:?} differs from {:?}", simplified_path, expected)
+            }
+        }
+        // Одна из цепочек между 1 и 2 схлопнута, остальные параллельны ей и остаются
+        if simplified.vertex_count() < graph.vertex_count() && simplified.edge_count() < graph.edge_count() {
+            Ok(())
+        } else {
+            bail!("graph not simplified")
+        }
+    }
+
+    #[test]
+    fn pure_cycle() -> Result<()> {
+        let graph = path_graph(GraphType::Undirected, &[(1, 2, 1), (2, 3, 1), (3, 4, 1), (4, 5, 1), (5, 1, 1)])?;
+        let (simplified, chains) = graph.collapse_chains(sum);
+        let directed_graph = path_graph(GraphType::Directed, &[(1, 2, 1), (2, 3, 1), (3, 1, 1), (3, 4, 1)])?;
+        let (directed_simplified, directed_chains) = directed_graph.collapse_chains(sum);
+        if simplified.vertex_count() == 1
+            && simplified.edge_count() == 1
+            && simplified.edges()[0].2 == Some(&5)
+            && chains.values().next().is_some_and(|ids| ids.len() == 4)
+            && directed_simplified.vertex_count() == 2
+            && directed_chains.get(&(3, 3)) == Some(&vec![1, 2])
+            && directed_simplified.edges().len() == 2 {
+            Ok(())
+        } else {
+            bail!("wrong cycle collapse: {:?} {:?}", chains, directed_chains)
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+use super::*;
+
+/// Вершина со значением
+pub type VertexEntry<'a, VT, ID = DefaultGraphIdType> = (ID, Option<&'a VT>);
+/// Ребро со значением
+pub type EdgeEntry<'a, ET, ID = DefaultGraphIdType> = (ID, ID, Option<&'a ET>);
+/// Вершина с прежним и новым значением
+pub type ChangedVertex<'a, VT, ID = DefaultGraphIdType> = (ID, Option<&'a VT>, Option<&'a VT>);
+/// Ребро с прежним и новым значением
+pub type ChangedEdge<'a, ET, ID = DefaultGraphIdType> = (ID, ID, Option<&'a ET>, Option<&'a ET>);
+
+/// Различия двух версий графа, см. `Graph::diff`. Списки упорядочены по идентификаторам.
+/// Ребро неориентированного графа записывается от меньшего идентификатора к большему
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphChanges<'a, VT, ET, ID = DefaultGraphIdType> {
+    pub added_vertices: Vec<VertexEntry<'a, VT, ID>>,
+    pub removed_vertices: Vec<VertexEntry<'a, VT, ID>>,
+    pub changed_vertices: Vec<ChangedVertex<'a, VT, ID>>,
+    pub added_edges: Vec<EdgeEntry<'a, ET, ID>>,
+    pub removed_edges: Vec<EdgeEntry<'a, ET, ID>>,
+    pub changed_edges: Vec<ChangedEdge<'a, ET, ID>>,
+}
+
+impl<VT, ET, ID> GraphChanges<'_, VT, ET, ID> {
+    pub fn is_empty(&self) -> bool {
+        self.added_vertices.is_empty()
+            && self.removed_vertices.is_empty()
+            && self.changed_vertices.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_edges.is_empty()
+    }
+}
+
+/// По строке на изменение: `+` - добавлено, `-` - удалено, `~` - изменено значение, `(none)` - нет значения
+impl<VT: Display, ET: Display, ID: Display> Display for GraphChanges<'_, VT, ET, ID> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (id, value) in &self.added_vertices {
+            writeln!(formatter, "+ vertex {} {}", id, ValueOrNone(*value))?;
+        }
+        for (id, value) in &self.removed_vertices {
+            writeln!(formatter, "- vertex {} {}", id, ValueOrNone(*value))?;
+        }
+        for (id, old_value, new_value) in &self.changed_vertices {
+            writeln!(formatter, "~ vertex {} {} -> {}", id, ValueOrNone(*old_value), ValueOrNone(*new_value))?;
+        }
+        for (from_id, to_id, value) in &self.added_edges {
+            writeln!(formatter, "+ edge {} {} {}", from_id, to_id, ValueOrNone(*value))?;
+        }
+        for (from_id, to_id, value) in &self.removed_edges {
+            writeln!(formatter, "- edge {} {} {}", from_id, to_id, ValueOrNone(*value))?;
+        }
+        for (from_id, to_id, old_value, new_value) in &self.changed_edges {
+            writeln!(formatter, "~ edge {} {} {} -> {}", from_id, to_id, ValueOrNone(*old_value), ValueOrNone(*new_value))?;
+        }
+        Ok(())
+    }
+}
+
+struct ValueOrNone<'a, T>(Option<&'a T>);
+
+impl<T: Display> Display for ValueOrNone<'_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(value) => write!(formatter, "{}", value),
+            None => write!(formatter, "(none)"),
+        }
+    }
+}
+
+impl<VT: PartialEq, ET: PartialEq, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Что изменилось в `other` по сравнению с этим графом. Рёбра сравниваются по концам (у неориентированного
+    /// графа - без учёта порядка). Из кратных рёбер между одними вершинами сначала сопоставляются рёбра
+    /// с равными значениями, затем оставшиеся попарно считаются изменёнными, а лишние - добавленными или удалёнными.
+    /// Графы разных типов сравнивать нельзя
+    pub fn diff<'a>(&'a self, other: &'a Graph<VT, ET, ID>) -> Result<GraphChanges<'a, VT, ET, ID>, GraphError> {
+        if self.r#type != other.r#type {
+            return Err(GraphError::WrongGraphType { expected: self.r#type.name(), actual: other.r#type.name() });
+        }
+        let mut changes = GraphChanges {
+            added_vertices: Vec::new(),
+            removed_vertices: Vec::new(),
+            changed_vertices: Vec::new(),
+            added_edges: Vec::new(),
+            removed_edges: Vec::new(),
+            changed_edges: Vec::new(),
+        };
+
+        for (id, vertex) in &self.vertices {
+            match other.vertices.get(id) {
+                None => changes.removed_vertices.push((id.clone(), vertex.value.as_ref())),
+                Some(other_vertex) if other_vertex.value != vertex.value => {
+                    changes.changed_vertices.push((id.clone(), vertex.value.as_ref(), other_vertex.value.as_ref()));
+                }
+                Some(_) => {}
+            }
+        }
+        changes.added_vertices = other.vertices.iter()
+            .filter(|(id, _)| !self.contains_vertex(id))
+            .map(|(id, vertex)| (id.clone(), vertex.value.as_ref()))
+            .collect();
+
+        let mut edges = self.edge_values_by_ends();
+        let mut other_edges = other.edge_values_by_ends();
+        for (ends, values) in &mut edges {
+            let other_values = other_edges.remove(ends).unwrap_or_default();
+            // Рёбра с равными значениями не изменились
+            let mut unmatched_other = Vec::new();
+            for other_value in other_values {
+                match values.iter().position(|value| *value == other_value) {
+                    Some(position) => {
+                        values.swap_remove(position);
+                    }
+                    None => unmatched_other.push(other_value),
+                }
+            }
+            let (from_id, to_id) = ends.clone();
+            let changed_count = values.len().min(unmatched_other.len());
+            for (value, other_value) in values.drain(..changed_count).zip(unmatched_other.drain(..changed_count)) {
+                changes.changed_edges.push((from_id.clone(), to_id.clone(), value, other_value));
+            }
+            changes.removed_edges.extend(values.drain(..).map(|value| (from_id.clone(), to_id.clone(), value)));
+            changes.added_edges.extend(unmatched_other.into_iter().map(|value| (from_id.clone(), to_id.clone(), value)));
+        }
+        for ((from_id, to_id), values) in other_edges {
+            changes.added_edges.extend(values.into_iter().map(|value| (from_id.clone(), to_id.clone(), value)));
+        }
+
+        changes.added_vertices.sort_unstable_by(|(id, _), (other_id, _)| id.cmp(other_id));
+        changes.removed_vertices.sort_unstable_by(|(id, _), (other_id, _)| id.cmp(other_id));
+        changes.changed_vertices.sort_unstable_by(|(id, ..), (other_id, ..)| id.cmp(other_id));
+        changes.added_edges.sort_by(|(from_id, to_id, _), (other_from_id, other_to_id, _)| (from_id, to_id).cmp(&(other_from_id, other_to_id)));
+        changes.removed_edges.sort_by(|(from_id, to_id, _), (other_from_id, other_to_id, _)| (from_id, to_id).cmp(&(other_from_id, other_to_id)));
+        changes.changed_edges.sort_by(|(from_id, to_id, ..), (other_from_id, other_to_id, ..)| (from_id, to_id).cmp(&(other_from_id, other_to_id)));
+        Ok(changes)
+    }
+
+    /// Значения рёбер по концам. Концы ребра неориентированного графа упорядочены
+    fn edge_values_by_ends(&self) -> HashMap<(ID, ID), Vec<Option<&ET>>> {
+        let mut edges: HashMap<_, Vec<_>> = HashMap::new();
+        for (from_id, to_id, value) in self.edges() {
+            let ends = match self.r#type {
+                GraphType::Undirected if to_id < from_id => (to_id, from_id),
+                _ => (from_id, to_id),
+            };
+            edges.entry(ends).or_default().push(value);
+        }
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const TGF_GRAPH: &str = "1 January\n2 March\n3 April\n#\n1 2 first\n3 2 second\n";
+
+    #[test]
+    fn diff_with_itself_is_empty() -> Result<()> {
+        let graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let changes = graph.diff(&graph)?;
+        let directed = Graph::<String, String>::new(GraphType::Directed);
+        if changes.is_empty()
+            && changes.to_string().is_empty()
+            && graph.diff(&directed) == Err(GraphError::WrongGraphType { expected: "undirected", actual: "directed" }) {
+            Ok(())
+        } else {
+            bail!("graph differs from itself:\n{}", changes)
+        }
+    }
+
+    #[test]
+    fn one_edge_value_changed() -> Result<()> {
+        let graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let mut other = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        other.delete_edge(2, 3)?;
+        other.add_edge(2, 3, Some("renamed".to_owned()))?;
+        let changes = graph.diff(&other)?;
+        if changes.to_string() == "~ edge 2 3 second -> renamed\n" && changes.changed_edges.len() == 1 {
+            Ok(())
+        } else {
+            bail!("wrong changes:\n{}", changes)
+        }
+    }
+
+    #[test]
+    fn added_removed_and_changed() -> Result<()> {
+        let mut graph = Graph::<String, String>::new_multigraph(GraphType::Directed);
+        let mut other = Graph::new_multigraph(GraphType::Directed);
+        for (id, value) in [(1, "a"), (2, "b"), (3, "c")] {
+            graph.add_vertex(Vertex::new(id, Some(value.to_owned())))?;
+        }
+        for (id, value) in [(1, Some("a")), (2, None), (4, Some("d"))] {
+            other.add_vertex(Vertex::new(id, value.map(str::to_owned)))?;
+        }
+        graph.add_edge(1, 2, Some("x".to_owned()))?;
+        graph.add_edge(1, 2, Some("y".to_owned()))?;
+        graph.add_edge(2, 3, None)?;
+        other.add_edge(1, 2, Some("y".to_owned()))?;
+        other.add_edge(2, 1, Some("x".to_owned()))?;
+        other.add_edge(4, 1, None)?;
+        let changes = graph.diff(&other)?;
+        let expected = "+ vertex 4 d\n- vertex 3 c\n~ vertex 2 b -> (none)\n+ edge 2 1 x\n+ edge 4 1 (none)\n\
+            - edge 1 2 x\n- edge 2 3 (none)\n";
+        if changes.to_string() == expected && !changes.is_empty() {
+            Ok(())
+        } else {
+            bail!("wrong changes:\n{}", changes)
+        }
+    }
+}
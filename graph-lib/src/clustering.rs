@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+use crate::utils::UnionFind;
+use super::*;
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Кластеризация методом одиночной связи на `k` кластеров (только для неориентированного графа):
+    /// из минимального остовного дерева связного графа удаляются `k - 1` самых тяжёлых рёбер, кластеры -
+    /// получившиеся компоненты. У несвязного графа кластеров не меньше, чем компонент связности,
+    /// при `k` больше числа вершин каждая вершина - отдельный кластер.
+    /// Вершины кластера упорядочены по идентификаторам, кластеры - по наименьшему идентификатору
+    pub fn single_linkage_clusters<W: Fn(&ET) -> f64>(&self, weight: W, k: usize) -> Result<Vec<Vec<ID>>, GraphError> {
+        let tree = self.minimum_spanning_tree(weight)?;
+        let mut ids: Vec<_> = self.vertices.keys().collect();
+        ids.sort_unstable();
+        let indices: HashMap<_, _> = ids.iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+
+        // Рёбра дерева идут по возрастанию веса, и каждое объединяет два кластера: из n вершин k кластеров
+        // получаются после n - k слияний, а у несвязного графа рёбер дерева может не хватить
+        let merge_count = ids.len().saturating_sub(k.max(1)).min(tree.len());
+        let mut components = UnionFind::new(ids.len());
+        for (from_id, to_id, _) in &tree[..merge_count] {
+            components.union(indices[from_id], indices[to_id]);
+        }
+
+        let mut clusters: Vec<Vec<ID>> = Vec::new();
+        let mut cluster_indices = HashMap::new();
+        for (index, &id) in ids.iter().enumerate() {
+            let cluster_index = *cluster_indices.entry(components.find(index)).or_insert_with(|| {
+                clusters.push(Vec::new());
+                clusters.len() - 1
+            });
+            clusters[cluster_index].push(id.clone());
+        }
+        Ok(clusters)
+    }
+
+    /// Последовательность слияний кластеров при кластеризации одиночной связью: рёбра минимального остовного
+    /// дерева по неубыванию веса. Каждое ребро объединяет кластеры, содержащие его концы.
+    /// Только для неориентированного графа
+    pub fn single_linkage_dendrogram<W: Fn(&ET) -> f64>(&self, weight: W) -> Result<Vec<WeightedEdge<ID>>, GraphError> {
+        self.minimum_spanning_tree(weight)
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Локальный коэффициент кластеризации вершины (только для неориентированного графа): доля пар её соседей,
+    /// соединённых ребром. Параллельные рёбра и петли не учитываются, у вершины меньше чем с двумя соседями - 0
+    pub fn clustering_coefficient(&self, vertex_id: ID) -> Result<f64, GraphError> {
+        if self.r#type != GraphType::Undirected {
+            return Err(GraphError::WrongGraphType { expected: GraphType::Undirected.name(), actual: self.r#type.name() });
+        }
+        if !self.contains_vertex(&vertex_id) {
+            return Err(GraphError::VertexNotFound(vertex_id.to_string()));
+        }
+        // Достаточно соседей самой вершины и соседей каждого из них
+        let own_neighbours = self.distinct_neighbours(&vertex_id);
+        let mut neighbours: HashMap<_, _> = own_neighbours.iter()
+            .map(|&neighbour_id| (neighbour_id, self.distinct_neighbours(neighbour_id)))
+            .collect();
+        neighbours.insert(&vertex_id, own_neighbours);
+        Ok(local_clustering(&neighbours, &vertex_id))
+    }
+
+    /// Средний по всем вершинам локальный коэффициент кластеризации, см. `clustering_coefficient`.
+    /// У графа без вершин - 0
+    pub fn average_clustering_coefficient(&self) -> Result<f64, GraphError> {
+        if self.r#type != GraphType::Undirected {
+            return Err(GraphError::WrongGraphType { expected: GraphType::Undirected.name(), actual: self.r#type.name() });
+        }
+        if self.vertices.is_empty() {
+            return Ok(0.0);
+        }
+        let neighbours = self.neighbour_sets();
+        let total: f64 = self.vertices.keys()
+            .map(|id| local_clustering(&neighbours, id))
+            .sum();
+        Ok(total / self.vertices.len() as f64)
+    }
+
+    /// Различные соседи вершины без неё самой. У вершины неориентированного графа хранятся и её рёбра,
+    /// и зеркальные копии рёбер соседей, поэтому каждый сосед встречается хотя бы раз
+    fn distinct_neighbours(&self, vertex_id: &ID) -> HashSet<&ID> {
+        self.vertices[vertex_id].edge_directions
+            .iter()
+            .map(|edge_direction| &edge_direction.to_vertex_id)
+            .filter(|&to_id| to_id != vertex_id)
+            .collect()
+    }
+
+    /// Различные соседи каждой вершины без неё самой. Ребро берётся один раз у владельца
+    /// и записывается обоим концам, зеркальные копии у второго конца пропускаются
+    fn neighbour_sets(&self) -> HashMap<&ID, HashSet<&ID>> {
+        let mut neighbours: HashMap<&ID, HashSet<&ID>> = self.vertices.keys()
+            .map(|id| (id, HashSet::new()))
+            .collect();
+        for (from_id, edge_direction) in self.logical_edges() {
+            let to_id = &edge_direction.to_vertex_id;
+            if from_id != to_id {
+                neighbours.entry(from_id).or_default().insert(to_id);
+                neighbours.entry(to_id).or_default().insert(from_id);
+            }
+        }
+        neighbours
+    }
+}
+
+/// Доля пар соседей вершины, соединённых ребром: каждый треугольник с вершиной виден от обоих соседей
+fn local_clustering<ID: GraphId>(neighbours: &HashMap<&ID, HashSet<&ID>>, vertex_id: &ID) -> f64 {
+    let own_neighbours = &neighbours[vertex_id];
+    let degree = own_neighbours.len();
+    if degree < 2 {
+        return 0.0;
+    }
+    let linked_pairs: usize = own_neighbours.iter()
+        .map(|neighbour_id| neighbours[neighbour_id].intersection(own_neighbours).count())
+        .sum();
+    linked_pairs as f64 / (degree * (degree - 1)) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Два треугольника с лёгкими рёбрами, соединённые тяжёлым ребром 3 - 4, и изолированная вершина 7
+    fn two_clusters() -> Result<Graph<(), f64>> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for id in 1..=7 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id, weight) in [(1, 2, 1.0), (2, 3, 2.0), (3, 1, 1.5), (4, 5, 1.0), (5, 6, 1.0), (6, 4, 3.0), (3, 4, 10.0)] {
+            graph.add_edge(from_id, to_id, Some(weight))?;
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn heavy_edge_splits_clusters() -> Result<()> {
+        let graph = two_clusters()?;
+        let clusters = graph.single_linkage_clusters(|&weight| weight, 3)?;
+        let whole = graph.single_linkage_clusters(|&weight| weight, 1)?;
+        let singletons = graph.single_linkage_clusters(|&weight| weight, 10)?;
+        let directed = Graph::<(), f64>::new(GraphType::Directed).single_linkage_clusters(|&weight| weight, 2);
+        if clusters == vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]
+            && whole == vec![vec![1, 2, 3, 4, 5, 6], vec![7]]
+            && whole == graph.single_linkage_clusters(|&weight| weight, 0)?
+            && singletons == (1..=7).map(|id| vec![id]).collect::<Vec<_>>()
+            && matches!(directed, Err(GraphError::WrongGraphType { .. })) {
+            Ok(())
+        } else {
+            bail!("wrong clusters {:?}, {:?}", clusters, whole)
+        }
+    }
+
+    #[test]
+    fn connected_graph_splits_into_k() -> Result<()> {
+        let mut graph = two_clusters()?;
+        graph.delete_vertex(7)?;
+        let clusters = graph.single_linkage_clusters(|&weight| weight, 2)?;
+        let whole = graph.single_linkage_clusters(|&weight| weight, 1)?;
+        if clusters == vec![vec![1, 2, 3], vec![4, 5, 6]]
+            && whole == graph.connected_components().into_iter().map(|mut component| {
+                component.sort_unstable();
+                component
+            }).collect::<Vec<_>>() {
+            Ok(())
+        } else {
+            bail!("wrong clusters {:?}", clusters)
+        }
+    }
+
+    #[test]
+    fn dendrogram_weights_nondecreasing() -> Result<()> {
+        let graph = two_clusters()?;
+        let dendrogram = graph.single_linkage_dendrogram(|&weight| weight)?;
+        let weights: Vec<_> = dendrogram.iter().map(|&(_, _, weight)| weight).collect();
+        if weights == vec![1.0, 1.0, 1.0, 1.5, 10.0]
+            && weights.windows(2).all(|pair| pair[0] <= pair[1]) {
+            Ok(())
+        } else {
+            bail!("wrong dendrogram {:?}", dendrogram)
+        }
+    }
+
+    #[test]
+    fn clustering_coefficients() -> Result<()> {
+        // Треугольник 1 - 2 - 3 и висячая вершина 4 у вершины 3
+        let mut graph = Graph::<(), ()>::new_multigraph(GraphType::Undirected);
+        for id in 1..=4 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(1, 2), (2, 3), (3, 1), (3, 4)] {
+            graph.add_edge(from_id, to_id, None)?;
+        }
+        let coefficients: Vec<_> = (1..=4).map(|id| graph.clustering_coefficient(id)).collect::<Result<_, _>>()?;
+        let average = graph.average_clustering_coefficient()?;
+        // Параллельные рёбра, ребро в обратную сторону и петля не меняют коэффициенты
+        graph.add_edge(2, 1, None)?;
+        graph.add_edge(3, 4, None)?;
+        graph.add_edge(3, 3, None)?;
+        let with_parallel: Vec<_> = (1..=4).map(|id| graph.clustering_coefficient(id)).collect::<Result<_, _>>()?;
+        if coefficients == vec![1.0, 1.0, 1.0 / 3.0, 0.0]
+            && (average - 7.0 / 12.0).abs() < 1e-12
+            && with_parallel == coefficients
+            && graph.clustering_coefficient(5) == Err(GraphError::VertexNotFound("5".to_owned()))
+            && Graph::<(), ()>::new(GraphType::Directed).average_clustering_coefficient().is_err() {
+            Ok(())
+        } else {
+            bail!("wrong clustering coefficients {:?}, average {}", coefficients, average)
+        }
+    }
+
+    #[test]
+    fn karate_club_average_clustering() -> Result<()> {
+        let graph = Graph::<String, ()>::karate_club();
+        let average = graph.average_clustering_coefficient()?;
+        let mut total = 0.0;
+        for id in 1..=34 {
+            total += graph.clustering_coefficient(id)?;
+        }
+        let complete = Graph::<(), ()>::complete(5, GraphType::Undirected).average_clustering_coefficient()?;
+        if (average - 0.5706384782076823).abs() < 1e-9
+            && (total / 34.0 - average).abs() < 1e-12
+            && complete == 1.0 {
+            Ok(())
+        } else {
+            bail!("wrong average clustering {}, complete graph {}", average, complete)
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::cmp::Reverse;
+use super::*;
+
+/// Раскраска вершин, см. `Graph::greedy_coloring`
+#[derive(Debug, Clone)]
+pub struct Coloring<ID = DefaultGraphIdType> {
+    /// Цвет каждой вершины: 0, 1, ... `color_count - 1`
+    pub colors: HashMap<ID, u32>,
+    pub color_count: u32,
+}
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Правильная раскраска неориентированного графа жадным алгоритмом Уэлша - Пауэлла: вершины по убыванию
+    /// степени (при равной степени - по идентификатору) получают наименьший цвет, не занятый соседями.
+    /// Результат не зависит от порядка добавления вершин и рёбер. Цветов не больше, чем наибольшая степень плюс один.
+    /// Ориентированный граф - ошибка `WrongGraphType`, петля - `SelfLoop` с наименьшей вершиной с петлёй
+    pub fn greedy_coloring(&self) -> Result<Coloring<ID>, GraphError> {
+        if self.r#type == GraphType::Directed {
+            return Err(GraphError::WrongGraphType { expected: GraphType::Undirected.name(), actual: self.r#type.name() });
+        }
+        let self_loop_id = self.vertices.values()
+            .filter(|vertex| vertex.has_edge_direction_to(&vertex.id))
+            .map(|vertex| &vertex.id)
+            .min();
+        if let Some(id) = self_loop_id {
+            return Err(GraphError::SelfLoop(id.to_string()));
+        }
+
+        let mut vertices: Vec<_> = self.vertices.values().collect();
+        vertices.sort_unstable_by_key(|vertex| (Reverse(vertex.edge_directions.len()), &vertex.id));
+        let mut colors = HashMap::with_capacity(vertices.len());
+        let mut color_count = 0;
+        for vertex in vertices {
+            // Среди цветов 0..=степень всегда есть свободный
+            let mut used_colors = vec![false; vertex.edge_directions.len() + 1];
+            for edge_direction in &vertex.edge_directions {
+                if let Some(&color) = colors.get(&edge_direction.to_vertex_id) {
+                    if let Some(used) = used_colors.get_mut(color as usize) {
+                        *used = true;
+                    }
+                }
+            }
+            let color = used_colors.iter().position(|&used| !used).expect("one of degree + 1 colors is free") as u32;
+            color_count = color_count.max(color + 1);
+            colors.insert(vertex.id.clone(), color);
+        }
+        Ok(Coloring { colors, color_count })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn random_graph_coloring_is_proper() -> Result<()> {
+        let graph = Graph::<(), ()>::gnm_random(60, 300, 7)?;
+        let coloring = graph.greedy_coloring()?;
+        let max_degree = (0..60).map(|id| graph.degree(id)).collect::<Result<Vec<_>, _>>()?.into_iter().max().unwrap_or(0);
+        let conflicts: Vec<_> = graph.edges()
+            .into_iter()
+            .filter(|(from_id, to_id, _)| coloring.colors[from_id] == coloring.colors[to_id])
+            .collect();
+
+        // Тот же граф, построенный в обратном порядке, раскрашивается так же
+        let mut reversed = Graph::<(), ()>::new(GraphType::Undirected);
+        for id in (0..60).rev() {
+            reversed.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id, _) in graph.edges().into_iter().rev() {
+            reversed.add_edge(to_id, from_id, None)?;
+        }
+        if conflicts.is_empty()
+            && coloring.colors.len() == 60
+            && coloring.color_count as usize <= max_degree + 1
+            && coloring.colors.values().all(|&color| color < coloring.color_count)
+            && reversed.greedy_coloring()?.colors == coloring.colors {
+            Ok(())
+        } else {
+            bail!("improper or unstable coloring, conflicting edges: {:?}", conflicts)
+        }
+    }
+
+    #[test]
+    fn odd_cycle_needs_three_colors() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Undirected);
+        for id in 1..=5 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..=5 {
+            graph.add_edge(id, id % 5 + 1, None)?;
+        }
+        let coloring = graph.greedy_coloring()?;
+        let directed = Graph::<(), ()>::new(GraphType::Directed);
+        graph.add_edge(4, 4, None)?;
+        graph.add_edge(2, 2, None)?;
+        if coloring.color_count == 3
+            && coloring.colors[&1] == 0
+            && graph.greedy_coloring().err() == Some(GraphError::SelfLoop("2".to_owned()))
+            && directed.greedy_coloring().err() == Some(GraphError::WrongGraphType { expected: "undirected", actual: "directed" }) {
+            Ok(())
+        } else {
+            bail!("wrong coloring of a 5-cycle: {:?}", coloring)
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use crate::utils::UnionFind;
+use super::*;
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Компоненты связности, от большей к меньшей. В ориентированном графе направление рёбер не учитывается
+    /// (компоненты слабой связности)
+    pub fn connected_components(&self) -> Vec<Vec<ID>> {
+        let ids: Vec<_> = self.vertices.keys().collect();
+        let indices: HashMap<_, _> = ids.iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        let mut components = UnionFind::new(ids.len());
+        for (from_id, edge_direction) in self.logical_edges() {
+            components.union(indices[from_id], indices[&edge_direction.to_vertex_id]);
+        }
+
+        let mut component_ids: HashMap<usize, Vec<ID>> = HashMap::new();
+        for (index, id) in ids.into_iter().enumerate() {
+            component_ids.entry(components.find(index)).or_default().push(id.clone());
+        }
+        let mut components: Vec<_> = component_ids.into_values().collect();
+        components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn connected_components() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=6 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(3, 2, None)?;
+        graph.add_edge(4, 5, None)?;
+        let mut components = graph.connected_components();
+        for component in &mut components {
+            component.sort_unstable();
+        }
+        if components[0] == vec![1, 2, 3] && components[1] == vec![4, 5] && components[2] == vec![6] && components.len() == 3 {
+            Ok(())
+        } else {
+            bail!("wrong connected components {:?}", components)
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+};
+use crate::bulk::NewEdge;
+use crate::distribution::DegreeKind;
+use super::*;
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Ориентированный ациклический граф: `add_edge` отклоняет ребро, замыкающее цикл, ошибкой `WouldCreateCycle`.
+    /// Остальные операции работают как в обычном ориентированном графе
+    pub fn new_dag() -> Graph<VT, ET, ID> {
+        Graph {
+            dag: true,
+            ..Graph::new(GraphType::Directed)
+        }
+    }
+
+    pub fn is_dag(&self) -> bool {
+        self.dag
+    }
+
+    /// Ребро `from_id -> to_id` замыкает цикл, если `from_id` достижима из `to_id`; петля - цикл из одной вершины
+    pub(crate) fn check_acyclic_edge(&self, from_id: &ID, to_id: &ID) -> Result<(), GraphError> {
+        match self.shortest_path(to_id.clone(), from_id.clone())? {
+            Some(path) => Err(GraphError::WouldCreateCycle {
+                from: from_id.to_string(),
+                to: to_id.to_string(),
+                witness_path: path.iter().map(ToString::to_string).collect(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Проверка пакета рёбер, как `check_acyclic_edge` для каждого ребра по очереди: ребро пакета замыкает цикл,
+    /// если его начало достижимо из конца по рёбрам графа и предыдущим рёбрам пакета. Граф не меняется
+    pub(crate) fn check_acyclic_edges(&self, edges: &[NewEdge<ET, ID>]) -> Result<(), (usize, GraphError)> {
+        let mut new_successors: HashMap<&ID, Vec<&ID>> = HashMap::new();
+        for (index, (from_id, to_id, _)) in edges.iter().enumerate() {
+            if let Some(path) = self.path_with_new_edges(to_id, from_id, &new_successors) {
+                return Err((index, GraphError::WouldCreateCycle {
+                    from: from_id.to_string(),
+                    to: to_id.to_string(),
+                    witness_path: path.iter().map(ToString::to_string).collect(),
+                }));
+            }
+            new_successors.entry(from_id).or_default().push(to_id);
+        }
+        Ok(())
+    }
+
+    /// Кратчайший путь обходом в ширину по рёбрам графа и дополнительным рёбрам `new_successors`
+    fn path_with_new_edges<'a>(&'a self, start_id: &'a ID, end_id: &ID, new_successors: &HashMap<&ID, Vec<&'a ID>>) -> Option<Vec<&'a ID>> {
+        let mut parents: HashMap<&ID, Option<&ID>> = HashMap::from([(start_id, None)]);
+        let mut queue = VecDeque::from([start_id]);
+        while let Some(id) = queue.pop_front() {
+            if id == end_id {
+                let mut path = vec![id];
+                while let Some(&Some(parent_id)) = parents.get(path[path.len() - 1]) {
+                    path.push(parent_id);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let successors = self.vertices[id].edge_directions.iter().map(|edge_direction| &edge_direction.to_vertex_id);
+            for successor_id in successors.chain(new_successors.get(id).into_iter().flatten().copied()) {
+                if !parents.contains_key(successor_id) {
+                    parents.insert(successor_id, Some(id));
+                    queue.push_back(successor_id);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Топологическая сортировка ориентированного графа (алгоритм Кана): каждое ребро ведёт от более ранней
+    /// вершины к более поздней. Из допустимых порядков выбирается лексикографически наименьший по идентификаторам,
+    /// поэтому результат не зависит от порядка добавления. Неориентированный граф - ошибка `WrongGraphType`,
+    /// граф с циклом (включая петлю) - `CycleDetected`
+    pub fn topological_sort(&self) -> Result<Vec<ID>, GraphError> {
+        if self.r#type == GraphType::Undirected {
+            return Err(GraphError::WrongGraphType { expected: GraphType::Directed.name(), actual: self.r#type.name() });
+        }
+        let mut in_degrees = self.degrees(DegreeKind::In);
+        let mut ready: BinaryHeap<_> = in_degrees.iter()
+            .filter(|&(_, &in_degree)| in_degree == 0)
+            .map(|(&id, _)| Reverse(id))
+            .collect();
+        let mut order = Vec::with_capacity(self.vertices.len());
+        while let Some(Reverse(id)) = ready.pop() {
+            for edge_direction in &self.vertices[id].edge_directions {
+                let to_id = &edge_direction.to_vertex_id;
+                let in_degree = in_degrees.get_mut(to_id).expect("edge ends are graph vertices");
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    ready.push(Reverse(to_id));
+                }
+            }
+            order.push(id.clone());
+        }
+
+        // Вершины цикла и достижимые из него никогда не получают нулевую полустепень захода
+        if order.len() < self.vertices.len() {
+            return Err(GraphError::CycleDetected);
+        }
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn dag(vertex_count: u32, edges: &[(u32, u32)]) -> Result<Graph<(), ()>> {
+        let mut graph = Graph::new_dag();
+        for id in 1..=vertex_count {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for &(from_id, to_id) in edges {
+            graph.add_edge(from_id, to_id, None)?;
+        }
+        Ok(graph)
+    }
+
+    fn witness(result: Result<(), GraphError>) -> Option<Vec<String>> {
+        match result {
+            Err(GraphError::WouldCreateCycle { witness_path, .. }) => Some(witness_path),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn closing_edge_rejected() -> Result<()> {
+        let mut graph = dag(4, &[(1, 2), (2, 3), (3, 4)])?;
+        let closing = graph.add_edge(4, 1, None);
+        let message = closing.as_ref().map_err(ToString::to_string).err();
+        let self_loop = witness(graph.add_edge(2, 2, None));
+        if witness(closing) == Some(vec!["1".into(), "2".into(), "3".into(), "4".into()])
+            && message.as_deref() == Some("edge from \"4\" to \"1\" would create the cycle 4 -> 1 -> 2 -> 3 -> 4")
+            && self_loop == Some(vec!["2".into()])
+            && graph.edge_count() == 3
+            && graph.predecessors(1)?.is_empty() {
+            Ok(())
+        } else {
+            bail!("closing edge not rejected: {:?}", graph.edges())
+        }
+    }
+
+    #[test]
+    fn diamond_accepted() -> Result<()> {
+        let graph = dag(5, &[(1, 2), (1, 3), (2, 4), (3, 4), (1, 4), (4, 5)])?;
+        if graph.edge_count() == 6 && graph.is_dag() {
+            Ok(())
+        } else {
+            bail!("diamond rejected")
+        }
+    }
+
+    #[test]
+    fn deleting_edge_allows_insertion() -> Result<()> {
+        let mut graph = dag(3, &[(1, 2), (2, 3)])?;
+        let rejected = graph.add_edge(3, 1, None).is_err();
+        graph.delete_edge(2, 3)?;
+        graph.add_edge(3, 1, None)?;
+        let reversed = witness(graph.add_edge(2, 3, None));
+        if rejected && reversed == Some(vec!["3".into(), "1".into(), "2".into()]) {
+            Ok(())
+        } else {
+            bail!("insertion after delete not handled")
+        }
+    }
+
+    #[test]
+    fn topological_sort() -> Result<()> {
+        let graph = dag(6, &[(5, 1), (3, 4), (1, 4), (6, 2), (2, 3), (1, 2)])?;
+        let mut cyclic = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=3 {
+            cyclic.add_vertex(Vertex::new(id, None))?;
+        }
+        cyclic.add_edge(1, 2, None)?;
+        let acyclic_order = cyclic.topological_sort()?;
+        cyclic.add_edge(3, 3, None)?;
+        let undirected = Graph::<(), ()>::path(3);
+        if graph.topological_sort()? == vec![5, 1, 6, 2, 3, 4]
+            && acyclic_order == vec![1, 2, 3]
+            && cyclic.topological_sort() == Err(GraphError::CycleDetected)
+            && undirected.topological_sort() == Err(GraphError::WrongGraphType { expected: "directed", actual: "undirected" })
+            && Graph::<(), ()>::new_dag().topological_sort()?.is_empty() {
+            Ok(())
+        } else {
+            bail!("wrong topological order {:?}", graph.topological_sort())
+        }
+    }
+
+    #[test]
+    fn cyclic_file_rejected() -> Result<()> {
+        let options = DeserializeOptions {
+            dag: true,
+            ..DeserializeOptions::default()
+        };
+        let acyclic = Graph::deserialize_with_options("1\n2\n3\n#\n1 2\n1 3\n2 3\n".as_bytes(), options)?;
+        let cyclic = Graph::deserialize_with_options("1\n2\n3\n#\n1 2\n2 3\n3 1\n".as_bytes(), options);
+        match cyclic {
+            Err(GraphError::ParseLine { line_no: 7, source }) if acyclic.is_dag() && matches!(
+                *source,
+                GraphError::WouldCreateCycle { ref from, ref to, .. } if from == "3" && to == "1"
+            ) => Ok(()),
+            result => bail!("cyclic file not rejected: {:?}", result.map(|graph| graph.edges().len())),
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+};
+use super::*;
+
+/// Во сколько раз квадрат числа вершин может превышать число рёбер, чтобы граф считался плотным
+/// и расстояния считались алгоритмом Флойда - Уоршелла, а не поиском Дейкстры из каждой вершины
+const DENSE_GRAPH_RATIO: usize = 8;
+
+/// Исходящие рёбра вершин по индексам: индекс конца и вес
+pub(crate) type WeightedAdjacency = Vec<Vec<(usize, f64)>>;
+
+/// Расстояния до вершин по индексам и индексы предыдущих вершин кратчайших путей
+type ShortestPathTree = (Vec<f64>, Vec<Option<usize>>);
+
+/// Кратчайшие пути из одной вершины, см. `Graph::dijkstra`
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<ID = DefaultGraphIdType> {
+    /// Длина кратчайшего пути до каждой достижимой вершины, до начальной - 0.0
+    pub distances: HashMap<ID, f64>,
+    /// Предыдущая вершина кратчайшего пути до каждой достижимой вершины, кроме начальной
+    pub predecessors: HashMap<ID, ID>,
+}
+
+impl<ID: GraphId> ShortestPaths<ID> {
+    /// Кратчайший путь от начальной вершины до `to_id` включительно. `None` - вершина недостижима
+    pub fn path_to(&self, to_id: ID) -> Option<Vec<ID>> {
+        if !self.distances.contains_key(&to_id) {
+            return None;
+        }
+        let mut path = vec![to_id];
+        while let Some(predecessor_id) = path.last().and_then(|id| self.predecessors.get(id)) {
+            path.push(predecessor_id.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Результат по расстояниям и предыдущим вершинам с индексами вершин из `Graph::weighted_adjacency`
+    fn from_indices(ids: &[&ID], distances: Vec<f64>, predecessors: Vec<Option<usize>>) -> ShortestPaths<ID> {
+        ShortestPaths {
+            distances: distances.into_iter()
+                .enumerate()
+                .filter(|(_, distance)| distance.is_finite())
+                .map(|(index, distance)| (ids[index].clone(), distance))
+                .collect(),
+            predecessors: predecessors.into_iter()
+                .enumerate()
+                .filter_map(|(index, predecessor)| Some((ids[index].clone(), ids[predecessor?].clone())))
+                .collect(),
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Длины кратчайших путей между всеми парами вершин, включая расстояние 0 от вершины до самой себя.
+    /// Недостижимые пары в результат не входят. Вес ребра - `weight` от его значения (неотрицательный),
+    /// рёбра без значения весят 1.0. Плотные графы считаются алгоритмом Флойда - Уоршелла за O(n³),
+    /// разреженные - алгоритмом Дейкстры из каждой вершины
+    pub fn all_pairs_shortest_paths<W: Fn(&ET) -> f64>(&self, weight: W) -> HashMap<(ID, ID), f64> {
+        let (ids, adjacency) = self.weighted_adjacency(&weight);
+        let vertex_count = ids.len();
+        let distances = if self.edge_count.saturating_mul(DENSE_GRAPH_RATIO) >= vertex_count * vertex_count {
+            floyd_warshall(&adjacency)
+        } else {
+            (0..vertex_count).map(|index| dijkstra(&adjacency, index)).collect()
+        };
+
+        let mut result = HashMap::new();
+        for (from_index, row) in distances.into_iter().enumerate() {
+            for (to_index, distance) in row.into_iter().enumerate() {
+                if distance.is_finite() {
+                    result.insert((ids[from_index].clone(), ids[to_index].clone()), distance);
+                }
+            }
+        }
+        result
+    }
+
+    /// Кратчайшие пути из `start_id` до всех достижимых вершин алгоритмом Дейкстры. Вес ребра - `weight`
+    /// от его значения (неотрицательный), рёбра без значения весят 1.0. Из путей равной длины
+    /// берётся найденный первым
+    pub fn dijkstra<W: Fn(&ET) -> f64>(&self, start_id: ID, weight: W) -> Result<ShortestPaths<ID>, GraphError> {
+        let (ids, adjacency) = self.weighted_adjacency(&weight);
+        let start = ids.iter()
+            .position(|&id| *id == start_id)
+            .ok_or_else(|| GraphError::VertexNotFound(start_id.to_string()))?;
+        let (distances, predecessors) = dijkstra_tree(&adjacency, start);
+        Ok(ShortestPaths::from_indices(&ids, distances, predecessors))
+    }
+
+    /// Наибольшее расстояние от вершины до остальных вершин. Если какая-то вершина недостижима - бесконечность
+    pub fn eccentricity<W: Fn(&ET) -> f64>(&self, vertex_id: ID, weight: W) -> Result<f64, GraphError> {
+        let (ids, adjacency) = self.weighted_adjacency(&weight);
+        let index = ids.iter()
+            .position(|&id| *id == vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        Ok(dijkstra(&adjacency, index).into_iter().fold(0.0, f64::max))
+    }
+
+    /// Наибольший эксцентриситет вершин: бесконечность, если граф несвязен (ориентированный - не сильно связен).
+    /// `None` для графа без вершин
+    pub fn diameter<W: Fn(&ET) -> f64>(&self, weight: W) -> Option<f64> {
+        self.eccentricities(weight).into_iter().reduce(f64::max)
+    }
+
+    /// Наименьший эксцентриситет вершин, `None` для графа без вершин
+    pub fn radius<W: Fn(&ET) -> f64>(&self, weight: W) -> Option<f64> {
+        self.eccentricities(weight).into_iter().reduce(f64::min)
+    }
+
+    fn eccentricities<W: Fn(&ET) -> f64>(&self, weight: W) -> Vec<f64> {
+        // Наибольшее расстояние и число достижимых вершин для каждой вершины
+        let mut reached: HashMap<_, _> = self.vertices.keys().map(|id| (id.clone(), (0.0, 0))).collect();
+        for ((from_id, _), distance) in self.all_pairs_shortest_paths(weight) {
+            let (eccentricity, reachable_count) = reached.get_mut(&from_id).expect("distances are between graph vertices");
+            *eccentricity = distance.max(*eccentricity);
+            *reachable_count += 1;
+        }
+        reached.into_values()
+            .map(|(eccentricity, reachable_count)| if reachable_count < self.vertices.len() { f64::INFINITY } else { eccentricity })
+            .collect()
+    }
+
+    /// Вершины по индексам и их исходящие рёбра
+    pub(crate) fn weighted_adjacency<W: Fn(&ET) -> f64>(&self, weight: &W) -> (Vec<&ID>, WeightedAdjacency) {
+        let ids: Vec<_> = self.vertices.keys().collect();
+        let indices: HashMap<_, _> = ids.iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        let adjacency = ids.iter()
+            .map(|&id| self.vertices[id].edge_directions
+                .iter()
+                .map(|edge_direction| (
+                    indices[&edge_direction.to_vertex_id],
+                    edge_direction.value.as_ref().as_ref().map_or(DEFAULT_EDGE_COST, weight),
+                ))
+                .collect())
+            .collect();
+        (ids, adjacency)
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Кратчайшие пути из `start_id` до всех достижимых вершин алгоритмом Беллмана - Форда за O(nm).
+    /// В отличие от `dijkstra` вес ребра - `weight` от его значения - может быть отрицательным, рёбра без значения
+    /// весят 1.0. Ребро неориентированного графа с отрицательным весом само образует отрицательный цикл.
+    /// Достижимый из `start_id` цикл отрицательного веса - ошибка `NegativeCycle` с вершинами цикла по порядку.
+    /// Цикл начинается с одной и той же вершины при любом `start_id`, из которого он достижим
+    pub fn bellman_ford<W: Fn(&ET) -> f64>(&self, start_id: ID, weight: W) -> Result<ShortestPaths<ID>, GraphError> {
+        let (ids, adjacency) = self.weighted_adjacency(&weight);
+        let start = ids.iter()
+            .position(|&id| *id == start_id)
+            .ok_or_else(|| GraphError::VertexNotFound(start_id.to_string()))?;
+        let (distances, predecessors) = bellman_ford_tree(&adjacency, start).map_err(|mut cycle| {
+            // Цикл поворачивается к вершине с наименьшим индексом, от начальной вершины поиска он не зависит
+            let first = cycle.iter()
+                .enumerate()
+                .min_by_key(|&(_, index)| index)
+                .map_or(0, |(position, _)| position);
+            cycle.rotate_left(first);
+            GraphError::NegativeCycle(cycle.into_iter().map(|index| ids[index].to_string()).collect())
+        })?;
+        Ok(ShortestPaths::from_indices(&ids, distances, predecessors))
+    }
+}
+
+/// Расстояния от `start` до всех вершин, бесконечность - для недостижимых
+fn dijkstra(adjacency: &[Vec<(usize, f64)>], start: usize) -> Vec<f64> {
+    dijkstra_tree(adjacency, start).0
+}
+
+/// Расстояния от `start` до всех вершин и предыдущие вершины кратчайших путей
+fn dijkstra_tree(adjacency: &[Vec<(usize, f64)>], start: usize) -> ShortestPathTree {
+    let mut distances = vec![f64::INFINITY; adjacency.len()];
+    let mut predecessors = vec![None; adjacency.len()];
+    distances[start] = 0.0;
+    let mut queue = BinaryHeap::from([QueueEntry { distance: 0.0, index: start }]);
+    while let Some(QueueEntry { distance, index }) = queue.pop() {
+        // Устаревшая запись: вершина уже извлечена с меньшим расстоянием
+        if distance > distances[index] {
+            continue;
+        }
+        for &(neighbour_index, edge_weight) in &adjacency[index] {
+            let neighbour_distance = distance + edge_weight;
+            if neighbour_distance < distances[neighbour_index] {
+                distances[neighbour_index] = neighbour_distance;
+                predecessors[neighbour_index] = Some(index);
+                queue.push(QueueEntry { distance: neighbour_distance, index: neighbour_index });
+            }
+        }
+    }
+    (distances, predecessors)
+}
+
+/// Расстояния от `start` до всех вершин и предыдущие вершины кратчайших путей при любых весах рёбер.
+/// Ошибка - индексы вершин достижимого из `start` цикла отрицательного веса в порядке обхода
+fn bellman_ford_tree(adjacency: &[Vec<(usize, f64)>], start: usize) -> Result<ShortestPathTree, Vec<usize>> {
+    let vertex_count = adjacency.len();
+    let mut distances = vec![f64::INFINITY; vertex_count];
+    let mut predecessors = vec![None; vertex_count];
+    distances[start] = 0.0;
+    let mut last_relaxed_index = start;
+    // Без отрицательных циклов расстояния перестают меняться не позже чем через n - 1 проходов,
+    // изменение на n-м проходе означает отрицательный цикл
+    for _ in 0..vertex_count {
+        let mut relaxed_index = None;
+        for (index, edges) in adjacency.iter().enumerate() {
+            if distances[index].is_infinite() {
+                continue;
+            }
+            for &(neighbour_index, edge_weight) in edges {
+                let neighbour_distance = distances[index] + edge_weight;
+                if neighbour_distance < distances[neighbour_index] {
+                    distances[neighbour_index] = neighbour_distance;
+                    predecessors[neighbour_index] = Some(index);
+                    relaxed_index = Some(neighbour_index);
+                }
+            }
+        }
+        let Some(relaxed_index) = relaxed_index else {
+            return Ok((distances, predecessors));
+        };
+        last_relaxed_index = relaxed_index;
+    }
+
+    // После n шагов назад по предыдущим вершинам от вершины, изменённой на n-м проходе, обход уже внутри цикла
+    let mut index = last_relaxed_index;
+    for _ in 0..vertex_count {
+        index = predecessors[index].expect("vertex relaxed on the last pass has a chain of predecessors");
+    }
+    let mut cycle = vec![index];
+    let mut predecessor = predecessors[index].expect("cycle vertices have predecessors");
+    while predecessor != index {
+        cycle.push(predecessor);
+        predecessor = predecessors[predecessor].expect("cycle vertices have predecessors");
+    }
+    cycle.reverse();
+    Err(cycle)
+}
+
+fn floyd_warshall(adjacency: &[Vec<(usize, f64)>]) -> Vec<Vec<f64>> {
+    let vertex_count = adjacency.len();
+    let mut distances = vec![vec![f64::INFINITY; vertex_count]; vertex_count];
+    for (index, edges) in adjacency.iter().enumerate() {
+        distances[index][index] = 0.0;
+        for &(to_index, edge_weight) in edges {
+            distances[index][to_index] = distances[index][to_index].min(edge_weight);
+        }
+    }
+    for middle in 0..vertex_count {
+        let middle_row = distances[middle].clone();
+        for row in &mut distances {
+            let to_middle = row[middle];
+            if to_middle.is_infinite() {
+                continue;
+            }
+            for (distance, from_middle) in row.iter_mut().zip(&middle_row) {
+                *distance = distance.min(to_middle + from_middle);
+            }
+        }
+    }
+    distances
+}
+
+/// Элемент очереди с приоритетом. Порядок обратный, чтобы `BinaryHeap` выдавал наименьшее расстояние
+pub(crate) struct QueueEntry {
+    pub(crate) distance: f64,
+    pub(crate) index: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn unit_weight_diameter() -> Result<()> {
+        let graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let distances = graph.all_pairs_shortest_paths(|_| 1.0);
+        if graph.diameter(|_| 1.0) == Some(3.0)
+            && graph.radius(|_| 1.0) == Some(2.0)
+            && graph.eccentricity(4, |_| 1.0)? == 3.0
+            && graph.eccentricity(3, |_| 1.0)? == 2.0
+            && distances.len() == 49
+            && distances[&(4, 7)] == 3.0
+            && distances[&(7, 4)] == 3.0
+            && distances[&(2, 2)] == 0.0
+            && matches!(graph.eccentricity(8, |_| 1.0), Err(GraphError::VertexNotFound(_))) {
+            Ok(())
+        } else {
+            bail!("wrong distances, diameter {:?}", graph.diameter(|_| 1.0))
+        }
+    }
+
+    #[test]
+    fn directed_unreachable_pairs_absent() -> Result<()> {
+        let mut graph = Graph::<(), f64>::new(GraphType::Directed);
+        for id in 1..=4 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, Some(2.5))?;
+        graph.add_edge(2, 3, Some(1.0))?;
+        graph.add_edge(1, 3, Some(5.0))?;
+        let distances = graph.all_pairs_shortest_paths(|&weight| weight);
+        if distances[&(1, 3)] == 3.5
+            && !distances.contains_key(&(3, 1))
+            && !distances.contains_key(&(1, 4))
+            && distances.len() == 7
+            && graph.eccentricity(1, |&weight| weight)? == f64::INFINITY
+            && graph.diameter(|&weight| weight) == Some(f64::INFINITY)
+            && Graph::<(), f64>::new(GraphType::Directed).diameter(|&weight| weight).is_none() {
+            Ok(())
+        } else {
+            bail!("wrong directed distances {:?}", distances)
+        }
+    }
+
+    #[test]
+    fn single_source_paths() -> Result<()> {
+        let mut graph = Graph::<(), f64>::new(GraphType::Directed);
+        for id in 1..=5 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id, weight) in [(1, 2, 4.0), (1, 3, 1.0), (3, 2, 2.0), (2, 4, 1.0), (3, 4, 5.0)] {
+            graph.add_edge(from_id, to_id, Some(weight))?;
+        }
+        let paths = graph.dijkstra(1, |&weight| weight)?;
+        let unit_paths = Graph::deserialize(TGF_GRAPH.as_bytes())?.dijkstra(4, |_| 1.0)?;
+        if paths.distances == HashMap::from([(1, 0.0), (3, 1.0), (2, 3.0), (4, 4.0)])
+            && paths.path_to(4) == Some(vec![1, 3, 2, 4])
+            && paths.path_to(1) == Some(vec![1])
+            && paths.path_to(5).is_none()
+            && !paths.predecessors.contains_key(&1)
+            && unit_paths.path_to(7).map(|path| path.len()) == Some(4)
+            && matches!(graph.dijkstra(6, |&weight| weight), Err(GraphError::VertexNotFound(_))) {
+            Ok(())
+        } else {
+            bail!("wrong shortest paths {:?}", paths)
+        }
+    }
+
+    #[test]
+    fn negative_weights() -> Result<()> {
+        let mut graph = Graph::<(), f64>::new(GraphType::Directed);
+        for id in 1..=5 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id, weight) in [(1, 2, 4.0), (1, 3, 2.0), (2, 4, 1.0), (3, 2, -3.0), (4, 5, 2.0)] {
+            graph.add_edge(from_id, to_id, Some(weight))?;
+        }
+        let paths = graph.bellman_ford(1, |&weight| weight)?;
+        let unit_graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let unit_paths = unit_graph.bellman_ford(4, |_| 1.0)?;
+        let unit_distances = unit_graph.dijkstra(4, |_| 1.0)?.distances;
+        graph.add_edge(5, 3, Some(-1.0))?;
+        let from_cycle = graph.bellman_ford(3, |&weight| weight).err();
+        let cycle = match &from_cycle {
+            Some(GraphError::NegativeCycle(cycle)) => cycle.clone(),
+            _ => Vec::new(),
+        };
+        if paths.distances == HashMap::from([(1, 0.0), (3, 2.0), (2, -1.0), (4, 0.0), (5, 2.0)])
+            && paths.path_to(5) == Some(vec![1, 3, 2, 4, 5])
+            && unit_paths.distances == unit_distances
+            && cycle.len() == 4
+            && (0..4).any(|shift| cycle.iter().cycle().skip(shift).take(4).eq(["2", "4", "5", "3"].iter()))
+            && from_cycle == graph.bellman_ford(1, |&weight| weight).err()
+            && graph.bellman_ford(5, |&weight| weight.abs()).is_ok()
+            && matches!(graph.bellman_ford(6, |&weight| weight), Err(GraphError::VertexNotFound(_))) {
+            Ok(())
+        } else {
+            bail!("wrong negative weight paths {:?}, cycle {:?}", paths, from_cycle)
+        }
+    }
+
+    #[test]
+    fn dense_and_sparse_algorithms_agree() -> Result<()> {
+        for graph_type in [GraphType::Directed, GraphType::Undirected] {
+            let graph = Graph::<(), ()>::gnp_random(40, 0.1, graph_type, 7)?;
+            let (_, adjacency) = graph.weighted_adjacency(&|_| 1.0);
+            let repeated_dijkstra: Vec<_> = (0..adjacency.len()).map(|index| dijkstra(&adjacency, index)).collect();
+            if floyd_warshall(&adjacency) != repeated_dijkstra {
+                bail!("Floyd - Warshall differs from Dijkstra for {:?} graph", graph_type)
+            }
+        }
+        Ok(())
+    }
+}
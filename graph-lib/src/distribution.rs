@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, HashMap};
+use super::*;
+
+/// Минимальное число степеней не меньше `xmin`, при котором оценивается показатель степенного закона
+const MIN_POWERLAW_SAMPLES: usize = 10;
+
+/// Какая степень вершины учитывается в распределении.
+/// В неориентированном графе все виды степени совпадают
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DegreeKind {
+    /// Число входящих рёбер
+    In,
+    /// Число исходящих рёбер
+    Out,
+    /// Сумма входящих и исходящих рёбер, петля учитывается дважды
+    Total,
+    /// Степень без учёта направления, как в `Graph::degree`: петля учитывается один раз
+    Undirected,
+}
+
+/// Сводка по степеням вершин, см. `Graph::degree_stats`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegreeStats {
+    /// Наименьшая, наибольшая и средняя степень. У пустого графа все три равны нулю
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    /// Степень -> число вершин с такой степенью, как в `Graph::degree_distribution`
+    pub histogram: BTreeMap<usize, usize>,
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Наименьшая, наибольшая и средняя степень вида `which` и распределение степеней
+    pub fn degree_stats(&self, which: DegreeKind) -> DegreeStats {
+        let histogram = self.degree_distribution(which);
+        let degree_sum: usize = histogram.iter().map(|(degree, count)| degree * count).sum();
+        DegreeStats {
+            min: histogram.keys().next().copied().unwrap_or(0),
+            max: histogram.keys().next_back().copied().unwrap_or(0),
+            mean: if self.vertices.is_empty() { 0.0 } else { degree_sum as f64 / self.vertices.len() as f64 },
+            histogram,
+        }
+    }
+
+    /// Число входящих рёбер вершины, петля учитывается один раз. В неориентированном графе совпадает с `degree`
+    pub fn in_degree(&self, vertex_id: ID) -> Result<usize, GraphError> {
+        let vertex = self.vertices.get(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        Ok(match self.r#type {
+            GraphType::Undirected => vertex.edge_directions.len(),
+            GraphType::Directed => {
+                let self_loop_count = vertex.edge_directions
+                    .iter()
+                    .filter(|edge_direction| edge_direction.to_vertex_id == vertex_id)
+                    .count();
+                self.incoming_edge_count(&vertex_id) + self_loop_count
+            }
+        })
+    }
+
+    /// Число исходящих рёбер вершины, петля учитывается один раз. В неориентированном графе совпадает с `degree`
+    pub fn out_degree(&self, vertex_id: ID) -> Result<usize, GraphError> {
+        self.vertices.get(&vertex_id)
+            .map(|vertex| vertex.edge_directions.len())
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))
+    }
+
+    /// Распределение степеней: степень -> число вершин с такой степенью
+    pub fn degree_distribution(&self, which: DegreeKind) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
+        for degree in self.degrees(which).into_values() {
+            *distribution.entry(degree).or_insert(0) += 1;
+        }
+        distribution
+    }
+
+    /// Оценка показателя степенного закона методом максимального правдоподобия по степеням (без учёта направления)
+    /// не меньше `xmin`. `None`, если таких степеней меньше `MIN_POWERLAW_SAMPLES`
+    pub fn powerlaw_alpha_estimate(&self, xmin: usize) -> Option<f64> {
+        powerlaw_alpha_mle(self.degrees(DegreeKind::Undirected).into_values(), xmin)
+    }
+
+    /// Записывает распределение степеней в CSV: заголовок `degree,count` и по строке на степень в порядке возрастания
+    pub fn write_distribution_csv<W: Write>(&self, which: DegreeKind, writer: &mut W) -> Result<(), GraphError> {
+        writeln!(writer, "degree,count")?;
+        for (degree, count) in self.degree_distribution(which) {
+            writeln!(writer, "{},{}", degree, count)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn degrees(&self, which: DegreeKind) -> HashMap<&ID, usize> {
+        let out_degrees = self.vertices.iter()
+            .map(|(id, vertex)| (id, vertex.edge_directions.len()));
+        if let GraphType::Undirected = self.r#type {
+            return out_degrees.collect();
+        }
+
+        let mut in_degrees: HashMap<&ID, usize> = self.vertices.keys().map(|id| (id, 0)).collect();
+        let mut undirected_degrees = in_degrees.clone();
+        for (from_id, vertex) in &self.vertices {
+            for edge_direction in &vertex.edge_directions {
+                let to_id = &edge_direction.to_vertex_id;
+                *in_degrees.entry(to_id).or_insert(0) += 1;
+                *undirected_degrees.entry(from_id).or_insert(0) += 1;
+                if to_id != from_id {
+                    *undirected_degrees.entry(to_id).or_insert(0) += 1;
+                }
+            }
+        }
+
+        match which {
+            DegreeKind::In => in_degrees,
+            DegreeKind::Out => out_degrees.collect(),
+            DegreeKind::Total => out_degrees
+                .map(|(id, out_degree)| (id, out_degree + in_degrees[id]))
+                .collect(),
+            DegreeKind::Undirected => undirected_degrees,
+        }
+    }
+}
+
+/// Дискретная аппроксимация оценки Клаузета: alpha = 1 + n / sum(ln(x / (xmin - 0.5)))
+fn powerlaw_alpha_mle(degrees: impl Iterator<Item = usize>, xmin: usize) -> Option<f64> {
+    if xmin == 0 {
+        return None;
+    }
+    let shifted_xmin = xmin as f64 - 0.5;
+    let (sample_count, log_sum) = degrees
+        .filter(|&degree| degree >= xmin)
+        .fold((0, 0.0), |(count, sum), degree| (count + 1, sum + (degree as f64 / shifted_xmin).ln()));
+    if sample_count < MIN_POWERLAW_SAMPLES || log_sum <= 0.0 {
+        return None;
+    }
+    Some(1.0 + sample_count as f64 / log_sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const KINDS: [DegreeKind; 4] = [DegreeKind::In, DegreeKind::Out, DegreeKind::Total, DegreeKind::Undirected];
+
+    fn star(r#type: GraphType, leaf_count: u32) -> Result<Graph<(), ()>> {
+        let mut graph = Graph::new(r#type);
+        for id in 0..=leaf_count {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..=leaf_count {
+            graph.add_edge(0, id, None)?;
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn star_distribution() -> Result<()> {
+        let graph = star(GraphType::Undirected, 5)?;
+        let directed_graph = star(GraphType::Directed, 5)?;
+        if graph.degree_distribution(DegreeKind::Undirected) == BTreeMap::from([(1, 5), (5, 1)])
+            && directed_graph.degree_distribution(DegreeKind::Out) == BTreeMap::from([(0, 5), (5, 1)])
+            && directed_graph.degree_distribution(DegreeKind::In) == BTreeMap::from([(0, 1), (1, 5)])
+            && directed_graph.degree_distribution(DegreeKind::Total) == BTreeMap::from([(1, 5), (5, 1)]) {
+            Ok(())
+        } else {
+            bail!("wrong star degree distribution")
+        }
+    }
+
+    #[test]
+    fn distribution_sums_to_vertex_count() -> Result<()> {
+        let mut graph = Graph::<String, String>::deserialize("1\n2\n3\n4\n#\n1 2\n2 3\n3 1\n3 3\n".as_bytes())?;
+        let mut directed_graph = star(GraphType::Directed, 4)?;
+        directed_graph.add_edge(2, 2, None)?;
+        directed_graph.add_edge(3, 0, None)?;
+        graph.add_edge(4, 4, None)?;
+        for kind in KINDS {
+            let count: usize = graph.degree_distribution(kind).values().sum();
+            let directed_count: usize = directed_graph.degree_distribution(kind).values().sum();
+            if count != graph.vertex_count() || directed_count != directed_graph.vertex_count() {
+                bail!("{:?} distribution does not cover all vertices", kind)
+            }
+        }
+        let directed_total = directed_graph.degree_distribution(DegreeKind::Total);
+        let directed_undirected = directed_graph.degree_distribution(DegreeKind::Undirected);
+        if directed_total == BTreeMap::from([(1, 2), (3, 1), (2, 1), (5, 1)])
+            && directed_undirected == BTreeMap::from([(1, 2), (2, 2), (5, 1)]) {
+            Ok(())
+        } else {
+            bail!("wrong directed distribution: {:?} {:?}", directed_total, directed_undirected)
+        }
+    }
+
+    #[test]
+    fn degree_stats_and_vertex_degrees() -> Result<()> {
+        let mut directed_graph = star(GraphType::Directed, 4)?;
+        directed_graph.add_edge(2, 2, None)?;
+        directed_graph.add_edge(3, 0, None)?;
+        let undirected_stats = star(GraphType::Undirected, 4)?.degree_stats(DegreeKind::Undirected);
+        let in_stats = directed_graph.degree_stats(DegreeKind::In);
+        let in_degrees = (0..=4).map(|id| directed_graph.in_degree(id)).collect::<Result<Vec<_>, _>>()?;
+        let out_degrees = (0..=4).map(|id| directed_graph.out_degree(id)).collect::<Result<Vec<_>, _>>()?;
+        if undirected_stats == (DegreeStats { min: 1, max: 4, mean: 1.6, histogram: BTreeMap::from([(1, 4), (4, 1)]) })
+            && in_stats == (DegreeStats { min: 1, max: 2, mean: 1.2, histogram: BTreeMap::from([(1, 4), (2, 1)]) })
+            && in_degrees == vec![1, 1, 2, 1, 1]
+            && out_degrees == vec![4, 0, 1, 1, 0]
+            && directed_graph.in_degree(5) == Err(GraphError::VertexNotFound("5".to_owned()))
+            && Graph::<(), ()>::new(GraphType::Directed).degree_stats(DegreeKind::Out).mean == 0.0 {
+            Ok(())
+        } else {
+            bail!("wrong degree stats {:?}, in-degrees {:?}, out-degrees {:?}", in_stats, in_degrees, out_degrees)
+        }
+    }
+
+    #[test]
+    fn powerlaw_alpha() -> Result<()> {
+        const ALPHA: f64 = 2.5;
+        const XMIN: usize = 5;
+        const SAMPLE_COUNT: usize = 20_000;
+        // Округлённые квантили непрерывного распределения Парето с минимумом xmin - 0.5
+        let degrees = (0..SAMPLE_COUNT).map(|index| {
+            let quantile = (index as f64 + 0.5) / SAMPLE_COUNT as f64;
+            ((XMIN as f64 - 0.5) * (1.0 - quantile).powf(-1.0 / (ALPHA - 1.0))).round() as usize
+        });
+        let estimate = powerlaw_alpha_mle(degrees, XMIN);
+        let star_estimate = star(GraphType::Undirected, 5)?.powerlaw_alpha_estimate(1);
+        match estimate {
+            Some(alpha) if (alpha - ALPHA).abs() < 0.05 && star_estimate.is_none() => Ok(()),
+            _ => bail!("wrong power-law estimate {:?}", estimate),
+        }
+    }
+
+    #[test]
+    fn distribution_csv() -> Result<()> {
+        let graph = star(GraphType::Directed, 3)?;
+        let mut csv = Vec::new();
+        graph.write_distribution_csv(DegreeKind::In, &mut csv)?;
+        if String::from_utf8(csv)? == "degree,count\n0,1\n1,3\n" {
+            Ok(())
+        } else {
+            bail!("wrong distribution csv")
+        }
+    }
+}
@@ -1,17 +1,254 @@
+//! Ошибка `GraphError`, общая для всех операций с графом
+
 use std::io;
 use thiserror::Error;
-use super::*;
+use crate::{DefaultGraphIdType, LimitKind};
 
+/// Идентификаторы вершин хранятся в ошибках в виде строки, чтобы тип ошибки не зависел от типа идентификатора графа.
+/// Ошибки сравниваются по содержимому, ошибки ввода-вывода - по `io::ErrorKind`
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum GraphError {
     #[error("vertex with id \"{0}\" already exists in the graph")]
-    VertexAlreadyExist(DefaultGraphIdType),
+    VertexAlreadyExist(String),
     #[error("vertex id \"{0}\" not found in graph")]
-    VertexNotFound(DefaultGraphIdType),
+    VertexNotFound(String),
+    #[error("vertex ids {} not found in graph", .0.iter().map(|id| format!("\"{}\"", id)).collect::<Vec<_>>().join(", "))]
+    VerticesNotFound(Vec<String>),
+    #[error("edge from \"{0}\" to \"{1}\" already exists in the graph")]
+    EdgeAlreadyExist(String, String),
+    #[error("edge from \"{0}\" to \"{1}\" not found in graph")]
+    EdgeNotFound(String, String),
     #[error("{0}")]
     SerializeGraph(#[from] io::Error),
     #[error("vertex id in \"{0}\" not set")]
     ParseVertexId(String),
     #[error("wrong vertex id type in \"{0}\"")]
     WrongVertexIdType(String),
-}
\ No newline at end of file
+    #[error("wrong patch line \"{0}\"")]
+    WrongPatchLine(String),
+    #[error("wrong vertex role line \"{0}\"")]
+    WrongRoleLine(String),
+    /// Значение ребра взвешенного графа не число, см. `Graph::deserialize_weighted`
+    #[error("wrong edge weight in \"{0}\"")]
+    WrongEdgeWeight(String),
+    /// Операция применима только к графам типа `expected` (см. `GraphType::name`)
+    #[error("wrong graph type: expected {expected} graph, found {actual} graph")]
+    WrongGraphType {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    #[error("graph contains a cycle")]
+    CycleDetected,
+    #[error("edge from \"{from}\" to \"{to}\" would create the cycle {from} -> {}", .witness_path.join(" -> "))]
+    WouldCreateCycle {
+        from: String,
+        to: String,
+        /// Существующий путь от `to` до `from`, который новое ребро замкнуло бы в цикл
+        witness_path: Vec<String>,
+    },
+    /// Петлю нельзя раскрасить: концы ребра совпадают, см. `Graph::greedy_coloring`
+    #[error("vertex \"{0}\" has a self-loop")]
+    SelfLoop(String),
+    /// Цикл отрицательного веса, достижимый из начальной вершины, см. `Graph::bellman_ford`
+    #[error("graph contains the negative cycle {} -> {}", .0.join(" -> "), .0.first().map(String::as_str).unwrap_or_default())]
+    NegativeCycle(Vec<String>),
+    /// Граф не двудольный, см. `Graph::max_bipartite_matching`: нечётный цикл, как в `Bipartition::OddCycle`
+    #[error("graph is not bipartite: it contains the odd cycle {} -> {}", .0.join(" -> "), .0.first().map(String::as_str).unwrap_or_default())]
+    OddCycle(Vec<String>),
+    /// Параметр алгоритма вне допустимых значений, см. `Graph::pagerank_until`
+    #[error("{name} {value} must be {expected}")]
+    InvalidParameter {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+    #[error("graph has {} errors: {}", .0.len(), .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    InvalidGraph(Vec<GraphError>),
+    #[error("plan was made at graph generation {planned_generation}, but the graph is at generation {generation}")]
+    StalePlan {
+        planned_generation: u64,
+        generation: u64,
+    },
+    #[error("error at line {line_no}")]
+    ParseLine {
+        line_no: usize,
+        source: Box<GraphError>,
+    },
+    #[error("{which} limit {limit} exceeded{}", .at_line.map(|line| format!(" at line {}", line)).unwrap_or_default())]
+    LimitExceeded {
+        which: LimitKind,
+        limit: usize,
+        at_line: Option<usize>,
+    },
+}
+
+impl GraphError {
+    /// Привязывает ошибку разбора к строке. Превышение ограничения уже содержит номер строки и не оборачивается
+    pub(crate) fn at_line(self, line_no: usize) -> GraphError {
+        match self {
+            GraphError::LimitExceeded { .. } | GraphError::ParseLine { .. } => self,
+            error => GraphError::ParseLine { line_no, source: Box::new(error) },
+        }
+    }
+
+    /// Подсказка пользователю, как исправить ошибку
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            GraphError::VertexAlreadyExist(id) => Some(format!(
+                "vertex {} is declared twice; if this line is an edge, did you forget the '#' separator between vertices and edges?", id
+            )),
+            GraphError::VertexNotFound(id) => Some(format!("declare vertex {} before the '#' line", id)),
+            GraphError::VerticesNotFound(ids) => Some(format!("declare vertices {} before the '#' line", ids.join(", "))),
+            GraphError::EdgeAlreadyExist(from, to) => Some(format!(
+                "edge between {} and {} is declared twice; remove the duplicate or load the graph as a multigraph", from, to
+            )),
+            GraphError::EdgeNotFound(from, to) => Some(format!(
+                "there is no edge from {} to {}; for directed graphs check the order of the vertices", from, to
+            )),
+            GraphError::SerializeGraph(_) => None,
+            GraphError::ParseVertexId(line) => Some(format!("line \"{}\" must start with a vertex id", line)),
+            GraphError::WrongVertexIdType(line) => {
+                let wrong_id = line.split(' ')
+                    .take(2)
+                    .find(|id| id.parse::<DefaultGraphIdType>().is_err())
+                    .unwrap_or(line);
+                if !wrong_id.is_empty() && wrong_id.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return Some(format!("vertex id '{}' is out of range; ids must not exceed {}", wrong_id, DefaultGraphIdType::MAX));
+                }
+                Some(format!("vertex ids must be non-negative integers; found '{}'", wrong_id))
+            }
+            GraphError::WrongPatchLine(_) => Some("patch lines must start with '+v', '-v', '+e', '-e' or '+r'".to_owned()),
+            GraphError::WrongRoleLine(_) => Some("role lines must be '<id> source', '<id> sink' or '<id> internal'".to_owned()),
+            GraphError::WrongEdgeWeight(_) => Some("edge values of a weighted graph must be numbers such as 2, 0.5 or 1e-3".to_owned()),
+            GraphError::WrongGraphType { expected, .. } => Some(format!("build the graph as {} to use this operation", expected)),
+            GraphError::CycleDetected => Some("the operation requires an acyclic graph; remove an edge of the cycle first".to_owned()),
+            GraphError::WouldCreateCycle { from, to, .. } => Some(format!(
+                "the graph must stay acyclic; remove an edge on the path from {} back to {} first", to, from
+            )),
+            GraphError::SelfLoop(id) => Some(format!("a vertex cannot get a color different from its own; remove the edge from {} to itself", id)),
+            GraphError::NegativeCycle(ids) => Some(format!(
+                "shortest paths through vertices {} are unbounded; make the total weight of the cycle non-negative", ids.join(", ")
+            )),
+            GraphError::OddCycle(ids) => Some(format!(
+                "an odd cycle through vertices {} cannot be split into two parts; remove one of its edges", ids.join(", ")
+            )),
+            GraphError::InvalidParameter { name, expected, .. } => Some(format!("pass {} {}", name, expected)),
+            GraphError::InvalidGraph(errors) => errors.first().and_then(GraphError::hint),
+            GraphError::StalePlan { .. } => Some("the graph changed after the plan was made; make the plan again".to_owned()),
+            GraphError::ParseLine { source, .. } => source.hint(),
+            GraphError::LimitExceeded { which, limit, .. } => Some(format!(
+                "the graph is larger than allowed; raise the {} limit above {} or split the input", which, limit
+            )),
+        }
+    }
+}
+
+impl PartialEq for GraphError {
+    fn eq(&self, other: &GraphError) -> bool {
+        match (self, other) {
+            (GraphError::VertexAlreadyExist(id), GraphError::VertexAlreadyExist(other_id))
+            | (GraphError::VertexNotFound(id), GraphError::VertexNotFound(other_id))
+            | (GraphError::SelfLoop(id), GraphError::SelfLoop(other_id)) => id == other_id,
+            (GraphError::VerticesNotFound(ids), GraphError::VerticesNotFound(other_ids))
+            | (GraphError::NegativeCycle(ids), GraphError::NegativeCycle(other_ids))
+            | (GraphError::OddCycle(ids), GraphError::OddCycle(other_ids)) => ids == other_ids,
+            (GraphError::EdgeAlreadyExist(from, to), GraphError::EdgeAlreadyExist(other_from, other_to))
+            | (GraphError::EdgeNotFound(from, to), GraphError::EdgeNotFound(other_from, other_to)) => from == other_from && to == other_to,
+            (GraphError::SerializeGraph(error), GraphError::SerializeGraph(other_error)) => error.kind() == other_error.kind(),
+            (GraphError::ParseVertexId(line), GraphError::ParseVertexId(other_line))
+            | (GraphError::WrongVertexIdType(line), GraphError::WrongVertexIdType(other_line))
+            | (GraphError::WrongPatchLine(line), GraphError::WrongPatchLine(other_line))
+            | (GraphError::WrongRoleLine(line), GraphError::WrongRoleLine(other_line))
+            | (GraphError::WrongEdgeWeight(line), GraphError::WrongEdgeWeight(other_line)) => line == other_line,
+            (
+                GraphError::WrongGraphType { expected, actual },
+                GraphError::WrongGraphType { expected: other_expected, actual: other_actual },
+            ) => expected == other_expected && actual == other_actual,
+            (GraphError::CycleDetected, GraphError::CycleDetected) => true,
+            (
+                GraphError::WouldCreateCycle { from, to, witness_path },
+                GraphError::WouldCreateCycle { from: other_from, to: other_to, witness_path: other_witness_path },
+            ) => from == other_from && to == other_to && witness_path == other_witness_path,
+            (
+                GraphError::InvalidParameter { name, value, expected },
+                GraphError::InvalidParameter { name: other_name, value: other_value, expected: other_expected },
+            ) => name == other_name && value == other_value && expected == other_expected,
+            (GraphError::InvalidGraph(errors), GraphError::InvalidGraph(other_errors)) => errors == other_errors,
+            (
+                GraphError::StalePlan { planned_generation, generation },
+                GraphError::StalePlan { planned_generation: other_planned_generation, generation: other_generation },
+            ) => planned_generation == other_planned_generation && generation == other_generation,
+            (
+                GraphError::ParseLine { line_no, source },
+                GraphError::ParseLine { line_no: other_line_no, source: other_source },
+            ) => line_no == other_line_no && source == other_source,
+            (
+                GraphError::LimitExceeded { which, limit, at_line },
+                GraphError::LimitExceeded { which: other_which, limit: other_limit, at_line: other_at_line },
+            ) => which == other_which && limit == other_limit && at_line == other_at_line,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for GraphError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn hints() -> Result<()> {
+        let hints = [
+            (GraphError::WrongVertexIdType("a 2".to_owned()), Some("vertex ids must be non-negative integers; found 'a'")),
+            (GraphError::WrongVertexIdType("1 b".to_owned()), Some("vertex ids must be non-negative integers; found 'b'")),
+            (
+                GraphError::WrongVertexIdType("1 4294967296".to_owned()),
+                Some("vertex id '4294967296' is out of range; ids must not exceed 4294967295"),
+            ),
+            (GraphError::VertexNotFound("9".to_owned()), Some("declare vertex 9 before the '#' line")),
+            (
+                GraphError::VertexAlreadyExist("1".to_owned()),
+                Some("vertex 1 is declared twice; if this line is an edge, did you forget the '#' separator between vertices and edges?"),
+            ),
+            (
+                GraphError::EdgeNotFound("1".to_owned(), "2".to_owned()),
+                Some("there is no edge from 1 to 2; for directed graphs check the order of the vertices"),
+            ),
+            (GraphError::ParseVertexId("".to_owned()), Some("line \"\" must start with a vertex id")),
+            (GraphError::SerializeGraph(io::Error::other("disk full")), None),
+            (
+                GraphError::VertexNotFound("9".to_owned()).at_line(4),
+                Some("declare vertex 9 before the '#' line"),
+            ),
+        ];
+        for (error, expected_hint) in hints {
+            if error.hint().as_deref() != expected_hint {
+                bail!("wrong hint for {:?}: {:?}", error, error.hint())
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn errors_compare_by_content() -> Result<()> {
+        let wrong_type = GraphError::WrongGraphType { expected: "undirected", actual: "directed" };
+        if GraphError::EdgeNotFound("1".to_owned(), "2".to_owned()) == GraphError::EdgeNotFound("1".to_owned(), "2".to_owned())
+            && GraphError::EdgeNotFound("1".to_owned(), "2".to_owned()) != GraphError::EdgeNotFound("2".to_owned(), "1".to_owned())
+            && GraphError::SerializeGraph(io::Error::other("disk full")) == GraphError::SerializeGraph(io::Error::other("quota"))
+            && GraphError::SerializeGraph(io::Error::other("disk full")) != GraphError::SerializeGraph(io::ErrorKind::NotFound.into())
+            && GraphError::VertexNotFound("3".to_owned()).at_line(2) == GraphError::VertexNotFound("3".to_owned()).at_line(2)
+            && GraphError::VertexNotFound("3".to_owned()).at_line(2) != GraphError::VertexNotFound("3".to_owned()).at_line(5)
+            && GraphError::CycleDetected != wrong_type
+            && wrong_type.to_string() == "wrong graph type: expected undirected graph, found directed graph" {
+            Ok(())
+        } else {
+            bail!("errors compared by identity")
+        }
+    }
+}
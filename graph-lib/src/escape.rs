@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+
+/// Экранирование значений вершин и рёбер в TGF, чтобы любое значение переживало запись и чтение без изменений:
+/// `\\` - обратная косая черта, `\n`, `\r`, `\t` - перевод строки, возврат каретки и табуляция,
+/// `\u{20}` - символ по коду (так записываются пробельные символы по краям значения, которые иначе срезает разбор строки),
+/// `\e` - пустая последовательность (так записывается пустое значение, которое иначе не отличить от отсутствующего).
+/// Обратная косая черта перед любым другим символом остаётся как есть
+const ESCAPE: char = '\\';
+
+pub(crate) fn escape_value(value: &str) -> Cow<'_, str> {
+    let needs_escape = value.is_empty()
+        || value.contains([ESCAPE, '\n', '\r', '\t'])
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace);
+    if !needs_escape {
+        return Cow::Borrowed(value);
+    }
+    if value.is_empty() {
+        return Cow::Borrowed("\\e");
+    }
+
+    let last_index = value.char_indices().last().map_or(0, |(index, _)| index);
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for (index, symbol) in value.char_indices() {
+        match symbol {
+            ESCAPE => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            symbol if symbol.is_whitespace() && (index == 0 || index == last_index) => {
+                escaped.push_str(&format!("\\u{{{:x}}}", u32::from(symbol)));
+            }
+            symbol => escaped.push(symbol),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+pub(crate) fn unescape_value(value: &str) -> Cow<'_, str> {
+    if !value.contains(ESCAPE) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(position) = rest.find(ESCAPE) {
+        unescaped.push_str(&rest[..position]);
+        let escape_sequence = &rest[position + ESCAPE.len_utf8()..];
+        let (replacement, consumed) = match escape_sequence.chars().next() {
+            Some(ESCAPE) => (Some(ESCAPE), 1),
+            Some('n') => (Some('\n'), 1),
+            Some('r') => (Some('\r'), 1),
+            Some('t') => (Some('\t'), 1),
+            Some('e') => (None, 1),
+            Some('u') => match parse_code_point(escape_sequence) {
+                Some((symbol, consumed)) => (Some(symbol), consumed),
+                None => (Some(ESCAPE), 0),
+            },
+            _ => (Some(ESCAPE), 0),
+        };
+        unescaped.extend(replacement);
+        rest = &escape_sequence[consumed..];
+    }
+    unescaped.push_str(rest);
+    Cow::Owned(unescaped)
+}
+
+/// Разбирает `u{hex}` в начале `escape_sequence`, возвращая символ и длину последовательности
+fn parse_code_point(escape_sequence: &str) -> Option<(char, usize)> {
+    let hex_end = escape_sequence.find('}')?;
+    let hex = escape_sequence.get(..hex_end)?.strip_prefix("u{")?;
+    let symbol = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)?;
+    Some((symbol, hex_end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn escape_round_trip() -> Result<()> {
+        let values = ["", " ", "plain", " both ", "a\nb", "tab\tin", "back\\slash", "\\e", "\\u{20}", "\u{a0}nbsp\u{2003}", "#", "a  b"];
+        for value in values {
+            let escaped = escape_value(value);
+            if unescape_value(&escaped) != value
+                || escaped.contains('\n')
+                || escaped.is_empty()
+                || escaped.trim() != escaped {
+                bail!("value {:?} escaped as {:?}", value, escaped)
+            }
+        }
+        if unescape_value("legacy\\path\\u{zz}\\") == "legacy\\path\\u{zz}\\" {
+            Ok(())
+        } else {
+            bail!("unknown escape sequences changed")
+        }
+    }
+}
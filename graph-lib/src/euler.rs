@@ -0,0 +1,204 @@
+use crate::distribution::DegreeKind;
+use super::*;
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Эйлеров путь (алгоритм Хирхольцера): последовательность вершин, проходящая по каждому ребру ровно один раз,
+    /// для m рёбер - m + 1 вершина с повторами. Неориентированный граф должен иметь 0 или 2 вершины нечётной степени
+    /// (петля добавляет к степени 2), ориентированный - совпадающие полустепени захода и исхода у всех вершин,
+    /// кроме, возможно, начала (исходящих на одно больше) и конца. Все рёбра должны лежать в одной компоненте,
+    /// вершины без рёбер не учитываются. Путь начинается с наименьшей допустимой вершины, рёбра вершины
+    /// берутся в порядке добавления. `None` - пути нет. У графа без рёбер путь - наименьшая вершина
+    pub fn eulerian_path(&self) -> Result<Option<Vec<ID>>, GraphError> {
+        let Some(start_id) = self.eulerian_start() else {
+            return Ok(None);
+        };
+        let Some(start_id) = start_id else {
+            return Ok(Some(self.vertices.keys().min().into_iter().cloned().collect()));
+        };
+
+        // Ребро неориентированного графа хранится у обоих концов, поэтому пройденные рёбра считаются по парам концов
+        let mut remaining_edges: HashMap<(&ID, &ID), usize> = HashMap::new();
+        if self.r#type == GraphType::Undirected {
+            for (from_id, edge_direction) in self.logical_edges() {
+                *remaining_edges.entry(edge_ends(from_id, &edge_direction.to_vertex_id)).or_default() += 1;
+            }
+        }
+        let mut next_edge_indexes: HashMap<&ID, usize> = HashMap::new();
+        let mut pending_ids = vec![start_id];
+        let mut path = Vec::with_capacity(self.edge_count + 1);
+        while let Some(&id) = pending_ids.last() {
+            let edge_directions = &self.vertices[id].edge_directions;
+            let next_edge_index = next_edge_indexes.entry(id).or_default();
+            let mut next_id = None;
+            while let Some(edge_direction) = edge_directions.get(*next_edge_index) {
+                *next_edge_index += 1;
+                let to_id = &edge_direction.to_vertex_id;
+                let unused = match self.r#type {
+                    GraphType::Directed => true,
+                    GraphType::Undirected => match remaining_edges.get_mut(&edge_ends(id, to_id)) {
+                        Some(count) if *count > 0 => {
+                            *count -= 1;
+                            true
+                        }
+                        _ => false,
+                    },
+                };
+                if unused {
+                    next_id = Some(to_id);
+                    break;
+                }
+            }
+            match next_id {
+                Some(to_id) => pending_ids.push(to_id),
+                None => {
+                    pending_ids.pop();
+                    path.push(id.clone());
+                }
+            }
+        }
+
+        // Путь короче - часть рёбер в другой компоненте
+        if path.len() != self.edge_count + 1 {
+            return Ok(None);
+        }
+        path.reverse();
+        Ok(Some(path))
+    }
+
+    /// Есть ли эйлеров цикл: эйлеров путь, который заканчивается в начальной вершине, см. `eulerian_path`.
+    /// У графа без рёбер цикл есть
+    pub fn has_eulerian_circuit(&self) -> bool {
+        match self.eulerian_path() {
+            Ok(Some(path)) => path.first() == path.last(),
+            _ => false,
+        }
+    }
+
+    /// Начало эйлерова пути по степеням вершин: `None` - степени не позволяют построить путь,
+    /// `Some(None)` - в графе нет рёбер
+    fn eulerian_start(&self) -> Option<Option<&ID>> {
+        let first_id_with_edges = self.vertices.values()
+            .filter(|vertex| !vertex.edge_directions.is_empty())
+            .map(|vertex| &vertex.id)
+            .min();
+        let Some(first_id_with_edges) = first_id_with_edges else {
+            return Some(None);
+        };
+
+        match self.r#type {
+            GraphType::Undirected => {
+                let mut odd_ids: Vec<_> = self.vertices.values()
+                    .filter(|vertex| vertex.edge_directions
+                        .iter()
+                        .filter(|edge_direction| edge_direction.to_vertex_id != vertex.id)
+                        .count() % 2 == 1)
+                    .map(|vertex| &vertex.id)
+                    .collect();
+                odd_ids.sort_unstable();
+                match odd_ids[..] {
+                    [] => Some(Some(first_id_with_edges)),
+                    [start_id, _] => Some(Some(start_id)),
+                    _ => None,
+                }
+            }
+            GraphType::Directed => {
+                let in_degrees = self.degrees(DegreeKind::In);
+                let mut start_id = None;
+                let mut end_found = false;
+                for vertex in self.vertices.values() {
+                    let out_degree = vertex.edge_directions.len();
+                    let in_degree = in_degrees[&vertex.id];
+                    if out_degree == in_degree + 1 && start_id.is_none() {
+                        start_id = Some(&vertex.id);
+                    } else if in_degree == out_degree + 1 && !end_found {
+                        end_found = true;
+                    } else if in_degree != out_degree {
+                        return None;
+                    }
+                }
+                match (start_id, end_found) {
+                    (None, false) => Some(Some(first_id_with_edges)),
+                    (Some(start_id), true) => Some(Some(start_id)),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Концы ребра неориентированного графа в порядке возрастания
+fn edge_ends<'a, ID: Ord>(first_id: &'a ID, second_id: &'a ID) -> (&'a ID, &'a ID) {
+    if first_id <= second_id {
+        (first_id, second_id)
+    } else {
+        (second_id, first_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn graph_with_edges(graph_type: GraphType, edges: &[(u32, u32)]) -> Result<Graph<(), ()>> {
+        let mut graph = Graph::new_multigraph(graph_type);
+        for &(from_id, to_id) in edges {
+            for id in [from_id, to_id] {
+                if graph.degree(id).is_err() {
+                    graph.add_vertex(Vertex::new(id, None))?;
+                }
+            }
+            graph.add_edge(from_id, to_id, None)?;
+        }
+        Ok(graph)
+    }
+
+    /// Каждое ребро пути есть в графе, и рёбер в пути столько же, сколько в графе
+    fn covers_all_edges(graph: &Graph<(), ()>, path: &[u32]) -> bool {
+        let mut remaining = graph.clone();
+        path.len() == graph.edge_count() + 1
+            && path.windows(2).all(|hop| remaining.delete_edge(hop[0], hop[1]).is_ok())
+    }
+
+    #[test]
+    fn konigsberg_bridges_have_no_path() -> Result<()> {
+        // Берега 1 и 2, острова 3 и 4: семь мостов, все четыре части суши нечётной степени
+        let konigsberg = graph_with_edges(GraphType::Undirected, &[(1, 3), (1, 3), (2, 3), (2, 3), (1, 4), (2, 4), (3, 4)])?;
+        let two_components = graph_with_edges(GraphType::Undirected, &[(1, 2), (2, 3), (3, 1), (4, 5), (5, 6), (6, 4)])?;
+        let unbalanced = graph_with_edges(GraphType::Directed, &[(1, 2), (1, 3), (2, 3), (3, 4)])?;
+        if konigsberg.eulerian_path()?.is_none()
+            && !konigsberg.has_eulerian_circuit()
+            && two_components.eulerian_path()?.is_none()
+            && unbalanced.eulerian_path()?.is_none() {
+            Ok(())
+        } else {
+            bail!("path found where none exists")
+        }
+    }
+
+    #[test]
+    fn square_with_diagonal_has_path() -> Result<()> {
+        let square = graph_with_edges(GraphType::Undirected, &[(1, 2), (2, 3), (3, 4), (4, 1), (1, 3)])?;
+        let path = square.eulerian_path()?.unwrap_or_default();
+        let directed = graph_with_edges(GraphType::Directed, &[(1, 2), (2, 3), (3, 1), (3, 3), (3, 4)])?;
+        let directed_path = directed.eulerian_path()?.unwrap_or_default();
+        let cycle = graph_with_edges(GraphType::Undirected, &[(1, 2), (2, 3), (3, 1), (2, 2)])?;
+        let mut isolated = Graph::<(), ()>::new(GraphType::Undirected);
+        isolated.add_vertex(Vertex::new(2, None))?;
+        if path == vec![1, 2, 3, 4, 1, 3]
+            && covers_all_edges(&square, &path)
+            && !square.has_eulerian_circuit()
+            && directed_path == vec![3, 1, 2, 3, 3, 4]
+            && covers_all_edges(&directed, &directed_path)
+            && cycle.has_eulerian_circuit()
+            && isolated.eulerian_path()? == Some(vec![2])
+            && isolated.has_eulerian_circuit() {
+            Ok(())
+        } else {
+            bail!("wrong eulerian path {:?}, directed {:?}", path, directed_path)
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Чтение и запись графов: TGF, параметры разбора, канонический порядок вывода и инкрементальные обновления.
+//! DOT и JSON выводятся методами `Graph::to_dot` и `Graph::to_json`
+
+pub mod tgf;
+
+pub use crate::fragments::CollisionPolicy;
+pub use crate::limits::{DeserializeLimits, DeserializeOptions, LimitKind};
+pub use crate::order::VertexOrder;
+pub use crate::patch::{GraphFingerprint, UpdateOutcome, UpdateStrategy};
+pub use crate::resilient::LineErrors;
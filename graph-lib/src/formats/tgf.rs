@@ -0,0 +1,490 @@
+//! Текстовый формат TGF (Trivial Graph Format): строки вершин `<id> [значение]`, разделитель `#`
+//! и строки рёбер `<id> <id> [значение]`. Значения экранируются, если содержат переводы строк, табуляцию
+//! или пробелы по краям. После рёбер могут идти роли вершин и изменения, записанные `Graph::serialize_update`
+
+use std::{
+    cmp::Ordering,
+    io::{self, BufRead, Write, BufWriter},
+    iter,
+    marker::PhantomData,
+    mem,
+    str::FromStr,
+};
+use crate::{
+    DefaultGraphIdType, DeserializeLimits, DeserializeOptions, Graph, GraphError, GraphId, GraphType, LimitKind, Vertex, VertexRole,
+    bulk::NewEdge,
+    escape::{escape_value, unescape_value},
+    patch::{FINGERPRINT_MARKER, PATCH_MARKER},
+    roles::ROLES_MARKER,
+};
+
+/// Секция TGF, которую разбирает `TgfReader` или `Graph::deserialize_line`
+#[derive(Debug)]
+pub(crate) enum ScanState {
+    Vertex,
+    Edge,
+    /// Секция ролей вершин после рёбер
+    Roles,
+    /// Секция изменений, дописанная `Graph::serialize_update`
+    Patch,
+}
+
+/// Разделитель секций вершин и рёбер
+pub(crate) const VERTEX_EDGE_DELEMITER: &str = "#";
+/// Разделитель идентификаторов и значения в строке
+pub(crate) const DATA_DELIMITER: &str = " ";
+
+/// Запись TGF-файла, см. `TgfReader`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TgfRecord<ID = DefaultGraphIdType> {
+    /// Строка секции вершин
+    Vertex {
+        /// Идентификатор вершины
+        id: ID,
+        /// Значение вершины без экранирования
+        value: Option<String>,
+    },
+    /// Строка секции рёбер
+    Edge {
+        /// Начальная вершина
+        from: ID,
+        /// Конечная вершина
+        to: ID,
+        /// Значение ребра без экранирования
+        value: Option<String>,
+    },
+    /// Строка секции ролей вершин
+    Role {
+        /// Идентификатор вершины
+        id: ID,
+        /// Роль вершины
+        role: VertexRole,
+    },
+    /// Строка секции изменений, дописанной `Graph::serialize_update`. Изменение относится к графу из предыдущих записей
+    Patch(String),
+}
+
+/// Потоковое чтение TGF по записям без построения графа, например для подсчёта или фильтрации записей
+/// в файле, не помещающемся в память. Секция рёбер начинается на строке `#`: без неё все строки читаются
+/// как вершины, а строка ребра до неё - как вершина со значением. Строки `#`, заголовки секций и отпечатки
+/// записей не дают. Ошибка разбора строки содержит её номер, и чтение можно продолжить со следующей строки.
+/// После ошибки ввода-вывода записей больше нет
+pub struct TgfReader<BR, ID = DefaultGraphIdType> {
+    lines: Option<io::Lines<BR>>,
+    scan_state: ScanState,
+    options: DeserializeOptions,
+    line_no: usize,
+    id_type: PhantomData<ID>,
+}
+
+impl<BR: BufRead, ID: GraphId + FromStr> TgfReader<BR, ID> {
+    /// Чтение со строгим разбором идентификаторов
+    pub fn new(reader: BR) -> TgfReader<BR, ID> {
+        TgfReader::with_options(reader, DeserializeOptions::default())
+    }
+
+    /// Чтение с приведением идентификаторов согласно `allow_hex_ids` и `allow_digit_separators`.
+    /// Ограничения и остальные параметры проверяются при построении графа, а не при чтении
+    pub fn with_options(reader: BR, options: DeserializeOptions) -> TgfReader<BR, ID> {
+        TgfReader {
+            lines: Some(reader.lines()),
+            scan_state: ScanState::Vertex,
+            options,
+            line_no: 0,
+            id_type: PhantomData,
+        }
+    }
+
+    /// Номер строки (с единицы) последней выданной записи или ошибки
+    pub fn line_no(&self) -> usize {
+        self.line_no
+    }
+}
+
+impl<BR: BufRead, ID: GraphId + FromStr> Iterator for TgfReader<BR, ID> {
+    type Item = Result<TgfRecord<ID>, GraphError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.as_mut()?.next()?;
+            self.line_no += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    self.lines = None;
+                    return Some(Err(error.into()));
+                }
+            };
+            match parse_line(&mut self.scan_state, line.trim(), &self.options) {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => {}
+                Err(error) => return Some(Err(error.at_line(self.line_no))),
+            }
+        }
+    }
+}
+
+/// Разбор строки секции `scan_state`. Строка `#` и заголовки секций переключают секцию и записи не дают
+fn parse_line<ID: GraphId + FromStr>(
+    scan_state: &mut ScanState,
+    line: &str,
+    options: &DeserializeOptions,
+) -> Result<Option<TgfRecord<ID>>, GraphError> {
+    let id_count = match scan_state {
+        ScanState::Vertex | ScanState::Roles => 1,
+        ScanState::Edge => 2,
+        ScanState::Patch => 0,
+    };
+    let line = &*options.normalize_ids(line, id_count);
+    let record = match scan_state {
+        ScanState::Vertex if line == VERTEX_EDGE_DELEMITER => {
+            *scan_state = ScanState::Edge;
+            return Ok(None);
+        }
+        ScanState::Vertex => {
+            let vertex = Graph::<String, String, ID>::parse_vertex(line)?;
+            TgfRecord::Vertex { id: vertex.id, value: vertex.value }
+        }
+        ScanState::Edge | ScanState::Roles | ScanState::Patch if line.starts_with(FINGERPRINT_MARKER) => return Ok(None),
+        ScanState::Edge | ScanState::Roles | ScanState::Patch if line == PATCH_MARKER => {
+            *scan_state = ScanState::Patch;
+            return Ok(None);
+        }
+        ScanState::Edge if line == ROLES_MARKER => {
+            *scan_state = ScanState::Roles;
+            return Ok(None);
+        }
+        ScanState::Roles => {
+            let (id, role) = Graph::<String, String, ID>::parse_role_line(line)?;
+            TgfRecord::Role { id, role }
+        }
+        ScanState::Patch => TgfRecord::Patch(line.to_owned()),
+        ScanState::Edge => {
+            let (from, to, value) = Graph::<String, String, ID>::parse_edge_ids(line)?;
+            TgfRecord::Edge { from, to, value }
+        }
+    };
+    Ok(Some(record))
+}
+
+/// Записи TGF-текста, уже находящегося в памяти, с номерами строк, как у `TgfReader`, но без чтения строк в буфер
+#[cfg(feature = "mmap")]
+pub(crate) fn text_records<'a, ID: GraphId + FromStr + 'a>(
+    text: &'a str,
+    options: &'a DeserializeOptions,
+) -> impl Iterator<Item = (usize, Result<TgfRecord<ID>, GraphError>)> + 'a {
+    let mut scan_state = ScanState::Vertex;
+    text.lines()
+        .zip(1..)
+        .filter_map(move |(line, line_no)| match parse_line(&mut scan_state, line.trim(), options) {
+            Ok(record) => record.map(|record| (line_no, Ok(record))),
+            Err(error) => Some((line_no, Err(error.at_line(line_no)))),
+        })
+}
+
+impl Graph<String, String> {
+    /// Десериализация графа с идентификаторами вершин по умолчанию (`DefaultGraphIdType`)
+    pub fn deserialize<BR: BufRead>(reader: BR) -> Result<Graph<String, String>, GraphError> {
+        Graph::deserialize_typed(reader)
+    }
+
+    /// Десериализация с ограничениями размера графа. Ошибка возвращается на первой строке, превысившей ограничение
+    pub fn deserialize_with_limits<BR: BufRead>(reader: BR, limits: DeserializeLimits) -> Result<Graph<String, String>, GraphError> {
+        Graph::deserialize_impl(reader, &DeserializeOptions { limits, ..DeserializeOptions::default() })
+    }
+
+    /// Десериализация с заданными параметрами, см. `DeserializeOptions`
+    pub fn deserialize_with_options<BR: BufRead>(reader: BR, options: DeserializeOptions) -> Result<Graph<String, String>, GraphError> {
+        Graph::deserialize_impl(reader, &options)
+    }
+}
+
+impl Graph<String, f64> {
+    /// Десериализация графа, значения рёбер которого - числа (веса). Ошибка `WrongEdgeWeight` с номером строки,
+    /// если значение ребра не число
+    pub fn deserialize_weighted<BR: BufRead>(reader: BR) -> Result<Graph<String, f64>, GraphError> {
+        let options = DeserializeOptions::default();
+        let mut text_graph = Graph::<String, String>::new(GraphType::Undirected);
+        let mut scan_state = ScanState::Vertex;
+        for (line_index, line) in reader.lines().enumerate() {
+            let line_no = line_index + 1;
+            let line = line?;
+            let line = line.trim();
+            if let (ScanState::Edge, Ok((_, _, Some(value)))) = (&scan_state, Graph::<String, String>::parse_edge_ids(line)) {
+                parse_weight(&value, line).map_err(|error| error.at_line(line_no))?;
+            }
+            text_graph.deserialize_line(&mut scan_state, line, Some(line_no), &options)
+                .map_err(|error| error.at_line(line_no))?;
+        }
+
+        let mut graph = Graph::new(text_graph.r#type);
+        for vertex in text_graph.vertices.values() {
+            graph.add_vertex(Vertex::new(vertex.id, vertex.value.clone()))?;
+        }
+        for (from_id, to_id, value) in text_graph.edges() {
+            let weight = value.map(|value| parse_weight(value, value)).transpose()?;
+            graph.add_edge(from_id, to_id, weight)?;
+        }
+        graph.roles = text_graph.roles;
+        Ok(graph)
+    }
+}
+
+/// Вес ребра из его значения. `line` - строка для сообщения об ошибке
+fn parse_weight(value: &str, line: &str) -> Result<f64, GraphError> {
+    value.parse().map_err(|_| GraphError::WrongEdgeWeight(line.to_owned()))
+}
+
+impl<ID: GraphId + Ord> Graph<String, f64, ID> {
+    /// Сериализация взвешенного графа в TGF, как `serialize`. Веса записываются кратчайшей десятичной записью,
+    /// которая читается `deserialize_weighted` в то же самое число
+    pub fn serialize_weighted<W: Write>(&self, buf_writer: &mut BufWriter<W>) -> Result<(), GraphError> {
+        self.serialize_with(buf_writer, |buf_writer, weight| write!(buf_writer, "{}", weight), f64::total_cmp)
+    }
+}
+
+impl<ET, ID: GraphId + Ord> Graph<String, ET, ID> {
+    /// Строки TGF в порядке, не зависящем от порядка хранения: вершины и роли по возрастанию идентификатора,
+    /// рёбра по начальной, затем по конечной вершине, параллельные рёбра - по значению (`compare_edge_values`,
+    /// ребро без значения - первым). Значения рёбер записываются функцией `write_edge_value`
+    fn serialize_with<W, F, C>(&self, buf_writer: &mut BufWriter<W>, write_edge_value: F, compare_edge_values: C) -> Result<(), GraphError>
+    where
+        W: Write,
+        F: Fn(&mut BufWriter<W>, &ET) -> io::Result<()>,
+        C: Fn(&ET, &ET) -> Ordering,
+    {
+        let mut vertices: Vec<_> = self.vertices.values().collect();
+        vertices.sort_unstable_by(|first, second| first.id.cmp(&second.id));
+        for vertex in vertices {
+            if let Some(vertex_value) = &vertex.value {
+                writeln!(buf_writer, "{} {}", vertex.id, escape_value(vertex_value))?;
+            } else {
+                writeln!(buf_writer, "{}", vertex.id)?;
+            }
+        }
+
+        writeln!(buf_writer, "{}", VERTEX_EDGE_DELEMITER)?;
+
+        let mut edges: Vec<_> = self.logical_edges().collect();
+        edges.sort_unstable_by(|(first_from_id, first), (second_from_id, second)| first_from_id.cmp(second_from_id)
+            .then_with(|| first.to_vertex_id.cmp(&second.to_vertex_id))
+            .then_with(|| match (first.value.as_ref(), second.value.as_ref()) {
+                (Some(first_value), Some(second_value)) => compare_edge_values(first_value, second_value),
+                (first_value, second_value) => first_value.is_some().cmp(&second_value.is_some()),
+            }));
+        for (from_id, edge_direction) in edges {
+            let to_id = &edge_direction.to_vertex_id;
+            if let Some(value) = edge_direction.value.as_ref() {
+                write!(buf_writer, "{} {} ", from_id, to_id)?;
+                write_edge_value(buf_writer, value)?;
+                writeln!(buf_writer)?;
+            } else {
+                writeln!(buf_writer, "{} {}", from_id, to_id)?;
+            }
+        }
+
+        if !self.roles.is_empty() {
+            writeln!(buf_writer, "{}", ROLES_MARKER)?;
+            let mut roles: Vec<_> = self.roles.iter().collect();
+            roles.sort_unstable_by_key(|&(id, _)| id);
+            for (id, role) in roles {
+                writeln!(buf_writer, "{} {}", id, role)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<ID: GraphId + FromStr + Ord> Graph<String, String, ID> {
+    /// Сериализация в TGF: вершины, разделитель `#`, рёбра и, если заданы, роли вершин. Порядок строк
+    /// определяется только содержимым графа (см. `serialize_with`), поэтому одинаковые графы дают
+    /// одинаковый файл. Другой порядок вершин задаёт `serialize_ordered`
+    pub fn serialize<W: Write>(&self, buf_writer: &mut BufWriter<W>) -> Result<(), GraphError> {
+        self.serialize_with(buf_writer, |buf_writer, value| write!(buf_writer, "{}", escape_value(value)), Ord::cmp)
+    }
+}
+
+impl<ID: GraphId + FromStr> Graph<String, String, ID> {
+    /// Десериализация графа с произвольным типом идентификатора вершины, разбираемым через `FromStr`
+    pub fn deserialize_typed<BR: BufRead>(reader: BR) -> Result<Graph<String, String, ID>, GraphError> {
+        Graph::deserialize_impl(reader, &DeserializeOptions::default())
+    }
+
+    fn deserialize_impl<BR: BufRead>(reader: BR, options: &DeserializeOptions) -> Result<Graph<String, String, ID>, GraphError> {
+        let mut reader = TgfReader::with_options(reader, *options);
+        let records = iter::from_fn(|| {
+            let record = reader.next()?;
+            Some((reader.line_no(), record))
+        });
+        Graph::deserialize_records(records, options)
+    }
+
+    pub(crate) fn parse_vertex(line: &str) -> Result<Vertex<String, String, ID>, GraphError> {
+        let mut vertex_data = line.split(DATA_DELIMITER);
+    
+        let vertex_id = vertex_data.next()
+            .ok_or_else(|| GraphError::ParseVertexId(line.to_owned()))?
+            .parse::<ID>()
+            .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
+        let vertex_value: String = vertex_data.collect::<Vec<&str>>().join(DATA_DELIMITER);
+        let vertex_value = if vertex_value.is_empty() {
+            None
+        }
+        else {
+            Some(unescape_value(&vertex_value).into_owned())
+        };
+    
+        Ok(Vertex::new(vertex_id, vertex_value))
+    }
+    
+    /// Возвращает кортеж из двух инцидентных вершин и значения ребра, проверяя, что обе вершины есть в графе
+    pub(crate) fn parse_edge<VT, ET>(line: &str, graph: &Graph<VT, ET, ID>) -> Result<(ID, ID, Option<String>), GraphError> {
+        let (first_vertex_id, second_vertex_id, edge_value) = Graph::<String, String, ID>::parse_edge_ids(line)?;
+        if !graph.contains_vertex(&first_vertex_id) {
+            return Err(GraphError::VertexNotFound(first_vertex_id.to_string()));
+        };
+        if !graph.contains_vertex(&second_vertex_id) {
+            return Err(GraphError::VertexNotFound(second_vertex_id.to_string()));
+        };
+        Ok((first_vertex_id, second_vertex_id, edge_value))
+    }
+
+    /// Возвращает кортеж из двух инцидентных вершин и значения ребра без проверки вершин
+    fn parse_edge_ids(line: &str) -> Result<(ID, ID, Option<String>), GraphError> {
+        let mut edge_data = line.split(DATA_DELIMITER);
+    
+        let first_vertex_id = edge_data.next()
+            .ok_or_else(|| GraphError::ParseVertexId(line.to_owned()))?
+            .parse::<ID>()
+            .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
+        let second_vertex_id = edge_data.next()
+            .ok_or_else(|| GraphError::ParseVertexId(line.to_owned()))?
+            .parse::<ID>()
+            .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
+        let edge_value: String = edge_data.collect::<Vec<&str>>().join(DATA_DELIMITER);
+        let edge_value = if edge_value.is_empty() {
+            None
+        }
+        else {
+            Some(unescape_value(&edge_value).into_owned())
+        };
+
+        Ok((
+            first_vertex_id,
+            second_vertex_id,
+            edge_value
+        ))
+    }
+}
+
+impl<VT: From<String>, ET: From<String>, ID: GraphId + FromStr> Graph<VT, ET, ID> {
+    /// Граф из записей TGF с номерами их строк. Ошибки записей уже содержат номер строки
+    pub(crate) fn deserialize_records<I>(records: I, options: &DeserializeOptions) -> Result<Graph<VT, ET, ID>, GraphError>
+    where
+        I: Iterator<Item = (usize, Result<TgfRecord<ID>, GraphError>)>,
+    {
+        let mut graph = if options.dag {
+            Graph::new_dag()
+        } else if options.directed {
+            Graph::new(GraphType::Directed)
+        } else {
+            Graph::new(GraphType::Undirected)
+        };
+        // Рёбра проверяются сразу, а добавляются пакетом до следующей секции или конца файла
+        let mut edges = Vec::new();
+        let mut edge_line_nos = Vec::new();
+
+        for (line_no, record) in records {
+            match record? {
+                TgfRecord::Edge { from, to, value } => {
+                    graph.prepare_edge(&from, &to, value.as_ref(), Some(line_no), options, edges.len())
+                        .map_err(|error| error.at_line(line_no))?;
+                    edges.push((from, to, value.map(ET::from)));
+                    edge_line_nos.push(line_no);
+                }
+                record => {
+                    graph.flush_edges(&mut edges, &mut edge_line_nos)?;
+                    graph.apply_record(record, Some(line_no), options)
+                        .map_err(|error| error.at_line(line_no))?;
+                }
+            }
+        }
+        graph.flush_edges(&mut edges, &mut edge_line_nos)?;
+
+        Ok(graph)
+    }
+
+    /// Добавляет накопленные рёбра пакетом, ошибка относится к строке ребра
+    fn flush_edges(&mut self, edges: &mut Vec<NewEdge<ET, ID>>, edge_line_nos: &mut Vec<usize>) -> Result<(), GraphError> {
+        if edges.is_empty() {
+            return Ok(());
+        }
+        self.insert_edges(mem::take(edges))
+            .map_err(|(index, error)| error.at_line(edge_line_nos[index]))?;
+        edge_line_nos.clear();
+        Ok(())
+    }
+
+    /// Разбор одной строки TGF. При ошибке граф не меняется
+    pub(crate) fn deserialize_line(
+        &mut self,
+        scan_state: &mut ScanState,
+        line: &str,
+        line_no: Option<usize>,
+        options: &DeserializeOptions,
+    ) -> Result<(), GraphError> {
+        match parse_line(scan_state, line, options)? {
+            Some(record) => self.apply_record(record, line_no, options),
+            None => Ok(()),
+        }
+    }
+
+    /// Добавляет в граф запись с проверкой ограничений. При ошибке граф не меняется
+    fn apply_record(&mut self, record: TgfRecord<ID>, line_no: Option<usize>, options: &DeserializeOptions) -> Result<(), GraphError> {
+        let limits = &options.limits;
+        match record {
+            TgfRecord::Vertex { id, value } => {
+                DeserializeLimits::check(limits.max_vertices, self.vertex_count() + 1, LimitKind::Vertices, line_no)?;
+                DeserializeLimits::check(limits.max_value_len, value.as_ref().map_or(0, String::len), LimitKind::ValueLength, line_no)?;
+                self.add_vertex(Vertex::new(id, value.map(VT::from)))
+            }
+            TgfRecord::Edge { from, to, value } => {
+                self.prepare_edge(&from, &to, value.as_ref(), line_no, options, 0)?;
+                self.add_edge(from, to, value.map(ET::from))
+            }
+            TgfRecord::Role { id, role } => self.set_role(id, role).map(drop),
+            TgfRecord::Patch(line) => self.apply_patch_line(&line),
+        }
+    }
+
+    /// Проверка ограничений для ребра и создание недостающих концов (если разрешено).
+    /// `pending_edges` - число проверенных, но ещё не добавленных в граф рёбер
+    fn prepare_edge(
+        &mut self,
+        from: &ID,
+        to: &ID,
+        value: Option<&String>,
+        line_no: Option<usize>,
+        options: &DeserializeOptions,
+        pending_edges: usize,
+    ) -> Result<(), GraphError> {
+        let limits = &options.limits;
+        let mut missing_ids = vec![from.clone()];
+        if from != to {
+            missing_ids.push(to.clone());
+        }
+        missing_ids.retain(|id| !self.contains_vertex(id));
+        if let (false, Some(missing_id)) = (options.auto_create_vertices, missing_ids.first()) {
+            return Err(GraphError::VertexNotFound(missing_id.to_string()));
+        }
+        DeserializeLimits::check(limits.max_vertices, self.vertex_count() + missing_ids.len(), LimitKind::Vertices, line_no)?;
+        DeserializeLimits::check(limits.max_edges, self.edge_count() + pending_edges + 1, LimitKind::Edges, line_no)?;
+        DeserializeLimits::check(limits.max_value_len, value.map_or(0, String::len), LimitKind::ValueLength, line_no)?;
+        // У только что созданной вершины нет рёбер, поэтому ошибка повтора ребра возможна, только если вершины не создавались
+        for missing_id in missing_ids {
+            self.add_vertex(Vertex::new(missing_id, None))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use super::*;
+
+/// Что делать с вершинами, идентификаторы которых повторяются в разных фрагментах
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Повтор идентификатора - ошибка
+    Error,
+    /// Остаётся вершина из первого фрагмента, рёбра следующих фрагментов присоединяются к ней
+    KeepFirst,
+    /// Идентификаторы каждого следующего фрагмента сдвигаются за максимальный идентификатор уже прочитанных
+    Offset,
+}
+
+/// Строка, начинающаяся с этого символа и разделителя фрагментов, считается экранированной строкой фрагмента
+const FRAGMENT_ESCAPE: char = '\\';
+
+impl Graph<String, String> {
+    /// Десериализация потока из нескольких TGF-фрагментов, разделённых строкой `fragment_separator`.
+    /// Каждый фрагмент разбирается отдельно (рёбра ссылаются на вершины своего фрагмента), затем фрагменты объединяются
+    pub fn deserialize_concatenated<BR: BufRead>(
+        reader: BR,
+        fragment_separator: &str,
+        collision: CollisionPolicy,
+    ) -> Result<Graph<String, String>, GraphError> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        let mut fragment = String::new();
+        // Номер строки потока, предшествующей фрагменту: ошибки фрагмента пересчитываются в номера строк потока
+        let mut fragment_line_offset = 0;
+
+        for (line_index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let trimmed_line = line.trim();
+            if trimmed_line == fragment_separator {
+                graph.merge_fragment(&fragment, fragment_line_offset, collision)?;
+                fragment.clear();
+                fragment_line_offset = line_index + 1;
+                continue;
+            }
+            match trimmed_line.strip_prefix(FRAGMENT_ESCAPE) {
+                Some(unescaped_line) if unescaped_line == fragment_separator => fragment.push_str(unescaped_line),
+                _ => fragment.push_str(&line),
+            }
+            fragment.push('\n');
+        }
+        graph.merge_fragment(&fragment, fragment_line_offset, collision)?;
+
+        Ok(graph)
+    }
+
+    fn merge_fragment(&mut self, fragment: &str, line_offset: usize, collision: CollisionPolicy) -> Result<(), GraphError> {
+        if fragment.trim().is_empty() {
+            return Ok(());
+        }
+        let fragment_graph = Graph::deserialize(fragment.as_bytes())
+            .map_err(|error| match error {
+                GraphError::ParseLine { line_no, source } => GraphError::ParseLine { line_no: line_no + line_offset, source },
+                error => error,
+            })?;
+
+        let offset = match (collision, self.vertices.keys().max()) {
+            (CollisionPolicy::Offset, Some(max_id)) => max_id + 1,
+            _ => 0,
+        };
+        let mut fragment_ids = HashMap::new();
+        for &id in fragment_graph.vertices.keys() {
+            let new_id = id.checked_add(offset)
+                .ok_or_else(|| GraphError::WrongVertexIdType(id.to_string()))?;
+            fragment_ids.insert(id, new_id);
+        }
+
+        let mut edges = Vec::new();
+        for (id, vertex) in fragment_graph.vertices {
+            let new_id = fragment_ids[&id];
+            for edge_direction in vertex.own_edge_directions() {
+                let value = edge_direction.value.as_ref().clone();
+                edges.push((new_id, fragment_ids[&edge_direction.to_vertex_id], value));
+            }
+            if self.contains_vertex(&new_id) {
+                match collision {
+                    CollisionPolicy::KeepFirst => continue,
+                    CollisionPolicy::Error | CollisionPolicy::Offset => {
+                        return Err(GraphError::VertexAlreadyExist(new_id.to_string()));
+                    }
+                }
+            }
+            self.add_vertex(Vertex::new(new_id, vertex.value))?;
+        }
+        for (from_id, to_id, value) in edges {
+            if collision == CollisionPolicy::KeepFirst && self.contains_edge_direction(&from_id, &to_id)? {
+                continue;
+            }
+            self.add_edge(from_id, to_id, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const FRAGMENTS: &str = "1 January
+2 February
+#
+1 2 winter
+%%
+1 March
+3 May
+#
+3 1 spring";
+
+    fn edges(graph: &Graph<String, String>) -> Vec<(DefaultGraphIdType, DefaultGraphIdType, Option<String>)> {
+        let mut edges: Vec<_> = graph.vertices.values()
+            .flat_map(|vertex| vertex.own_edge_directions()
+                .map(move |edge_direction| (vertex.id, edge_direction.to_vertex_id, edge_direction.value.as_ref().clone())))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    fn value(graph: &Graph<String, String>, id: DefaultGraphIdType) -> Option<&str> {
+        graph.vertices[&id].value.as_deref()
+    }
+
+    #[test]
+    fn collision_error() -> Result<()> {
+        let result = Graph::deserialize_concatenated(FRAGMENTS.as_bytes(), "%%", CollisionPolicy::Error);
+        match result {
+            Err(GraphError::VertexAlreadyExist(id)) if id == "1" => Ok(()),
+            _ => bail!("vertex id collision not reported"),
+        }
+    }
+
+    #[test]
+    fn collision_keep_first() -> Result<()> {
+        let graph = Graph::deserialize_concatenated(FRAGMENTS.as_bytes(), "%%", CollisionPolicy::KeepFirst)?;
+        if graph.vertices.len() == 3
+            && value(&graph, 1) == Some("January")
+            && edges(&graph) == vec![(1, 2, Some("winter".to_owned())), (3, 1, Some("spring".to_owned()))] {
+            Ok(())
+        } else {
+            bail!("first fragment vertex not kept")
+        }
+    }
+
+    #[test]
+    fn collision_offset() -> Result<()> {
+        let graph = Graph::deserialize_concatenated(FRAGMENTS.as_bytes(), "%%", CollisionPolicy::Offset)?;
+        if graph.vertices.len() == 4
+            && value(&graph, 1) == Some("January")
+            && value(&graph, 4) == Some("March")
+            && value(&graph, 6) == Some("May")
+            && edges(&graph) == vec![(1, 2, Some("winter".to_owned())), (6, 4, Some("spring".to_owned()))] {
+            Ok(())
+        } else {
+            bail!("fragment ids not offset")
+        }
+    }
+
+    #[test]
+    fn escaped_separator() -> Result<()> {
+        let tgf = "1 %%\n2 a\n#\n1 2 %%\n%%\n3 b\n#\n";
+        let graph = Graph::deserialize_concatenated(tgf.as_bytes(), "%%", CollisionPolicy::Error)?;
+        let escaped_tgf = "1 a\n\\%%\n";
+        let escaped_result = Graph::deserialize_concatenated(escaped_tgf.as_bytes(), "%%", CollisionPolicy::Error);
+        if graph.vertices.len() == 3
+            && value(&graph, 1) == Some("%%")
+            && edges(&graph) == vec![(1, 2, Some("%%".to_owned()))]
+            && matches!(escaped_result, Err(GraphError::ParseLine { line_no: 2, source }) if matches!(*source, GraphError::WrongVertexIdType(ref line) if line == "%%")) {
+            Ok(())
+        } else {
+            bail!("separator inside fragment confused with fragment separator")
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use crate::utils::SplitMix64;
+use super::*;
+
+impl<VT, ET> Graph<VT, ET> {
+    /// Случайный простой неориентированный граф G(n, m): `vertex_count` вершин с идентификаторами 0..n
+    /// и `edge_count` различных рёбер без петель. Одно и то же зерно даёт один и тот же граф
+    pub fn gnm_random(vertex_count: u32, edge_count: usize, seed: u64) -> Result<Graph<VT, ET>, GraphError> {
+        let max_edge_count = u64::from(vertex_count) * u64::from(vertex_count.saturating_sub(1)) / 2;
+        if edge_count as u64 > max_edge_count {
+            return Err(GraphError::LimitExceeded {
+                which: LimitKind::Edges,
+                limit: max_edge_count as usize,
+                at_line: None,
+            });
+        }
+
+        let mut graph = Graph::with_vertices(0..vertex_count, GraphType::Undirected);
+        let mut rng = SplitMix64::new(seed);
+        let mut edges = HashSet::with_capacity(edge_count);
+        while edges.len() < edge_count {
+            let from_id = rng.next_below(u64::from(vertex_count)) as DefaultGraphIdType;
+            let to_id = rng.next_below(u64::from(vertex_count)) as DefaultGraphIdType;
+            if from_id != to_id && edges.insert((from_id.min(to_id), from_id.max(to_id))) {
+                graph.add_edge(from_id, to_id, None)?;
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Случайный граф Эрдёша - Реньи G(n, p): `vertex_count` вершин с идентификаторами 0..n, каждое ребро
+    /// между различными вершинами (в ориентированном графе - каждая упорядоченная пара) есть с вероятностью `probability`.
+    /// Промежутки между выбранными парами берутся из геометрического распределения (метод Батагеля - Брандеса),
+    /// поэтому время растёт с числом рёбер, а не с квадратом числа вершин. Одно и то же зерно даёт один и тот же граф
+    pub fn gnp_random(vertex_count: u32, probability: f64, graph_type: GraphType, seed: u64) -> Result<Graph<VT, ET>, GraphError> {
+        assert!((0.0..=1.0).contains(&probability), "edge probability must be in [0, 1]");
+        let mut graph = Graph::with_vertices(0..vertex_count, graph_type);
+        if probability == 0.0 {
+            return Ok(graph);
+        }
+
+        // Пары перебираются по строкам: в строке `row` неориентированного графа пары с вершинами 0..row,
+        // ориентированного - рёбра из `row` во все остальные вершины
+        let vertex_count = i64::from(vertex_count);
+        let row_len = |row: i64| match graph_type {
+            GraphType::Undirected => row,
+            GraphType::Directed => vertex_count - 1,
+        };
+        let max_skip = (vertex_count * vertex_count) as f64;
+        let log_miss_probability = (1.0 - probability).ln();
+        let mut rng = SplitMix64::new(seed);
+        let (mut row, mut column) = (0, -1);
+        loop {
+            let skip = if probability < 1.0 {
+                ((1.0 - rng.next_f64()).ln() / log_miss_probability).floor().min(max_skip) as i64
+            } else {
+                0
+            };
+            column += 1 + skip;
+            while row < vertex_count && column >= row_len(row) {
+                column -= row_len(row);
+                row += 1;
+            }
+            if row >= vertex_count {
+                return Ok(graph);
+            }
+            let (from_id, to_id) = match graph_type {
+                GraphType::Undirected => (column, row),
+                GraphType::Directed if column >= row => (row, column + 1),
+                GraphType::Directed => (row, column),
+            };
+            graph.add_edge(from_id as DefaultGraphIdType, to_id as DefaultGraphIdType, None)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn gnm_random() -> Result<()> {
+        let graph = Graph::<(), ()>::gnm_random(100, 300, 7)?;
+        let same_seed_graph = Graph::<(), ()>::gnm_random(100, 300, 7)?;
+        let other_seed_graph = Graph::<(), ()>::gnm_random(100, 300, 8)?;
+        let complete_graph = Graph::<(), ()>::gnm_random(5, 10, 1)?;
+        let too_many_edges = Graph::<(), ()>::gnm_random(5, 11, 1);
+        if graph.vertex_count() == 100
+            && graph.edge_count() == 300
+            && graph == same_seed_graph
+            && graph != other_seed_graph
+            && complete_graph.edge_count() == 10
+            && matches!(too_many_edges, Err(GraphError::LimitExceeded { limit: 10, .. })) {
+            Ok(())
+        } else {
+            bail!("wrong random graph")
+        }
+    }
+
+    #[test]
+    fn gnp_random() -> Result<()> {
+        let graph = Graph::<(), ()>::gnp_random(200, 0.05, GraphType::Undirected, 3)?;
+        let same_seed_graph = Graph::<(), ()>::gnp_random(200, 0.05, GraphType::Undirected, 3)?;
+        let other_seed_graph = Graph::<(), ()>::gnp_random(200, 0.05, GraphType::Undirected, 4)?;
+        let directed_graph = Graph::<(), ()>::gnp_random(200, 0.05, GraphType::Directed, 3)?;
+        let stats = graph.stats();
+        // Ожидаемое число рёбер: 0.05 * 200 * 199 / 2 = 995 и вдвое больше для ориентированного графа
+        if graph == same_seed_graph
+            && graph != other_seed_graph
+            && (900..1100).contains(&graph.edge_count())
+            && (1800..2200).contains(&directed_graph.edge_count())
+            && stats.self_loops == 0
+            && stats.average_degree * 200.0 == 2.0 * graph.edge_count() as f64
+            && graph.edges().iter().all(|(from_id, to_id, _)| from_id < to_id)
+            && Graph::<(), ()>::gnp_random(50, 0.0, GraphType::Directed, 1)?.edge_count() == 0
+            && Graph::<(), ()>::gnp_random(1, 1.0, GraphType::Undirected, 1)?.edge_count() == 0 {
+            Ok(())
+        } else {
+            bail!("wrong G(n, p) graph with {} edges", graph.edge_count())
+        }
+    }
+}
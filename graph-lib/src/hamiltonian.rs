@@ -0,0 +1,145 @@
+use super::*;
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Гамильтонов путь: последовательность всех вершин графа, каждая ровно один раз, соседние соединены ребром
+    /// (в ориентированном графе - по направлению рёбер). Точный перебор с возвратом без рекурсии: время растёт
+    /// экспоненциально с числом вершин, поэтому метод подходит только для небольших графов (десятки вершин).
+    /// Перебор отсекается заранее, если граф несвязен, в ориентированном графе больше одной вершины без входящих рёбер
+    /// или в неориентированном больше двух вершин степени 1 - такие вершины могут быть только концами пути.
+    /// Начальные вершины и соседи перебираются по возрастанию идентификатора, поэтому результат детерминирован.
+    /// `None` - пути нет. У графа без вершин путь пустой
+    pub fn hamiltonian_path(&self) -> Option<Vec<ID>> {
+        let mut ids: Vec<_> = self.vertices.keys().collect();
+        ids.sort_unstable();
+        if ids.len() <= 1 {
+            return Some(ids.into_iter().cloned().collect());
+        }
+        if self.connected_components().len() > 1 {
+            return None;
+        }
+
+        let mut adjacency = self.index_adjacency(&ids);
+        for (index, neighbours) in adjacency.iter_mut().enumerate() {
+            neighbours.retain(|&neighbour_index| neighbour_index != index);
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+        // Вершины, которые могут быть только началом пути
+        let forced_starts: Vec<_> = match self.r#type {
+            GraphType::Directed => {
+                let mut has_predecessor = vec![false; ids.len()];
+                for neighbours in &adjacency {
+                    for &neighbour_index in neighbours {
+                        has_predecessor[neighbour_index] = true;
+                    }
+                }
+                (0..ids.len()).filter(|&index| !has_predecessor[index]).collect()
+            }
+            GraphType::Undirected => {
+                let ends: Vec<_> = (0..ids.len()).filter(|&index| adjacency[index].len() == 1).collect();
+                if ends.len() > 2 {
+                    return None;
+                }
+                ends.into_iter().take(1).collect()
+            }
+        };
+        if forced_starts.len() > 1 {
+            return None;
+        }
+        let starts = if forced_starts.is_empty() { (0..ids.len()).collect() } else { forced_starts };
+
+        let mut visited = vec![false; ids.len()];
+        for start in starts {
+            let mut path = vec![start];
+            // Номер следующего соседа для каждой вершины пути
+            let mut next_positions = vec![0];
+            visited[start] = true;
+            while let (Some(&index), Some(next_position)) = (path.last(), next_positions.last_mut()) {
+                if path.len() == ids.len() {
+                    return Some(path.into_iter().map(|index| ids[index].clone()).collect());
+                }
+                if let Some(&neighbour_index) = adjacency[index].get(*next_position) {
+                    *next_position += 1;
+                    if !visited[neighbour_index] {
+                        visited[neighbour_index] = true;
+                        path.push(neighbour_index);
+                        next_positions.push(0);
+                    }
+                } else {
+                    visited[index] = false;
+                    path.pop();
+                    next_positions.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Путь проходит все вершины по одному разу по рёбрам графа
+    fn is_hamiltonian<VT, ET>(graph: &Graph<VT, ET>, path: &[u32]) -> bool {
+        let mut sorted_path = path.to_vec();
+        sorted_path.sort_unstable();
+        sorted_path.dedup();
+        sorted_path.len() == graph.vertex_count()
+            && path.len() == graph.vertex_count()
+            && path.windows(2).all(|hop| graph.has_edge(hop[0], hop[1]))
+    }
+
+    #[test]
+    fn small_graphs() -> Result<()> {
+        let (grid, _) = Graph::<(), ()>::grid(3, 4)?;
+        let petersen = Graph::<(), ()>::petersen();
+        let grid_path = grid.hamiltonian_path().unwrap_or_default();
+        let petersen_path = petersen.hamiltonian_path().unwrap_or_default();
+        let mut star = Graph::<(), ()>::new(GraphType::Undirected);
+        for id in 0..4 {
+            star.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..4 {
+            star.add_edge(0, id, None)?;
+        }
+        if is_hamiltonian(&grid, &grid_path)
+            && grid_path[0] == 0
+            && is_hamiltonian(&petersen, &petersen_path)
+            && star.hamiltonian_path().is_none()
+            && Graph::<(), ()>::complete(1, GraphType::Undirected).hamiltonian_path() == Some(vec![0])
+            && Graph::<(), ()>::new(GraphType::Undirected).hamiltonian_path() == Some(Vec::new()) {
+            Ok(())
+        } else {
+            bail!("wrong hamiltonian paths: grid {:?}, petersen {:?}", grid_path, petersen_path)
+        }
+    }
+
+    #[test]
+    fn directed_and_disconnected() -> Result<()> {
+        let mut directed = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=5 {
+            directed.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(1, 2), (1, 3), (3, 2), (2, 4), (4, 5), (5, 3), (3, 3)] {
+            directed.add_edge(from_id, to_id, None)?;
+        }
+        let path = directed.hamiltonian_path();
+        let mut two_sources = directed.clone();
+        two_sources.add_vertex(Vertex::new(6, None))?;
+        two_sources.add_edge(6, 2, None)?;
+        let mut disconnected = Graph::<(), ()>::path(3);
+        disconnected.add_vertex(Vertex::new(3, None))?;
+        if path == Some(vec![1, 2, 4, 5, 3])
+            && two_sources.hamiltonian_path().is_none()
+            && disconnected.hamiltonian_path().is_none() {
+            Ok(())
+        } else {
+            bail!("wrong directed hamiltonian path {:?}", path)
+        }
+    }
+}
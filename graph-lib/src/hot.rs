@@ -0,0 +1,271 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use super::*;
+
+/// Счётчики обращений к закреплённым спискам соседей
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HotStats {
+    pub pinned_vertices: usize,
+    /// Запросы соседей, обслуженные из закреплённых списков
+    pub hits: u64,
+    /// Запросы соседей незакреплённых вершин или вершин, рёбра которых менялись после закрепления.
+    /// Считаются, только пока есть закреплённые вершины
+    pub misses: u64,
+}
+
+/// Закреплённые списки соседей: идентификаторы соседей всех закреплённых вершин лежат подряд в одном буфере,
+/// вершина находится в нём через небольшую таблицу с открытой адресацией
+#[derive(Debug)]
+pub(crate) struct HotCache<ID> {
+    /// Номер записи в `entries` для каждой ячейки таблицы, размер таблицы - степень двойки
+    slots: Vec<Option<usize>>,
+    entries: Vec<HotEntry<ID>>,
+    neighbour_ids: Vec<ID>,
+    hasher: RandomState,
+    /// Счётчики атомарные, чтобы запросы соседей из нескольких потоков не требовали `&mut`
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug)]
+struct HotEntry<ID> {
+    id: ID,
+    start: usize,
+    len: usize,
+    /// Сбрасывается при изменении рёбер вершины, после чего запросы идут мимо закреплённого списка
+    valid: bool,
+}
+
+impl<ID> Default for HotCache<ID> {
+    fn default() -> HotCache<ID> {
+        HotCache {
+            slots: Vec::new(),
+            entries: Vec::new(),
+            neighbour_ids: Vec::new(),
+            hasher: RandomState::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<ID: GraphId> HotCache<ID> {
+    pub(crate) fn lookup(&self, id: &ID) -> Option<&[ID]> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        match self.find_entry(id) {
+            Some(entry) if entry.valid => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(&self.neighbour_ids[entry.start..entry.start + entry.len])
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn invalidate(&mut self, id: &ID) {
+        if self.entries.is_empty() {
+            return;
+        }
+        if let Some(entry_index) = self.find_slot(id).and_then(|slot| self.slots[slot]) {
+            self.entries[entry_index].valid = false;
+        }
+    }
+
+    /// Переименовывает закреплённые вершины и их соседей, сохраняя счётчики.
+    /// Записи, переставшие использоваться после изменения рёбер, отбрасываются
+    pub(crate) fn relabel(&mut self, new_ids: &HashMap<ID, ID>) {
+        self.entries.retain(|entry| entry.valid && new_ids.contains_key(&entry.id));
+        for entry in &mut self.entries {
+            entry.id = new_ids[&entry.id].clone();
+            for neighbour_id in &mut self.neighbour_ids[entry.start..entry.start + entry.len] {
+                *neighbour_id = new_ids[neighbour_id].clone();
+            }
+        }
+        self.slots.fill(None);
+        for entry_index in 0..self.entries.len() {
+            let slot = self.find_slot(&self.entries[entry_index].id).expect("slot table is not empty");
+            self.slots[slot] = Some(entry_index);
+        }
+    }
+
+    fn find_entry(&self, id: &ID) -> Option<&HotEntry<ID>> {
+        self.find_slot(id)
+            .and_then(|slot| self.slots[slot])
+            .map(|entry_index| &self.entries[entry_index])
+    }
+
+    /// Ячейка таблицы с записью вершины либо первая пустая ячейка, в которую запись была бы добавлена
+    fn find_slot(&self, id: &ID) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.slots.len() - 1;
+        let mut slot = self.hasher.hash_one(id) as usize & mask;
+        loop {
+            match self.slots[slot] {
+                Some(entry_index) if self.entries[entry_index].id != *id => slot = (slot + 1) & mask,
+                _ => return Some(slot),
+            }
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Копирует списки соседей заданных вершин в общий непрерывный буфер, который `neighbours`,
+    /// `neighbour_count` и обходы просматривают в первую очередь. Уже закреплённые вершины остаются закреплёнными.
+    /// Закреплённый список вершины перестаёт использоваться, как только меняются её рёбра
+    pub fn pin_hot(&mut self, ids: &[ID]) -> Result<(), GraphError> {
+        let missing_ids: Vec<_> = ids
+            .iter()
+            .filter(|&id| !self.contains_vertex(id))
+            .map(|id| id.to_string())
+            .collect();
+        if !missing_ids.is_empty() {
+            return Err(GraphError::VerticesNotFound(missing_ids));
+        }
+
+        let mut pinned_ids: Vec<_> = self.hot.entries
+            .drain(..)
+            .map(|entry| entry.id)
+            .filter(|id| self.vertices.contains_key(id))
+            .collect();
+        pinned_ids.extend(ids.iter().cloned());
+
+        let mut hot = HotCache {
+            slots: vec![None; (pinned_ids.len() * 2).next_power_of_two()],
+            hits: AtomicU64::new(self.hot.hits.load(Ordering::Relaxed)),
+            misses: AtomicU64::new(self.hot.misses.load(Ordering::Relaxed)),
+            ..HotCache::default()
+        };
+        for id in pinned_ids {
+            let slot = hot.find_slot(&id).expect("slot table is not empty");
+            if hot.slots[slot].is_some() {
+                continue;
+            }
+            let start = hot.neighbour_ids.len();
+            hot.neighbour_ids.extend(self.vertices[&id].edge_directions
+                .iter()
+                .map(|edge_direction| edge_direction.to_vertex_id.clone()));
+            hot.slots[slot] = Some(hot.entries.len());
+            hot.entries.push(HotEntry { id, start, len: hot.neighbour_ids.len() - start, valid: true });
+        }
+        self.hot = hot;
+
+        Ok(())
+    }
+
+    /// Снимает закрепление со всех вершин и сбрасывает счётчики
+    pub fn unpin_all(&mut self) {
+        self.hot = HotCache::default();
+    }
+
+    pub fn hot_stats(&self) -> HotStats {
+        HotStats {
+            pinned_vertices: self.hot.entries.iter().filter(|entry| entry.valid).count(),
+            hits: self.hot.hits.load(Ordering::Relaxed),
+            misses: self.hot.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn fixtures() -> Result<Vec<Graph<String, String>>> {
+        let mut directed_graph = Graph::new(GraphType::Directed);
+        let mut multigraph = Graph::new_multigraph(GraphType::Undirected);
+        for id in 1..=5 {
+            directed_graph.add_vertex(Vertex::new(id, None))?;
+            multigraph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(1, 2), (2, 3), (3, 1), (1, 4), (4, 4), (5, 1)] {
+            directed_graph.add_edge(from_id, to_id, None)?;
+            multigraph.add_edge(from_id, to_id, None)?;
+        }
+        multigraph.add_edge(1, 2, None)?;
+        let undirected_graph = Graph::deserialize("1 a\n2 b\n3 c\n4 d\n#\n1 2\n2 3\n3 4\n4 1\n1 3\n".as_bytes())?;
+        Ok(vec![directed_graph, multigraph, undirected_graph])
+    }
+
+    /// Все наблюдаемые через публичный API соседи и порядки обхода графа
+    fn observations(graph: &Graph<String, String>) -> Result<Vec<Vec<DefaultGraphIdType>>> {
+        let mut ids: Vec<_> = graph.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        let mut observations = Vec::new();
+        for &id in &ids {
+            observations.push(graph.neighbours(id)?);
+            observations.push(vec![graph.neighbour_count(id)? as DefaultGraphIdType]);
+            observations.push(graph.bfs(id)?.into_iter().map(|(id, _, _)| id).collect());
+            observations.push(graph.dfs(id)?.into_iter().map(|(id, _, _)| id).collect());
+        }
+        Ok(observations)
+    }
+
+    #[test]
+    fn same_results_with_pinning() -> Result<()> {
+        for mut graph in fixtures()? {
+            let unpinned = observations(&graph)?;
+            graph.pin_hot(&[1, 3])?;
+            let pinned = observations(&graph)?;
+            if unpinned != pinned || graph.hot_stats().hits == 0 {
+                bail!("pinning changed graph behaviour")
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalidation_on_edge_mutation() -> Result<()> {
+        for mut graph in fixtures()? {
+            graph.pin_hot(&[1, 2, 3])?;
+            graph.delete_edge(1, 2)?;
+            graph.add_edge(3, 3, None)?;
+            graph.delete_vertex(4)?;
+            let pinned = observations(&graph)?;
+            graph.unpin_all();
+            if pinned != observations(&graph)? {
+                bail!("stale pinned neighbours used after edge mutation")
+            }
+        }
+
+        let mut graph = fixtures()?.remove(0);
+        graph.pin_hot(&[1, 2])?;
+        graph.add_edge(1, 5, None)?;
+        if graph.neighbours(1)? == vec![2, 4, 5] && graph.hot_stats().pinned_vertices == 1 {
+            Ok(())
+        } else {
+            bail!("mutated vertex still pinned")
+        }
+    }
+
+    #[test]
+    fn hit_counter() -> Result<()> {
+        let mut graph = fixtures()?.remove(0);
+        graph.pin_hot(&[1])?;
+        graph.neighbours(1)?;
+        graph.neighbour_count(1)?;
+        graph.neighbours(2)?;
+        let missing_result = graph.pin_hot(&[1, 7, 8]);
+        let stats = graph.hot_stats();
+        graph.unpin_all();
+        if stats == (HotStats { pinned_vertices: 1, hits: 2, misses: 1 })
+            && graph.hot_stats() == (HotStats { pinned_vertices: 0, hits: 0, misses: 0 })
+            && matches!(missing_result, Err(GraphError::VerticesNotFound(ids)) if ids == vec!["7", "8"]) {
+            Ok(())
+        } else {
+            bail!("wrong hot stats {:?}", stats)
+        }
+    }
+}
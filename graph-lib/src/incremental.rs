@@ -0,0 +1,217 @@
+use super::*;
+
+/// Вершины, значения или списки рёбер которых отличаются между двумя версиями графа
+#[derive(Debug, Clone, Default)]
+pub struct GraphDiff<ID = DefaultGraphIdType> {
+    changed_ids: HashSet<ID>,
+}
+
+impl<ID: GraphId> GraphDiff<ID> {
+    pub fn from_vertices<I: IntoIterator<Item = ID>>(changed_ids: I) -> GraphDiff<ID> {
+        GraphDiff { changed_ids: changed_ids.into_iter().collect() }
+    }
+
+    /// Вершины, добавленные, удалённые или изменённые в `new_graph` по сравнению с `old_graph`.
+    /// Порядок рёбер вершины учитывается, т.к. от него зависит порядок обхода
+    pub fn between<VT: PartialEq, ET: PartialEq>(old_graph: &Graph<VT, ET, ID>, new_graph: &Graph<VT, ET, ID>) -> GraphDiff<ID> {
+        let removed_ids = old_graph.vertices.keys()
+            .filter(|&id| !new_graph.contains_vertex(id));
+        let changed_ids = new_graph.vertices.iter()
+            .filter(|(id, vertex)| old_graph.vertices.get(id)
+                .is_none_or(|old_vertex| old_vertex.value != vertex.value
+                    || !same_edge_sequence(&old_vertex.edge_directions, &vertex.edge_directions)))
+            .map(|(id, _)| id);
+        GraphDiff::from_vertices(removed_ids.chain(changed_ids).cloned())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changed_ids.is_empty()
+    }
+
+    pub fn contains(&self, id: &ID) -> bool {
+        self.changed_ids.contains(id)
+    }
+}
+
+fn same_edge_sequence<ET: PartialEq, ID: PartialEq>(edges: &[EdgeDirection<ET, ID>], other_edges: &[EdgeDirection<ET, ID>]) -> bool {
+    edges.len() == other_edges.len()
+        && edges.iter().zip(other_edges).all(|(edge_direction, other_edge_direction)| {
+            edge_direction.to_vertex_id == other_edge_direction.to_vertex_id && edge_direction.value == other_edge_direction.value
+        })
+}
+
+/// Дерево обхода в ширину, сохранённое для повторного использования в `Graph::bfs_incremental`
+#[derive(Debug, Clone)]
+pub struct TraversalCache<ID = DefaultGraphIdType> {
+    start_id: ID,
+    order: Vec<ID>,
+    /// Вершина, из которой обход впервые пришёл в данную
+    parents: HashMap<ID, ID>,
+    /// Номер версии кэша: 0 у первого обхода, увеличивается при каждом `bfs_incremental`
+    generation: u64,
+    reused: bool,
+    expanded_vertices: usize,
+}
+
+impl<ID: GraphId> TraversalCache<ID> {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Получен ли кэш без обхода, из предыдущего кэша. `false` - обход выполнен заново
+    pub fn reused(&self) -> bool {
+        self.reused
+    }
+
+    /// Сколько вершин обошёл вызов, построивший кэш
+    pub fn expanded_vertices(&self) -> usize {
+        self.expanded_vertices
+    }
+
+    pub fn parent(&self, id: &ID) -> Option<&ID> {
+        self.parents.get(id)
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Обход в ширину, как `bfs`, вместе с кэшем для `bfs_incremental`
+    pub fn bfs_cached(&self, start_id: ID) -> Result<(BfsResult<'_, VT, ID>, TraversalCache<ID>), GraphError> {
+        let mut order = Vec::new();
+        let mut parents = HashMap::new();
+        self.traverse(start_id.clone(), TraversalOrder::BreadthFirst, |vertex| {
+            order.push(vertex.id.clone());
+            for edge_direction in &vertex.edge_directions {
+                if edge_direction.to_vertex_id != start_id {
+                    parents.entry(edge_direction.to_vertex_id.clone()).or_insert_with(|| vertex.id.clone());
+                }
+            }
+            ControlFlow::Continue(())
+        })?;
+
+        let cache = TraversalCache {
+            start_id,
+            expanded_vertices: order.len(),
+            order,
+            parents,
+            generation: 0,
+            reused: false,
+        };
+        Ok((self.bfs_result(&cache.order), cache))
+    }
+
+    /// Обход в ширину после изменения графа. `previous` - кэш обхода версии графа, от которой отсчитан `diff`.
+    /// Если ни одна изменённая вершина не была посещена предыдущим обходом, порядок обхода не мог измениться,
+    /// и результат собирается из кэша без обхода. Иначе обход выполняется заново (`TraversalCache::reused` = `false`).
+    /// Результат всегда совпадает с `bfs(start_id)`
+    pub fn bfs_incremental(
+        &self,
+        start_id: ID,
+        previous: &TraversalCache<ID>,
+        diff: &GraphDiff<ID>,
+    ) -> Result<(BfsResult<'_, VT, ID>, TraversalCache<ID>), GraphError> {
+        let generation = previous.generation + 1;
+        let affected = previous.start_id != start_id || previous.order.iter().any(|id| diff.contains(id));
+        if affected {
+            let (result, cache) = self.bfs_cached(start_id)?;
+            return Ok((result, TraversalCache { generation, ..cache }));
+        }
+
+        let cache = TraversalCache {
+            generation,
+            reused: true,
+            expanded_vertices: 0,
+            ..previous.clone()
+        };
+        Ok((self.bfs_result(&cache.order), cache))
+    }
+
+    fn bfs_result(&self, order: &[ID]) -> BfsResult<'_, VT, ID> {
+        order.iter()
+            .map(|id| {
+                let vertex = &self.vertices[id];
+                let neighbour_ids = vertex.edge_directions
+                    .iter()
+                    .map(|edge_direction| edge_direction.to_vertex_id.clone())
+                    .collect();
+                (id.clone(), vertex.value.as_ref(), neighbour_ids)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::SplitMix64;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const VERTEX_COUNT: u64 = 30;
+
+    fn random_edit(graph: &mut Graph<String, String>, random: &mut SplitMix64) {
+        let first_id = random.next_below(VERTEX_COUNT) as DefaultGraphIdType;
+        let second_id = random.next_below(VERTEX_COUNT) as DefaultGraphIdType;
+        // Ошибки (например, повторное ребро или отсутствующая вершина) означают пустую правку
+        let _ = match random.next_below(5) {
+            0 | 1 => graph.add_edge(first_id, second_id, None),
+            2 => graph.delete_edge(first_id, second_id),
+            3 => graph.delete_vertex(first_id),
+            _ => graph.add_vertex(Vertex::new(first_id, Some(format!("v{}", second_id)))),
+        };
+    }
+
+    #[test]
+    fn randomized_edits_match_fresh_bfs() -> Result<()> {
+        for seed in 0..20 {
+            let mut random = SplitMix64::new(seed);
+            let mut graph = Graph::<String, String>::gnm_random(VERTEX_COUNT as u32, 25, seed)?;
+            let (_, mut cache) = graph.bfs_cached(0)?;
+            for _ in 0..50 {
+                let previous_graph = graph.clone();
+                random_edit(&mut graph, &mut random);
+                if !graph.contains_vertex(&0) {
+                    graph.add_vertex(Vertex::new(0, None))?;
+                }
+                let diff = GraphDiff::between(&previous_graph, &graph);
+                let (result, new_cache) = graph.bfs_incremental(0, &cache, &diff)?;
+                if result != graph.bfs(0)? {
+                    bail!("incremental bfs differs from fresh bfs, seed {}", seed)
+                }
+                cache = new_cache;
+            }
+            if cache.generation() != 50 {
+                bail!("wrong cache generation {}", cache.generation())
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn irrelevant_edit_skips_traversal() -> Result<()> {
+        let mut graph = Graph::deserialize("1\n2\n3\n4\n5\n6\n#\n1 2\n2 3\n4 5".as_bytes())?;
+        let (_, cache) = graph.bfs_cached(1)?;
+        let previous_graph = graph.clone();
+        graph.add_edge(5, 6, Some("far".to_owned()))?;
+        graph.set_vertex_value(4, Some("away".to_owned()))?;
+        let (_, irrelevant_cache) = graph.bfs_incremental(1, &cache, &GraphDiff::between(&previous_graph, &graph))?;
+        let (_, empty_cache) = graph.bfs_incremental(1, &irrelevant_cache, &GraphDiff::default())?;
+        let previous_graph = graph.clone();
+        graph.add_edge(3, 4, None)?;
+        let (result, relevant_cache) = graph.bfs_incremental(1, &empty_cache, &GraphDiff::between(&previous_graph, &graph))?;
+
+        if cache.expanded_vertices() == 3
+            && irrelevant_cache.reused()
+            && irrelevant_cache.expanded_vertices() == 0
+            && empty_cache.reused()
+            && !relevant_cache.reused()
+            && relevant_cache.expanded_vertices() == 6
+            && relevant_cache.parent(&4) == Some(&3)
+            && result.len() == 6 {
+            Ok(())
+        } else {
+            bail!("wrong incremental work: {:?}", relevant_cache)
+        }
+    }
+}
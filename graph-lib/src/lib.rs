@@ -1,61 +1,259 @@
 use std::{
     io::{BufRead, Write, BufWriter},
     collections::{HashMap, HashSet, VecDeque},
-    rc::Rc,
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::ControlFlow,
+    str::FromStr,
+    sync::Arc,
 };
-use errors::GraphError;
+pub use errors::GraphError;
+pub use fragments::CollisionPolicy;
+pub use limits::{DeserializeLimits, DeserializeOptions, LimitKind};
+pub use rewrite::{RewriteReport, RewriteTargets};
+pub use traversal::BfsIter;
+pub use astar::AstarPath;
+pub use distribution::{DegreeKind, DegreeStats};
+pub use distances::ShortestPaths;
+pub use spanning_tree::WeightedEdge;
+pub use order::VertexOrder;
+pub use hot::HotStats;
+use hot::HotCache;
+pub use patch::{GraphFingerprint, UpdateOutcome, UpdateStrategy};
+pub use chains::CollapsedChains;
+pub use merge::MergePolicy;
+pub use memory::MemoryEstimate;
+pub use roles::{RoleViolation, VertexRole};
+use roles::ROLES_MARKER;
+use value_ids::ValueIds;
+use value_index::ValueIndex;
+use adaptive::TargetIndex;
+pub use incremental::{GraphDiff, TraversalCache};
+pub use resilient::LineErrors;
+pub use pattern::{Pattern, PatternMatches, PatternVar};
+pub use multi_source::NearestSource;
+pub use stats::GraphStats;
+pub use plan::MutationPlan;
+pub use builder::GraphBuilder;
+pub use neighbour::{BfsEdgesResult, Neighbour};
+pub use align::{AlignmentReport, normalized_levenshtein};
+pub use reachability::ReachabilityIndex;
+pub use reachability_cache::ReachabilityCache;
+pub use weight::EdgeWeight;
+pub use changes::{ChangedEdge, ChangedVertex, EdgeEntry, GraphChanges, VertexEntry};
+pub use attributes::AttributeMap;
+pub use atlas::GridPositions;
+use attributes::Attributes;
+pub use coloring::Coloring;
+pub use bipartite::Bipartition;
+pub use flow::{FlowAlgorithm, MinCut};
+pub use scc::StronglyConnectedComponents;
+pub use formats::tgf::{TgfReader, TgfRecord};
+pub use traversal_options::{EdgeFilter, NeighbourOrder, TraversalOptions, TraversalResult};
+use traversal::{Traversal, TraversalOrder};
+use escape::escape_value;
+use formats::tgf::{DATA_DELIMITER, ScanState, VERTEX_EDGE_DELEMITER};
+
+#[deny(missing_docs)]
+pub mod algo;
+pub mod errors;
+#[deny(missing_docs)]
+pub mod formats;
+#[deny(missing_docs)]
+pub mod metrics;
+#[deny(missing_docs)]
+pub mod prelude;
 
 mod utils;
-mod errors;
+mod escape;
+mod atlas;
+mod fragments;
+mod limits;
+mod rewrite;
+mod positional;
+mod traversal;
+mod astar;
+mod distribution;
+mod spanning_tree;
+mod order;
+mod hot;
+mod patch;
+mod transpose;
+mod generators;
+mod components;
+mod centrality;
+mod chains;
+mod subgraph;
+mod resilient;
+mod merge;
+mod relabel;
+mod memory;
+mod bipartite;
+mod prune;
+mod roles;
+mod incremental;
+mod value_ids;
+mod dag;
+mod pattern;
+mod multi_source;
+mod stats;
+mod plan;
+mod adaptive;
+mod builder;
+mod neighbour;
+mod align;
+mod distances;
+mod clustering;
+mod reachability;
+mod reachability_cache;
+mod changes;
+mod weight;
+mod path;
+mod flow;
+mod canonical;
+mod attributes;
+mod bulk;
+mod traversal_options;
+mod coloring;
+mod euler;
+mod value_index;
+mod scc;
+mod bridges;
+mod matching;
+mod hamiltonian;
+#[cfg(feature = "mmap")]
+mod mmap;
 
 pub type DefaultGraphIdType = u32;
 
+/// Стоимость (вес) ребра без значения в алгоритмах на взвешенных графах
+pub(crate) const DEFAULT_EDGE_COST: f64 = 1.0;
+
+/// Требования к типу идентификатора вершины
+pub trait GraphId: Eq + Hash + Clone + Debug + Display {}
+
+impl<T: Eq + Hash + Clone + Debug + Display> GraphId for T {}
+
+/// Список из идентификатора вершины, значения вершины и соседних идентификаторов вершин
+pub type BfsResult<'a, VT, ID = DefaultGraphIdType> = Vec<(ID, Option<&'a VT>, Vec<ID>)>;
+
+/// Значение удалённой вершины и удалённые вместе с ней рёбра: сосед и значение ребра, см. `Graph::take_vertex`
+pub type TakenVertex<VT, ET, ID = DefaultGraphIdType> = (Option<VT>, Vec<(ID, Option<ET>)>);
+
+/// Вершина, посещённая обходом, с разделёнными исходящими и входящими соседями
 #[derive(Debug)]
-pub struct Graph<VT, ET> {
-    vertices: HashMap<DefaultGraphIdType, Vertex<VT, ET>>,
-    r#type: GraphType,
+pub struct BfsVertexLinks<'a, VT, ID = DefaultGraphIdType> {
+    pub id: ID,
+    pub value: Option<&'a VT>,
+    pub out_neighbours: Vec<ID>,
+    pub in_neighbours: Vec<ID>,
 }
 
 #[derive(Debug)]
+pub struct Graph<VT, ET, ID = DefaultGraphIdType> {
+    vertices: HashMap<ID, Vertex<VT, ET, ID>>,
+    /// Обратный индекс ориентированного графа: для каждой вершины - вершины, из которых в неё ведут рёбра.
+    /// В неориентированном графе не используется, т.к. каждое ребро хранится в обоих направлениях
+    predecessors: HashMap<ID, HashSet<ID>>,
+    /// Количество рёбер (ребро неориентированного графа считается один раз)
+    edge_count: usize,
+    limits: DeserializeLimits,
+    /// Разрешены ли параллельные рёбра между одной парой вершин
+    multigraph: bool,
+    /// Отклоняет ли `add_edge` рёбра, замыкающие цикл, см. `Graph::new_dag`
+    dag: bool,
+    /// Порядок вершин и рёбер при выводе графа через `Display`
+    display_order: VertexOrder,
+    /// Копии списков соседей часто запрашиваемых вершин, см. `Graph::pin_hot`
+    hot: HotCache<ID>,
+    /// Вершины, которые или рёбра которых менялись после `Graph::track_changes`. `None` - изменения не отслеживаются
+    changed_vertices: Option<HashSet<ID>>,
+    /// Роли вершин, см. `Graph::set_role`
+    roles: HashMap<ID, VertexRole>,
+    /// Именованные атрибуты вершин и рёбер, см. `Graph::set_vertex_attr`
+    attributes: Attributes<ID>,
+    /// Идентификаторы вершин по их значениям, см. `Graph::add_vertex_by_value`. `None` - идентификация по значению не включена
+    value_ids: Option<ValueIds<ID>>,
+    /// Обратный индекс значений вершин, см. `Graph::with_value_index`. `None` - индекс не включён
+    value_index: Option<ValueIndex<VT, ID>>,
+    /// Счётчик изменений графа, см. `Graph::generation`
+    generation: u64,
+    /// Число рёбер вершины, начиная с которого строится её индекс соседей, см. `Graph::set_adaptive_threshold`
+    adaptive_threshold: usize,
+    r#type: GraphType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GraphType {
     Directed,
     Undirected,
 }
 
+impl GraphType {
+    /// Название типа графа в сообщениях об ошибках: `directed` или `undirected`
+    pub fn name(self) -> &'static str {
+        match self {
+            GraphType::Directed => "directed",
+            GraphType::Undirected => "undirected",
+        }
+    }
+}
+
 #[derive(Debug)]
-pub struct Vertex<VT, ET> {
-    id: DefaultGraphIdType,
+pub struct Vertex<VT, ET, ID = DefaultGraphIdType> {
+    id: ID,
     value: Option<VT>,
-    edge_directions: Vec<EdgeDirection<ET>>
+    edge_directions: Vec<EdgeDirection<ET, ID>>,
+    /// Отсортированные концы направлений рёбер у вершин с большим числом рёбер, см. `Graph::set_adaptive_threshold`
+    target_index: Option<TargetIndex<ID>>,
 }
 
 #[derive(Debug)]
-pub struct EdgeDirection<ET> {
-    to_vertex_id: DefaultGraphIdType,
-    value: Rc<Option<ET>>,
+pub struct EdgeDirection<ET, ID = DefaultGraphIdType> {
+    to_vertex_id: ID,
+    value: Arc<Option<ET>>,
     r#type: EdgeDirectionType,
 }
 
 /// EdgeDirectionType.Strong - простое ребро
 /// EdgeDirectionType.Weak  - зеркальная копия ребра, создаваемая в неориентированно графе для настоящего ребра (детали реализации)
-#[derive(Debug)]
+/// Петля неориентированного графа хранится одним направлением Strong, без зеркальной копии
+/// Рёбра без зеркальных копий перебираются только через `Vertex::own_edge_directions`, `Graph::logical_edges`
+/// и `EdgeDirection::is_own`
+#[derive(Debug, Clone, Copy)]
 enum EdgeDirectionType {
     Strong,
     Weak,
 }
 
-impl<VT, ET> Vertex<VT, ET> {
-    pub fn new(id: DefaultGraphIdType, value: Option<VT>) -> Vertex<VT, ET> {
+
+impl<VT, ET, ID> Vertex<VT, ET, ID> {
+    pub fn new(id: ID, value: Option<VT>) -> Vertex<VT, ET, ID> {
+        Vertex::with_edge_capacity(id, value, 0)
+    }
+
+    /// Вершина с памятью, заранее выделенной под `edge_capacity` направлений рёбер
+    pub fn with_edge_capacity(id: ID, value: Option<VT>, edge_capacity: usize) -> Vertex<VT, ET, ID> {
         Vertex {
             id,
             value,
-            edge_directions: Vec::new()
+            edge_directions: Vec::with_capacity(edge_capacity),
+            target_index: None,
         }
     }
+
+    /// Направления рёбер без зеркальных копий неориентированного графа: каждое ребро графа
+    /// принадлежит ровно одной вершине. Обходы рёбер графа без повторов идут через этот метод,
+    /// а изменяющие рёбра обходы - через `EdgeDirection::is_own`
+    fn own_edge_directions(&self) -> impl Iterator<Item = &EdgeDirection<ET, ID>> {
+        self.edge_directions
+            .iter()
+            .filter(|edge_direction| edge_direction.is_own())
+    }
 }
 
-impl<ET> EdgeDirection<ET> {
-    pub fn new(to_vertex_id: DefaultGraphIdType, value: Rc<Option<ET>>) -> EdgeDirection<ET> {
+impl<ET, ID> EdgeDirection<ET, ID> {
+    pub fn new(to_vertex_id: ID, value: Arc<Option<ET>>) -> EdgeDirection<ET, ID> {
         EdgeDirection {
             to_vertex_id,
             value,
@@ -63,79 +261,445 @@ impl<ET> EdgeDirection<ET> {
         }
     }
 
-    pub fn new_weak(to_vertex_id: DefaultGraphIdType, value: Rc<Option<ET>>) -> EdgeDirection<ET> {
+    pub fn new_weak(to_vertex_id: ID, value: Arc<Option<ET>>) -> EdgeDirection<ET, ID> {
         EdgeDirection {
             to_vertex_id,
             value,
             r#type: EdgeDirectionType::Weak
         }
     }
+
+    /// Основное направление ребра, а не зеркальная копия ребра неориентированного графа
+    fn is_own(&self) -> bool {
+        matches!(self.r#type, EdgeDirectionType::Strong)
+    }
 }
 
-impl<ET> PartialEq for EdgeDirection<ET>  {
+impl<ET, ID: PartialEq> PartialEq for EdgeDirection<ET, ID>  {
     fn eq(&self, other: &Self) -> bool {
         self.to_vertex_id == other.to_vertex_id
     }
 }
-impl<ET> Eq for EdgeDirection<ET> {}
+impl<ET, ID: Eq> Eq for EdgeDirection<ET, ID> {}
+
+/// Копия графа. Зеркальная копия ребра неориентированного графа в копии разделяет значение с основным
+/// направлением копии, а не с исходным графом. Закреплённые списки соседей (`pin_hot`) не копируются
+impl<VT: Clone, ET: Clone, ID: GraphId> Clone for Graph<VT, ET, ID> {
+    fn clone(&self) -> Self {
+        let cloned_values: HashMap<_, _> = self.logical_edges()
+            .map(|(_, edge_direction)| (Arc::as_ptr(&edge_direction.value), Arc::new(edge_direction.value.as_ref().clone())))
+            .collect();
+        let vertices = self.vertices.iter()
+            .map(|(id, vertex)| {
+                let edge_directions = vertex.edge_directions
+                    .iter()
+                    .map(|edge_direction| EdgeDirection {
+                        to_vertex_id: edge_direction.to_vertex_id.clone(),
+                        value: Arc::clone(&cloned_values[&Arc::as_ptr(&edge_direction.value)]),
+                        r#type: edge_direction.r#type,
+                    })
+                    .collect();
+                let target_index = vertex.target_index.clone();
+                (id.clone(), Vertex { id: id.clone(), value: vertex.value.clone(), edge_directions, target_index })
+            })
+            .collect();
+
+        Graph {
+            vertices,
+            predecessors: self.predecessors.clone(),
+            edge_count: self.edge_count,
+            limits: self.limits,
+            multigraph: self.multigraph,
+            dag: self.dag,
+            display_order: self.display_order,
+            hot: HotCache::default(),
+            changed_vertices: self.changed_vertices.clone(),
+            roles: self.roles.clone(),
+            attributes: self.attributes.clone(),
+            value_ids: self.value_ids.clone(),
+            value_index: self.value_index.clone(),
+            generation: self.generation,
+            adaptive_threshold: self.adaptive_threshold,
+            r#type: self.r#type,
+        }
+    }
+}
+
+/// Структурное равенство: одинаковые тип графа, вершины с их значениями и ролями и мультимножества рёбер со значениями.
+/// Для неориентированного графа направление, в котором ребро было добавлено, не учитывается
+impl<VT: PartialEq, ET: PartialEq, ID: GraphId> PartialEq for Graph<VT, ET, ID> {
+    fn eq(&self, other: &Self) -> bool {
+        self.r#type == other.r#type
+            && self.vertex_count() == other.vertex_count()
+            && self.edge_count() == other.edge_count()
+            && self.roles == other.roles
+            && self.vertices.iter().all(|(id, vertex)| other.vertices.get(id)
+                .is_some_and(|other_vertex| vertex.value == other_vertex.value
+                    && same_edge_multiset(&vertex.edge_directions, &other_vertex.edge_directions)))
+    }
+}
+
+fn same_edge_multiset<ET: PartialEq, ID: PartialEq>(edges: &[EdgeDirection<ET, ID>], other_edges: &[EdgeDirection<ET, ID>]) -> bool {
+    if edges.len() != other_edges.len() {
+        return false;
+    }
+    let mut matched = vec![false; other_edges.len()];
+    edges.iter().all(|edge_direction| {
+        let position = other_edges.iter()
+            .enumerate()
+            .position(|(index, other_edge_direction)| !matched[index]
+                && edge_direction.to_vertex_id == other_edge_direction.to_vertex_id
+                && edge_direction.value == other_edge_direction.value);
+        position.map(|position| matched[position] = true).is_some()
+    })
+}
 
-impl<VT, ET> Graph<VT, ET> {
-    pub fn new(r#type: GraphType) -> Graph<VT, ET> {
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    pub fn new(r#type: GraphType) -> Graph<VT, ET, ID> {
         Graph {
             vertices: HashMap::new(),
+            predecessors: HashMap::new(),
+            edge_count: 0,
+            limits: DeserializeLimits::default(),
+            multigraph: false,
+            dag: false,
+            display_order: VertexOrder::ById,
+            hot: HotCache::default(),
+            changed_vertices: None,
+            roles: HashMap::new(),
+            attributes: Attributes::default(),
+            value_ids: None,
+            value_index: None,
+            generation: 0,
+            adaptive_threshold: adaptive::default_adaptive_threshold(),
             r#type
         }
     }
 
-    pub fn add_vertex(&mut self, vertex: Vertex<VT, ET>) -> Result<(), GraphError> {
+    /// Мультиграф: между одной парой вершин может быть несколько рёбер
+    pub fn new_multigraph(r#type: GraphType) -> Graph<VT, ET, ID> {
+        Graph {
+            multigraph: true,
+            ..Graph::new(r#type)
+        }
+    }
+
+    /// Граф с памятью, заранее выделенной под `vertex_capacity` вершин, см. также `add_vertices` и `add_edges`
+    pub fn with_capacity(r#type: GraphType, vertex_capacity: usize) -> Graph<VT, ET, ID> {
+        let predecessor_capacity = match r#type {
+            GraphType::Directed => vertex_capacity,
+            GraphType::Undirected => 0,
+        };
+        Graph {
+            vertices: HashMap::with_capacity(vertex_capacity),
+            predecessors: HashMap::with_capacity(predecessor_capacity),
+            ..Graph::new(r#type)
+        }
+    }
+
+    pub fn is_multigraph(&self) -> bool {
+        self.multigraph
+    }
+
+    /// Граф, отказывающийся добавлять вершины и рёбра сверх заданных ограничений
+    pub fn new_limited(r#type: GraphType, limits: DeserializeLimits) -> Graph<VT, ET, ID> {
+        Graph {
+            limits,
+            ..Graph::new(r#type)
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Счётчик изменений: растёт при каждом изменении вершин, рёбер, их значений и ролей
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn add_vertex(&mut self, vertex: Vertex<VT, ET, ID>) -> Result<(), GraphError> {
         if self.vertices.contains_key(&vertex.id) {
-            return Err(GraphError::VertexAlreadyExist(vertex.id));
+            return Err(GraphError::VertexAlreadyExist(vertex.id.to_string()));
+        }
+        DeserializeLimits::check(self.limits.max_vertices, self.vertices.len() + 1, LimitKind::Vertices, None)?;
+        self.mark_changed(&vertex.id);
+        self.vertices.insert(vertex.id.clone(), vertex);
+        Ok(())
+    }
+
+    /// Ссылка на значение вершины
+    pub fn vertex_value(&self, vertex_id: ID) -> Option<&VT> {
+        self.vertices.get(&vertex_id)
+            .and_then(|vertex| vertex.value.as_ref())
+    }
+
+    /// Изменяемая ссылка на значение вершины
+    pub fn vertex_value_mut(&mut self, vertex_id: ID) -> Option<&mut VT> {
+        if !self.contains_vertex(&vertex_id) {
+            return None;
+        }
+        self.mark_changed(&vertex_id);
+        self.vertices.get_mut(&vertex_id)
+            .and_then(|vertex| vertex.value.as_mut())
+    }
+
+    /// Заменяет значение вершины, возвращая предыдущее
+    pub fn set_vertex_value(&mut self, vertex_id: ID, value: Option<VT>) -> Result<Option<VT>, GraphError> {
+        let vertex = self.vertices.get_mut(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        let previous_value = std::mem::replace(&mut vertex.value, value);
+        self.mark_changed(&vertex_id);
+        Ok(previous_value)
+    }
+
+    /// Добавляет вершину, а если вершина с таким идентификатором уже есть - заменяет её значение, сохраняя рёбра.
+    /// Возвращает предыдущее значение вершины. Новая вершина проверяется на ограничение числа вершин, как в `add_vertex`
+    pub fn upsert_vertex(&mut self, vertex: Vertex<VT, ET, ID>) -> Result<Option<VT>, GraphError> {
+        if !self.vertices.contains_key(&vertex.id) {
+            DeserializeLimits::check(self.limits.max_vertices, self.vertices.len() + 1, LimitKind::Vertices, None)?;
+        }
+        self.mark_changed(&vertex.id);
+        match self.vertices.get_mut(&vertex.id) {
+            Some(existing_vertex) => Ok(std::mem::replace(&mut existing_vertex.value, vertex.value)),
+            None => {
+                self.vertices.insert(vertex.id.clone(), vertex);
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn delete_vertex(&mut self, vertex_id: ID) -> Result<(), GraphError> {
+        self.take_vertex(vertex_id)?;
+        Ok(())
+    }
+
+    /// Удаляет вершину, как `delete_vertex`, и возвращает её значение и удалённые вместе с ней рёбра:
+    /// соседа и значение ребра. Сначала идут рёбра из вершины в порядке добавления, затем рёбра в вершину
+    /// ориентированного графа. Ребро неориентированного графа возвращается один раз, петля - тоже
+    pub fn take_vertex(&mut self, vertex_id: ID) -> Result<TakenVertex<VT, ET, ID>, GraphError> {
+        let vertex = self.vertices.remove(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        self.touch_vertex(&vertex_id);
+        self.roles.remove(&vertex_id);
+        self.attributes.remove_vertex(&vertex_id);
+
+        self.edge_count -= vertex.edge_directions.len();
+        let mut incoming_edges = Vec::new();
+        match self.r#type {
+            GraphType::Directed => {
+                for edge_direction in &vertex.edge_directions {
+                    if let Some(predecessors) = self.predecessors.get_mut(&edge_direction.to_vertex_id) {
+                        predecessors.remove(&vertex_id);
+                    }
+                }
+                for incoming_id in self.predecessors.remove(&vertex_id).unwrap_or_default() {
+                    let deleted_directions = self.delete_edge_direction(&incoming_id, &vertex_id);
+                    self.edge_count -= deleted_directions.len();
+                    incoming_edges.extend(deleted_directions
+                        .into_iter()
+                        .map(|edge_direction| (incoming_id.clone(), Arc::try_unwrap(edge_direction.value).ok().flatten())));
+                }
+            }
+            // Зеркальные копии удаляются до извлечения значений, чтобы значение ребра больше не было общим
+            GraphType::Undirected => {
+                for edge_direction in &vertex.edge_directions {
+                    self.delete_edge_direction(&edge_direction.to_vertex_id, &vertex_id);
+                }
+            }
+        }
+
+        let mut edges: Vec<_> = vertex.edge_directions
+            .into_iter()
+            .map(|edge_direction| (edge_direction.to_vertex_id, Arc::try_unwrap(edge_direction.value).ok().flatten()))
+            .collect();
+        edges.extend(incoming_edges);
+        Ok((vertex.value, edges))
+    }
+
+    /// Добавляет ребро. В простом графе повторное ребро между той же парой вершин
+    /// (в неориентированном графе - в любом порядке) возвращает `EdgeAlreadyExist`, значение ребра не меняется.
+    /// В мультиграфе такое ребро добавляется параллельным со своим значением
+    pub fn add_edge(&mut self, from_id: ID, to_id: ID, value: Option<ET>) -> Result<(), GraphError> {
+        if !self.vertices.contains_key(&to_id) {
+            return Err(GraphError::VertexNotFound(to_id.to_string()));
+        }
+        // Зеркальное направление добавляется к `to_id` раньше основного, поэтому `from_id` проверяется заранее
+        if !self.vertices.contains_key(&from_id) {
+            return Err(GraphError::VertexNotFound(from_id.to_string()));
         }
-        self.vertices.insert(vertex.id, vertex);
+        if !self.multigraph && self.contains_edge_direction(&from_id, &to_id)? {
+            return Err(GraphError::EdgeAlreadyExist(from_id.to_string(), to_id.to_string()));
+        }
+        if self.dag {
+            self.check_acyclic_edge(&from_id, &to_id)?;
+        }
+        DeserializeLimits::check(self.limits.max_edges, self.edge_count + 1, LimitKind::Edges, None)?;
+
+        let value = Arc::new(value);
+        match self.r#type {
+            GraphType::Undirected => {
+                if from_id != to_id {
+                    self.add_edge_direction(to_id.clone(), from_id.clone(), Arc::clone(&value), EdgeDirectionType::Weak)?;
+                }
+                self.add_edge_direction(from_id, to_id, value, EdgeDirectionType::Strong)?;
+            }
+            GraphType::Directed => {
+                self.add_edge_direction(from_id.clone(), to_id.clone(), value, EdgeDirectionType::Strong)?;
+                self.predecessors.entry(to_id).or_default().insert(from_id);
+            }
+        }
+        self.edge_count += 1;
         Ok(())
     }
 
-    pub fn delete_vertex(&mut self, vertex_id: DefaultGraphIdType)  {
-        self.vertices.remove(&vertex_id);
-        for vertex in self.vertices.values_mut() {
-            utils::remove_from_vec(&mut vertex.edge_directions, |edge_direction| edge_direction.to_vertex_id == vertex_id);
+    /// Все рёбра графа: начальная вершина, конечная вершина и значение ребра.
+    /// Ребро неориентированного графа возвращается один раз, параллельные рёбра мультиграфа - каждое отдельно
+    pub fn edges(&self) -> Vec<(ID, ID, Option<&ET>)> {
+        self.logical_edges()
+            .map(|(from_id, edge_direction)| (from_id.clone(), edge_direction.to_vertex_id.clone(), edge_direction.value.as_ref().as_ref()))
+            .collect()
+    }
+
+    /// Исходящие соседи вершины (для неориентированного графа - все соседи).
+    /// Петля даёт вершине саму себя в соседях один раз
+    pub fn neighbours(&self, vertex_id: ID) -> Result<Vec<ID>, GraphError> {
+        if let Some(neighbour_ids) = self.hot.lookup(&vertex_id) {
+            return Ok(neighbour_ids.to_vec());
+        }
+        let vertex = self.vertices.get(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        Ok(vertex.edge_directions
+            .iter()
+            .map(|edge_direction| edge_direction.to_vertex_id.clone())
+            .collect())
+    }
+
+    /// Количество исходящих соседей вершины, как у `neighbours`, без выделения памяти
+    pub fn neighbour_count(&self, vertex_id: ID) -> Result<usize, GraphError> {
+        if let Some(neighbour_ids) = self.hot.lookup(&vertex_id) {
+            return Ok(neighbour_ids.len());
+        }
+        let vertex = self.vertices.get(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        Ok(vertex.edge_directions.len())
+    }
+
+    /// Количество рёбер, инцидентных вершине (для ориентированного графа - входящих и исходящих).
+    /// Петля хранится как одно ребро и учитывается в степени один раз
+    pub fn degree(&self, vertex_id: ID) -> Result<usize, GraphError> {
+        let vertex = self.vertices.get(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        let out_degree = vertex.edge_directions.len();
+        match self.r#type {
+            GraphType::Undirected => Ok(out_degree),
+            GraphType::Directed => Ok(out_degree + self.incoming_edge_count(&vertex_id)),
+        }
+    }
+
+    /// Количество рёбер ориентированного графа, входящих в вершину из других вершин, без петель
+    fn incoming_edge_count(&self, vertex_id: &ID) -> usize {
+        self.predecessors.get(vertex_id)
+            .into_iter()
+            .flatten()
+            .filter(|&id| id != vertex_id)
+            .map(|id| self.vertices[id].edge_directions
+                .iter()
+                .filter(|edge_direction| edge_direction.to_vertex_id == *vertex_id)
+                .count())
+            .sum()
+    }
+
+    /// Заменяет значение ребра, возвращая предыдущее.
+    /// В неориентированном графе значение обновляется у обоих направлений ребра,
+    /// в мультиграфе - у первого из параллельных рёбер
+    pub fn set_edge_value(&mut self, from_id: ID, to_id: ID, value: Option<ET>) -> Result<Option<ET>, GraphError> {
+        let value = Arc::new(value);
+        let previous_value = self.replace_edge_direction_value(&from_id, &to_id, Arc::clone(&value), None)?;
+        if let GraphType::Undirected = self.r#type {
+            if from_id != to_id {
+                self.replace_edge_direction_value(&to_id, &from_id, value, Some(&previous_value))?;
+            }
         }
+        Ok(Arc::try_unwrap(previous_value).ok().flatten())
     }
 
-    pub fn add_edge(&mut self, from_id: DefaultGraphIdType, to_id: DefaultGraphIdType, value: Option<ET>) -> Result<(), GraphError> {
-        let value = Rc::new(value);
+    /// Удаляет ребро. В неориентированном графе порядок вершин не важен: удаляются оба направления ребра.
+    /// В мультиграфе удаляются все параллельные рёбра между вершинами
+    pub fn delete_edge(&mut self, from_id: ID, to_id: ID) -> Result<(), GraphError> {
+        if !self.contains_vertex(&to_id) {
+            return Err(GraphError::VertexNotFound(to_id.to_string()));
+        }
+        if !self.contains_edge_direction(&from_id, &to_id)? {
+            return Err(GraphError::EdgeNotFound(from_id.to_string(), to_id.to_string()));
+        }
+
+        self.edge_count -= self.delete_edge_direction(&from_id, &to_id).len();
         match self.r#type {
             GraphType::Undirected => {
-                self.add_edge_direction(from_id, to_id, Rc::clone(&value), EdgeDirectionType::Strong)?;
-                self.add_edge_direction(to_id, from_id, value, EdgeDirectionType::Weak)?;
-                Ok(())
+                self.delete_edge_direction(&to_id, &from_id);
             }
             GraphType::Directed => {
-                if !self.vertices.contains_key(&to_id) {
-                    return Err(GraphError::VertexNotFound(to_id));
+                if let Some(predecessors) = self.predecessors.get_mut(&to_id) {
+                    predecessors.remove(&from_id);
                 }
-                self.add_edge_direction(from_id, to_id, value, EdgeDirectionType::Strong)?;
-                Ok(())
             }
         }
+        self.remove_edge_attrs(from_id, to_id);
+        Ok(())
     }
 
-    pub fn delete_edge(&mut self, from_id: DefaultGraphIdType, to_id: DefaultGraphIdType) {
+    /// Удаляет одно ребро между вершинами - первое, значение которого удовлетворяет предикату.
+    /// Позволяет удалить конкретное из параллельных рёбер мультиграфа
+    pub fn delete_edge_where<P: Fn(Option<&ET>) -> bool>(&mut self, from_id: ID, to_id: ID, predicate: P) -> Result<(), GraphError> {
+        if !self.contains_vertex(&to_id) {
+            return Err(GraphError::VertexNotFound(to_id.to_string()));
+        }
+        let threshold = self.adaptive_threshold;
+        let vertex_from = self.vertices.get_mut(&from_id)
+            .ok_or_else(|| GraphError::VertexNotFound(from_id.to_string()))?;
+        let deleted_edge_direction = vertex_from
+            .remove_edge_direction_where(
+                |edge_direction| edge_direction.to_vertex_id == to_id && predicate(edge_direction.value.as_ref().as_ref()),
+                threshold,
+            )
+            .ok_or_else(|| GraphError::EdgeNotFound(from_id.to_string(), to_id.to_string()))?;
+        let has_parallel_edge = vertex_from.has_edge_direction_to(&to_id);
+        self.edge_count -= 1;
+        self.touch_vertex(&from_id);
+
         match self.r#type {
             GraphType::Undirected => {
-                self.delete_edge_direction(from_id, to_id);
-                self.delete_edge_direction(to_id, from_id);
+                self.touch_vertex(&to_id);
+                if let Some(vertex_to) = self.vertices.get_mut(&to_id) {
+                    vertex_to.remove_edge_direction_where(
+                        |edge_direction| Arc::ptr_eq(&edge_direction.value, &deleted_edge_direction.value),
+                        threshold,
+                    );
+                }
             }
             GraphType::Directed => {
-                self.delete_edge_direction(from_id, to_id);
+                if !has_parallel_edge {
+                    if let Some(predecessors) = self.predecessors.get_mut(&to_id) {
+                        predecessors.remove(&from_id);
+                    }
+                }
             }
         }
+        if !has_parallel_edge {
+            self.remove_edge_attrs(from_id, to_id);
+        }
+        Ok(())
     }
 
-    pub fn bfs_random_start(&self) -> Result<Vec<(DefaultGraphIdType, Option<&VT>, Vec<DefaultGraphIdType>)>, GraphError> {
+    pub fn bfs_random_start(&self) -> Result<BfsResult<'_, VT, ID>, GraphError> {
         let vertex_id = self.vertices.keys().next();
         if let Some(vertex_id) = vertex_id {
-            self.bfs(*vertex_id)
+            self.bfs(vertex_id.clone())
         }
         else {
             Ok(vec![])
@@ -143,220 +707,283 @@ impl<VT, ET> Graph<VT, ET> {
     }
 
     /// Список из идентификатора вершины, соседних идентификаторов вершин и значения вершины
-    pub fn bfs(&self, start_id: DefaultGraphIdType) -> Result<Vec<(DefaultGraphIdType, Option<&VT>, Vec<DefaultGraphIdType>)>, GraphError> {
-        let start_vertex = self.vertices.get(&start_id)
-            .ok_or(GraphError::VertexNotFound(start_id))?;
+    pub fn bfs(&self, start_id: ID) -> Result<BfsResult<'_, VT, ID>, GraphError> {
+        self.collect_traversal(start_id, TraversalOrder::BreadthFirst)
+    }
 
-        let mut result = Vec::new();
-        let mut queue_vertex = VecDeque::new();
-        let mut visited_vertices = HashSet::new();
-        queue_vertex.push_back(start_vertex);
-
-        while !queue_vertex.is_empty() {
-            if let Some(current_vertex) = queue_vertex.pop_front() {
-                if visited_vertices.contains(&current_vertex.id) {
-                    continue;
-                };
-                visited_vertices.insert(current_vertex.id);
-                let neighbours: Vec<_> = current_vertex.edge_directions
-                    .iter()
-                    .filter_map(|edge_direction| self.vertices.get(&edge_direction.to_vertex_id))
-                    .collect();
-                let neighbour_ids = neighbours
-                    .iter()
-                    .map(|vertex| vertex.id)
-                    .collect();
-                neighbours
-                    .iter()
-                    .filter(|&vertex| !visited_vertices.contains(&vertex.id))
-                    .for_each(|vertex| queue_vertex.push_back(vertex));
+    /// Список вершин в порядке обхода в глубину, в том же формате, что и у `bfs`. Обход идёт по явному стеку,
+    /// без рекурсии, поэтому глубина графа не ограничена размером стека потока
+    pub fn dfs(&self, start_id: ID) -> Result<BfsResult<'_, VT, ID>, GraphError> {
+        self.collect_traversal(start_id, TraversalOrder::DepthFirst)
+    }
+
+    /// Обход в ширину без накопления результата: вершины передаются в `visitor` по мере посещения,
+    /// обход прекращается, когда `visitor` возвращает `ControlFlow::Break`
+    pub fn bfs_visit<F>(&self, start_id: ID, mut visitor: F) -> Result<(), GraphError>
+    where
+        F: FnMut(ID, Option<&VT>) -> ControlFlow<()>,
+    {
+        self.traverse(start_id, TraversalOrder::BreadthFirst, |vertex| visitor(vertex.id.clone(), vertex.value.as_ref()))
+    }
+
+    /// Обход в глубину без накопления результата, аналогичный `bfs_visit`
+    pub fn dfs_visit<F>(&self, start_id: ID, mut visitor: F) -> Result<(), GraphError>
+    where
+        F: FnMut(ID, Option<&VT>) -> ControlFlow<()>,
+    {
+        self.traverse(start_id, TraversalOrder::DepthFirst, |vertex| visitor(vertex.id.clone(), vertex.value.as_ref()))
+    }
+
+    /// Кратчайший по числу рёбер путь от `from_id` до `to_id` включительно, `None` - если путь не существует
+    pub fn shortest_path(&self, from_id: ID, to_id: ID) -> Result<Option<Vec<ID>>, GraphError> {
+        if !self.contains_vertex(&to_id) {
+            return Err(GraphError::VertexNotFound(to_id.to_string()));
+        }
+        let mut parents = HashMap::new();
+        let mut target_found = false;
+        self.traverse(from_id.clone(), TraversalOrder::BreadthFirst, |vertex| {
+            if vertex.id == to_id {
+                target_found = true;
+                return ControlFlow::Break(());
+            }
+            for edge_direction in &vertex.edge_directions {
+                if edge_direction.to_vertex_id != from_id {
+                    parents.entry(edge_direction.to_vertex_id.clone()).or_insert_with(|| vertex.id.clone());
+                }
+            }
+            ControlFlow::Continue(())
+        })?;
+        if !target_found {
+            return Ok(None);
+        }
+
+        let mut path = vec![to_id];
+        while let Some(parent_id) = parents.get(&path[path.len() - 1]) {
+            path.push(parent_id.clone());
+        }
+        path.reverse();
+
+        Ok(Some(path))
+    }
 
-                result.push((current_vertex.id, current_vertex.value.as_ref(), neighbour_ids));
+    /// Есть ли ребро из `from_id` в `to_id`; в неориентированном графе порядок вершин не важен.
+    /// Для отсутствующих вершин возвращает `false`
+    pub fn has_edge(&self, from_id: ID, to_id: ID) -> bool {
+        self.contains_edge_direction(&from_id, &to_id).unwrap_or(false)
+    }
+
+    /// Достижима ли `to_id` из `from_id`. Обход в ширину останавливается, как только извлечена `to_id`.
+    /// Вершина достижима сама из себя
+    pub fn is_reachable(&self, from_id: ID, to_id: ID) -> Result<bool, GraphError> {
+        Ok(self.visits_until_reached(from_id, to_id)?.is_some())
+    }
+
+    /// Количество вершин, посещённых обходом в ширину до `to_id` включительно, `None` - если `to_id` недостижима
+    fn visits_until_reached(&self, from_id: ID, to_id: ID) -> Result<Option<usize>, GraphError> {
+        if !self.contains_vertex(&to_id) {
+            return Err(GraphError::VertexNotFound(to_id.to_string()));
+        }
+        let mut visit_count = 0;
+        let mut target_found = false;
+        self.traverse(from_id, TraversalOrder::BreadthFirst, |vertex| {
+            visit_count += 1;
+            target_found = vertex.id == to_id;
+            if target_found {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
             }
-            else {
+        })?;
+
+        Ok(target_found.then_some(visit_count))
+    }
+
+    fn collect_traversal(&self, start_id: ID, order: TraversalOrder) -> Result<BfsResult<'_, VT, ID>, GraphError> {
+        let mut result = Vec::new();
+        self.traverse(start_id, order, |vertex| {
+            let neighbour_ids = vertex.edge_directions
+                .iter()
+                .map(|edge_direction| edge_direction.to_vertex_id.clone())
+                .collect();
+            result.push((vertex.id.clone(), vertex.value.as_ref(), neighbour_ids));
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(result)
+    }
+
+    fn traverse<'a, F>(&'a self, start_id: ID, order: TraversalOrder, mut visitor: F) -> Result<(), GraphError>
+    where
+        F: FnMut(&'a Vertex<VT, ET, ID>) -> ControlFlow<()>,
+    {
+        for vertex in Traversal::new(self, start_id, order)? {
+            if visitor(vertex).is_break() {
                 break;
             }
         }
 
+        Ok(())
+    }
+
+    /// Обход в ширину, для каждой вершины возвращающий исходящих соседей и всех предшественников.
+    /// Для неориентированного графа оба списка совпадают
+    pub fn bfs_with_in_edges(&self, start_id: ID) -> Result<Vec<BfsVertexLinks<'_, VT, ID>>, GraphError> {
+        let bfs_result = self.bfs(start_id)?;
+
+        let result = bfs_result
+            .into_iter()
+            .map(|(id, value, out_neighbours)| {
+                let in_neighbours = match self.r#type {
+                    GraphType::Directed => self.predecessor_ids(&id),
+                    GraphType::Undirected => out_neighbours.clone(),
+                };
+                BfsVertexLinks { id, value, out_neighbours, in_neighbours }
+            })
+            .collect();
+
         Ok(result)
     }
 
+    /// Вершины, из которых есть ребро в данную вершину. Для неориентированного графа - все соседи вершины
+    pub fn predecessors(&self, vertex_id: ID) -> Result<Vec<ID>, GraphError> {
+        let vertex = self.vertices.get(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        match self.r#type {
+            GraphType::Directed => Ok(self.predecessor_ids(&vertex_id)),
+            GraphType::Undirected => Ok(vertex.edge_directions
+                .iter()
+                .map(|edge_direction| edge_direction.to_vertex_id.clone())
+                .collect()),
+        }
+    }
+
+    fn predecessor_ids(&self, vertex_id: &ID) -> Vec<ID> {
+        self.predecessors.get(vertex_id)
+            .map(|predecessors| predecessors.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     fn add_edge_direction(
         &mut self,
-        from_id: DefaultGraphIdType,
-        to_id: DefaultGraphIdType,
-        value: Rc<Option<ET>>,
+        from_id: ID,
+        to_id: ID,
+        value: Arc<Option<ET>>,
         edge_direction_type: EdgeDirectionType
     ) -> Result<(), GraphError> {
         let vertex_from = self.vertices.get_mut(&from_id)
-            .ok_or(GraphError::VertexNotFound(from_id))?;
+            .ok_or_else(|| GraphError::VertexNotFound(from_id.to_string()))?;
 
         let edge_to = match edge_direction_type {
             EdgeDirectionType::Strong => EdgeDirection::new(to_id, value),
             EdgeDirectionType::Weak => EdgeDirection::new_weak(to_id, value),
         };
-        if vertex_from.edge_directions.contains(&edge_to) {
-            return Ok(());
-        }
-        vertex_from.edge_directions.push(edge_to);
+        vertex_from.push_edge_direction(edge_to, self.adaptive_threshold);
+        self.touch_vertex(&from_id);
 
         Ok(())
     }
-    
-    fn delete_edge_direction(&mut self, from_id: DefaultGraphIdType, to_id: DefaultGraphIdType) {
-        let vertex_from = self.vertices.get_mut(&from_id);
-        if let Some(vertex_from) = vertex_from {
-            utils::remove_from_vec(&mut vertex_from.edge_directions, |edge_direction| edge_direction.to_vertex_id == to_id);
-        }
-    }
 
-    fn contains_vertex(&self, vertex_id: DefaultGraphIdType) -> bool {
-        self.vertices.contains_key(&vertex_id)
+    /// Заменяет значение направления ребра. Если задано `shared_value`, заменяется только направление,
+    /// разделяющее это значение (зеркальная копия конкретного ребра)
+    fn replace_edge_direction_value(
+        &mut self,
+        from_id: &ID,
+        to_id: &ID,
+        value: Arc<Option<ET>>,
+        shared_value: Option<&Arc<Option<ET>>>,
+    ) -> Result<Arc<Option<ET>>, GraphError> {
+        let edge_direction = self.vertices.get_mut(from_id)
+            .ok_or_else(|| GraphError::VertexNotFound(from_id.to_string()))?
+            .edge_directions
+            .iter_mut()
+            .find(|edge_direction| {
+                edge_direction.to_vertex_id == *to_id
+                    && shared_value.is_none_or(|shared_value| Arc::ptr_eq(&edge_direction.value, shared_value))
+            })
+            .ok_or_else(|| GraphError::EdgeNotFound(from_id.to_string(), to_id.to_string()))?;
+        let previous_value = std::mem::replace(&mut edge_direction.value, value);
+        self.mark_changed(from_id);
+        self.mark_changed(to_id);
+        Ok(previous_value)
     }
-}
 
-#[derive(Debug)]
-enum ScanState {
-    Vertex,
-    Edge,
-}
-
-const VERTEX_EDGE_DELEMITER: &str = "#";
-const DATA_DELIMITER: &str = " ";
-
-impl Graph<String, String> {
-    pub fn serialize<W: Write>(&self, buf_writer: &mut BufWriter<W>) -> Result<(), GraphError> {
-        for vertex in self.vertices.values() {
-            if let Some(vertex_value) = &vertex.value {
-                write!(buf_writer, "{} {}\n", vertex.id, vertex_value)?;
-            } else {
-                write!(buf_writer, "{}\n", vertex.id)?;
-            }
+    /// Удаляет все направления рёбер из одной вершины в другую, возвращая удалённые
+    fn delete_edge_direction(&mut self, from_id: &ID, to_id: &ID) -> Vec<EdgeDirection<ET, ID>> {
+        self.touch_vertex(from_id);
+        match self.vertices.get_mut(from_id) {
+            Some(vertex_from) => vertex_from.remove_edge_directions_to(to_id, self.adaptive_threshold),
+            None => Vec::new(),
         }
+    }
 
-        write!(buf_writer, "{}\n", VERTEX_EDGE_DELEMITER)?;
-
-        for vertex in self.vertices.values() {
-            for edge_direction in &vertex.edge_directions {
-                if let EdgeDirectionType::Weak = edge_direction.r#type {
-                    continue;
-                }
-                let (to_id, from_id) = (vertex.id, edge_direction.to_vertex_id);
-                if let Some(edge_value) = &edge_direction.value.as_ref() {
-                    write!(buf_writer, "{} {} {}\n", to_id, from_id, edge_value)?;
-                } else {
-                    write!(buf_writer, "{} {}\n", to_id, from_id)?;
-                }
-            }
-        }
+    fn contains_edge_direction(&self, from_id: &ID, to_id: &ID) -> Result<bool, GraphError> {
+        let vertex_from = self.vertices.get(from_id)
+            .ok_or_else(|| GraphError::VertexNotFound(from_id.to_string()))?;
+        Ok(vertex_from.has_edge_direction_to(to_id))
+    }
 
-        Ok(())
+    /// Рёбра графа без зеркальных копий неориентированного графа
+    fn logical_edges(&self) -> impl Iterator<Item = (&ID, &EdgeDirection<ET, ID>)> {
+        self.vertices.values()
+            .flat_map(|vertex| vertex.own_edge_directions().map(move |edge_direction| (&vertex.id, edge_direction)))
     }
 
-    pub fn deserialize<BR: BufRead>(reader: BR) -> Result<Graph<String, String>, GraphError> {
-        let mut graph = Graph::new(GraphType::Undirected);
-        let mut scan_state = ScanState::Vertex;
-
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            match scan_state {
-                ScanState::Vertex => {
-                    let vertex = Graph::parse_vertex(&line);
-                    match vertex {
-                        Ok(vertex) => {
-                            graph.add_vertex(vertex)?;
-                        },
-                        Err(error) => {
-                            if Graph::is_delimiter(line) {
-                                scan_state = ScanState::Edge;
-                                continue;
-                            }
-                            else {
-                                return Err(error);
-                            }
-                        }
-                    }
-                },
-                ScanState::Edge => {
-                    let (to, from, value) = Graph::parse_edge(&line, &graph)?;
-                    graph.add_edge(to, from, value)?;
-                }
-            }
-        };
+    fn contains_vertex(&self, vertex_id: &ID) -> bool {
+        self.vertices.contains_key(vertex_id)
+    }
 
-        Ok(graph)
+    /// Отмечает изменение рёбер вершины: закреплённый список соседей вершины больше не используется
+    fn touch_vertex(&mut self, vertex_id: &ID) {
+        self.hot.invalidate(vertex_id);
+        self.mark_changed(vertex_id);
     }
 
-    fn parse_vertex(line: &str) -> Result<Vertex<String, String>, GraphError> {
-        let mut vertex_data = line.split(DATA_DELIMITER);
-    
-        let vertex_id = vertex_data.next()
-            .ok_or(GraphError::ParseVertexId(line.to_owned()))?
-            .parse::<u32>()
-            .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
-        let vertex_value: String = vertex_data.collect::<Vec<&str>>().join(DATA_DELIMITER);
-        let vertex_value = if vertex_value.is_empty() {
-            None
+    /// Запоминает изменённую вершину, если изменения отслеживаются
+    fn mark_changed(&mut self, vertex_id: &ID) {
+        self.generation += 1;
+        if let Some(changed_vertices) = self.changed_vertices.as_mut() {
+            changed_vertices.insert(vertex_id.clone());
         }
-        else {
-            Some(vertex_value)
-        };
-    
-        Ok(Vertex::new(vertex_id, vertex_value))
-    }
-    
-    fn is_delimiter(line: &str) -> bool {
-        line == VERTEX_EDGE_DELEMITER
-    }
-    
-    /// Возвращает кортеж из двух инцидентных вершин и значения ребра
-    fn parse_edge(line: &str, graph: &Graph<String, String>) -> Result<(DefaultGraphIdType, DefaultGraphIdType, Option<String>), GraphError> {
-        let mut edge_data = line.split(DATA_DELIMITER);
-    
-        let first_vertex_id = edge_data.next()
-            .ok_or(GraphError::ParseVertexId(line.to_owned()))?
-            .parse::<DefaultGraphIdType>()
-            .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
-        let second_vertex_id = edge_data.next()
-            .ok_or(GraphError::ParseVertexId(line.to_owned()))?
-            .parse::<DefaultGraphIdType>()
-            .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
-        let edge_value: String = edge_data.collect::<Vec<&str>>().join(DATA_DELIMITER);
-        let edge_value = if edge_value.is_empty() {
-            None
+        // Предыдущее изменение уже завершено, а значение этой вершины ещё может измениться
+        if let Some(value_index) = self.value_index.as_mut() {
+            value_index.refresh(&self.vertices);
+            value_index.mark_pending(vertex_id);
         }
-        else {
-            Some(edge_value)
-        };
-    
-        if !graph.contains_vertex(first_vertex_id) {
-            return Err(GraphError::VertexNotFound(first_vertex_id));
-        };
-        if !graph.contains_vertex(second_vertex_id) {
-            return Err(GraphError::VertexNotFound(second_vertex_id));
-        };
-        Ok((
-            first_vertex_id,
-            second_vertex_id,
-            edge_value
-        ))
     }
 }
 
+/// Граф для тестов модулей: праздники на рёбрах между месяцами
+#[cfg(test)]
+pub(crate) const TGF_GRAPH: &str = "1 January
+2 March
+3 April
+4 May
+5 December
+6 June
+7 September
+#
+1 2
+3 2
+4 3
+5 1 Happy New Year!
+5 3 April Fools Day
+6 3
+6 1
+7 5
+7 6
+7 1";
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{
-        io::{BufReader, BufWriter},
-        collections::HashSet,
-    };
+    use std::io::{BufReader, BufWriter};
     use anyhow::{
         Result,
         bail,
     };
 
-    const TGF_GRAPH: &str = "1 January
+    /// `TGF_GRAPH` в порядке `serialize`
+    const SERIALIZED_TGF_GRAPH: &str = "1 January
 2 March
 3 April
 4 May
@@ -369,39 +996,129 @@ mod tests {
 4 3
 5 1 Happy New Year!
 5 3 April Fools Day
-6 3
 6 1
+6 3
+7 1
 7 5
 7 6
-7 1";
+";
+
+    fn serialized(graph: &Graph<String, String>) -> Result<String> {
+        let mut bufer = BufWriter::new(Vec::new());
+        graph.serialize(&mut bufer)?;
+        Ok(String::from_utf8(bufer.into_inner()?)?)
+    }
 
     #[test]
     fn deserialize_serialize() -> Result<()> {
         let reader = BufReader::new(TGF_GRAPH.as_bytes());
         let graph = Graph::deserialize(reader)?;
-        
-        let mut bufer = BufWriter::new(Vec::new());
-        graph.serialize(&mut bufer)?;
-        let serialized_graph = String::from_utf8(bufer.into_inner()?)?;
-
-        let mut original_lines = HashSet::new();
-        let mut serialized_lines = HashSet::new();
-        original_lines.extend(TGF_GRAPH.lines());
-        serialized_lines.extend(serialized_graph.lines());
+        let serialized_graph = serialized(&graph)?;
 
-        if original_lines == serialized_lines {
+        if serialized_graph == SERIALIZED_TGF_GRAPH {
             Ok(())
         } else {
-            bail!("serialized graph not equals original graph")
+            bail!("serialized graph not equals original graph:\n{}", serialized_graph)
         }
     }
 
     #[test]
-    fn unique_vertex_id() -> Result<()> {
-        let mut graph = Graph::<(), ()>::new(GraphType::Undirected);
-        graph.add_vertex(Vertex::new(1, None))?;
-        let adding_result = graph.add_vertex(Vertex::new(1, None));
-
+    fn serialize_is_deterministic() -> Result<()> {
+        let mut graph = Graph::new_multigraph(GraphType::Directed);
+        let mut reversed = Graph::new_multigraph(GraphType::Directed);
+        let edges = [(3, 1, Some("b")), (1, 2, None), (3, 1, None), (3, 1, Some("a")), (2, 3, Some("c"))];
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, Some(format!("v{}", id))))?;
+            reversed.add_vertex(Vertex::new(4 - id, Some(format!("v{}", 4 - id))))?;
+        }
+        for (from_id, to_id, value) in edges {
+            graph.add_edge(from_id, to_id, value.map(str::to_owned))?;
+        }
+        for (from_id, to_id, value) in edges.into_iter().rev() {
+            reversed.add_edge(from_id, to_id, value.map(str::to_owned))?;
+        }
+        graph.set_role(3, VertexRole::Sink)?;
+        graph.set_role(1, VertexRole::Source)?;
+        reversed.set_role(1, VertexRole::Source)?;
+        reversed.set_role(3, VertexRole::Sink)?;
+
+        let serialized_graph = serialized(&graph)?;
+        if serialized_graph == serialized(&graph)?
+            && serialized_graph == serialized(&reversed)?
+            && serialized_graph == "1 v1\n2 v2\n3 v3\n#\n1 2\n2 3 c\n3 1\n3 1 a\n3 1 b\n!roles\n1 source\n3 sink\n" {
+            Ok(())
+        } else {
+            bail!("serialization depends on insertion order:\n{}", serialized_graph)
+        }
+    }
+
+    #[test]
+    fn reversed_undirected_edge() -> Result<()> {
+        fn shareable<T: Send + Sync>(_: &T) {}
+
+        let mut graph = Graph::new(GraphType::Undirected);
+        let mut multigraph = Graph::new_multigraph(GraphType::Undirected);
+        for id in 1..=2 {
+            graph.add_vertex(Vertex::new(id, None))?;
+            multigraph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, Some("v".to_owned()))?;
+        let reversed = graph.add_edge(2, 1, Some("w".to_owned()));
+        multigraph.add_edge(1, 2, Some("v".to_owned()))?;
+        multigraph.add_edge(2, 1, Some("w".to_owned()))?;
+        shareable(&graph);
+
+        let value = |value: &str| Some(value.to_owned());
+        if reversed == Err(GraphError::EdgeAlreadyExist("2".to_owned(), "1".to_owned()))
+            && graph.edges() == vec![(1, 2, value("v").as_ref())]
+            && graph.neighbours(2)? == vec![1]
+            && serialized(&graph)? == "1\n2\n#\n1 2 v\n"
+            && multigraph.edge_count() == 2
+            && multigraph.degree(1)? == 2
+            && serialized(&multigraph)? == "1\n2\n#\n1 2 v\n2 1 w\n" {
+            Ok(())
+        } else {
+            bail!("ambiguous reversed edge: {:?}", multigraph.edges())
+        }
+    }
+
+    #[test]
+    fn values_round_trip_byte_for_byte() -> Result<()> {
+        let values = ["", "  padded  ", "two\nlines\r\n", "back\\slash \\n", "#", "#patch", "!roles", "\t", "1 2"];
+        let mut graph = Graph::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(0, None))?;
+        for (id, value) in (1..).zip(values) {
+            graph.add_vertex(Vertex::new(id, Some(value.to_owned())))?;
+            graph.add_edge(0, id, Some(value.to_owned()))?;
+        }
+        let mut bufer = BufWriter::new(Vec::new());
+        graph.serialize(&mut bufer)?;
+        let serialized_graph = String::from_utf8(bufer.into_inner()?)?;
+        let reparsed_graph = Graph::deserialize(serialized_graph.as_bytes())?;
+        let displayed_graph = Graph::deserialize(graph.to_string().as_bytes())?;
+
+        // Вершина без значения, за которой идут вершина и ребро со значением "#", не путается с разделителем
+        let hash_labels = Graph::deserialize("1\n2 #\n#\n1 2 #\n".as_bytes())?;
+        if serialized_graph.lines().count() == 2 * values.len() + 2
+            && reparsed_graph == graph
+            && displayed_graph == graph
+            && reparsed_graph.vertex_value(0).is_none()
+            && reparsed_graph.vertex_value(1).map(String::as_str) == Some("")
+            && hash_labels.vertex_value(1).is_none()
+            && hash_labels.vertex_value(2).map(String::as_str) == Some("#")
+            && hash_labels.edges() == vec![(1, 2, Some(&"#".to_owned()))] {
+            Ok(())
+        } else {
+            bail!("values changed in round trip:\n{}", serialized_graph)
+        }
+    }
+
+    #[test]
+    fn unique_vertex_id() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, None))?;
+        let adding_result = graph.add_vertex(Vertex::new(1, None));
+
         if let Err(GraphError::VertexAlreadyExist(_)) = adding_result {
             Ok(())
         } else {
@@ -481,7 +1198,7 @@ mod tests {
         graph.add_edge(VERTEX_ID_1, VERTEX_ID_2, None)?;
         graph.add_edge(VERTEX_ID_2, VERTEX_ID_3, None)?;
 
-        graph.delete_edge(VERTEX_ID_2, VERTEX_ID_3);
+        graph.delete_edge(VERTEX_ID_2, VERTEX_ID_3)?;
 
         let vertex_ids: Vec<_>= graph.bfs(VERTEX_ID_1)?
             .iter()
@@ -506,7 +1223,7 @@ mod tests {
         graph.add_edge(VERTEX_ID_1, VERTEX_ID_2, None)?;
         graph.add_edge(VERTEX_ID_2, VERTEX_ID_3, None)?;
 
-        graph.delete_vertex(VERTEX_ID_2);
+        graph.delete_vertex(VERTEX_ID_2)?;
 
         let vertex_ids: Vec<_>= graph.bfs(VERTEX_ID_1)?
             .iter()
@@ -543,4 +1260,900 @@ mod tests {
             bail!("bfs return wrong result")
         }
     }
+
+    #[test]
+    fn string_vertex_id() -> Result<()> {
+        let mut graph = Graph::<(), (), String>::new(GraphType::Directed);
+        graph.add_vertex(Vertex::new("a".to_owned(), None))?;
+        graph.add_vertex(Vertex::new("b".to_owned(), None))?;
+        graph.add_edge("a".to_owned(), "b".to_owned(), None)?;
+
+        let vertex_ids: Vec<_> = graph.bfs("a".to_owned())?
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect();
+        if vertex_ids == vec!["a".to_owned(), "b".to_owned()] {
+            Ok(())
+        } else {
+            bail!("bfs return wrong result")
+        }
+    }
+
+    #[test]
+    fn deserialize_u64_vertex_id() -> Result<()> {
+        const BIG_ID: u64 = 10_000_000_000;
+        let tgf = format!("{} big\n1 small\n#\n{} 1", BIG_ID, BIG_ID);
+        let graph = Graph::<String, String, u64>::deserialize_typed(BufReader::new(tgf.as_bytes()))?;
+
+        let vertex_ids: Vec<_> = graph.bfs(BIG_ID)?
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect();
+        if vertex_ids == vec![BIG_ID, 1] {
+            Ok(())
+        } else {
+            bail!("bfs return wrong result")
+        }
+    }
+
+    #[test]
+    fn tgf_reader_records() -> Result<()> {
+        let tgf = "1 first\n2\n#\n1 2 a\\tb\n2 2\n!roles\n1 source\n";
+        let records = TgfReader::new(tgf.as_bytes()).collect::<Result<Vec<TgfRecord>, _>>()?;
+        let edge_count = TgfReader::<_>::new(limited_tgf(1000, 5000).as_bytes())
+            .filter(|record| matches!(record, Ok(TgfRecord::Edge { .. })))
+            .count();
+        if records == vec![
+                TgfRecord::Vertex { id: 1, value: Some("first".to_owned()) },
+                TgfRecord::Vertex { id: 2, value: None },
+                TgfRecord::Edge { from: 1, to: 2, value: Some("a\tb".to_owned()) },
+                TgfRecord::Edge { from: 2, to: 2, value: None },
+                TgfRecord::Role { id: 1, role: VertexRole::Source },
+            ]
+            && edge_count == 5000 {
+            Ok(())
+        } else {
+            bail!("wrong tgf records: {:?}", records)
+        }
+    }
+
+    #[test]
+    fn tgf_without_delimiter() -> Result<()> {
+        let vertices_only = Graph::deserialize("1\n2 two\n3\n".as_bytes())?;
+        let mut reader = TgfReader::<_>::new("1\n2\n1 2\n#\n1 2\n".as_bytes());
+        let edge_too_early = reader.by_ref().nth(2);
+        let line_no = reader.line_no();
+        let next_record = reader.next();
+        let edge_too_early_graph = Graph::deserialize("1\n2\n1 2\n#\n1 2\n".as_bytes());
+        let expected = GraphError::ParseLine { line_no: 3, source: Box::new(GraphError::VertexAlreadyExist("1".to_owned())) };
+        if vertices_only.vertex_count() == 3
+            && vertices_only.edge_count() == 0
+            && vertices_only.vertex_value(2) == Some(&"two".to_owned())
+            // Ребро до `#` читается как вершина со значением, ошибку даёт повтор вершины при построении графа
+            && edge_too_early.is_some_and(|record| record.is_ok_and(|record| record == TgfRecord::Vertex { id: 1, value: Some("2".to_owned()) }))
+            && line_no == 3
+            && next_record.is_some_and(|record| record.is_ok_and(|record| record == TgfRecord::Edge { from: 1, to: 2, value: None }))
+            && edge_too_early_graph.err() == Some(expected) {
+            Ok(())
+        } else {
+            bail!("wrong handling of missing delimiter")
+        }
+    }
+
+    #[test]
+    fn bfs_with_in_edges_directed() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=5 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(1, 3, None)?;
+        graph.add_edge(2, 4, None)?;
+        graph.add_edge(3, 4, None)?;
+        graph.add_edge(4, 5, None)?;
+
+        let links = graph.bfs_with_in_edges(1)?;
+        let vertex_4 = links.iter()
+            .find(|links| links.id == 4)
+            .unwrap();
+        let mut in_neighbours = vertex_4.in_neighbours.clone();
+        in_neighbours.sort_unstable();
+        let tuple_neighbours: Vec<_> = graph.bfs(1)?
+            .into_iter()
+            .map(|(_, _, neighbours)| neighbours)
+            .collect();
+        let links_neighbours: Vec<_> = links.iter()
+            .map(|links| links.out_neighbours.clone())
+            .collect();
+        if in_neighbours == vec![2, 3] && vertex_4.out_neighbours == vec![5] && tuple_neighbours == links_neighbours {
+            Ok(())
+        } else {
+            bail!("bfs_with_in_edges return wrong result")
+        }
+    }
+
+    #[test]
+    fn bfs_with_in_edges_undirected() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Undirected);
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(2, 3, None)?;
+
+        let links = graph.bfs_with_in_edges(1)?;
+        if links.iter().all(|links| links.in_neighbours == links.out_neighbours) {
+            Ok(())
+        } else {
+            bail!("bfs_with_in_edges return wrong result")
+        }
+    }
+
+    #[test]
+    fn set_vertex_value() -> Result<()> {
+        let mut graph = Graph::<String, ()>::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, Some("old".to_owned())))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, None)?;
+
+        let previous_value = graph.set_vertex_value(1, Some("new".to_owned()))?;
+        if let Some(value) = graph.vertex_value_mut(1) {
+            value.push('!');
+        }
+        let generation = graph.generation();
+        let missing_value = graph.vertex_value_mut(3);
+        let missing_unchanged = missing_value.is_none() && graph.generation() == generation;
+        let upsert_previous_value = graph.upsert_vertex(Vertex::new(2, Some("upserted".to_owned())))?;
+
+        let bfs_result = graph.bfs(1)?;
+        let (_, value_1, neighbours_1) = bfs_result.iter().find(|(id, _, _)| *id == 1).unwrap();
+        let (_, value_2, _) = bfs_result.iter().find(|(id, _, _)| *id == 2).unwrap();
+        if previous_value.as_deref() == Some("old")
+            && upsert_previous_value.is_none()
+            && missing_unchanged
+            && value_1.map(String::as_str) == Some("new!")
+            && value_2.map(String::as_str) == Some("upserted")
+            && neighbours_1 == &vec![2] {
+            Ok(())
+        } else {
+            bail!("vertex value not changed")
+        }
+    }
+
+    #[test]
+    fn set_edge_value_undirected() -> Result<()> {
+        let mut graph = Graph::<(), String>::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, None))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, Some("old".to_owned()))?;
+
+        let previous_value = graph.set_edge_value(2, 1, Some("new".to_owned()))?;
+
+        let edge_values: Vec<_> = [(1, 2), (2, 1)]
+            .iter()
+            .map(|(from_id, to_id)| {
+                graph.vertices[from_id].edge_directions
+                    .iter()
+                    .find(|edge_direction| edge_direction.to_vertex_id == *to_id)
+                    .and_then(|edge_direction| edge_direction.value.as_ref().clone())
+            })
+            .collect();
+        let missing_edge = graph.set_edge_value(1, 1, None);
+        if previous_value.as_deref() == Some("old")
+            && edge_values == vec![Some("new".to_owned()), Some("new".to_owned())]
+            && matches!(missing_edge, Err(GraphError::EdgeNotFound(_, _))) {
+            Ok(())
+        } else {
+            bail!("edge value not changed in both directions")
+        }
+    }
+
+    #[test]
+    fn delete_missing_vertex_and_edge() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        graph.add_vertex(Vertex::new(1, None))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, None)?;
+
+        let missing_vertex = graph.delete_vertex(3);
+        let missing_edge = graph.delete_edge(2, 1);
+        let missing_endpoint = graph.delete_edge(1, 3);
+        if matches!(missing_vertex, Err(GraphError::VertexNotFound(id)) if id == "3")
+            && matches!(missing_edge, Err(GraphError::EdgeNotFound(from, to)) if from == "2" && to == "1")
+            && matches!(missing_endpoint, Err(GraphError::VertexNotFound(id)) if id == "3")
+            && graph.vertices[&1].edge_directions.len() == 1 {
+            Ok(())
+        } else {
+            bail!("deleting missing vertex or edge not reported")
+        }
+    }
+
+    #[test]
+    fn delete_undirected_edge_reverse_order() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, None))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, None)?;
+
+        graph.delete_edge(2, 1)?;
+
+        let second_delete = graph.delete_edge(1, 2);
+        if graph.vertices.values().all(|vertex| vertex.edge_directions.is_empty())
+            && matches!(second_delete, Err(GraphError::EdgeNotFound(_, _))) {
+            Ok(())
+        } else {
+            bail!("undirected edge not deleted in both directions")
+        }
+    }
+
+    #[test]
+    fn take_vertex_returns_severed_edges() -> Result<()> {
+        let mut undirected = Graph::<String, String>::new_multigraph(GraphType::Undirected);
+        for id in 1..=3 {
+            undirected.add_vertex(Vertex::new(id, Some(format!("v{}", id))))?;
+        }
+        undirected.add_edge(1, 2, Some("a".to_owned()))?;
+        undirected.add_edge(3, 1, Some("b".to_owned()))?;
+        undirected.add_edge(1, 1, Some("loop".to_owned()))?;
+        undirected.add_edge(2, 1, None)?;
+        let (value, edges) = undirected.take_vertex(1)?;
+
+        let mut directed = Graph::<(), u32>::new(GraphType::Directed);
+        for id in 1..=3 {
+            directed.add_vertex(Vertex::new(id, None))?;
+        }
+        directed.add_edge(1, 2, Some(12))?;
+        directed.add_edge(3, 1, Some(31))?;
+        directed.add_edge(2, 3, Some(23))?;
+        let (_, mut directed_edges) = directed.take_vertex(1)?;
+        directed_edges.sort_unstable();
+
+        if value.as_deref() == Some("v1")
+            && edges == vec![(2, Some("a".to_owned())), (3, Some("b".to_owned())), (1, Some("loop".to_owned())), (2, None)]
+            && undirected.edge_count() == 0
+            && undirected.vertices.values().all(|vertex| vertex.edge_directions.is_empty())
+            && directed_edges == vec![(2, Some(12)), (3, Some(31))]
+            && directed.edges() == vec![(2, 3, Some(&23))]
+            && matches!(directed.take_vertex(1), Err(GraphError::VertexNotFound(_))) {
+            Ok(())
+        } else {
+            bail!("wrong severed edges {:?}, {:?}", edges, directed_edges)
+        }
+    }
+
+    #[test]
+    fn directed_predecessors() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=4 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 3, None)?;
+        graph.add_edge(2, 3, None)?;
+        graph.add_edge(3, 4, None)?;
+        graph.add_edge(4, 1, None)?;
+
+        let mut predecessors_3 = graph.predecessors(3)?;
+        predecessors_3.sort_unstable();
+        graph.delete_edge(2, 3)?;
+        let predecessors_3_after_delete = graph.predecessors(3)?;
+        graph.delete_vertex(1)?;
+        let predecessors_3_after_vertex_delete = graph.predecessors(3)?;
+        let predecessors_4 = graph.predecessors(4)?;
+        if predecessors_3 == vec![1, 2]
+            && predecessors_3_after_delete == vec![1]
+            && predecessors_3_after_vertex_delete.is_empty()
+            && predecessors_4 == vec![3]
+            && graph.vertices[&4].edge_directions.is_empty()
+            && matches!(graph.predecessors(1), Err(GraphError::VertexNotFound(_))) {
+            Ok(())
+        } else {
+            bail!("predecessors index not consistent")
+        }
+    }
+
+    #[test]
+    fn delete_many_vertices() -> Result<()> {
+        const VERTEX_COUNT: DefaultGraphIdType = 50_000;
+        for graph_type in [GraphType::Directed, GraphType::Undirected] {
+            let mut graph = Graph::<(), ()>::new(graph_type);
+            for id in 0..VERTEX_COUNT {
+                graph.add_vertex(Vertex::new(id, None))?;
+            }
+            for id in 1..VERTEX_COUNT {
+                graph.add_edge(id - 1, id, None)?;
+                if id / 2 != id - 1 {
+                    graph.add_edge(id, id / 2, None)?;
+                }
+            }
+
+            for id in (0..VERTEX_COUNT).step_by(2) {
+                graph.delete_vertex(id)?;
+            }
+
+            let dangling_edge = graph.vertices.values()
+                .flat_map(|vertex| &vertex.edge_directions)
+                .any(|edge_direction| !graph.vertices.contains_key(&edge_direction.to_vertex_id));
+            if graph.vertices.len() != VERTEX_COUNT as usize / 2 || dangling_edge {
+                bail!("edges to deleted vertices left in graph")
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn edge_count() -> Result<()> {
+        let mut graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let deserialized_edge_count = graph.edge_count();
+        let duplicate_edge_result = graph.add_edge(2, 1, None);
+        let duplicate_edge_count = graph.edge_count();
+        graph.delete_vertex(7)?;
+        if deserialized_edge_count == 10
+            && matches!(duplicate_edge_result, Err(GraphError::EdgeAlreadyExist(_, _)))
+            && duplicate_edge_count == 10
+            && graph.edge_count() == 7 {
+            Ok(())
+        } else {
+            bail!("wrong edge count")
+        }
+    }
+
+    fn limited_tgf(vertex_count: u32, edge_count: u32) -> String {
+        let mut tgf = String::new();
+        for id in 0..vertex_count {
+            tgf.push_str(&format!("{} v{}\n", id, id));
+        }
+        tgf.push_str("#\n");
+        for id in 0..edge_count {
+            tgf.push_str(&format!("0 {}\n", id + 1));
+        }
+        tgf
+    }
+
+    #[test]
+    fn deserialize_with_limits() -> Result<()> {
+        let limits = DeserializeLimits {
+            max_vertices: Some(10),
+            max_edges: Some(5),
+            max_value_len: Some(2),
+        };
+        Graph::deserialize_with_limits(limited_tgf(10, 5).as_bytes(), limits)?;
+
+        let vertices_result = Graph::deserialize_with_limits(limited_tgf(11, 0).as_bytes(), limits);
+        let edges_result = Graph::deserialize_with_limits(limited_tgf(10, 6).as_bytes(), limits);
+        let value_result = Graph::deserialize_with_limits("1 ab\n2 cd\n3 efg\n#\n".as_bytes(), limits);
+        if matches!(vertices_result, Err(GraphError::LimitExceeded { which: LimitKind::Vertices, limit: 10, at_line: Some(11) }))
+            && matches!(edges_result, Err(GraphError::LimitExceeded { which: LimitKind::Edges, limit: 5, at_line: Some(17) }))
+            && matches!(value_result, Err(GraphError::LimitExceeded { which: LimitKind::ValueLength, limit: 2, at_line: Some(3) })) {
+            Ok(())
+        } else {
+            bail!("limits not enforced")
+        }
+    }
+
+    #[test]
+    fn auto_create_vertices() -> Result<()> {
+        let auto_create = DeserializeOptions {
+            auto_create_vertices: true,
+            ..DeserializeOptions::default()
+        };
+        let bare_tgf = "#\n1 2\n2 3 x\n3 3\n";
+        let strict_result = Graph::deserialize(bare_tgf.as_bytes());
+        let auto_graph = Graph::deserialize_with_options(bare_tgf.as_bytes(), auto_create)?;
+        let mut buf_writer = BufWriter::new(Vec::new());
+        auto_graph.serialize(&mut buf_writer)?;
+        let serialized = String::from_utf8(buf_writer.into_inner()?)?;
+
+        let labelled_tgf = "1 a\n#\n1 2\n";
+        let labelled_graph = Graph::deserialize_with_options(labelled_tgf.as_bytes(), auto_create)?;
+        let mut buf_writer = BufWriter::new(Vec::new());
+        labelled_graph.serialize(&mut buf_writer)?;
+        let labelled_serialized = String::from_utf8(buf_writer.into_inner()?)?;
+
+        if matches!(&strict_result, Err(GraphError::ParseLine { line_no: 2, source }) if matches!(**source, GraphError::VertexNotFound(ref id) if id == "1"))
+            && auto_graph.vertex_count() == 3
+            && auto_graph.edge_count() == 3
+            && auto_graph.vertex_value(3).is_none()
+            && Graph::deserialize(serialized.as_bytes())? == auto_graph
+            && labelled_graph.vertex_value(1).map(String::as_str) == Some("a")
+            && Graph::deserialize(labelled_serialized.as_bytes())? == labelled_graph
+            && Graph::deserialize_with_options(labelled_serialized.as_bytes(), auto_create)? == labelled_graph
+            && Graph::deserialize(TGF_GRAPH.as_bytes())? == Graph::deserialize_with_options(TGF_GRAPH.as_bytes(), auto_create)? {
+            Ok(())
+        } else {
+            bail!("auto created vertices differ: {:?}", auto_graph.edges())
+        }
+    }
+
+    #[test]
+    fn stats() -> Result<()> {
+        let graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let dag_options = DeserializeOptions { dag: true, ..DeserializeOptions::default() };
+        let directed_graph = Graph::deserialize_with_options(TGF_GRAPH.as_bytes(), dag_options)?;
+        let mut cyclic_graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=4 {
+            cyclic_graph.add_vertex(Vertex::new(id, None))?;
+        }
+        cyclic_graph.add_edge(1, 2, None)?;
+        cyclic_graph.add_edge(2, 1, None)?;
+        cyclic_graph.add_edge(3, 3, None)?;
+
+        let expected = GraphStats {
+            vertices: 7,
+            edges: 10,
+            self_loops: 0,
+            isolated_vertices: 0,
+            min_degree: 1,
+            max_degree: 4,
+            average_degree: 20.0 / 7.0,
+            components: 1,
+            largest_component: 7,
+            acyclic: None,
+        };
+        let cyclic_stats = cyclic_graph.stats();
+        if graph.stats() == expected
+            && directed_graph.stats() == (GraphStats { acyclic: Some(true), ..expected.clone() })
+            && graph.stats().to_string() == "vertices: 7\nedges: 10\nself-loops: 0\nisolated vertices: 0\n\
+                degree: min 1, max 4, average 2.86\ncomponents: 1\nlargest component: 7\n"
+            && cyclic_stats.self_loops == 1
+            && cyclic_stats.isolated_vertices == 1
+            && cyclic_stats.components == 3
+            && cyclic_stats.acyclic == Some(false)
+            && cyclic_stats.to_string().ends_with("dag: no\n")
+            && Graph::<(), ()>::new(GraphType::Directed).stats().acyclic == Some(true) {
+            Ok(())
+        } else {
+            bail!("unexpected stats {:?}", graph.stats())
+        }
+    }
+
+    #[test]
+    fn hex_and_separated_ids() -> Result<()> {
+        let relaxed = DeserializeOptions {
+            allow_hex_ids: true,
+            allow_digit_separators: true,
+            ..DeserializeOptions::default()
+        };
+        let tgf = "0x1A a\n1_000_000 b\n7 0x10\n#\n0x1a 1_000_000 0x2A\n7 0x00_1A\n!roles\n0X1A source\n";
+        let graph = Graph::deserialize_with_options(tgf.as_bytes(), relaxed)?;
+        let mut bufer = BufWriter::new(Vec::new());
+        graph.serialize(&mut bufer)?;
+        let serialized_graph = String::from_utf8(bufer.into_inner()?)?;
+        let strict_result = Graph::deserialize(tgf.as_bytes());
+        let hex_only_result = Graph::deserialize_with_options("1_000 a\n".as_bytes(), DeserializeOptions {
+            allow_hex_ids: true,
+            ..DeserializeOptions::default()
+        });
+        let overflow_result = Graph::deserialize_with_options("0x1_0000_0000 a\n".as_bytes(), relaxed);
+
+        let mut edges: Vec<_> = graph.edges().into_iter()
+            .map(|(from_id, to_id, value)| (from_id.min(to_id), from_id.max(to_id), value.cloned()))
+            .collect();
+        edges.sort();
+        if graph.vertex_value(26).map(String::as_str) == Some("a")
+            && graph.vertex_value(1_000_000).map(String::as_str) == Some("b")
+            && graph.vertex_value(7).map(String::as_str) == Some("0x10")
+            && edges == vec![(7, 26, None), (26, 1_000_000, Some("0x2A".to_owned()))]
+            && graph.role(26) == Some(VertexRole::Source)
+            && serialized_graph.lines().all(|line| !line.starts_with("0x") && !line.contains('_'))
+            && Graph::deserialize(serialized_graph.as_bytes())? == graph
+            && matches!(&strict_result, Err(GraphError::ParseLine { line_no: 1, source }) if matches!(**source, GraphError::WrongVertexIdType(_)))
+            && hex_only_result.is_err()
+            && matches!(&overflow_result, Err(GraphError::ParseLine { source, .. }) if source.hint().is_some_and(|hint| hint.contains("out of range"))) {
+            Ok(())
+        } else {
+            bail!("relaxed ids parsed wrong: {:?}\n{}", edges, serialized_graph)
+        }
+    }
+
+    #[test]
+    fn limited_graph() -> Result<()> {
+        let limits = DeserializeLimits {
+            max_vertices: Some(3),
+            max_edges: Some(2),
+            ..DeserializeLimits::default()
+        };
+        let mut graph = Graph::<(), ()>::new_limited(GraphType::Directed, limits);
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(2, 3, None)?;
+
+        let vertex_result = graph.add_vertex(Vertex::new(4, None));
+        let upsert_result = graph.upsert_vertex(Vertex::new(4, None));
+        let edge_result = graph.add_edge(3, 1, None);
+        if matches!(vertex_result, Err(GraphError::LimitExceeded { which: LimitKind::Vertices, limit: 3, at_line: None }))
+            && matches!(upsert_result, Err(GraphError::LimitExceeded { which: LimitKind::Vertices, limit: 3, at_line: None }))
+            && graph.upsert_vertex(Vertex::new(3, Some(())))?.is_none()
+            && matches!(edge_result, Err(GraphError::LimitExceeded { which: LimitKind::Edges, limit: 2, at_line: None }))
+            && graph.vertex_count() == 3
+            && graph.edge_count() == 2 {
+            Ok(())
+        } else {
+            bail!("limited graph accepted too many vertices or edges")
+        }
+    }
+
+    #[test]
+    fn simple_graph_rejects_parallel_edges() -> Result<()> {
+        let mut graph = Graph::<(), String>::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, None))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, Some("first".to_owned()))?;
+
+        let same_order_result = graph.add_edge(1, 2, Some("second".to_owned()));
+        let reverse_order_result = graph.add_edge(2, 1, Some("second".to_owned()));
+        if matches!(same_order_result, Err(GraphError::EdgeAlreadyExist(from, to)) if from == "1" && to == "2")
+            && matches!(reverse_order_result, Err(GraphError::EdgeAlreadyExist(from, to)) if from == "2" && to == "1")
+            && graph.edges() == vec![(1, 2, Some(&"first".to_owned()))] {
+            Ok(())
+        } else {
+            bail!("parallel edge in simple graph not rejected")
+        }
+    }
+
+    #[test]
+    fn multigraph_parallel_edges() -> Result<()> {
+        let mut graph = Graph::<String, String>::new_multigraph(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, Some("Minsk".to_owned())))?;
+        graph.add_vertex(Vertex::new(2, Some("Brest".to_owned())))?;
+        graph.add_edge(1, 2, Some("train".to_owned()))?;
+        graph.add_edge(2, 1, Some("bus".to_owned()))?;
+        graph.add_edge(1, 2, Some("plane".to_owned()))?;
+
+        let mut bufer = BufWriter::new(Vec::new());
+        graph.serialize(&mut bufer)?;
+        let serialized_graph = String::from_utf8(bufer.into_inner()?)?;
+        let edge_lines = serialized_graph.lines()
+            .skip_while(|line| *line != "#")
+            .count() - 1;
+
+        graph.delete_edge_where(1, 2, |value| value.map(String::as_str) == Some("bus"))?;
+        let mut remaining_values: Vec<_> = graph.edges()
+            .into_iter()
+            .filter_map(|(_, _, value)| value.cloned())
+            .collect();
+        remaining_values.sort();
+        let mirror_values: Vec<_> = graph.vertices[&2].edge_directions
+            .iter()
+            .filter_map(|edge_direction| edge_direction.value.as_ref().clone())
+            .collect();
+        let edge_count_before_delete = graph.edge_count();
+        graph.delete_edge(2, 1)?;
+
+        if edge_lines == 3
+            && edge_count_before_delete == 2
+            && remaining_values == vec!["plane".to_owned(), "train".to_owned()]
+            && mirror_values.len() == 2
+            && !mirror_values.contains(&"bus".to_owned())
+            && graph.edge_count() == 0
+            && graph.vertices.values().all(|vertex| vertex.edge_directions.is_empty()) {
+            Ok(())
+        } else {
+            bail!("parallel edges not handled")
+        }
+    }
+
+    #[test]
+    fn multigraph_edge_from_missing_vertex() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new_multigraph(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(2, None))?;
+        let result = graph.add_edge(7, 2, None);
+        if result == Err(GraphError::VertexNotFound("7".to_owned()))
+            && graph.neighbours(2)?.is_empty()
+            && graph.degree(2)? == 0
+            && graph.edge_count() == 0 {
+            Ok(())
+        } else {
+            bail!("edge from a missing vertex left a mirror: {:?}", graph.neighbours(2))
+        }
+    }
+
+    #[test]
+    fn directed_multigraph_delete_one_parallel_edge() -> Result<()> {
+        let mut graph = Graph::<(), u32>::new_multigraph(GraphType::Directed);
+        graph.add_vertex(Vertex::new(1, None))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 2, Some(10))?;
+        graph.add_edge(1, 2, Some(20))?;
+
+        graph.delete_edge_where(1, 2, |value| value == Some(&10))?;
+        let predecessors_after_first_delete = graph.predecessors(2)?;
+        graph.delete_edge_where(1, 2, |_| true)?;
+        if predecessors_after_first_delete == vec![1] && graph.predecessors(2)?.is_empty() && graph.edge_count() == 0 {
+            Ok(())
+        } else {
+            bail!("predecessors index broken by parallel edges")
+        }
+    }
+
+    #[test]
+    fn undirected_self_loop() -> Result<()> {
+        let mut graph = Graph::<String, String>::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(1, Some("loop".to_owned())))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 1, Some("self".to_owned()))?;
+        graph.add_edge(1, 2, None)?;
+
+        let duplicate_result = graph.add_edge(1, 1, None);
+        let neighbours = graph.neighbours(1)?;
+        let degree = graph.degree(1)?;
+        let bfs_neighbours = graph.bfs(1)?.remove(0).2;
+        let in_neighbours = graph.bfs_with_in_edges(1)?.remove(0).in_neighbours;
+        let previous_value = graph.set_edge_value(1, 1, Some("changed".to_owned()))?;
+        let edges = graph.edges().len();
+
+        let mut bufer = BufWriter::new(Vec::new());
+        graph.serialize(&mut bufer)?;
+        let serialized_graph = String::from_utf8(bufer.into_inner()?)?;
+        let mut deserialized_graph = Graph::deserialize(serialized_graph.as_bytes())?;
+        let deserialized_edges = deserialized_graph.edges().len();
+        let deserialized_degree = deserialized_graph.degree(1)?;
+
+        deserialized_graph.delete_edge(1, 1)?;
+        graph.delete_vertex(1)?;
+        if matches!(duplicate_result, Err(GraphError::EdgeAlreadyExist(_, _)))
+            && neighbours.iter().filter(|&&id| id == 1).count() == 1
+            && degree == 2
+            && bfs_neighbours.iter().filter(|&&id| id == 1).count() == 1
+            && in_neighbours.iter().filter(|&&id| id == 1).count() == 1
+            && previous_value.as_deref() == Some("self")
+            && edges == 2
+            && serialized_graph.lines().filter(|line| *line == "1 1 changed").count() == 1
+            && deserialized_edges == 2
+            && deserialized_degree == 2
+            && deserialized_graph.neighbours(1)? == vec![2]
+            && deserialized_graph.edge_count() == 1
+            && graph.edge_count() == 0
+            && graph.neighbours(2)?.is_empty() {
+            Ok(())
+        } else {
+            bail!("undirected self-loop handled inconsistently")
+        }
+    }
+
+    #[test]
+    fn directed_self_loop() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        graph.add_vertex(Vertex::new(1, None))?;
+        graph.add_vertex(Vertex::new(2, None))?;
+        graph.add_edge(1, 1, None)?;
+        graph.add_edge(2, 1, None)?;
+
+        let neighbours = graph.neighbours(1)?;
+        let degree = graph.degree(1)?;
+        let mut predecessors = graph.predecessors(1)?;
+        predecessors.sort_unstable();
+        let bfs_neighbours = graph.bfs(1)?.remove(0).2;
+
+        graph.delete_edge(1, 1)?;
+        let predecessors_after_delete = graph.predecessors(1)?;
+        graph.add_edge(1, 1, None)?;
+        graph.delete_vertex(1)?;
+        if neighbours == vec![1]
+            && degree == 2
+            && predecessors == vec![1, 2]
+            && bfs_neighbours == vec![1]
+            && predecessors_after_delete == vec![2]
+            && graph.edge_count() == 0
+            && graph.neighbours(2)?.is_empty() {
+            Ok(())
+        } else {
+            bail!("directed self-loop handled inconsistently")
+        }
+    }
+
+    #[test]
+    fn bfs_visit_same_order_as_bfs() -> Result<()> {
+        let graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let bfs_ids: Vec<_> = graph.bfs(7)?
+            .iter()
+            .map(|(id, _, _)| *id)
+            .collect();
+        let mut visited_ids = Vec::new();
+        graph.bfs_visit(7, |id, _| {
+            visited_ids.push(id);
+            ControlFlow::Continue(())
+        })?;
+
+        if bfs_ids == visited_ids && visited_ids.len() == 7 {
+            Ok(())
+        } else {
+            bail!("bfs_visit order {:?} differs from bfs order {:?}", visited_ids, bfs_ids)
+        }
+    }
+
+    #[test]
+    fn traversal_visit_early_termination() -> Result<()> {
+        let graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let mut bfs_visited_count = 0;
+        graph.bfs_visit(7, |_, value| {
+            bfs_visited_count += 1;
+            if value.map(String::as_str) == Some("September") {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })?;
+        let mut dfs_visited_count = 0;
+        graph.dfs_visit(1, |_, _| {
+            dfs_visited_count += 1;
+            if dfs_visited_count == 3 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        })?;
+
+        if bfs_visited_count == 1 && dfs_visited_count == 3 {
+            Ok(())
+        } else {
+            bail!("traversal continued after visitor break")
+        }
+    }
+
+    #[test]
+    fn has_edge_either_order() -> Result<()> {
+        let graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let mut directed_graph = Graph::<(), ()>::new(GraphType::Directed);
+        directed_graph.add_vertex(Vertex::new(1, None))?;
+        directed_graph.add_vertex(Vertex::new(2, None))?;
+        directed_graph.add_edge(1, 2, None)?;
+        if graph.has_edge(5, 1) && graph.has_edge(1, 5) && !graph.has_edge(1, 4) && !graph.has_edge(1, 99)
+            && directed_graph.has_edge(1, 2) && !directed_graph.has_edge(2, 1) {
+            Ok(())
+        } else {
+            bail!("wrong edge existence")
+        }
+    }
+
+    #[test]
+    fn reachability_stops_at_target() -> Result<()> {
+        // Звезда из 20 лучей с длинной цепочкой за последним лучом
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 0..=1000 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..=20 {
+            graph.add_edge(0, id, None)?;
+        }
+        for id in 20..1000 {
+            graph.add_edge(id, id + 1, None)?;
+        }
+        let visits = graph.visits_until_reached(0, 3)?;
+        if visits.is_some_and(|visits| visits <= 21)
+            && graph.is_reachable(0, 1000)?
+            && !graph.is_reachable(1000, 0)?
+            && graph.is_reachable(7, 7)?
+            && graph.is_reachable(0, 1001).is_err()
+            && graph.is_reachable(1001, 1001).is_err() {
+            Ok(())
+        } else {
+            bail!("reachability visited {:?} vertices", visits)
+        }
+    }
+
+    #[test]
+    fn dfs_order() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=5 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(1, 3, None)?;
+        graph.add_edge(2, 4, None)?;
+        graph.add_edge(3, 5, None)?;
+        graph.add_edge(4, 3, None)?;
+
+        let dfs_ids: Vec<_> = graph.dfs(1)?
+            .iter()
+            .map(|(id, _, _)| *id)
+            .collect();
+        let bfs_ids: Vec<_> = graph.bfs(1)?
+            .iter()
+            .map(|(id, _, _)| *id)
+            .collect();
+        if dfs_ids == vec![1, 2, 4, 3, 5] && bfs_ids == vec![1, 2, 3, 4, 5] {
+            Ok(())
+        } else {
+            bail!("wrong traversal order: dfs {:?}, bfs {:?}", dfs_ids, bfs_ids)
+        }
+    }
+
+    #[test]
+    fn dfs_deep_path() -> Result<()> {
+        const PATH_LEN: u32 = 200_000;
+        let mut graph = Graph::<(), ()>::with_capacity(GraphType::Undirected, PATH_LEN as usize);
+        for id in 0..PATH_LEN {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..PATH_LEN {
+            graph.add_edge(id - 1, id, None)?;
+        }
+        let dfs = graph.dfs(0)?;
+        if dfs.len() == PATH_LEN as usize && dfs.iter().enumerate().all(|(index, (id, _, _))| *id as usize == index) {
+            Ok(())
+        } else {
+            bail!("deep path traversed out of order")
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn graph_is_send_and_sync() {
+        assert_send_sync::<Graph<String, String>>();
+        assert_send_sync::<Graph<String, String, u64>>();
+    }
+
+    #[test]
+    fn parallel_bfs() -> Result<()> {
+        let mut graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        graph.pin_hot(&[1, 3])?;
+        let sequential: Vec<_> = (1..=7).map(|start_id| graph.bfs(start_id).map(|result| result.len())).collect::<Result<_, _>>()?;
+        let parallel: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (1..=7)
+                .map(|start_id| {
+                    let graph = &graph;
+                    scope.spawn(move || graph.bfs(start_id).map(|result| result.len()))
+                })
+                .collect();
+            handles.into_iter()
+                .map(|handle| handle.join().expect("bfs thread panicked"))
+                .collect::<Result<_, _>>()
+        })?;
+        if parallel == sequential && parallel.iter().all(|&visited| visited == 7) && graph.hot_stats().hits > 0 {
+            Ok(())
+        } else {
+            bail!("parallel bfs differs: {:?}", parallel)
+        }
+    }
+
+    #[test]
+    fn clone_then_mutate() -> Result<()> {
+        let graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let mut cloned_graph = graph.clone();
+        let equal_after_clone = cloned_graph == graph;
+
+        cloned_graph.set_edge_value(1, 5, Some("Christmas".to_owned()))?;
+        let original_values: Vec<_> = graph.vertices[&5].edge_directions
+            .iter()
+            .chain(&graph.vertices[&1].edge_directions)
+            .filter(|edge_direction| edge_direction.to_vertex_id == 1 || edge_direction.to_vertex_id == 5)
+            .map(|edge_direction| edge_direction.value.as_ref().clone())
+            .collect();
+        let cloned_mirror_value = cloned_graph.vertices[&5].edge_directions
+            .iter()
+            .find(|edge_direction| edge_direction.to_vertex_id == 1)
+            .and_then(|edge_direction| edge_direction.value.as_ref().clone());
+
+        if equal_after_clone
+            && cloned_graph != graph
+            && original_values == vec![Some("Happy New Year!".to_owned()); 2]
+            && cloned_mirror_value.as_deref() == Some("Christmas") {
+            Ok(())
+        } else {
+            bail!("mutating the clone changed the original graph")
+        }
+    }
+
+    #[test]
+    fn structural_equality() -> Result<()> {
+        let graph = Graph::<String, String>::deserialize("1 a\n2 b\n3\n#\n1 2 x\n3 2\n".as_bytes())?;
+        let reordered_graph = Graph::<String, String>::deserialize("3\n2 b\n1 a\n#\n2 3\n2 1 x\n".as_bytes())?;
+        let other_value_graph = Graph::<String, String>::deserialize("1 a\n2 b\n3\n#\n1 2 y\n3 2\n".as_bytes())?;
+        let parallel_graph = |values: [u32; 2]| -> Result<Graph<(), u32>> {
+            let mut graph = Graph::new_multigraph(GraphType::Directed);
+            graph.add_vertex(Vertex::new(1, None))?;
+            graph.add_vertex(Vertex::new(2, None))?;
+            for value in values {
+                graph.add_edge(1, 2, Some(value))?;
+            }
+            Ok(graph)
+        };
+        if graph == reordered_graph
+            && graph != other_value_graph
+            && parallel_graph([12, 21])? == parallel_graph([21, 12])?
+            && parallel_graph([12, 12])? != parallel_graph([12, 21])? {
+            Ok(())
+        } else {
+            bail!("wrong structural equality")
+        }
+    }
 }
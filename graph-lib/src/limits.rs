@@ -0,0 +1,97 @@
+use std::{
+    borrow::Cow,
+    fmt,
+};
+
+/// Ограничения размера графа. `None` - ограничения нет
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeserializeLimits {
+    pub max_vertices: Option<usize>,
+    pub max_edges: Option<usize>,
+    /// Максимальная длина значения вершины или ребра в байтах (проверяется только при десериализации)
+    pub max_value_len: Option<usize>,
+}
+
+/// Параметры десериализации TGF. По умолчанию - строгий разбор без ограничений размера
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    pub limits: DeserializeLimits,
+    /// Создавать вершины без значения для концов рёбер, не объявленных в секции вершин, вместо ошибки `VertexNotFound`
+    pub auto_create_vertices: bool,
+    /// Читать граф как ориентированный ациклический (`Graph::new_dag`): ребро, замыкающее цикл, - ошибка
+    pub dag: bool,
+    /// Читать граф как ориентированный: ребро `1 2` ведёт из вершины 1 в вершину 2. Для `dag` граф ориентирован всегда
+    pub directed: bool,
+    /// Принимать шестнадцатеричные идентификаторы вершин с префиксом `0x`
+    pub allow_hex_ids: bool,
+    /// Принимать идентификаторы вершин с разделителями разрядов `_`, например `1_000_000`
+    pub allow_digit_separators: bool,
+}
+
+impl DeserializeOptions {
+    /// Приводит первые `id_count` слов строки к десятичной записи идентификатора, если это разрешено параметрами.
+    /// Слова, которые не удалось привести, остаются как есть и дают обычную ошибку разбора
+    pub(crate) fn normalize_ids<'a>(&self, line: &'a str, id_count: usize) -> Cow<'a, str> {
+        if !self.allow_hex_ids && !self.allow_digit_separators {
+            return Cow::Borrowed(line);
+        }
+        let mut words: Vec<Cow<'_, str>> = line.split(' ').map(Cow::Borrowed).collect();
+        let mut changed = false;
+        for word in words.iter_mut().take(id_count) {
+            if let Some(id) = self.normalize_id(word) {
+                *word = Cow::Owned(id);
+                changed = true;
+            }
+        }
+        if changed {
+            Cow::Owned(words.join(" "))
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+
+    fn normalize_id(&self, word: &str) -> Option<String> {
+        let separated = self.allow_digit_separators
+            && word.contains('_')
+            && !word.starts_with('_')
+            && !word.ends_with('_');
+        let id = if separated {
+            Cow::Owned(word.replace('_', ""))
+        } else {
+            Cow::Borrowed(word)
+        };
+        let hex_digits = id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")).filter(|_| self.allow_hex_ids);
+        match hex_digits {
+            Some(hex_digits) => u128::from_str_radix(hex_digits, 16).ok().map(|id| id.to_string()),
+            None if separated => Some(id.into_owned()),
+            None => None,
+        }
+    }
+}
+
+/// Какое из ограничений `DeserializeLimits` превышено
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    Vertices,
+    Edges,
+    ValueLength,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKind::Vertices => write!(f, "vertex count"),
+            LimitKind::Edges => write!(f, "edge count"),
+            LimitKind::ValueLength => write!(f, "value length"),
+        }
+    }
+}
+
+impl DeserializeLimits {
+    pub(crate) fn check(limit: Option<usize>, value: usize, which: LimitKind, at_line: Option<usize>) -> Result<(), crate::GraphError> {
+        match limit {
+            Some(limit) if value > limit => Err(crate::GraphError::LimitExceeded { which, limit, at_line }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use super::*;
+
+const UNREACHED: usize = usize::MAX;
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Наибольшее паросочетание двудольного графа (алгоритм Хопкрофта - Карпа, O(m √n)): пары вершин из разных
+    /// долей, соединённых ребром, без общих вершин. Доли - как у `bipartition`, в паре первой идёт вершина
+    /// первой доли, пары - по возрастанию. Рёбра ориентированного графа считаются неориентированными.
+    /// Недвудольный граф - ошибка `OddCycle` с нечётным циклом
+    pub fn max_bipartite_matching(&self) -> Result<Vec<(ID, ID)>, GraphError> {
+        let first_part = match self.bipartition() {
+            Bipartition::Parts(first_part, _) => first_part,
+            Bipartition::OddCycle(cycle) => return Err(GraphError::OddCycle(cycle.iter().map(ToString::to_string).collect())),
+        };
+        let mut ids: Vec<_> = self.vertices.keys().collect();
+        ids.sort_unstable();
+        let indices: HashMap<_, _> = ids.iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        let mut neighbours = vec![Vec::new(); ids.len()];
+        for (from_id, edge_direction) in self.logical_edges() {
+            let (from_index, to_index) = (indices[from_id], indices[&edge_direction.to_vertex_id]);
+            neighbours[from_index].push(to_index);
+            neighbours[to_index].push(from_index);
+        }
+        let first_indices: Vec<_> = first_part.iter().map(|id| indices[id]).collect();
+
+        let mates = hopcroft_karp(&neighbours, &first_indices);
+        Ok(first_indices.into_iter()
+            .filter_map(|index| Some((ids[index].clone(), ids[mates[index]?].clone())))
+            .collect())
+    }
+}
+
+/// Пара каждой вершины в наибольшем паросочетании. `first_indices` - вершины первой доли,
+/// рёбра `neighbours` соединяют только вершины разных долей
+fn hopcroft_karp(neighbours: &[Vec<usize>], first_indices: &[usize]) -> Vec<Option<usize>> {
+    let mut mates = vec![None; neighbours.len()];
+    let mut levels = vec![UNREACHED; neighbours.len()];
+    loop {
+        // Слои вершин первой доли по длине чередующегося пути от свободных вершин первой доли
+        let mut queue = VecDeque::new();
+        for &index in first_indices {
+            levels[index] = if mates[index].is_none() {
+                queue.push_back(index);
+                0
+            } else {
+                UNREACHED
+            };
+        }
+        let mut free_reached = false;
+        while let Some(index) = queue.pop_front() {
+            for &neighbour_index in &neighbours[index] {
+                match mates[neighbour_index] {
+                    None => free_reached = true,
+                    Some(mate_index) if levels[mate_index] == UNREACHED => {
+                        levels[mate_index] = levels[index] + 1;
+                        queue.push_back(mate_index);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if !free_reached {
+            return mates;
+        }
+
+        // Непересекающиеся увеличивающие пути по слоям: поиск в глубину без рекурсии из каждой свободной вершины
+        let mut next_edges = vec![0; neighbours.len()];
+        for &root in first_indices {
+            if mates[root].is_some() {
+                continue;
+            }
+            // Вершины первой доли на пути и выбранные для них вершины второй доли
+            let mut path = vec![root];
+            let mut chosen = Vec::new();
+            while let Some(&index) = path.last() {
+                let Some(&neighbour_index) = neighbours[index].get(next_edges[index]) else {
+                    // Из вершины свободная вершина недостижима, в этой фазе она больше не рассматривается
+                    levels[index] = UNREACHED;
+                    path.pop();
+                    chosen.pop();
+                    continue;
+                };
+                next_edges[index] += 1;
+                match mates[neighbour_index] {
+                    None => {
+                        chosen.push(neighbour_index);
+                        for (&index, &neighbour_index) in path.iter().zip(&chosen) {
+                            mates[index] = Some(neighbour_index);
+                            mates[neighbour_index] = Some(index);
+                        }
+                        break;
+                    }
+                    Some(mate_index) if levels[index] != UNREACHED && levels[mate_index] == levels[index] + 1 => {
+                        chosen.push(neighbour_index);
+                        path.push(mate_index);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::SplitMix64;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn workers_and_tasks() -> Result<()> {
+        // Работники 1..=4 умеют выполнять задачи 10..=13, работники 1 и 2 - только задачу 10
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in [1, 2, 3, 4, 10, 11, 12, 13] {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (worker_id, task_id) in [(1, 10), (2, 10), (3, 10), (3, 11), (4, 11), (4, 12), (4, 13)] {
+            graph.add_edge(worker_id, task_id, None)?;
+        }
+        let matching = graph.max_bipartite_matching()?;
+        let triangle = Graph::deserialize("1\n2\n3\n#\n1 2\n2 3\n3 1".as_bytes())?;
+        if matching.len() == 3
+            && matching.iter().all(|&(worker_id, task_id)| graph.has_edge(worker_id, task_id))
+            && matching.iter().filter(|&&(worker_id, _)| worker_id <= 2).count() == 1
+            && matches!(triangle.max_bipartite_matching(), Err(GraphError::OddCycle(cycle)) if cycle.len() == 3)
+            && Graph::<(), ()>::new(GraphType::Undirected).max_bipartite_matching()?.is_empty() {
+            Ok(())
+        } else {
+            bail!("wrong matching {:?}", matching)
+        }
+    }
+
+    #[test]
+    fn matching_size_equals_unit_flow() -> Result<()> {
+        const SIDE: u32 = 40;
+        const SOURCE: u32 = 1000;
+        const SINK: u32 = 1001;
+        let mut random = SplitMix64::new(41);
+        for _ in 0..5 {
+            let mut graph = Graph::<(), ()>::new(GraphType::Undirected);
+            let mut network = Graph::<(), ()>::new(GraphType::Directed);
+            for id in (0..SIDE).chain(100..100 + SIDE) {
+                graph.add_vertex(Vertex::new(id, None))?;
+                network.add_vertex(Vertex::new(id, None))?;
+            }
+            network.add_vertex(Vertex::new(SOURCE, None))?;
+            network.add_vertex(Vertex::new(SINK, None))?;
+            for id in 0..SIDE {
+                network.add_edge(SOURCE, id, None)?;
+                network.add_edge(100 + id, SINK, None)?;
+            }
+            for _ in 0..60 {
+                let (left_id, right_id) = (random.next_below(SIDE as u64) as u32, 100 + random.next_below(SIDE as u64) as u32);
+                if !graph.has_edge(left_id, right_id) {
+                    graph.add_edge(left_id, right_id, None)?;
+                    network.add_edge(left_id, right_id, None)?;
+                }
+            }
+            let matching = graph.max_bipartite_matching()?;
+            let mut matched_ids: Vec<_> = matching.iter().flat_map(|&(left_id, right_id)| [left_id, right_id]).collect();
+            matched_ids.sort_unstable();
+            matched_ids.dedup();
+            let flow = network.max_flow(SOURCE, SINK, |_| 1.0, FlowAlgorithm::default())?;
+            if matching.len() as f64 != flow || matched_ids.len() != 2 * matching.len() {
+                bail!("matching of {} pairs, unit flow {}", matching.len(), flow)
+            }
+        }
+        Ok(())
+    }
+}
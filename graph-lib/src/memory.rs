@@ -0,0 +1,123 @@
+use std::mem;
+use super::*;
+
+/// Оценка занимаемой графом памяти в байтах по категориям
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Записи таблицы вершин: ключ, структура вершины, управляющий байт таблицы
+    pub vertices: usize,
+    /// Направления рёбер, общие значения рёбер с их счётчиками ссылок, множества предшественников
+    /// и индексы соседей вершин с большим числом рёбер
+    pub edge_directions: usize,
+    /// Память в куче, принадлежащая значениям вершин и рёбер, по оценке пользователя
+    pub values: usize,
+}
+
+impl MemoryEstimate {
+    pub fn total(&self) -> usize {
+        self.vertices + self.edge_directions + self.values
+    }
+}
+
+/// Управляющий байт на запись в хеш-таблицах стандартной библиотеки
+const HASH_CONTROL_BYTES: usize = 1;
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Грубая оценка памяти графа без учёта значений в куче. Модель: `size_of` каждой записи таблиц вершин
+    /// и предшественников, каждого направления ребра и каждого общего значения ребра (`Arc` со счётчиками),
+    /// запасная ёмкость таблиц и векторов не учитывается, поэтому оценка растёт линейно с размером графа
+    pub fn memory_estimate(&self) -> MemoryEstimate {
+        self.memory_estimate_with_value_sizer(|_| 0, |_| 0)
+    }
+
+    /// Оценка памяти, в которой память значений в куче (например, буферы строк) считают
+    /// `vertex_value_size` и `edge_value_size`. Значение ребра неориентированного графа считается один раз
+    pub fn memory_estimate_with_value_sizer<VS, ES>(&self, vertex_value_size: VS, edge_value_size: ES) -> MemoryEstimate
+    where
+        VS: Fn(&VT) -> usize,
+        ES: Fn(&ET) -> usize,
+    {
+        let vertex_entry_size = mem::size_of::<(ID, Vertex<VT, ET, ID>)>() + HASH_CONTROL_BYTES;
+        let edge_direction_size = mem::size_of::<EdgeDirection<ET, ID>>();
+        let edge_value_entry_size = mem::size_of::<Option<ET>>() + 2 * mem::size_of::<usize>();
+        let predecessor_set_size = mem::size_of::<(ID, HashSet<ID>)>() + HASH_CONTROL_BYTES;
+        let predecessor_size = mem::size_of::<ID>() + HASH_CONTROL_BYTES;
+
+        let edge_direction_count: usize = self.vertices.values()
+            .map(|vertex| vertex.edge_directions.len())
+            .sum();
+        let target_index_size = mem::size_of::<(u64, ID)>();
+        let target_index_count: usize = self.vertices.values()
+            .filter_map(|vertex| vertex.target_index.as_ref())
+            .map(TargetIndex::len)
+            .sum();
+        let predecessor_count: usize = self.predecessors.values()
+            .map(HashSet::len)
+            .sum();
+        let vertex_values: usize = self.vertices.values()
+            .filter_map(|vertex| vertex.value.as_ref())
+            .map(vertex_value_size)
+            .sum();
+        let edge_values: usize = self.logical_edges()
+            .filter_map(|(_, edge_direction)| edge_direction.value.as_ref().as_ref())
+            .map(edge_value_size)
+            .sum();
+
+        MemoryEstimate {
+            vertices: self.vertices.len() * vertex_entry_size,
+            edge_directions: edge_direction_count * edge_direction_size
+                + self.edge_count * edge_value_entry_size
+                + self.predecessors.len() * predecessor_set_size
+                + predecessor_count * predecessor_size
+                + target_index_count * target_index_size,
+            values: vertex_values + edge_values,
+        }
+    }
+}
+
+impl<ID: GraphId> Graph<String, String, ID> {
+    /// Оценка памяти графа со строковыми значениями: значения занимают в куче по длине строки
+    pub fn string_memory_estimate(&self) -> MemoryEstimate {
+        self.memory_estimate_with_value_sizer(String::len, String::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn estimate_scales_linearly() -> Result<()> {
+        let empty = Graph::<(), ()>::new(GraphType::Undirected).memory_estimate();
+        let small = Graph::<(), ()>::gnm_random(1000, 3000, 7)?.memory_estimate();
+        let large = Graph::<(), ()>::gnm_random(2000, 6000, 7)?.memory_estimate();
+        let ratio = large.total() as f64 / small.total() as f64;
+        if empty.total() == 0 && (1.8..=2.2).contains(&ratio) {
+            Ok(())
+        } else {
+            bail!("estimate does not scale linearly: {:?} -> {:?}", small, large)
+        }
+    }
+
+    #[test]
+    fn string_values_and_mutations() -> Result<()> {
+        let mut graph = Graph::deserialize("1 January\n2 March\n3\n#\n1 2 spring\n2 3\n3 1 ok".as_bytes())?;
+        let before = graph.string_memory_estimate();
+        graph.delete_vertex(3)?;
+        let after = graph.string_memory_estimate();
+        // "January" + "March" + "spring" + "ok", затем без вершины 3 и ребра "ok"
+        if before.values == 20
+            && after.values == 18
+            && after.vertices < before.vertices
+            && after.edge_directions < before.edge_directions
+            && graph.memory_estimate().values == 0 {
+            Ok(())
+        } else {
+            bail!("wrong string estimate: {:?} -> {:?}", before, after)
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::{collections::HashSet, mem};
+use super::*;
+
+/// Что делать с вершинами (и рёбрами простого графа), которые есть в обоих объединяемых графах
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Повтор идентификатора вершины - ошибка, граф не меняется
+    Error,
+    /// Остаются значения вершин и рёбер графа, в который идёт объединение
+    KeepExisting,
+    /// Значения вершин и рёбер заменяются значениями присоединяемого графа
+    Overwrite,
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Переносит в граф все вершины и рёбра графа `other`. Рёбра `other` к общим вершинам присоединяются
+    /// к уже существующим вершинам. В мультиграф рёбра добавляются всегда, в простом графе совпавшее ребро
+    /// обрабатывается согласно `policy`. Тип графов должен совпадать.
+    /// Атрибуты вершин и рёбер `other` дописываются к атрибутам тех, что получили значения из `other`.
+    /// Ошибки типа, повтора вершин и рёбер и превышения ограничений проверяются до изменения графа,
+    /// поэтому при ошибке граф остаётся прежним
+    pub fn merge_with(&mut self, mut other: Graph<VT, ET, ID>, policy: MergePolicy) -> Result<(), GraphError> {
+        if self.r#type != other.r#type {
+            return Err(GraphError::WrongGraphType { expected: self.r#type.name(), actual: other.r#type.name() });
+        }
+        if policy == MergePolicy::Error {
+            if let Some(id) = other.vertices.keys().find(|id| self.contains_vertex(id)) {
+                return Err(GraphError::VertexAlreadyExist(id.to_string()));
+            }
+        }
+
+        let new_vertex_count = other.vertices.keys()
+            .filter(|id| !self.contains_vertex(id))
+            .count();
+        DeserializeLimits::check(self.limits.max_vertices, self.vertices.len() + new_vertex_count, LimitKind::Vertices, None)?;
+        let new_edge_count = self.count_new_edges(&other, policy)?;
+        DeserializeLimits::check(self.limits.max_edges, self.edge_count + new_edge_count, LimitKind::Edges, None)?;
+
+        let mut other_attributes = mem::take(&mut other.attributes);
+        // Зеркальные направления отбрасываются, чтобы значения рёбер перешли во владение без копирования
+        let mut edges = Vec::new();
+        let mut vertices = Vec::with_capacity(other.vertices.len());
+        for (id, vertex) in other.vertices {
+            for edge_direction in vertex.edge_directions.into_iter().filter(EdgeDirection::is_own) {
+                edges.push((id.clone(), edge_direction.to_vertex_id, edge_direction.value));
+            }
+            vertices.push(Vertex::new(id, vertex.value));
+        }
+
+        for vertex in vertices {
+            let role = other.roles.get(&vertex.id).copied();
+            if !self.contains_vertex(&vertex.id) {
+                let id = vertex.id.clone();
+                self.add_vertex(vertex)?;
+                self.extend_vertex_attrs(id.clone(), other_attributes.take_vertex(&id));
+                if let Some(role) = role {
+                    self.set_role(id, role)?;
+                }
+            } else if policy == MergePolicy::Overwrite {
+                let id = vertex.id.clone();
+                self.upsert_vertex(vertex)?;
+                self.extend_vertex_attrs(id.clone(), other_attributes.take_vertex(&id));
+                match role {
+                    Some(role) => {
+                        self.set_role(id, role)?;
+                    }
+                    None => {
+                        self.clear_role(id);
+                    }
+                }
+            }
+        }
+        for (from_id, to_id, value) in edges {
+            let value = Arc::try_unwrap(value).ok().flatten();
+            let attributes = other_attributes.take_edge(&from_id, &to_id, other.r#type);
+            if self.multigraph || !self.contains_edge_direction(&from_id, &to_id)? {
+                self.add_edge(from_id.clone(), to_id.clone(), value)?;
+                self.extend_edge_attrs(from_id, to_id, attributes);
+                continue;
+            }
+            match policy {
+                MergePolicy::Error => return Err(GraphError::EdgeAlreadyExist(from_id.to_string(), to_id.to_string())),
+                MergePolicy::KeepExisting => {}
+                MergePolicy::Overwrite => {
+                    self.set_edge_value(from_id.clone(), to_id.clone(), value)?;
+                    self.extend_edge_attrs(from_id, to_id, attributes);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Число рёбер `other`, которые `merge_with` добавит в граф. Совпавшее ребро простого графа при
+    /// `MergePolicy::Error` - ошибка, при остальных политиках оно не добавляется
+    fn count_new_edges(&self, other: &Graph<VT, ET, ID>, policy: MergePolicy) -> Result<usize, GraphError> {
+        if self.multigraph {
+            return Ok(other.edge_count);
+        }
+        let mut merged_edges = HashSet::new();
+        let mut new_edge_count = 0;
+        for (from_id, edge_direction) in other.logical_edges() {
+            let to_id = &edge_direction.to_vertex_id;
+            let merged = merged_edges.contains(&(from_id, to_id))
+                || self.r#type == GraphType::Undirected && merged_edges.contains(&(to_id, from_id))
+                || self.contains_vertex(from_id) && self.contains_edge_direction(from_id, to_id)?;
+            if !merged {
+                merged_edges.insert((from_id, to_id));
+                new_edge_count += 1;
+            } else if policy == MergePolicy::Error {
+                return Err(GraphError::EdgeAlreadyExist(from_id.to_string(), to_id.to_string()));
+            }
+        }
+        Ok(new_edge_count)
+    }
+
+    /// Объединение графов, в которых не должно быть общих вершин (`MergePolicy::Error`)
+    pub fn merge(&mut self, other: Graph<VT, ET, ID>) -> Result<(), GraphError> {
+        self.merge_with(other, MergePolicy::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Граф зависимостей одного файла: вершины - модули, ребро - импорт
+    fn dependencies(modules: &[(u32, &str)], imports: &[(u32, u32)]) -> Result<Graph<String, ()>> {
+        let mut graph = Graph::new(GraphType::Directed);
+        for &(id, name) in modules {
+            graph.add_vertex(Vertex::new(id, Some(name.to_owned())))?;
+        }
+        for &(from_id, to_id) in imports {
+            graph.add_edge(from_id, to_id, None)?;
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn merge_shared_boundary_vertices() -> Result<()> {
+        let mut project = dependencies(&[(1, "main"), (2, "io"), (3, "util")], &[(1, 2), (2, 3)])?;
+        let file = dependencies(&[(3, "util.rs"), (4, "fmt"), (5, "alloc")], &[(3, 4), (4, 5), (5, 3)])?;
+        project.merge_with(file, MergePolicy::KeepExisting)?;
+
+        let reachable: Vec<_> = project.bfs(1)?
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect();
+        if project.vertex_count() == 5
+            && project.edge_count() == 5
+            && reachable == vec![1, 2, 3, 4, 5]
+            && project.vertex_value(3).map(String::as_str) == Some("util")
+            && project.predecessors(3)?.len() == 2 {
+            Ok(())
+        } else {
+            bail!("boundary vertices not merged: {:?}", reachable)
+        }
+    }
+
+    #[test]
+    fn merge_policies() -> Result<()> {
+        let first = Graph::deserialize("1 a\n2 b\n#\n1 2 old".as_bytes())?;
+        let second = Graph::deserialize("1 A\n2 B\n3 c\n#\n2 1 new\n2 3".as_bytes())?;
+
+        let mut error_graph = first.clone();
+        let error = error_graph.merge(second.clone());
+        let mut overwritten = first.clone();
+        overwritten.merge_with(second, MergePolicy::Overwrite)?;
+        let mut wrong_type = Graph::<String, String>::new(GraphType::Directed);
+
+        if matches!(error, Err(GraphError::VertexAlreadyExist(id)) if id == "1" || id == "2")
+            && error_graph == first
+            && overwritten.vertex_value(2).map(String::as_str) == Some("B")
+            && overwritten.edge_count() == 2
+            && overwritten.edges().contains(&(1, 2, Some(&"new".to_owned())))
+            && wrong_type.merge(first) == Err(GraphError::WrongGraphType { expected: "directed", actual: "undirected" }) {
+            Ok(())
+        } else {
+            bail!("wrong merge policies: {:?}", overwritten.edges())
+        }
+    }
+
+    #[test]
+    fn merge_over_limits_keeps_graph() -> Result<()> {
+        let limited = |max_vertices, max_edges| -> Result<Graph<String, ()>> {
+            let limits = DeserializeLimits { max_vertices: Some(max_vertices), max_edges: Some(max_edges), ..DeserializeLimits::default() };
+            let mut graph = Graph::new_limited(GraphType::Directed, limits);
+            for (id, name) in [(1, "main"), (2, "io")] {
+                graph.add_vertex(Vertex::new(id, Some(name.to_owned())))?;
+            }
+            graph.add_edge(1, 2, None)?;
+            Ok(graph)
+        };
+        let file = dependencies(&[(1, "main.rs"), (2, "io.rs"), (3, "fmt"), (4, "alloc")], &[(1, 2), (2, 3), (3, 4)])?;
+
+        let mut too_many_vertices = limited(3, 10)?;
+        let vertices_error = too_many_vertices.merge_with(file.clone(), MergePolicy::Overwrite);
+        let mut too_many_edges = limited(10, 2)?;
+        let edges_error = too_many_edges.merge_with(file.clone(), MergePolicy::Overwrite);
+        // Ребро 1 -> 2 уже есть в графе и не добавляется
+        let mut fitting = limited(4, 3)?;
+        fitting.merge_with(file, MergePolicy::Overwrite)?;
+
+        if vertices_error == Err(GraphError::LimitExceeded { which: LimitKind::Vertices, limit: 3, at_line: None })
+            && too_many_vertices == limited(3, 10)?
+            && edges_error == Err(GraphError::LimitExceeded { which: LimitKind::Edges, limit: 2, at_line: None })
+            && too_many_edges == limited(10, 2)?
+            && fitting.vertex_count() == 4
+            && fitting.edge_count() == 3
+            && fitting.vertex_value(2).map(String::as_str) == Some("io.rs") {
+            Ok(())
+        } else {
+            bail!("merge over limits changed the graph: {:?}, {:?}", vertices_error, edges_error)
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! Характеристики графа: сводная статистика, степени вершин, оценка памяти и статистика кеша соседей
+
+pub use crate::distribution::DegreeKind;
+pub use crate::hot::HotStats;
+pub use crate::memory::MemoryEstimate;
+pub use crate::stats::GraphStats;
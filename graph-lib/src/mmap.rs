@@ -0,0 +1,174 @@
+use std::{
+    fs::File,
+    io,
+    path::Path,
+};
+use memmap2::Mmap;
+use crate::formats::tgf::text_records;
+use super::*;
+
+/// Быстрая загрузка TGF-файла: файл отображается в память, UTF-8 проверяется один раз для всего файла,
+/// а строки разбираются из отображения без чтения в промежуточный буфер
+impl Graph<Box<str>, Box<str>> {
+    /// Загружает TGF-файл, как `deserialize`: ошибка разбора содержит номер строки (`GraphError::ParseLine`).
+    ///
+    /// Файл читается напрямую из отображения, поэтому его нельзя изменять или обрезать, пока идёт загрузка:
+    /// обрезка отображённого файла другим процессом приводит к аварийному завершению (SIGBUS).
+    /// Для файлов, которые могут переписываться во время чтения, используйте `deserialize`
+    pub fn deserialize_mmap<P: AsRef<Path>>(path: P) -> Result<Graph<Box<str>, Box<str>>, GraphError> {
+        let file = File::open(path)?;
+        // SAFETY: отображение только для чтения и живёт до конца функции. Неизменность файла на время загрузки -
+        // условие, описанное в документации функции
+        let mapped_file = unsafe { Mmap::map(&file)? };
+        let text = std::str::from_utf8(&mapped_file)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let options = DeserializeOptions::default();
+        Graph::deserialize_records(text_records(text, &options), &options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::BufReader,
+        path::PathBuf,
+        time::{Duration, Instant},
+    };
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const TGF_GRAPH: &str = "1 January\n2 March\n3 April\n4 May\n#\n1 2\n3 2 spring\n4 3 spring\n";
+
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, content: &[u8]) -> Result<TempFile> {
+            let path = std::env::temp_dir().join(format!("graph-lib-{}-{}", std::process::id(), name));
+            std::fs::write(&path, content)?;
+            Ok(TempFile(path))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn canonical_lines<VT: Display, ET: Display>(graph: &Graph<VT, ET>) -> Vec<String> {
+        let mut lines = Vec::new();
+        for vertex in graph.vertices.values() {
+            match &vertex.value {
+                Some(value) => lines.push(format!("{} {}", vertex.id, value)),
+                None => lines.push(format!("{}", vertex.id)),
+            }
+            for edge_direction in &vertex.edge_directions {
+                match edge_direction.value.as_ref() {
+                    Some(value) => lines.push(format!("{} -> {} {}", vertex.id, edge_direction.to_vertex_id, value)),
+                    None => lines.push(format!("{} -> {}", vertex.id, edge_direction.to_vertex_id)),
+                }
+            }
+        }
+        lines.sort();
+        lines
+    }
+
+    fn assert_same_as_standard(name: &str, content: &str) -> Result<()> {
+        let file = TempFile::new(name, content.as_bytes())?;
+        let mmap_graph = Graph::deserialize_mmap(&file.0)?;
+        let standard_graph = Graph::deserialize(BufReader::new(content.as_bytes()))?;
+        if canonical_lines(&mmap_graph) == canonical_lines(&standard_graph) {
+            Ok(())
+        } else {
+            bail!("mmap deserialization differs from standard one for {}", name)
+        }
+    }
+
+    #[test]
+    fn same_as_standard_deserialize() -> Result<()> {
+        assert_same_as_standard("lf.tgf", TGF_GRAPH)?;
+        assert_same_as_standard("crlf.tgf", &TGF_GRAPH.replace('\n', "\r\n"))?;
+        assert_same_as_standard("empty_values.tgf", "1\n2 \n3 x\n#\n1 2\n2 3 \n")?;
+        assert_same_as_standard("escaped_values.tgf", "1 \\u{20}a\\nb\n2 \\e\n#\n1 2 back\\\\slash\n")?;
+        assert_same_as_standard("empty.tgf", "")
+    }
+
+    #[test]
+    fn patched_file() -> Result<()> {
+        let file = TempFile::new("patched.tgf", b"")?;
+        let mut graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let baseline = graph.track_changes();
+        graph.serialize_update(&file.0, &baseline)?;
+        graph.delete_vertex(2)?;
+        graph.add_vertex(Vertex::new(5, Some("June".to_owned())))?;
+        graph.add_edge(5, 4, Some("spring".to_owned()))?;
+        graph.serialize_update(&file.0, &baseline)?;
+
+        let content = std::fs::read_to_string(&file.0)?;
+        assert_same_as_standard("patched_copy.tgf", &content)?;
+        if canonical_lines(&Graph::deserialize_mmap(&file.0)?) == canonical_lines(&graph) {
+            Ok(())
+        } else {
+            bail!("mmap deserialization of patched file differs from graph")
+        }
+    }
+
+    #[test]
+    fn wrong_vertex_id() -> Result<()> {
+        let file = TempFile::new("wrong_id.tgf", b"1 a\nx b\n#\n")?;
+        let missing_file = TempFile::new("missing_vertex.tgf", b"1 a\n#\n1 3\n")?;
+        let missing_result = Graph::deserialize_mmap(&missing_file.0);
+        match Graph::deserialize_mmap(&file.0) {
+            Err(GraphError::ParseLine { line_no: 2, source }) if *source == GraphError::WrongVertexIdType("x b".to_owned())
+                && missing_result.as_ref().err() == Graph::deserialize("1 a\n#\n1 3\n".as_bytes()).as_ref().err() => Ok(()),
+            _ => bail!("wrong vertex id not reported with its line"),
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn not_slower_than_standard_deserialize() -> Result<()> {
+        const VERTEX_COUNT: u32 = 2_000_000;
+        let mut content = String::new();
+        for id in 0..VERTEX_COUNT {
+            content.push_str(&format!("{} month {}\n", id, id % 12));
+        }
+        content.push_str("#\n");
+        for id in 0..VERTEX_COUNT * 2 {
+            // Соседи на расстоянии 1, затем 2: рёбра не повторяются
+            let from_id = id % VERTEX_COUNT;
+            let to_id = (from_id + 1 + id / VERTEX_COUNT) % VERTEX_COUNT;
+            content.push_str(&format!("{} {} holiday {}\n", from_id, to_id, id % 31));
+            if content.len() > 100 * 1024 * 1024 {
+                break;
+            }
+        }
+        let file = TempFile::new("bench.tgf", content.as_bytes())?;
+
+        // Время построения графа заметно зависит от того, сколько памяти освободил предыдущий запуск,
+        // поэтому способы запускаются поочерёдно дважды и сравниваются лучшие результаты
+        let mut standard_duration = Duration::MAX;
+        let mut mmap_duration = Duration::MAX;
+        for _ in 0..2 {
+            let start = Instant::now();
+            let mmap_graph = Graph::deserialize_mmap(&file.0)?;
+            mmap_duration = mmap_duration.min(start.elapsed());
+            drop(mmap_graph);
+
+            let start = Instant::now();
+            let standard_graph = Graph::deserialize(BufReader::new(File::open(&file.0)?))?;
+            standard_duration = standard_duration.min(start.elapsed());
+            drop(standard_graph);
+        }
+
+        // Разбор строк общий со стандартной загрузкой, поэтому отображение не должно её замедлять
+        if mmap_duration.as_secs_f64() < standard_duration.as_secs_f64() * 1.1 {
+            Ok(())
+        } else {
+            bail!("mmap deserialization took {:?}, standard took {:?}", mmap_duration, standard_duration)
+        }
+    }
+}
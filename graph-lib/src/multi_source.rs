@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use super::*;
+
+/// Ближайшая начальная вершина и расстояние от неё в рёбрах, см. `Graph::bfs_multi_source`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearestSource<ID = DefaultGraphIdType> {
+    pub source: ID,
+    pub distance: usize,
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Обход в ширину сразу из всех `starts`: для каждой достижимой вершины - ближайшая начальная вершина
+    /// и расстояние до неё (разбиение Вороного по числу рёбер). В ориентированном графе обход идёт по исходящим рёбрам.
+    /// При равных расстояниях вершина достаётся начальной вершине, стоящей в `starts` раньше. Повторы в `starts` игнорируются.
+    /// Обход идёт по уровням: каждый фронт строится целиком из предыдущего
+    pub fn bfs_multi_source(&self, starts: &[ID]) -> Result<HashMap<ID, NearestSource<ID>>, GraphError> {
+        if let Some(missing_id) = starts.iter().find(|id| !self.contains_vertex(id)) {
+            return Err(GraphError::VertexNotFound(missing_id.to_string()));
+        }
+
+        let mut nearest = HashMap::new();
+        let mut frontier = Vec::with_capacity(starts.len());
+        for start_id in starts {
+            if !nearest.contains_key(start_id) {
+                nearest.insert(start_id.clone(), NearestSource { source: start_id.clone(), distance: 0 });
+                frontier.push(start_id.clone());
+            }
+        }
+
+        let mut distance = 0;
+        while !frontier.is_empty() {
+            distance += 1;
+            let mut next_frontier = Vec::new();
+            // Фронт упорядочен по порядку начальных вершин, поэтому первой вершину занимает более ранняя начальная
+            for id in &frontier {
+                let source = nearest[id].source.clone();
+                for edge_direction in &self.vertices[id].edge_directions {
+                    if !nearest.contains_key(&edge_direction.to_vertex_id) {
+                        nearest.insert(edge_direction.to_vertex_id.clone(), NearestSource { source: source.clone(), distance });
+                        next_frontier.push(edge_direction.to_vertex_id.clone());
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+
+        Ok(nearest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn path_graph(r#type: GraphType, vertex_count: u32) -> Result<Graph<(), ()>> {
+        let mut graph = Graph::new(r#type);
+        for id in 0..vertex_count {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 1..vertex_count {
+            graph.add_edge(id - 1, id, None)?;
+        }
+        Ok(graph)
+    }
+
+    fn partition(nearest: &HashMap<u32, NearestSource>, vertex_count: u32) -> Vec<Option<(u32, usize)>> {
+        (0..vertex_count)
+            .map(|id| nearest.get(&id).map(|nearest| (nearest.source, nearest.distance)))
+            .collect()
+    }
+
+    #[test]
+    fn voronoi_partition() -> Result<()> {
+        let graph = path_graph(GraphType::Undirected, 7)?;
+        // Вершина 4 на равном расстоянии от 2 и 6 достаётся указанной раньше
+        let nearest = graph.bfs_multi_source(&[6, 2, 6])?;
+        let reversed = graph.bfs_multi_source(&[2, 6])?;
+        let directed = path_graph(GraphType::Directed, 5)?.bfs_multi_source(&[3, 1])?;
+        if partition(&nearest, 7) == vec![
+            Some((2, 2)), Some((2, 1)), Some((2, 0)), Some((2, 1)), Some((6, 2)), Some((6, 1)), Some((6, 0)),
+        ] && reversed[&4].source == 2
+            && partition(&directed, 5) == vec![None, Some((1, 0)), Some((1, 1)), Some((3, 0)), Some((3, 1))] {
+            Ok(())
+        } else {
+            bail!("wrong partition {:?}", partition(&nearest, 7))
+        }
+    }
+
+    #[test]
+    fn unknown_start() -> Result<()> {
+        let graph = path_graph(GraphType::Undirected, 3)?;
+        match graph.bfs_multi_source(&[0, 9]) {
+            Err(GraphError::VertexNotFound(id)) if id == "9" && graph.bfs_multi_source(&[])?.is_empty() => Ok(()),
+            result => bail!("unknown start not reported: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::*;
+
+/// Сосед вершины и значение ребра, ведущего к нему. В неориентированном графе значение общее
+/// для обоих концов ребра, поэтому совпадает при обходе с любой стороны
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Neighbour<'a, ET, ID = DefaultGraphIdType> {
+    pub id: ID,
+    pub edge_value: Option<&'a ET>,
+}
+
+/// Список из идентификатора вершины, значения вершины и соседей со значениями рёбер
+pub type BfsEdgesResult<'a, VT, ET, ID = DefaultGraphIdType> = Vec<(ID, Option<&'a VT>, Vec<Neighbour<'a, ET, ID>>)>;
+
+impl<VT, ET, ID: GraphId> Vertex<VT, ET, ID> {
+    fn edge_neighbours(&self) -> Vec<Neighbour<'_, ET, ID>> {
+        self.edge_directions
+            .iter()
+            .map(|edge_direction| Neighbour {
+                id: edge_direction.to_vertex_id.clone(),
+                edge_value: edge_direction.value.as_ref().as_ref(),
+            })
+            .collect()
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Соседи вершины в том же порядке, что и у `neighbours`, со значениями рёбер.
+    /// Параллельные рёбра мультиграфа дают соседа несколько раз, по разу на каждое значение
+    pub fn neighbours_with_edges(&self, vertex_id: ID) -> Result<Vec<Neighbour<'_, ET, ID>>, GraphError> {
+        let vertex = self.vertices.get(&vertex_id)
+            .ok_or_else(|| GraphError::VertexNotFound(vertex_id.to_string()))?;
+        Ok(vertex.edge_neighbours())
+    }
+
+    /// Обход в ширину, как у `bfs`, с соседями вместе со значениями рёбер
+    pub fn bfs_with_edges(&self, start_id: ID) -> Result<BfsEdgesResult<'_, VT, ET, ID>, GraphError> {
+        self.collect_traversal_with_edges(start_id, TraversalOrder::BreadthFirst)
+    }
+
+    /// Обход в глубину, как у `dfs`, с соседями вместе со значениями рёбер
+    pub fn dfs_with_edges(&self, start_id: ID) -> Result<BfsEdgesResult<'_, VT, ET, ID>, GraphError> {
+        self.collect_traversal_with_edges(start_id, TraversalOrder::DepthFirst)
+    }
+
+    fn collect_traversal_with_edges(&self, start_id: ID, order: TraversalOrder) -> Result<BfsEdgesResult<'_, VT, ET, ID>, GraphError> {
+        let mut result = Vec::new();
+        self.traverse(start_id, order, |vertex| {
+            result.push((vertex.id.clone(), vertex.value.as_ref(), vertex.edge_neighbours()));
+            ControlFlow::Continue(())
+        })?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const WEIGHTED_TGF: &str = "1\n2\n3\n#\n1 2 5\n3 1 7\n3 3 loop";
+
+    #[test]
+    fn undirected_edge_value_seen_from_both_ends() -> Result<()> {
+        let graph = Graph::deserialize(WEIGHTED_TGF.as_bytes())?;
+        let from_first: Vec<_> = graph.neighbours_with_edges(1)?
+            .into_iter()
+            .map(|neighbour| (neighbour.id, neighbour.edge_value.map(String::as_str)))
+            .collect();
+        let from_third: Vec<_> = graph.neighbours_with_edges(3)?
+            .into_iter()
+            .map(|neighbour| (neighbour.id, neighbour.edge_value.map(String::as_str)))
+            .collect();
+        if from_first == vec![(2, Some("5")), (3, Some("7"))]
+            && from_third == vec![(1, Some("7")), (3, Some("loop"))]
+            && matches!(graph.neighbours_with_edges(4), Err(GraphError::VertexNotFound(_))) {
+            Ok(())
+        } else {
+            bail!("wrong neighbours {:?}, {:?}", from_first, from_third)
+        }
+    }
+
+    #[test]
+    fn traversal_with_edges_matches_traversal() -> Result<()> {
+        let mut graph = Graph::<(), u32>::new_multigraph(GraphType::Directed);
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, Some(4))?;
+        graph.add_edge(1, 2, Some(6))?;
+        graph.add_edge(2, 3, None)?;
+        let bfs = graph.bfs_with_edges(1)?;
+        let dfs = graph.dfs_with_edges(1)?;
+        let ids_only = |result: &BfsEdgesResult<'_, (), u32>| -> Vec<(u32, Vec<u32>)> {
+            result.iter()
+                .map(|(id, _, neighbours)| (*id, neighbours.iter().map(|neighbour| neighbour.id).collect()))
+                .collect()
+        };
+        let plain_ids = |result: BfsResult<'_, ()>| -> Vec<(u32, Vec<u32>)> {
+            result.into_iter().map(|(id, _, neighbour_ids)| (id, neighbour_ids)).collect()
+        };
+        if ids_only(&bfs) == plain_ids(graph.bfs(1)?)
+            && ids_only(&dfs) == plain_ids(graph.dfs(1)?)
+            && bfs[0].2 == vec![Neighbour { id: 2, edge_value: Some(&4) }, Neighbour { id: 2, edge_value: Some(&6) }]
+            && bfs[1].2 == vec![Neighbour { id: 3, edge_value: None }] {
+            Ok(())
+        } else {
+            bail!("traversal with edges differs: {:?}", bfs)
+        }
+    }
+}
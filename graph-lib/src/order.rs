@@ -0,0 +1,426 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{self, Write as _},
+    io,
+};
+use crate::distribution::DegreeKind;
+use super::*;
+
+/// Канонический порядок вершин при выводе графа. Рёбра выводятся сгруппированными по начальной вершине
+/// в том же порядке, внутри группы - в порядке конечных вершин
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VertexOrder {
+    ById,
+    /// По значению вершины, вершины без значения - первыми, при равных значениях - по идентификатору
+    ByValueThenId,
+    /// По убыванию степени (без учёта направления), при равной степени - по идентификатору
+    ByDegreeDescending,
+}
+
+/// Вершины и логические рёбра графа в порядке вывода
+type OrderedItems<'a, VT, ET, ID> = (Vec<&'a Vertex<VT, ET, ID>>, Vec<(&'a ID, &'a EdgeDirection<ET, ID>)>);
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Порядок вывода графа через `Display`
+    pub fn set_display_order(&mut self, order: VertexOrder) {
+        self.display_order = order;
+    }
+}
+
+impl<VT: Ord, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    fn ordered_vertices(&self, order: VertexOrder) -> Vec<&Vertex<VT, ET, ID>> {
+        let mut vertices: Vec<_> = self.vertices.values().collect();
+        match order {
+            VertexOrder::ById => vertices.sort_unstable_by(|first, second| first.id.cmp(&second.id)),
+            VertexOrder::ByValueThenId => vertices.sort_unstable_by(|first, second| first.value.cmp(&second.value)
+                .then_with(|| first.id.cmp(&second.id))),
+            VertexOrder::ByDegreeDescending => {
+                let degrees = self.degrees(DegreeKind::Undirected);
+                vertices.sort_unstable_by(|first, second| degrees[&second.id].cmp(&degrees[&first.id])
+                    .then_with(|| first.id.cmp(&second.id)));
+            }
+        }
+        vertices
+    }
+
+    /// Вершины и логические рёбра графа в порядке `order`
+    fn ordered_items(&self, order: VertexOrder) -> OrderedItems<'_, VT, ET, ID> {
+        let vertices = self.ordered_vertices(order);
+        let positions: HashMap<_, _> = vertices.iter()
+            .enumerate()
+            .map(|(position, vertex)| (&vertex.id, position))
+            .collect();
+        // Позиции концов вычисляются один раз, а порядковый номер ребра сохраняет порядок параллельных рёбер
+        let mut keyed_edges: Vec<_> = self.logical_edges()
+            .enumerate()
+            .map(|(index, (from_id, edge_direction))| ((positions[from_id], positions[&edge_direction.to_vertex_id], index), (from_id, edge_direction)))
+            .collect();
+        keyed_edges.sort_unstable_by_key(|(key, _)| *key);
+        (vertices, keyed_edges.into_iter().map(|(_, edge)| edge).collect())
+    }
+
+    /// Пишет граф построчно прямо в `writer`: рёбра выводятся по начальным вершинам, и сортируются только
+    /// позиции концов рёбер одной вершины. Буферы переиспользуются между строками, поэтому память выделяется
+    /// на строку, только если значение нужно экранировать
+    fn write_tgf<W: fmt::Write>(&self, order: VertexOrder, writer: &mut W) -> fmt::Result
+    where
+        VT: Display,
+        ET: Display,
+    {
+        let vertices = self.ordered_vertices(order);
+        let mut value_buffer = String::new();
+        for vertex in &vertices {
+            match &vertex.value {
+                Some(vertex_value) => writeln!(writer, "{} {}", vertex.id, escaped(vertex_value, &mut value_buffer)?)?,
+                None => writeln!(writer, "{}", vertex.id)?,
+            }
+        }
+
+        writeln!(writer, "{}", VERTEX_EDGE_DELEMITER)?;
+
+        let positions: HashMap<_, _> = vertices.iter()
+            .enumerate()
+            .map(|(position, vertex)| (&vertex.id, position))
+            .collect();
+        // Позиция конечной вершины и номер ребра у начальной вершины: сортировка без выделения памяти
+        // сохраняет порядок параллельных рёбер
+        let mut out_edges = Vec::new();
+        for vertex in &vertices {
+            out_edges.clear();
+            out_edges.extend(vertex.own_edge_directions()
+                .enumerate()
+                .map(|(index, edge_direction)| (positions[&edge_direction.to_vertex_id], index, edge_direction)));
+            out_edges.sort_unstable_by_key(|&(position, index, _)| (position, index));
+            for &(_, _, edge_direction) in &out_edges {
+                let to_id = &edge_direction.to_vertex_id;
+                match edge_direction.value.as_ref() {
+                    Some(edge_value) => writeln!(writer, "{} {} {}", vertex.id, to_id, escaped(edge_value, &mut value_buffer)?)?,
+                    None => writeln!(writer, "{} {}", vertex.id, to_id)?,
+                }
+            }
+        }
+
+        if !self.roles.is_empty() {
+            writeln!(writer, "{}", ROLES_MARKER)?;
+            for vertex in vertices {
+                if let Some(role) = self.roles.get(&vertex.id) {
+                    writeln!(writer, "{} {}", vertex.id, role)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Граф в формате DOT (Graphviz) с вершинами и рёбрами в порядке `order`. Значения выводятся как метки,
+    /// атрибуты (см. `Graph::set_vertex_attr`) - как атрибуты DOT в порядке имён. Атрибут `label` заменяет значение
+    pub fn to_dot_ordered(&self, order: VertexOrder) -> String
+    where
+        VT: Display,
+        ET: Display,
+    {
+        let (graph_keyword, edge_operator) = match self.r#type {
+            GraphType::Directed => ("digraph", "->"),
+            GraphType::Undirected => ("graph", "--"),
+        };
+        let (vertices, edges) = self.ordered_items(order);
+
+        let mut dot = format!("{} {{\n", graph_keyword);
+        for vertex in vertices {
+            let attributes = dot_attributes(vertex.value.as_ref(), self.vertex_attrs(vertex.id.clone()));
+            dot.push_str(&format!("    \"{}\"{};\n", vertex.id, attributes));
+        }
+        for (from_id, edge_direction) in edges {
+            let to_id = &edge_direction.to_vertex_id;
+            let attributes = dot_attributes(edge_direction.value.as_ref().as_ref(), self.edge_attrs(from_id.clone(), to_id.clone()));
+            dot.push_str(&format!("    \"{}\" {} \"{}\"{};\n", from_id, edge_operator, to_id, attributes));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Граф в формате DOT с вершинами в порядке идентификаторов
+    pub fn to_dot(&self) -> String
+    where
+        VT: Display,
+        ET: Display,
+    {
+        self.to_dot_ordered(VertexOrder::ById)
+    }
+
+    /// Граф в формате JSON: `{"directed": ..., "vertices": [{"id", "value"}], "edges": [{"from", "to", "value"}]}`.
+    /// Вершины и рёбра идут в порядке идентификаторов, отсутствующее значение записывается как `null`.
+    /// У вершин и рёбер с атрибутами добавляется объект `"attributes"` с ключами в порядке имён
+    pub fn to_json(&self) -> String
+    where
+        VT: Display,
+        ET: Display,
+    {
+        let (vertices, edges) = self.ordered_items(VertexOrder::ById);
+        let vertices: Vec<_> = vertices.iter()
+            .map(|vertex| format!(
+                "{{\"id\":{},\"value\":{}{}}}",
+                json_string(&vertex.id),
+                json_value(&vertex.value),
+                json_attributes(self.vertex_attrs(vertex.id.clone())),
+            ))
+            .collect();
+        let edges: Vec<_> = edges.iter()
+            .map(|(from_id, edge_direction)| format!(
+                "{{\"from\":{},\"to\":{},\"value\":{}{}}}",
+                json_string(from_id),
+                json_string(&edge_direction.to_vertex_id),
+                json_value(edge_direction.value.as_ref()),
+                json_attributes(self.edge_attrs((*from_id).clone(), edge_direction.to_vertex_id.clone())),
+            ))
+            .collect();
+        format!(
+            "{{\"directed\":{},\"vertices\":[{}],\"edges\":[{}]}}",
+            matches!(self.r#type, GraphType::Directed),
+            vertices.join(","),
+            edges.join(","),
+        )
+    }
+}
+
+impl<ID: GraphId + FromStr + Ord> Graph<String, String, ID> {
+    /// Сериализация в TGF с вершинами и рёбрами в порядке `order`
+    pub fn serialize_ordered<W: Write>(&self, buf_writer: &mut BufWriter<W>, order: VertexOrder) -> Result<(), GraphError> {
+        let mut writer = IoWriter { writer: buf_writer, error: None };
+        self.write_tgf(order, &mut writer)
+            .map_err(|error| writer.error.take().unwrap_or_else(|| io::Error::other(error)))?;
+        Ok(())
+    }
+}
+
+/// `fmt::Write` поверх `io::Write`. Ошибка ввода-вывода сохраняется, т.к. `fmt::Error` её не передаёт
+struct IoWriter<'a, W: Write> {
+    writer: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.writer.write_all(text.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// Экранированное значение, отформатированное в переиспользуемый `buffer`
+fn escaped<'a, T: Display>(value: &T, buffer: &'a mut String) -> Result<Cow<'a, str>, fmt::Error> {
+    buffer.clear();
+    write!(buffer, "{}", value)?;
+    Ok(escape_value(buffer))
+}
+
+/// Граф в формате TGF в порядке, заданном `Graph::set_display_order`
+impl<VT: Display + Ord, ET: Display, ID: GraphId + Ord> Display for Graph<VT, ET, ID> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tgf(self.display_order, formatter)
+    }
+}
+
+/// Строка JSON: идентификаторы записываются строками, т.к. их тип может быть не числовым
+fn json_string<T: Display>(value: &T) -> String {
+    let mut json = String::from("\"");
+    for char in value.to_string().chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            char if char.is_control() => json.push_str(&format!("\\u{:04x}", u32::from(char))),
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_value<T: Display>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| "null".to_owned(), json_string)
+}
+
+fn dot_label<T: Display>(value: &T) -> String {
+    format!("\"{}\"", value.to_string().replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Атрибуты в порядке имён
+fn sorted_attributes(attributes: &AttributeMap) -> Vec<(&String, &String)> {
+    let mut attributes: Vec<_> = attributes.iter().collect();
+    attributes.sort_unstable();
+    attributes
+}
+
+/// Список атрибутов DOT ` [label=..., key=...]` или пустая строка
+fn dot_attributes<T: Display>(value: Option<&T>, attributes: &AttributeMap) -> String {
+    let label = value
+        .filter(|_| !attributes.contains_key("label"))
+        .map(|value| format!("label={}", dot_label(value)));
+    let pairs: Vec<_> = label.into_iter()
+        .chain(sorted_attributes(attributes).into_iter().map(|(key, value)| format!("{}={}", dot_label(key), dot_label(value))))
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", pairs.join(", "))
+    }
+}
+
+/// Поле `,"attributes":{...}` или пустая строка
+fn json_attributes(attributes: &AttributeMap) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<_> = sorted_attributes(attributes).into_iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+        .collect();
+    format!(",\"attributes\":{{{}}}", pairs.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const ORDERS: [VertexOrder; 3] = [VertexOrder::ById, VertexOrder::ByValueThenId, VertexOrder::ByDegreeDescending];
+
+    fn serialize_ordered(graph: &Graph<String, String>, order: VertexOrder) -> Result<String> {
+        let mut buf_writer = BufWriter::new(Vec::new());
+        graph.serialize_ordered(&mut buf_writer, order)?;
+        Ok(String::from_utf8(buf_writer.into_inner()?)?)
+    }
+
+    fn canonical_lines(tgf: &str) -> Vec<&str> {
+        let mut lines: Vec<_> = tgf.lines().collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    #[test]
+    fn value_order_differs_from_id_order() -> Result<()> {
+        let graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let by_id = serialize_ordered(&graph, VertexOrder::ById)?;
+        let by_value = serialize_ordered(&graph, VertexOrder::ByValueThenId)?;
+        if by_id != by_value
+            && canonical_lines(&by_id) == canonical_lines(&by_value)
+            && by_id.starts_with("1 January\n2 March\n")
+            && by_value.starts_with("3 April\n5 December\n")
+            && by_value.ends_with("#\n3 2\n5 3 April Fools Day\n5 1 Happy New Year!\n1 2\n6 3\n6 1\n4 3\n7 5\n7 1\n7 6\n") {
+            Ok(())
+        } else {
+            bail!("wrong value ordering:\n{}", by_value)
+        }
+    }
+
+    #[test]
+    fn degree_order() -> Result<()> {
+        let graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let by_degree = serialize_ordered(&graph, VertexOrder::ByDegreeDescending)?;
+        if by_degree.starts_with("1 January\n3 April\n") {
+            Ok(())
+        } else {
+            bail!("highest-degree month not first:\n{}", by_degree)
+        }
+    }
+
+    #[test]
+    fn reparse_every_order() -> Result<()> {
+        let graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let by_id = serialize_ordered(&graph, VertexOrder::ById)?;
+        for order in ORDERS {
+            let reparsed_graph = Graph::deserialize(BufReader::new(serialize_ordered(&graph, order)?.as_bytes()))?;
+            if serialize_ordered(&reparsed_graph, VertexOrder::ById)? != by_id {
+                bail!("graph serialized {:?} changed after reparsing", order)
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn display_and_dot_follow_order() -> Result<()> {
+        let mut graph = Graph::deserialize(BufReader::new("1 b\n2 a \"quoted\"\n3 c\n#\n1 2\n3 2 back\n".as_bytes()))?;
+        let by_id = graph.to_string();
+        graph.set_display_order(VertexOrder::ByValueThenId);
+        let by_value = graph.to_string();
+        let dot = graph.to_dot_ordered(VertexOrder::ByValueThenId);
+        if by_id == "1 b\n2 a \"quoted\"\n3 c\n#\n1 2\n3 2 back\n"
+            && by_value == "2 a \"quoted\"\n1 b\n3 c\n#\n1 2\n3 2 back\n"
+            && dot == "graph {\n    \"2\" [label=\"a \\\"quoted\\\"\"];\n    \"1\" [label=\"b\"];\n    \"3\" [label=\"c\"];\n    \"1\" -- \"2\";\n    \"3\" -- \"2\" [label=\"back\"];\n}\n" {
+            Ok(())
+        } else {
+            bail!("display order not applied:\n{}\n{}", by_value, dot)
+        }
+    }
+
+    #[test]
+    fn streamed_output_matches_display() -> Result<()> {
+        let mut graph = Graph::<String, String>::new_multigraph(GraphType::Directed);
+        graph.add_vertex(Vertex::new(1, Some("line\nbreak".to_owned())))?;
+        graph.add_vertex(Vertex::new(2, Some(" padded ".to_owned())))?;
+        graph.add_vertex(Vertex::new(3, None))?;
+        graph.add_edge(1, 2, Some("second".to_owned()))?;
+        graph.add_edge(1, 2, Some("first\tescaped".to_owned()))?;
+        graph.add_edge(2, 1, None)?;
+        graph.add_edge(3, 3, Some(String::new()))?;
+        for order in ORDERS {
+            graph.set_display_order(order);
+            let tgf = serialize_ordered(&graph, order)?;
+            if tgf != graph.to_string() {
+                bail!("streamed {:?} output differs:\n{}", order, tgf)
+            }
+        }
+        let by_id = serialize_ordered(&graph, VertexOrder::ById)?;
+        if by_id.ends_with("#\n1 2 second\n1 2 first\\tescaped\n2 1\n3 3 \\e\n")
+            && by_id.starts_with("1 line\\nbreak\n2 \\u{20}padded\\u{20}\n") {
+            Ok(())
+        } else {
+            bail!("parallel edges reordered:\n{}", by_id)
+        }
+    }
+
+    #[test]
+    fn json_escapes_values() -> Result<()> {
+        let graph = Graph::deserialize(BufReader::new("2 a \"quoted\"\n1\n#\n2 1 back\\slash\n".as_bytes()))?;
+        let json = graph.to_json();
+        if json == "{\"directed\":false,\"vertices\":[{\"id\":\"1\",\"value\":null},{\"id\":\"2\",\"value\":\"a \\\"quoted\\\"\"}],\
+\"edges\":[{\"from\":\"2\",\"to\":\"1\",\"value\":\"back\\\\slash\"}]}" {
+            Ok(())
+        } else {
+            bail!("wrong json: {}", json)
+        }
+    }
+
+    #[test]
+    fn attributes_in_dot_and_json() -> Result<()> {
+        let mut graph = Graph::deserialize(BufReader::new("1 start\n2\n#\n1 2 next\n".as_bytes()))?;
+        graph.set_vertex_attr(1, "shape", "box".to_owned())?;
+        graph.set_vertex_attr(2, "label", "end".to_owned())?;
+        graph.set_edge_attr(2, 1, "color", "red".to_owned())?;
+        let dot = graph.to_dot();
+        let json = graph.to_json();
+        let expected_dot = [
+            "graph {",
+            "    \"1\" [label=\"start\", \"shape\"=\"box\"];",
+            "    \"2\" [\"label\"=\"end\"];",
+            "    \"1\" -- \"2\" [label=\"next\", \"color\"=\"red\"];",
+            "}\n",
+        ].join("\n");
+        if dot == expected_dot
+            && json == "{\"directed\":false,\"vertices\":[{\"id\":\"1\",\"value\":\"start\",\"attributes\":{\"shape\":\"box\"}},\
+{\"id\":\"2\",\"value\":null,\"attributes\":{\"label\":\"end\"}}],\
+\"edges\":[{\"from\":\"1\",\"to\":\"2\",\"value\":\"next\",\"attributes\":{\"color\":\"red\"}}]}" {
+            Ok(())
+        } else {
+            bail!("wrong attributes output:\n{}\n{}", dot, json)
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+use super::*;
+
+/// Начало секции изменений в конце TGF-файла
+pub(crate) const PATCH_MARKER: &str = "#patch";
+/// Строка с отпечатком графа, получающегося после чтения файла до этой строки
+pub(crate) const FINGERPRINT_MARKER: &str = "#fingerprint ";
+
+const ADD_VERTEX: &str = "+v ";
+const DELETE_VERTEX: &str = "-v ";
+const ADD_EDGE: &str = "+e ";
+const DELETE_EDGE: &str = "-e ";
+pub(crate) const ADD_ROLE: &str = "+r ";
+
+/// Сколько байт с конца файла читается в поисках записанного отпечатка
+const FINGERPRINT_TAIL_LEN: u64 = 64;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Отпечаток содержимого графа, не зависящий от порядка вершин и рёбер
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphFingerprint(u64);
+
+impl Display for GraphFingerprint {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{:016x}", self.0)
+    }
+}
+
+/// Как `serialize_update` записал граф
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateStrategy {
+    /// В конец файла дописана секция изменений
+    Appended,
+    /// Файл перезаписан целиком
+    Rewritten,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateOutcome {
+    pub strategy: UpdateStrategy,
+    pub bytes_written: u64,
+    /// Отпечаток, записанный в файл, - базовый отпечаток для следующего обновления
+    pub fingerprint: GraphFingerprint,
+}
+
+impl<VT: Display, ET: Display, ID: GraphId> Graph<VT, ET, ID> {
+    pub fn fingerprint(&self) -> GraphFingerprint {
+        let mut hash = fnv_hash(&format!("{} {}", self.vertex_count(), self.edge_count()));
+        for vertex in self.vertices.values() {
+            hash = hash.wrapping_add(fnv_hash(&format!("v\u{1f}{}\u{1f}{}", vertex.id, OptionalValue(&vertex.value))));
+        }
+        for (from_id, edge_direction) in self.logical_edges() {
+            let (mut from_id, mut to_id) = (from_id.to_string(), edge_direction.to_vertex_id.to_string());
+            if let GraphType::Undirected = self.r#type {
+                if to_id < from_id {
+                    std::mem::swap(&mut from_id, &mut to_id);
+                }
+            }
+            let value = OptionalValue(edge_direction.value.as_ref());
+            hash = hash.wrapping_add(fnv_hash(&format!("e\u{1f}{}\u{1f}{}\u{1f}{}", from_id, to_id, value)));
+        }
+        for (id, role) in &self.roles {
+            hash = hash.wrapping_add(fnv_hash(&format!("r\u{1f}{}\u{1f}{}", id, role)));
+        }
+        GraphFingerprint(hash)
+    }
+
+    /// Начинает отслеживать изменённые вершины для `serialize_update` и возвращает базовый отпечаток графа
+    pub fn track_changes(&mut self) -> GraphFingerprint {
+        self.changed_vertices = Some(HashSet::new());
+        self.fingerprint()
+    }
+}
+
+impl<ID: GraphId + FromStr + Ord> Graph<String, String, ID> {
+    /// Сохраняет граф в файл, ранее записанный этой функцией. Если отпечаток в конце файла совпадает с
+    /// `baseline_fingerprint`, а изменения отслеживаются с момента его получения (`track_changes`),
+    /// в конец файла дописывается секция изменённых вершин. Иначе файл перезаписывается целиком
+    pub fn serialize_update<P: AsRef<Path>>(
+        &self,
+        path: P,
+        baseline_fingerprint: &GraphFingerprint,
+    ) -> Result<UpdateOutcome, GraphError> {
+        let path = path.as_ref();
+        let fingerprint = self.fingerprint();
+        let recorded_fingerprint = read_recorded_fingerprint(path)?;
+
+        match &self.changed_vertices {
+            Some(changed_vertices) if recorded_fingerprint.as_ref() == Some(baseline_fingerprint) => {
+                let file = OpenOptions::new().append(true).open(path)?;
+                let mut writer = CountingWriter::new(BufWriter::new(file));
+                if !changed_vertices.is_empty() {
+                    self.write_patch(changed_vertices, &mut writer)?;
+                    writeln!(writer, "{}{}", FINGERPRINT_MARKER, fingerprint)?;
+                }
+                writer.flush()?;
+                Ok(UpdateOutcome { strategy: UpdateStrategy::Appended, bytes_written: writer.written, fingerprint })
+            }
+            _ => {
+                let mut writer = CountingWriter::new(BufWriter::new(File::create(path)?));
+                self.serialize(&mut BufWriter::new(&mut writer))?;
+                writeln!(writer, "{}{}", FINGERPRINT_MARKER, fingerprint)?;
+                writer.flush()?;
+                Ok(UpdateOutcome { strategy: UpdateStrategy::Rewritten, bytes_written: writer.written, fingerprint })
+            }
+        }
+    }
+
+    /// Секция изменений заменяет каждую изменённую вершину целиком: вершина удаляется вместе с рёбрами
+    /// и добавляется заново со всеми инцидентными рёбрами. Рёбра идут после всех вершин,
+    /// т.к. могут ссылаться на вершины, добавляемые в этой же секции
+    fn write_patch<W: Write>(&self, changed_vertices: &HashSet<ID>, writer: &mut W) -> Result<(), GraphError> {
+        writeln!(writer, "{}", PATCH_MARKER)?;
+        for id in changed_vertices {
+            writeln!(writer, "{}{}", DELETE_VERTEX, id)?;
+            if let Some(vertex) = self.vertices.get(id) {
+                match &vertex.value {
+                    Some(value) => writeln!(writer, "{}{} {}", ADD_VERTEX, id, escape_value(value))?,
+                    None => writeln!(writer, "{}{}", ADD_VERTEX, id)?,
+                }
+                if let Some(role) = self.roles.get(id) {
+                    writeln!(writer, "{}{} {}", ADD_ROLE, id, role)?;
+                }
+            }
+        }
+
+        for id in changed_vertices {
+            let Some(vertex) = self.vertices.get(id) else {
+                continue;
+            };
+            for edge_direction in &vertex.edge_directions {
+                let to_id = &edge_direction.to_vertex_id;
+                // Ребро, основное направление которого хранится у другой изменённой вершины, запишет она
+                let owner_id = if edge_direction.is_own() {
+                    id
+                } else if changed_vertices.contains(to_id) {
+                    continue;
+                } else {
+                    to_id
+                };
+                let other_id = if owner_id == id { to_id } else { id };
+                write_patch_edge(writer, owner_id, other_id, edge_direction.value.as_ref())?;
+            }
+            if let GraphType::Directed = self.r#type {
+                for predecessor_id in self.predecessor_ids(id) {
+                    if changed_vertices.contains(&predecessor_id) {
+                        continue;
+                    }
+                    for edge_direction in &self.vertices[&predecessor_id].edge_directions {
+                        if edge_direction.to_vertex_id == *id {
+                            write_patch_edge(writer, &predecessor_id, id, edge_direction.value.as_ref())?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<VT: From<String>, ET: From<String>, ID: GraphId + FromStr> Graph<VT, ET, ID> {
+    /// Применяет строку секции изменений: `+v id [значение]`, `-v id`, `+e id id [значение]`, `-e id id`, `+r id роль`.
+    /// Удаление отсутствующей вершины не считается ошибкой
+    pub(crate) fn apply_patch_line(&mut self, line: &str) -> Result<(), GraphError> {
+        if let Some(vertex_line) = line.strip_prefix(ADD_VERTEX) {
+            let vertex = Graph::<String, String, ID>::parse_vertex(vertex_line)?;
+            self.add_vertex(Vertex::new(vertex.id, vertex.value.map(VT::from)))
+        } else if let Some(id) = line.strip_prefix(DELETE_VERTEX) {
+            let id = id.parse::<ID>()
+                .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
+            match self.delete_vertex(id) {
+                Err(GraphError::VertexNotFound(_)) => Ok(()),
+                result => result,
+            }
+        } else if let Some(role_line) = line.strip_prefix(ADD_ROLE) {
+            self.apply_role_line(role_line)
+        } else if let Some(edge_line) = line.strip_prefix(ADD_EDGE) {
+            let (from_id, to_id, value) = Graph::<String, String, ID>::parse_edge(edge_line, self)?;
+            self.add_edge(from_id, to_id, value.map(ET::from))
+        } else if let Some(edge_line) = line.strip_prefix(DELETE_EDGE) {
+            let (from_id, to_id, _) = Graph::<String, String, ID>::parse_edge(edge_line, self)?;
+            self.delete_edge(from_id, to_id)
+        } else {
+            Err(GraphError::WrongPatchLine(line.to_owned()))
+        }
+    }
+}
+
+fn write_patch_edge<W: Write, ID: Display>(writer: &mut W, from_id: &ID, to_id: &ID, value: &Option<String>) -> io::Result<()> {
+    match value {
+        Some(value) => writeln!(writer, "{}{} {} {}", ADD_EDGE, from_id, to_id, escape_value(value)),
+        None => writeln!(writer, "{}{} {}", ADD_EDGE, from_id, to_id),
+    }
+}
+
+/// Отпечаток из последней строки файла, `None` - если файла нет или он записан не `serialize_update`
+fn read_recorded_fingerprint(path: &Path) -> Result<Option<GraphFingerprint>, GraphError> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(FINGERPRINT_TAIL_LEN)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+
+    let fingerprint = String::from_utf8_lossy(&tail)
+        .lines()
+        .last()
+        .and_then(|line| line.trim().strip_prefix(FINGERPRINT_MARKER).map(str::to_owned))
+        .and_then(|hash| u64::from_str_radix(&hash, 16).ok())
+        .map(GraphFingerprint);
+    Ok(fingerprint)
+}
+
+fn fnv_hash(data: &str) -> u64 {
+    data.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+}
+
+/// Значение для отпечатка: отсутствующее значение отличается от любой строки
+struct OptionalValue<'a, T>(&'a Option<T>);
+
+impl<T: Display> Display for OptionalValue<'_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(value) => write!(formatter, "+{}", value),
+            None => write!(formatter, "-"),
+        }
+    }
+}
+
+/// Писатель, считающий записанные байты
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W> CountingWriter<W> {
+    fn new(inner: W) -> CountingWriter<W> {
+        CountingWriter { inner, written: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::BufReader, path::PathBuf};
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            TempFile(std::env::temp_dir().join(format!("graph-lib-patch-{}-{}", std::process::id(), name)))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn load(path: &Path) -> Result<Graph<String, String>> {
+        Ok(Graph::deserialize(BufReader::new(File::open(path)?))?)
+    }
+
+    #[test]
+    fn small_edit_appends_patch() -> Result<()> {
+        let file = TempFile::new("append.tgf");
+        let graph = Graph::<String, String>::hypercube(7)?;
+        let initial_outcome = graph.serialize_update(&file.0, &graph.fingerprint())?;
+        let initial_len = std::fs::metadata(&file.0)?.len();
+
+        let mut graph = load(&file.0)?;
+        let baseline = graph.track_changes();
+        graph.delete_edge(0, 1)?;
+        graph.set_vertex_value(4, Some("May".to_owned()))?;
+        graph.add_vertex(Vertex::new(200, Some("August".to_owned())))?;
+        graph.add_edge(200, 7, Some("summer".to_owned()))?;
+        let outcome = graph.serialize_update(&file.0, &baseline)?;
+        let patched_len = std::fs::metadata(&file.0)?.len();
+
+        let reloaded_graph = load(&file.0)?;
+        if initial_outcome.strategy == UpdateStrategy::Rewritten
+            && outcome.strategy == UpdateStrategy::Appended
+            && patched_len == initial_len + outcome.bytes_written
+            && outcome.bytes_written * 10 < initial_len
+            && reloaded_graph.fingerprint() == graph.fingerprint()
+            && reloaded_graph.fingerprint() == outcome.fingerprint {
+            Ok(())
+        } else {
+            bail!("small edit not appended as patch: {:?}, file size {}", outcome, initial_len)
+        }
+    }
+
+    #[test]
+    fn roles_in_patch() -> Result<()> {
+        let file = TempFile::new("roles.tgf");
+        let mut graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        graph.set_role(7, VertexRole::Source)?;
+        graph.set_role(2, VertexRole::Sink)?;
+        graph.serialize_update(&file.0, &graph.fingerprint())?;
+
+        let mut graph = load(&file.0)?;
+        let baseline = graph.track_changes();
+        graph.set_role(1, VertexRole::Internal)?;
+        graph.clear_role(2);
+        graph.set_vertex_value(7, Some("October".to_owned()))?;
+        let outcome = graph.serialize_update(&file.0, &baseline)?;
+
+        let reloaded_graph = load(&file.0)?;
+        if outcome.strategy == UpdateStrategy::Appended
+            && reloaded_graph == graph
+            && reloaded_graph.role(7) == Some(VertexRole::Source)
+            && reloaded_graph.role(2).is_none() {
+            Ok(())
+        } else {
+            bail!("roles lost in patch: {:?}", reloaded_graph.roles())
+        }
+    }
+
+    #[test]
+    fn mismatched_baseline_rewrites() -> Result<()> {
+        let file = TempFile::new("rewrite.tgf");
+        let mut graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let baseline = graph.track_changes();
+        graph.serialize_update(&file.0, &baseline)?;
+        graph.delete_vertex(3)?;
+        let stale_baseline = GraphFingerprint(baseline.0.wrapping_add(1));
+        let outcome = graph.serialize_update(&file.0, &stale_baseline)?;
+
+        if outcome.strategy == UpdateStrategy::Rewritten
+            && outcome.bytes_written == std::fs::metadata(&file.0)?.len()
+            && load(&file.0)?.fingerprint() == graph.fingerprint() {
+            Ok(())
+        } else {
+            bail!("mismatched baseline not rewritten: {:?}", outcome)
+        }
+    }
+
+    #[test]
+    fn repeated_patches() -> Result<()> {
+        let file = TempFile::new("repeated.tgf");
+        let mut graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        let mut baseline = graph.track_changes();
+        baseline = graph.serialize_update(&file.0, &baseline)?.fingerprint;
+        for id in [3, 5] {
+            graph.delete_vertex(id)?;
+            let outcome = graph.serialize_update(&file.0, &baseline)?;
+            if outcome.strategy != UpdateStrategy::Appended {
+                bail!("patch not appended after deleting vertex {}", id)
+            }
+            baseline = outcome.fingerprint;
+        }
+        graph.add_edge(7, 7, None)?;
+        graph.serialize_update(&file.0, &baseline)?;
+
+        let reloaded_graph = load(&file.0)?;
+        if reloaded_graph.fingerprint() == graph.fingerprint() && reloaded_graph.edge_count() == graph.edge_count() {
+            Ok(())
+        } else {
+            bail!("patched file differs from graph")
+        }
+    }
+
+    #[test]
+    fn wrong_patch_line() -> Result<()> {
+        let tgf = "1\n2\n#\n1 2\n#patch\n*v 3\n";
+        match Graph::deserialize(BufReader::new(tgf.as_bytes())) {
+            Err(GraphError::ParseLine { line_no: 6, source }) if matches!(*source, GraphError::WrongPatchLine(ref line) if line == "*v 3") => Ok(()),
+            _ => bail!("wrong patch line not reported"),
+        }
+    }
+}
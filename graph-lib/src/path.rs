@@ -0,0 +1,101 @@
+use super::*;
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Значения рёбер пути, заданного последовательностью вершин, например результатом `shortest_path`.
+    /// Каждая пара соседних вершин должна быть ребром графа (в неориентированном графе - в любом направлении),
+    /// иначе - `EdgeNotFound` с этой парой. Из кратных рёбер берётся первое добавленное.
+    /// Путь из одной вершины рёбер не содержит
+    pub fn path_edges(&self, path: &[ID]) -> Result<Vec<Option<&ET>>, GraphError> {
+        Ok(self.path_hops(path)?
+            .into_iter()
+            .map(|edge_directions| edge_directions[0].value.as_ref().as_ref())
+            .collect())
+    }
+
+    /// Суммарный вес рёбер пути, см. `path_edges`. Из кратных рёбер берётся самое лёгкое,
+    /// рёбра без значения весят 1.0. Вес пути из одной вершины - 0.0
+    pub fn path_cost<W: Fn(&ET) -> f64>(&self, path: &[ID], weight: W) -> Result<f64, GraphError> {
+        Ok(self.path_hops(path)?
+            .into_iter()
+            .map(|edge_directions| edge_directions
+                .iter()
+                .map(|edge_direction| edge_direction.value.as_ref().as_ref().map_or(DEFAULT_EDGE_COST, &weight))
+                .fold(f64::INFINITY, f64::min))
+            .sum())
+    }
+
+    /// Рёбра между каждой парой соседних вершин пути
+    fn path_hops(&self, path: &[ID]) -> Result<Vec<Vec<&EdgeDirection<ET, ID>>>, GraphError> {
+        if let Some(missing_id) = path.iter().find(|id| !self.contains_vertex(id)) {
+            return Err(GraphError::VertexNotFound(missing_id.to_string()));
+        }
+        path.windows(2).map(|hop| {
+            let (from_id, to_id) = (&hop[0], &hop[1]);
+            let from_vertex = &self.vertices[from_id];
+            let edge_directions: Vec<_> = if from_vertex.has_edge_direction_to(to_id) {
+                from_vertex.edge_directions
+                    .iter()
+                    .filter(|edge_direction| edge_direction.to_vertex_id == *to_id)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            if edge_directions.is_empty() {
+                return Err(GraphError::EdgeNotFound(from_id.to_string(), to_id.to_string()));
+            }
+            Ok(edge_directions)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn weighted(graph_type: GraphType) -> Result<Graph<(), f64>> {
+        let mut graph = Graph::new_multigraph(graph_type);
+        for id in 1..=4 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, Some(2.5))?;
+        graph.add_edge(2, 3, None)?;
+        graph.add_edge(3, 4, Some(4.0))?;
+        graph.add_edge(3, 4, Some(0.5))?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn path_values_and_cost() -> Result<()> {
+        let graph = weighted(GraphType::Directed)?;
+        let path = [1, 2, 3, 4];
+        if graph.path_edges(&path)? == vec![Some(&2.5), None, Some(&4.0)]
+            && graph.path_cost(&path, |&weight| weight)? == 4.0
+            && graph.path_cost(&[2], |&weight| weight)? == 0.0
+            && graph.path_edges(&[2])?.is_empty()
+            && graph.path_cost(&[], |&weight| weight)? == 0.0 {
+            Ok(())
+        } else {
+            bail!("wrong path edges {:?}", graph.path_edges(&path)?)
+        }
+    }
+
+    #[test]
+    fn missing_hops_rejected() -> Result<()> {
+        let directed = weighted(GraphType::Directed)?;
+        let undirected = weighted(GraphType::Undirected)?;
+        let reversed = [4, 3, 2, 1];
+        if directed.path_edges(&[1, 2, 4]) == Err(GraphError::EdgeNotFound("2".to_owned(), "4".to_owned()))
+            && directed.path_cost(&reversed, |&weight| weight) == Err(GraphError::EdgeNotFound("4".to_owned(), "3".to_owned()))
+            && directed.path_edges(&[5]) == Err(GraphError::VertexNotFound("5".to_owned()))
+            && undirected.path_cost(&reversed, |&weight| weight)? == 4.0
+            && undirected.path_edges(&[1, 2, 1])? == vec![Some(&2.5), Some(&2.5)] {
+            Ok(())
+        } else {
+            bail!("missing edge accepted")
+        }
+    }
+}
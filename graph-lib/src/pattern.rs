@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use super::*;
+
+/// Переменная шаблона, создаётся `Pattern::var`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PatternVar(usize);
+
+type VertexPredicate<VT> = Box<dyn Fn(Option<&VT>) -> bool>;
+type EdgePredicate<ET> = Box<dyn Fn(Option<&ET>) -> bool>;
+
+struct PatternEdge<ET> {
+    from: PatternVar,
+    to: PatternVar,
+    predicate: Option<EdgePredicate<ET>>,
+}
+
+/// Шаблон для `Graph::match_pattern`: переменные-вершины с необязательными условиями на значения
+/// и обязательные рёбра между ними с необязательными условиями на значения рёбер.
+/// Направленность рёбер шаблона та же, что у графа, в котором идёт поиск
+pub struct Pattern<VT, ET> {
+    vertex_predicates: Vec<Option<VertexPredicate<VT>>>,
+    edges: Vec<PatternEdge<ET>>,
+    max_matches: Option<usize>,
+    shared_vertices: bool,
+}
+
+/// Найденные вхождения шаблона. `truncated` - вхождений больше, чем `Pattern::max_matches`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternMatches<ID = DefaultGraphIdType> {
+    pub matches: Vec<HashMap<PatternVar, ID>>,
+    pub truncated: bool,
+}
+
+impl<VT, ET> Default for Pattern<VT, ET> {
+    fn default() -> Self {
+        Pattern {
+            vertex_predicates: Vec::new(),
+            edges: Vec::new(),
+            max_matches: None,
+            shared_vertices: false,
+        }
+    }
+}
+
+impl<VT, ET> Pattern<VT, ET> {
+    pub fn new() -> Pattern<VT, ET> {
+        Pattern::default()
+    }
+
+    /// Переменная, которой подходит любая вершина
+    pub fn var(&mut self) -> PatternVar {
+        self.vertex_predicates.push(None);
+        PatternVar(self.vertex_predicates.len() - 1)
+    }
+
+    /// Переменная, которой подходят вершины со значением, удовлетворяющим `predicate`
+    pub fn var_where<P: Fn(Option<&VT>) -> bool + 'static>(&mut self, predicate: P) -> PatternVar {
+        self.vertex_predicates.push(Some(Box::new(predicate)));
+        PatternVar(self.vertex_predicates.len() - 1)
+    }
+
+    /// Обязательное ребро между вершинами переменных `from` и `to`
+    pub fn edge(&mut self, from: PatternVar, to: PatternVar) -> &mut Pattern<VT, ET> {
+        self.push_edge(from, to, None)
+    }
+
+    /// Обязательное ребро, значение которого удовлетворяет `predicate`
+    pub fn edge_where<P: Fn(Option<&ET>) -> bool + 'static>(&mut self, from: PatternVar, to: PatternVar, predicate: P) -> &mut Pattern<VT, ET> {
+        self.push_edge(from, to, Some(Box::new(predicate)))
+    }
+
+    /// Поиск останавливается после `max_matches` вхождений
+    pub fn max_matches(&mut self, max_matches: usize) -> &mut Pattern<VT, ET> {
+        self.max_matches = Some(max_matches);
+        self
+    }
+
+    /// Разрешает разным переменным совпадать с одной вершиной графа
+    pub fn allow_shared_vertices(&mut self) -> &mut Pattern<VT, ET> {
+        self.shared_vertices = true;
+        self
+    }
+
+    fn push_edge(&mut self, from: PatternVar, to: PatternVar, predicate: Option<EdgePredicate<ET>>) -> &mut Pattern<VT, ET> {
+        assert!(
+            from.0 < self.vertex_predicates.len() && to.0 < self.vertex_predicates.len(),
+            "pattern edge uses a variable of another pattern"
+        );
+        self.edges.push(PatternEdge { from, to, predicate });
+        self
+    }
+
+    /// Порядок связывания переменных: сначала переменная с наибольшей степенью в шаблоне,
+    /// затем каждый раз переменная с наибольшим числом рёбер к уже выбранным
+    fn binding_order(&self) -> Vec<usize> {
+        let var_count = self.vertex_predicates.len();
+        let mut degrees = vec![0; var_count];
+        for edge in &self.edges {
+            degrees[edge.from.0] += 1;
+            degrees[edge.to.0] += 1;
+        }
+        let mut ordered = vec![false; var_count];
+        let mut order = Vec::with_capacity(var_count);
+        while order.len() < var_count {
+            let next_var = (0..var_count)
+                .filter(|&var| !ordered[var])
+                .max_by_key(|&var| {
+                    let bound_edges = self.edges.iter()
+                        .filter(|edge| (edge.from.0 == var && ordered[edge.to.0]) || (edge.to.0 == var && ordered[edge.from.0]))
+                        .count();
+                    (bound_edges, degrees[var], std::cmp::Reverse(var))
+                })
+                .expect("unordered variable exists while order is incomplete");
+            ordered[next_var] = true;
+            order.push(next_var);
+        }
+        order
+    }
+}
+
+/// Состояние поиска с возвратом
+struct PatternSearch<'a, VT, ET, ID> {
+    graph: &'a Graph<VT, ET, ID>,
+    pattern: &'a Pattern<VT, ET>,
+    order: Vec<usize>,
+    /// Все вершины графа по убыванию степени - кандидаты для переменной, не связанной рёбрами с уже выбранными
+    by_degree: Vec<&'a ID>,
+    bindings: Vec<Option<ID>>,
+    matches: PatternMatches<ID>,
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Все вхождения шаблона в граф поиском с возвратом. Вхождения могут пересекаться,
+    /// каждое - отдельное связывание переменных шаблона с вершинами графа
+    pub fn match_pattern(&self, pattern: &Pattern<VT, ET>) -> PatternMatches<ID> {
+        let mut by_degree: Vec<_> = self.vertices.keys().collect();
+        by_degree.sort_by_cached_key(|&id| std::cmp::Reverse(self.vertices[id].edge_directions.len() + self.predecessors.get(id).map_or(0, HashSet::len)));
+        let mut search = PatternSearch {
+            graph: self,
+            pattern,
+            order: pattern.binding_order(),
+            by_degree,
+            bindings: vec![None; pattern.vertex_predicates.len()],
+            matches: PatternMatches { matches: Vec::new(), truncated: false },
+        };
+        if !pattern.vertex_predicates.is_empty() {
+            let _ = search.extend(0);
+        }
+        search.matches
+    }
+
+    fn has_matching_edge(&self, from_id: &ID, to_id: &ID, predicate: Option<&EdgePredicate<ET>>) -> bool {
+        self.vertices[from_id].edge_directions
+            .iter()
+            .any(|edge_direction| edge_direction.to_vertex_id == *to_id
+                && predicate.is_none_or(|predicate| predicate(edge_direction.value.as_ref().as_ref())))
+    }
+}
+
+impl<VT, ET, ID: GraphId> PatternSearch<'_, VT, ET, ID> {
+    fn extend(&mut self, depth: usize) -> ControlFlow<()> {
+        if depth == self.order.len() {
+            if self.pattern.max_matches.is_some_and(|max_matches| self.matches.matches.len() >= max_matches) {
+                self.matches.truncated = true;
+                return ControlFlow::Break(());
+            }
+            let found = self.bindings.iter()
+                .enumerate()
+                .map(|(var, id)| (PatternVar(var), id.clone().expect("all variables are bound")))
+                .collect();
+            self.matches.matches.push(found);
+            return ControlFlow::Continue(());
+        }
+
+        let var = self.order[depth];
+        for candidate_id in self.candidates(var) {
+            if self.accepts(var, &candidate_id) {
+                self.bindings[var] = Some(candidate_id);
+                let flow = self.extend(depth + 1);
+                self.bindings[var] = None;
+                flow?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Соседи вершины, уже связанной с переменной ребром шаблона, иначе все вершины графа
+    fn candidates(&self, var: usize) -> Vec<ID> {
+        let graph = self.graph;
+        // Связанная вершина и признак того, что ребро шаблона идёт от неё к переменной
+        let bound_edge = self.pattern.edges.iter().find_map(|edge| match (edge.from.0 == var, edge.to.0 == var) {
+            (true, false) => self.bindings[edge.to.0].as_ref().map(|bound_id| (bound_id, false)),
+            (false, true) => self.bindings[edge.from.0].as_ref().map(|bound_id| (bound_id, true)),
+            _ => None,
+        });
+        let mut seen = HashSet::new();
+        match bound_edge {
+            Some((bound_id, outgoing)) if outgoing || graph.r#type == GraphType::Undirected => {
+                graph.vertices[bound_id].edge_directions
+                    .iter()
+                    .map(|edge_direction| edge_direction.to_vertex_id.clone())
+                    .filter(|id| seen.insert(id.clone()))
+                    .collect()
+            }
+            Some((bound_id, _)) => graph.predecessor_ids(bound_id),
+            None => self.by_degree.iter().map(|&id| id.clone()).collect(),
+        }
+    }
+
+    fn accepts(&self, var: usize, candidate_id: &ID) -> bool {
+        if !self.pattern.shared_vertices && self.bindings.iter().any(|bound_id| bound_id.as_ref() == Some(candidate_id)) {
+            return false;
+        }
+        if let Some(predicate) = &self.pattern.vertex_predicates[var] {
+            if !predicate(self.graph.vertices[candidate_id].value.as_ref()) {
+                return false;
+            }
+        }
+        let bound_id = |pattern_var: PatternVar| if pattern_var.0 == var {
+            Some(candidate_id)
+        } else {
+            self.bindings[pattern_var.0].as_ref()
+        };
+        self.pattern.edges.iter()
+            .filter(|edge| edge.from.0 == var || edge.to.0 == var)
+            .all(|edge| match (bound_id(edge.from), bound_id(edge.to)) {
+                (Some(from_id), Some(to_id)) => self.graph.has_matching_edge(from_id, to_id, edge.predicate.as_ref()),
+                _ => true,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn complete_graph(vertex_count: u32) -> Result<Graph<(), ()>> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for id in 0..vertex_count {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for from_id in 0..vertex_count {
+            for to_id in from_id + 1..vertex_count {
+                graph.add_edge(from_id, to_id, None)?;
+            }
+        }
+        Ok(graph)
+    }
+
+    fn triangle() -> Pattern<(), ()> {
+        let mut pattern = Pattern::new();
+        let (a, b, c) = (pattern.var(), pattern.var(), pattern.var());
+        pattern.edge(a, b).edge(b, c).edge(c, a);
+        pattern
+    }
+
+    #[test]
+    fn path_with_value_predicates() -> Result<()> {
+        let graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let mut pattern = Pattern::new();
+        let december = pattern.var_where(|value: Option<&String>| value.is_some_and(|value| value == "December"));
+        let middle = pattern.var();
+        let end = pattern.var();
+        pattern
+            .edge_where(december, middle, |value: Option<&String>| value.is_some_and(|value| value == "Happy New Year!"))
+            .edge(middle, end);
+
+        let found = graph.match_pattern(&pattern);
+        let mut ends: Vec<_> = found.matches.iter().map(|binding| binding[&end]).collect();
+        ends.sort_unstable();
+        if !found.truncated
+            && found.matches.iter().all(|binding| binding[&december] == 5 && binding[&middle] == 1)
+            && ends == vec![2, 6, 7] {
+            Ok(())
+        } else {
+            bail!("wrong path matches {:?}", found)
+        }
+    }
+
+    #[test]
+    fn triangles_in_complete_graph() -> Result<()> {
+        let k4 = complete_graph(4)?;
+        let all_triangles = k4.match_pattern(&triangle());
+        let mut shared = triangle();
+        shared.allow_shared_vertices();
+        let mut directed = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 0..3 {
+            directed.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(0, 1), (1, 2), (2, 0), (0, 2)] {
+            directed.add_edge(from_id, to_id, None)?;
+        }
+        // Ориентированный цикл 0 -> 1 -> 2 -> 0 находится трижды, по одному разу на каждую начальную вершину
+        let directed_cycles = directed.match_pattern(&triangle());
+        if all_triangles.matches.len() == 24
+            && !all_triangles.truncated
+            && k4.match_pattern(&shared).matches.len() == 24
+            && directed_cycles.matches.len() == 3 {
+            Ok(())
+        } else {
+            bail!("wrong triangle count {}", all_triangles.matches.len())
+        }
+    }
+
+    #[test]
+    fn result_cap() -> Result<()> {
+        let k4 = complete_graph(4)?;
+        let mut capped = triangle();
+        capped.max_matches(5);
+        let mut exact = triangle();
+        exact.max_matches(24);
+        let capped_matches = k4.match_pattern(&capped);
+        let exact_matches = k4.match_pattern(&exact);
+        if capped_matches.matches.len() == 5 && capped_matches.truncated
+            && exact_matches.matches.len() == 24 && !exact_matches.truncated {
+            Ok(())
+        } else {
+            bail!("result cap not applied")
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::utils::UnionFind;
+use super::*;
+
+/// Рассчитанное, но не выполненное удаление вершин, см. `Graph::plan_retain_vertices`.
+/// Выполняется `Graph::apply_plan`, если граф не менялся после расчёта
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutationPlan<ID = DefaultGraphIdType> {
+    pub removed_vertices: Vec<ID>,
+    /// Удаляемые вместе с вершинами рёбра: начальная и конечная вершина. Ребро неориентированного графа
+    /// указывается один раз, параллельные рёбра мультиграфа - каждое отдельно
+    pub removed_edges: Vec<(ID, ID)>,
+    /// Количество вершин и рёбер после удаления
+    pub vertex_count: usize,
+    pub edge_count: usize,
+    /// Распадётся ли какая-либо компонента связности (без учёта направления рёбер) на несколько
+    pub disconnects: bool,
+    /// `Graph::generation` на момент расчёта
+    generation: u64,
+}
+
+impl<ID> MutationPlan<ID> {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Расчёт `retain_vertices` без изменения графа
+    pub fn plan_retain_vertices<F: Fn(&ID) -> bool>(&self, keep: F) -> MutationPlan<ID> {
+        let removed_ids: HashSet<_> = self.vertices.keys()
+            .filter(|&id| !keep(id))
+            .collect();
+        self.plan_removal(removed_ids)
+    }
+
+    /// Расчёт `drop_low_degree_vertices` без изменения графа
+    pub fn plan_drop_low_degree_vertices(&self, min_degree: usize) -> MutationPlan<ID> {
+        let kept_ids = self.ids_with_min_degree(min_degree);
+        self.plan_retain_vertices(|id| kept_ids.contains(id))
+    }
+
+    /// Расчёт `delete_vertex` без изменения графа
+    pub fn plan_delete_vertex(&self, vertex_id: ID) -> Result<MutationPlan<ID>, GraphError> {
+        if !self.contains_vertex(&vertex_id) {
+            return Err(GraphError::VertexNotFound(vertex_id.to_string()));
+        }
+        Ok(self.plan_retain_vertices(|id| *id != vertex_id))
+    }
+
+    /// Выполняет план. Если граф изменился после расчёта плана, возвращается `StalePlan`, и граф не меняется
+    pub fn apply_plan(&mut self, plan: MutationPlan<ID>) -> Result<(), GraphError> {
+        if plan.generation != self.generation {
+            return Err(GraphError::StalePlan { planned_generation: plan.generation, generation: self.generation });
+        }
+        if let Some(missing_id) = plan.removed_vertices.iter().find(|&id| !self.contains_vertex(id)) {
+            return Err(GraphError::VertexNotFound(missing_id.to_string()));
+        }
+        for id in plan.removed_vertices {
+            self.delete_vertex(id)?;
+        }
+        Ok(())
+    }
+
+    fn plan_removal(&self, removed_ids: HashSet<&ID>) -> MutationPlan<ID> {
+        let removed_edges: Vec<_> = self.logical_edges()
+            .filter(|(from_id, edge_direction)| removed_ids.contains(from_id) || removed_ids.contains(&edge_direction.to_vertex_id))
+            .map(|(from_id, edge_direction)| (from_id.clone(), edge_direction.to_vertex_id.clone()))
+            .collect();
+        MutationPlan {
+            removed_vertices: removed_ids.iter().map(|&id| id.clone()).collect(),
+            vertex_count: self.vertices.len() - removed_ids.len(),
+            edge_count: self.edge_count - removed_edges.len(),
+            disconnects: self.splits_component(&removed_ids),
+            removed_edges,
+            generation: self.generation,
+        }
+    }
+
+    /// Окажутся ли две оставшиеся вершины одной компоненты связности в разных компонентах после удаления вершин
+    fn splits_component(&self, removed_ids: &HashSet<&ID>) -> bool {
+        let kept_ids: Vec<_> = self.vertices.keys()
+            .filter(|id| !removed_ids.contains(id))
+            .collect();
+        let indices: HashMap<_, _> = kept_ids.iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        let mut components_after = UnionFind::new(kept_ids.len());
+        for (from_id, edge_direction) in self.logical_edges() {
+            if let (Some(&from_index), Some(&to_index)) = (indices.get(from_id), indices.get(&edge_direction.to_vertex_id)) {
+                components_after.union(from_index, to_index);
+            }
+        }
+
+        self.connected_components().iter().any(|component| {
+            let mut roots = component.iter()
+                .filter_map(|id| indices.get(id))
+                .map(|&index| components_after.find(index));
+            roots.next().is_some_and(|root| roots.any(|other_root| other_root != root))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Треугольник с хвостом, отдельное ребро, вершина с петлёй и изолированная вершина
+    const PRUNE_TGF: &str = "1\n2\n3\n4\n5\n6\n7\n8\n#\n1 2\n2 3\n3 1\n3 4\n5 6\n7 7";
+
+    #[test]
+    fn plan_matches_direct_call() -> Result<()> {
+        let graph = Graph::deserialize(PRUNE_TGF.as_bytes())?;
+        let plan = graph.plan_drop_low_degree_vertices(2);
+        let mut pruned = graph.clone();
+        let removed_count = pruned.drop_low_degree_vertices(2);
+        let mut removed_vertices = plan.removed_vertices.clone();
+        removed_vertices.sort_unstable();
+        let delete_plan = graph.plan_delete_vertex(3)?;
+        let isolated_plan = graph.plan_retain_vertices(|&id| id != 8);
+
+        if plan.vertex_count == pruned.vertex_count()
+            && plan.edge_count == pruned.edge_count()
+            && removed_count == plan.removed_vertices.len()
+            && removed_vertices == vec![4, 5, 6, 7, 8]
+            && plan.removed_edges.len() == 3
+            && !plan.disconnects
+            && delete_plan.removed_edges.len() == 3
+            && delete_plan.disconnects
+            && !isolated_plan.disconnects
+            && isolated_plan.removed_edges.is_empty()
+            && matches!(graph.plan_delete_vertex(9), Err(GraphError::VertexNotFound(_)))
+            && graph.vertex_count() == 8 {
+            Ok(())
+        } else {
+            bail!("plan {:?} differs from direct call", plan)
+        }
+    }
+
+    #[test]
+    fn stale_plan_rejected() -> Result<()> {
+        let mut graph = Graph::deserialize(PRUNE_TGF.as_bytes())?;
+        let plan = graph.plan_delete_vertex(1)?;
+        graph.set_vertex_value(2, Some("b".to_owned()))?;
+        let result = graph.apply_plan(plan);
+        if matches!(result, Err(GraphError::StalePlan { planned_generation, generation }) if planned_generation < generation)
+            && graph.vertex_count() == 8
+            && graph.edge_count() == 6 {
+            Ok(())
+        } else {
+            bail!("stale plan applied: {:?}", result)
+        }
+    }
+
+    #[test]
+    fn applied_plan_equals_direct_call() -> Result<()> {
+        let mut planned = Graph::deserialize(PRUNE_TGF.as_bytes())?;
+        let mut direct = planned.clone();
+        let plan = planned.plan_drop_low_degree_vertices(2);
+        planned.apply_plan(plan)?;
+        direct.drop_low_degree_vertices(2);
+
+        let mut deleted = Graph::deserialize(PRUNE_TGF.as_bytes())?;
+        let mut directly_deleted = deleted.clone();
+        deleted.apply_plan(deleted.plan_delete_vertex(3)?)?;
+        directly_deleted.delete_vertex(3)?;
+        if planned == direct && deleted == directly_deleted {
+            Ok(())
+        } else {
+            bail!("applied plan differs from direct call")
+        }
+    }
+}
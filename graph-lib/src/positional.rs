@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use super::*;
+
+/// Значение признака для вершины, из которой якорь недостижим
+const UNREACHABLE_DISTANCE: f32 = -1.0;
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Позиционные признаки вершин: для каждой вершины - число рёбер в кратчайшем пути до каждого якоря
+    /// (в порядке `anchors`), `-1.0` - если якорь недостижим. Для каждого якоря выполняется один обход в ширину
+    pub fn positional_features(&self, anchors: &[ID]) -> Result<HashMap<ID, Vec<f32>>, GraphError> {
+        let missing_anchors: Vec<_> = anchors
+            .iter()
+            .filter(|&anchor| !self.contains_vertex(anchor))
+            .map(|anchor| anchor.to_string())
+            .collect();
+        if !missing_anchors.is_empty() {
+            return Err(GraphError::VerticesNotFound(missing_anchors));
+        }
+
+        let mut features: HashMap<_, _> = self.vertices.keys()
+            .map(|id| (id.clone(), vec![UNREACHABLE_DISTANCE; anchors.len()]))
+            .collect();
+        for (anchor_index, anchor) in anchors.iter().enumerate() {
+            for (id, distance) in self.distances_to(anchor) {
+                if let Some(vertex_features) = features.get_mut(id) {
+                    vertex_features[anchor_index] = distance as f32;
+                }
+            }
+        }
+
+        Ok(features)
+    }
+
+    /// Записывает позиционные признаки в CSV: заголовок `id,d_<якорь>,...` и по строке на вершину в порядке идентификаторов
+    pub fn write_features_csv<W: Write>(&self, anchors: &[ID], writer: &mut W) -> Result<(), GraphError>
+    where
+        ID: Ord,
+    {
+        let features = self.positional_features(anchors)?;
+
+        write!(writer, "id")?;
+        for anchor in anchors {
+            write!(writer, ",d_{}", anchor)?;
+        }
+        writeln!(writer)?;
+
+        let mut ids: Vec<_> = features.keys().collect();
+        ids.sort_unstable();
+        for id in ids {
+            write!(writer, "{}", id)?;
+            for distance in &features[id] {
+                write!(writer, ",{}", distance)?;
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Расстояния от всех вершин, из которых достижим `anchor`, до него. В ориентированном графе обход идёт
+    /// по входящим рёбрам, чтобы расстояние считалось от вершины к якорю
+    fn distances_to<'a>(&'a self, anchor: &'a ID) -> HashMap<&'a ID, usize> {
+        let mut distances = HashMap::from([(anchor, 0)]);
+        let mut queue = VecDeque::from([anchor]);
+        while let Some(id) = queue.pop_front() {
+            let distance = distances[id] + 1;
+            let neighbours: Box<dyn Iterator<Item = &ID>> = match self.r#type {
+                GraphType::Directed => Box::new(self.predecessors.get(id).into_iter().flatten()),
+                GraphType::Undirected => Box::new(self.vertices[id].edge_directions
+                    .iter()
+                    .map(|edge_direction| &edge_direction.to_vertex_id)),
+            };
+            for neighbour_id in neighbours {
+                if !distances.contains_key(neighbour_id) {
+                    distances.insert(neighbour_id, distance);
+                    queue.push_back(neighbour_id);
+                }
+            }
+        }
+        distances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// `TGF_GRAPH` с изолированной вершиной 8
+    fn graph_with_isolated_vertex() -> Result<Graph<String, String>> {
+        let mut graph = Graph::deserialize(BufReader::new(TGF_GRAPH.as_bytes()))?;
+        graph.add_vertex(Vertex::new(8, Some("August".to_owned())))?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn distances_match_shortest_paths() -> Result<()> {
+        let graph = graph_with_isolated_vertex()?;
+        let anchors = [2, 7];
+        let features = graph.positional_features(&anchors)?;
+        for (id, vertex_features) in &features {
+            for (anchor, &distance) in anchors.iter().zip(vertex_features) {
+                let expected_distance = match graph.shortest_path(*id, *anchor)? {
+                    Some(path) => (path.len() - 1) as f32,
+                    None => UNREACHABLE_DISTANCE,
+                };
+                if distance != expected_distance {
+                    bail!("distance from {} to {} is {}, shortest path length is {}", id, anchor, distance, expected_distance)
+                }
+            }
+        }
+        if features[&8] == vec![UNREACHABLE_DISTANCE, UNREACHABLE_DISTANCE] {
+            Ok(())
+        } else {
+            bail!("disconnected vertex not encoded as unreachable")
+        }
+    }
+
+    #[test]
+    fn directed_distances_to_anchor() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(2, 3, None)?;
+        let features = graph.positional_features(&[3])?;
+        if features[&1] == vec![2.0] && features[&3] == vec![0.0] && graph.positional_features(&[1])?[&3] == vec![-1.0] {
+            Ok(())
+        } else {
+            bail!("directed distances must follow edge direction to the anchor")
+        }
+    }
+
+    #[test]
+    fn unknown_anchors() -> Result<()> {
+        let graph = graph_with_isolated_vertex()?;
+        match graph.positional_features(&[1, 10, 20]) {
+            Err(GraphError::VerticesNotFound(ids)) if ids == vec!["10", "20"] => Ok(()),
+            _ => bail!("unknown anchors not reported"),
+        }
+    }
+
+    #[test]
+    fn features_csv() -> Result<()> {
+        let graph = graph_with_isolated_vertex()?;
+        let mut csv = Vec::new();
+        graph.write_features_csv(&[1, 4, 7], &mut csv)?;
+        let expected_csv = "id,d_1,d_4,d_7
+1,0,3,1
+2,1,2,2
+3,2,1,2
+4,3,0,3
+5,1,2,1
+6,1,2,1
+7,1,3,0
+8,-1,-1,-1
+";
+        if String::from_utf8(csv)? == expected_csv {
+            Ok(())
+        } else {
+            bail!("wrong features csv")
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Типы, нужные почти любому пользователю библиотеки: `use graph_lib::prelude::*;`
+
+pub use crate::{DefaultGraphIdType, Graph, GraphId, GraphType, Vertex};
+pub use crate::builder::GraphBuilder;
+pub use crate::distribution::DegreeKind;
+pub use crate::errors::GraphError;
+pub use crate::order::VertexOrder;
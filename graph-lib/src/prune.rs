@@ -0,0 +1,80 @@
+use super::*;
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Удаляет вершины, для которых `keep` вернул `false`, вместе с инцидентными рёбрами.
+    /// Возвращает количество удалённых вершин
+    pub fn retain_vertices<F: Fn(&ID) -> bool>(&mut self, keep: F) -> usize {
+        let deleted_ids: Vec<_> = self.vertices.keys()
+            .filter(|&id| !keep(id))
+            .cloned()
+            .collect();
+        for id in &deleted_ids {
+            self.delete_vertex(id.clone())
+                .expect("deleted vertex was taken from the graph");
+        }
+        deleted_ids.len()
+    }
+
+    /// Удаляет вершины со степенью (`degree`) меньше `min_degree` за один проход: степени считаются до удаления,
+    /// поэтому у оставшихся вершин степень после удаления может стать меньше
+    pub fn drop_low_degree_vertices(&mut self, min_degree: usize) -> usize {
+        let kept_ids = self.ids_with_min_degree(min_degree);
+        self.retain_vertices(|id| kept_ids.contains(id))
+    }
+
+    /// Вершины со степенью не меньше `min_degree`
+    pub(crate) fn ids_with_min_degree(&self, min_degree: usize) -> HashSet<ID> {
+        self.vertices.keys()
+            .filter(|&id| self.degree(id.clone()).is_ok_and(|degree| degree >= min_degree))
+            .cloned()
+            .collect()
+    }
+
+    /// Удаляет вершины без рёбер. Вершина только с петлёй изолированной не считается
+    pub fn drop_isolated_vertices(&mut self) -> usize {
+        self.drop_low_degree_vertices(1)
+    }
+
+    /// Оставляет только самую большую компоненту связности (слабой связности для ориентированного графа)
+    pub fn keep_largest_component(&mut self) -> usize {
+        let largest_component: HashSet<_> = self.connected_components()
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        self.retain_vertices(|id| largest_component.contains(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn prune_steps() -> Result<()> {
+        let tgf = "1\n2\n3\n4\n5\n6\n7\n8\n#\n1 2\n2 3\n3 1\n3 4\n5 6\n7 7";
+        let mut graph = Graph::deserialize(tgf.as_bytes())?;
+        let isolated = graph.drop_isolated_vertices();
+        let low_degree = graph.drop_low_degree_vertices(2);
+        let mut largest = Graph::deserialize(tgf.as_bytes())?;
+        let outside_largest = largest.keep_largest_component();
+        let mut ids: Vec<_> = largest.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        // После одного прохода у вершины 3 остаются рёбра только к 1 и 2
+        if isolated == 1
+            && low_degree == 4
+            && graph.vertex_count() == 3
+            && graph.edge_count() == 3
+            && outside_largest == 4
+            && ids == vec![1, 2, 3, 4] {
+            Ok(())
+        } else {
+            bail!("wrong pruning: {} {} {} {:?}", isolated, low_degree, outside_largest, ids)
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use super::*;
+
+const WORD_BITS: usize = u64::BITS as usize;
+const UNVISITED: usize = usize::MAX;
+
+/// Индекс достижимости, построенный `Graph::reachability_index`: вершины сгруппированы в компоненты сильной
+/// связности, и для каждой компоненты хранится битовое множество достижимых из неё компонент.
+/// Для c компонент индекс занимает c² / 8 байт плюс таблицу компонент вершин,
+/// построение - O(n + m · c / 64), запрос - поиск двух вершин в хеш-таблице и проверка бита
+#[derive(Debug, Clone)]
+pub struct ReachabilityIndex<ID = DefaultGraphIdType> {
+    components: HashMap<ID, usize>,
+    /// Строки по `words_per_row` слов, бит `j` строки `i` - достижима ли компонента `j` из компоненты `i`
+    reachable: Vec<u64>,
+    words_per_row: usize,
+    /// `Graph::generation` на момент построения
+    generation: u64,
+}
+
+impl<ID: GraphId> ReachabilityIndex<ID> {
+    /// Достижима ли `to_id` из `from_id` на момент построения индекса. Вершина достижима сама из себя
+    pub fn reaches(&self, from_id: &ID, to_id: &ID) -> Result<bool, GraphError> {
+        let from_component = self.component(from_id)?;
+        let to_component = self.component(to_id)?;
+        let word = self.reachable[from_component * self.words_per_row + to_component / WORD_BITS];
+        Ok(word & (1 << (to_component % WORD_BITS)) != 0)
+    }
+
+    /// Менялся ли граф после построения индекса. Индекс устаревшего графа нужно построить заново
+    pub fn is_stale<VT, ET>(&self, graph: &Graph<VT, ET, ID>) -> bool {
+        self.generation != graph.generation
+    }
+
+    /// Количество компонент сильной связности (для неориентированного графа - компонент связности)
+    pub fn component_count(&self) -> usize {
+        self.reachable.len().checked_div(self.words_per_row).unwrap_or(0)
+    }
+
+    fn component(&self, id: &ID) -> Result<usize, GraphError> {
+        self.components.get(id)
+            .copied()
+            .ok_or_else(|| GraphError::VertexNotFound(id.to_string()))
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Индекс для многократных проверок достижимости в графе, который редко меняется, см. `ReachabilityIndex`
+    pub fn reachability_index(&self) -> ReachabilityIndex<ID> {
+        let ids: Vec<_> = self.vertices.keys().collect();
+        let adjacency = self.index_adjacency(&ids);
+        let (vertex_components, component_count) = strongly_connected_components(&adjacency);
+
+        // Алгоритм Тарьяна нумерует компоненты так, что компоненты, достижимые из данной, имеют меньшие номера,
+        // поэтому при проходе по возрастанию номеров множества всех преемников уже вычислены
+        let words_per_row = component_count.div_ceil(WORD_BITS);
+        let mut reachable = vec![0; component_count * words_per_row];
+        let mut members = vec![Vec::new(); component_count];
+        for (index, &component) in vertex_components.iter().enumerate() {
+            members[component].push(index);
+        }
+        for (component, component_members) in members.iter().enumerate() {
+            let (done_rows, rest) = reachable.split_at_mut(component * words_per_row);
+            let row = &mut rest[..words_per_row];
+            row[component / WORD_BITS] |= 1 << (component % WORD_BITS);
+            for &index in component_members {
+                for &neighbour_index in &adjacency[index] {
+                    let neighbour_component = vertex_components[neighbour_index];
+                    if neighbour_component != component && row[neighbour_component / WORD_BITS] & (1 << (neighbour_component % WORD_BITS)) == 0 {
+                        let neighbour_row = &done_rows[neighbour_component * words_per_row..][..words_per_row];
+                        for (word, neighbour_word) in row.iter_mut().zip(neighbour_row) {
+                            *word |= neighbour_word;
+                        }
+                    }
+                }
+            }
+        }
+
+        ReachabilityIndex {
+            components: ids.into_iter()
+                .zip(vertex_components)
+                .map(|(id, component)| (id.clone(), component))
+                .collect(),
+            reachable,
+            words_per_row,
+            generation: self.generation,
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Концы исходящих рёбер каждой вершины `ids` по индексам в `ids`. `ids` - все вершины графа
+    pub(crate) fn index_adjacency(&self, ids: &[&ID]) -> Vec<Vec<usize>> {
+        let indices: HashMap<_, _> = ids.iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        ids.iter()
+            .map(|&id| self.vertices[id].edge_directions
+                .iter()
+                .map(|edge_direction| indices[&edge_direction.to_vertex_id])
+                .collect())
+            .collect()
+    }
+}
+
+/// Компоненты сильной связности (алгоритм Тарьяна без рекурсии): номер компоненты каждой вершины и число компонент.
+/// Компонента получает номер, когда все достижимые из неё компоненты уже пронумерованы
+pub(crate) fn strongly_connected_components(adjacency: &[Vec<usize>]) -> (Vec<usize>, usize) {
+    let vertex_count = adjacency.len();
+    let mut order = vec![UNVISITED; vertex_count];
+    let mut low_links = vec![0; vertex_count];
+    let mut components = vec![UNVISITED; vertex_count];
+    let mut component_count = 0;
+    let mut visit_count = 0;
+    let mut stack = Vec::new();
+    // Вершина и номер следующего просматриваемого ребра
+    let mut call_stack = Vec::new();
+
+    for root in 0..vertex_count {
+        if order[root] != UNVISITED {
+            continue;
+        }
+        call_stack.push((root, 0));
+        while let Some(&mut (index, ref mut edge_position)) = call_stack.last_mut() {
+            if *edge_position == 0 {
+                order[index] = visit_count;
+                low_links[index] = visit_count;
+                visit_count += 1;
+                stack.push(index);
+            }
+            if let Some(&neighbour_index) = adjacency[index].get(*edge_position) {
+                *edge_position += 1;
+                if order[neighbour_index] == UNVISITED {
+                    call_stack.push((neighbour_index, 0));
+                } else if components[neighbour_index] == UNVISITED {
+                    low_links[index] = low_links[index].min(order[neighbour_index]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent_index, _)) = call_stack.last() {
+                low_links[parent_index] = low_links[parent_index].min(low_links[index]);
+            }
+            if low_links[index] == order[index] {
+                while let Some(member) = stack.pop() {
+                    components[member] = component_count;
+                    if member == index {
+                        break;
+                    }
+                }
+                component_count += 1;
+            }
+        }
+    }
+
+    (components, component_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::SplitMix64;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn assert_matches_bfs<VT, ET>(graph: &Graph<VT, ET>, index: &ReachabilityIndex) -> Result<()> {
+        for from_id in graph.vertices.keys() {
+            for to_id in graph.vertices.keys() {
+                if index.reaches(from_id, to_id)? != graph.is_reachable(*from_id, *to_id)? {
+                    bail!("reachability from {} to {} differs from bfs", from_id, to_id)
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn random_dag_matches_bfs() -> Result<()> {
+        const VERTEX_COUNT: u32 = 200;
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 0..VERTEX_COUNT {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        let mut random = SplitMix64::new(17);
+        for _ in 0..400 {
+            let (first, second) = (random.next_below(VERTEX_COUNT as u64) as u32, random.next_below(VERTEX_COUNT as u64) as u32);
+            if first != second && !graph.has_edge(first.min(second), first.max(second)) {
+                graph.add_edge(first.min(second), first.max(second), None)?;
+            }
+        }
+        let index = graph.reachability_index();
+        if index.component_count() != VERTEX_COUNT as usize {
+            bail!("dag has {} strongly connected components", index.component_count())
+        }
+        assert_matches_bfs(&graph, &index)
+    }
+
+    #[test]
+    fn cyclic_digraph_matches_bfs() -> Result<()> {
+        let graph = Graph::<(), ()>::gnp_random(80, 0.02, GraphType::Directed, 5)?;
+        let index = graph.reachability_index();
+        let undirected = Graph::<(), ()>::gnp_random(80, 0.02, GraphType::Undirected, 5)?;
+        let undirected_index = undirected.reachability_index();
+        if index.component_count() == 80 || undirected_index.component_count() != undirected.connected_components().len() {
+            bail!("unexpected component counts {} and {}", index.component_count(), undirected_index.component_count())
+        }
+        assert_matches_bfs(&graph, &index)?;
+        assert_matches_bfs(&undirected, &undirected_index)
+    }
+
+    #[test]
+    fn stale_index_and_unknown_ids() -> Result<()> {
+        let mut graph = Graph::<(), ()>::new(GraphType::Directed);
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        let index = graph.reachability_index();
+        let fresh = !index.is_stale(&graph);
+        graph.add_edge(2, 3, None)?;
+        if fresh
+            && index.is_stale(&graph)
+            && index.reaches(&1, &2)?
+            && !index.reaches(&1, &3)?
+            && graph.reachability_index().reaches(&1, &3)?
+            && index.reaches(&4, &1) == Err(GraphError::VertexNotFound("4".to_owned()))
+            && index.reaches(&1, &4).is_err() {
+            Ok(())
+        } else {
+            bail!("stale index or unknown id not reported")
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use super::*;
+
+/// Множество вершин, достижимых из начальной, для чередования изменений графа с проверками достижимости.
+/// Изменения через `add_edge_cached`, `delete_edge_cached` и `delete_vertex_cached` сразу проверяются:
+/// если они не могли изменить множество, кэш остаётся актуальным. Иначе, как и после любого другого
+/// изменения графа, кэш устаревает и пересчитывается обходом при следующем `refresh`
+#[derive(Debug, Clone)]
+pub struct ReachabilityCache<ID = DefaultGraphIdType> {
+    start_id: ID,
+    reachable: HashSet<ID>,
+    /// `Graph::generation`, для которого множество актуально
+    generation: u64,
+    recomputations: usize,
+}
+
+impl<ID: GraphId> ReachabilityCache<ID> {
+    /// Достижима ли вершина из начальной на момент последнего обновления кэша
+    pub fn is_reachable(&self, id: &ID) -> bool {
+        self.reachable.contains(id)
+    }
+
+    /// Менялся ли граф так, что множество достижимых вершин нужно пересчитать
+    pub fn is_stale<VT, ET>(&self, graph: &Graph<VT, ET, ID>) -> bool {
+        self.generation != graph.generation
+    }
+
+    /// Пересчитывает устаревший кэш. Возвращает, был ли выполнен обход.
+    /// Если начальная вершина удалена, достижимых вершин нет
+    pub fn refresh<VT, ET>(&mut self, graph: &Graph<VT, ET, ID>) -> bool {
+        if !self.is_stale(graph) {
+            return false;
+        }
+        self.reachable = graph.reachable_set(&self.start_id);
+        self.generation = graph.generation;
+        self.recomputations += 1;
+        true
+    }
+
+    /// Сколько раз `refresh` выполнил обход после построения кэша
+    pub fn recomputations(&self) -> usize {
+        self.recomputations
+    }
+
+    /// Оставляет кэш актуальным после изменения графа, если кэш был актуален до изменения,
+    /// а изменение не затрагивает множество достижимых вершин
+    fn acknowledge<VT, ET>(&mut self, graph: &Graph<VT, ET, ID>, generation_before: u64, affected: bool) {
+        if self.generation == generation_before && !affected {
+            self.generation = graph.generation;
+        }
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Кэш вершин, достижимых из `start_id`, см. `ReachabilityCache`
+    pub fn reachability_cache(&self, start_id: ID) -> Result<ReachabilityCache<ID>, GraphError> {
+        if !self.contains_vertex(&start_id) {
+            return Err(GraphError::VertexNotFound(start_id.to_string()));
+        }
+        Ok(ReachabilityCache {
+            reachable: self.reachable_set(&start_id),
+            start_id,
+            generation: self.generation,
+            recomputations: 0,
+        })
+    }
+
+    /// `add_edge` с проверкой кэша: множество достижимых вершин растёт, только если ребро ведёт из достижимой
+    /// вершины в недостижимую (для неориентированного графа - соединяет достижимую вершину с недостижимой)
+    pub fn add_edge_cached(&mut self, from_id: ID, to_id: ID, value: Option<ET>, cache: &mut ReachabilityCache<ID>) -> Result<(), GraphError> {
+        let (from_reachable, to_reachable) = (cache.is_reachable(&from_id), cache.is_reachable(&to_id));
+        let affected = match self.r#type {
+            GraphType::Directed => from_reachable && !to_reachable,
+            GraphType::Undirected => from_reachable != to_reachable,
+        };
+        let generation_before = self.generation;
+        self.add_edge(from_id, to_id, value)?;
+        cache.acknowledge(self, generation_before, affected);
+        Ok(())
+    }
+
+    /// `delete_edge` с проверкой кэша: удаление ребра из недостижимой вершины множество не меняет
+    pub fn delete_edge_cached(&mut self, from_id: ID, to_id: ID, cache: &mut ReachabilityCache<ID>) -> Result<(), GraphError> {
+        let affected = cache.is_reachable(&from_id) || cache.is_reachable(&to_id) && self.r#type == GraphType::Undirected;
+        let generation_before = self.generation;
+        self.delete_edge(from_id, to_id)?;
+        cache.acknowledge(self, generation_before, affected);
+        Ok(())
+    }
+
+    /// `delete_vertex` с проверкой кэша: в недостижимую вершину не ведут рёбра из достижимых,
+    /// поэтому её удаление множество не меняет
+    pub fn delete_vertex_cached(&mut self, vertex_id: ID, cache: &mut ReachabilityCache<ID>) -> Result<(), GraphError> {
+        let affected = cache.is_reachable(&vertex_id);
+        let generation_before = self.generation;
+        self.delete_vertex(vertex_id)?;
+        cache.acknowledge(self, generation_before, affected);
+        Ok(())
+    }
+
+    fn reachable_set(&self, start_id: &ID) -> HashSet<ID> {
+        let mut reachable = HashSet::new();
+        if self.contains_vertex(start_id) {
+            self.traverse(start_id.clone(), TraversalOrder::BreadthFirst, |vertex| {
+                reachable.insert(vertex.id.clone());
+                ControlFlow::Continue(())
+            }).expect("start vertex is checked");
+        }
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Цепочка 1 -> 2 -> 3 и отдельная цепочка 4 -> 5 -> 6
+    fn two_chains() -> Result<Graph<(), ()>> {
+        let mut graph = Graph::new(GraphType::Directed);
+        for id in 1..=6 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id) in [(1, 2), (2, 3), (4, 5), (5, 6)] {
+            graph.add_edge(from_id, to_id, None)?;
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn changes_outside_reachable_set_skip_recomputation() -> Result<()> {
+        let mut graph = two_chains()?;
+        let mut cache = graph.reachability_cache(1)?;
+        graph.delete_edge_cached(4, 5, &mut cache)?;
+        graph.add_edge_cached(6, 1, None, &mut cache)?;
+        graph.add_edge_cached(3, 1, None, &mut cache)?;
+        graph.delete_vertex_cached(5, &mut cache)?;
+        let skipped = !cache.is_stale(&graph) && !cache.refresh(&graph) && cache.recomputations() == 0;
+
+        graph.delete_edge_cached(1, 2, &mut cache)?;
+        let stale_after_delete = cache.is_stale(&graph);
+        let recomputed = cache.refresh(&graph) && cache.recomputations() == 1;
+        if skipped
+            && stale_after_delete
+            && recomputed
+            && cache.is_reachable(&1)
+            && !cache.is_reachable(&2)
+            && !cache.is_reachable(&6)
+            && graph.delete_edge_cached(1, 9, &mut cache).is_err()
+            && !cache.is_stale(&graph) {
+            Ok(())
+        } else {
+            bail!("unexpected recomputations: {}", cache.recomputations())
+        }
+    }
+
+    #[test]
+    fn growth_and_uncached_changes_recompute() -> Result<()> {
+        let mut graph = two_chains()?;
+        let mut cache = graph.reachability_cache(1)?;
+        graph.add_edge_cached(3, 4, None, &mut cache)?;
+        let grew = cache.refresh(&graph) && cache.is_reachable(&6);
+
+        // Изменение без кэша делает кэш устаревшим, даже если затем изменения идут через кэш
+        graph.delete_edge(5, 6)?;
+        graph.delete_edge_cached(2, 3, &mut cache)?;
+        graph.add_vertex(Vertex::new(7, None))?;
+        let stale = cache.is_stale(&graph);
+        cache.refresh(&graph);
+        let shrunk = !cache.is_reachable(&3) && !cache.is_reachable(&6);
+
+        graph.delete_vertex_cached(1, &mut cache)?;
+        cache.refresh(&graph);
+        if grew
+            && stale
+            && shrunk
+            && !cache.is_reachable(&2)
+            && cache.recomputations() == 3
+            && matches!(graph.reachability_cache(1), Err(GraphError::VertexNotFound(_))) {
+            Ok(())
+        } else {
+            bail!("cache not recomputed after growth or uncached change")
+        }
+    }
+}
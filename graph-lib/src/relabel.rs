@@ -0,0 +1,144 @@
+use std::mem;
+use super::*;
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Заменяет идентификатор каждой вершины на `mapping(id)`, обновляя все направления рёбер,
+    /// включая зеркальные, роли, атрибуты и закреплённые списки соседей. Если два идентификатора переходят в один,
+    /// возвращается `VertexAlreadyExist`, и граф не меняется
+    pub fn relabel<F: Fn(ID) -> ID>(&mut self, mapping: F) -> Result<(), GraphError> {
+        let mut new_ids = HashMap::with_capacity(self.vertices.len());
+        let mut used_ids = HashSet::with_capacity(self.vertices.len());
+        for id in self.vertices.keys() {
+            let new_id = mapping(id.clone());
+            if !used_ids.insert(new_id.clone()) {
+                return Err(GraphError::VertexAlreadyExist(new_id.to_string()));
+            }
+            new_ids.insert(id.clone(), new_id);
+        }
+
+        self.vertices = mem::take(&mut self.vertices)
+            .into_values()
+            .map(|mut vertex| {
+                vertex.id = new_ids[&vertex.id].clone();
+                for edge_direction in &mut vertex.edge_directions {
+                    edge_direction.to_vertex_id = new_ids[&edge_direction.to_vertex_id].clone();
+                }
+                vertex.rebuild_target_index(self.adaptive_threshold);
+                (vertex.id.clone(), vertex)
+            })
+            .collect();
+        self.predecessors = mem::take(&mut self.predecessors)
+            .into_iter()
+            .map(|(id, predecessor_ids)| (
+                new_ids[&id].clone(),
+                predecessor_ids.iter().map(|predecessor_id| new_ids[predecessor_id].clone()).collect(),
+            ))
+            .collect();
+        self.roles = mem::take(&mut self.roles)
+            .into_iter()
+            .map(|(id, role)| (new_ids[&id].clone(), role))
+            .collect();
+        self.attributes.relabel(&new_ids);
+        if let Some(value_ids) = self.value_ids.as_mut() {
+            for id in value_ids.ids.values_mut() {
+                if let Some(new_id) = new_ids.get(id) {
+                    *id = new_id.clone();
+                }
+            }
+        }
+        self.value_index = self.value_index.as_ref().map(|value_index| value_index.rebuilt(&self.vertices));
+        self.hot.relabel(&new_ids);
+        self.generation += 1;
+        // Патч удаляет вершины со старыми идентификаторами и заново записывает вершины с новыми
+        if let Some(changed_vertices) = self.changed_vertices.as_mut() {
+            for (id, new_id) in new_ids {
+                changed_vertices.insert(id);
+                changed_vertices.insert(new_id);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<VT, ET> Graph<VT, ET> {
+    /// Перенумеровывает вершины в `0..n` в порядке возрастания старых идентификаторов.
+    /// Возвращает соответствие старых идентификаторов новым
+    pub fn compact(&mut self) -> HashMap<DefaultGraphIdType, DefaultGraphIdType> {
+        let mut ids: Vec<_> = self.vertices.keys().copied().collect();
+        ids.sort_unstable();
+        let new_ids: HashMap<_, _> = ids.into_iter().zip(0..).collect();
+        self.relabel(|id| new_ids[&id])
+            .expect("compacted ids are distinct");
+        new_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufWriter;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const TGF_GRAPH: &str = "10 a\n20 b\n35 c\n47 d\n90 e\n#\n10 20 x\n20 35\n35 10 y\n47 47 loop\n90 35 z";
+
+    fn bfs_values(graph: &Graph<String, String>, start_id: DefaultGraphIdType) -> Result<Vec<(DefaultGraphIdType, Option<String>)>> {
+        Ok(graph.bfs(start_id)?
+            .into_iter()
+            .map(|(id, value, _)| (id, value.cloned()))
+            .collect())
+    }
+
+    #[test]
+    fn relabel_keeps_structure() -> Result<()> {
+        let mut graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let before = bfs_values(&graph, 10)?;
+        graph.pin_hot(&[10])?;
+        graph.relabel(|id| id * 3 + 1)?;
+        let mut pinned_neighbours = graph.neighbours(31)?;
+        pinned_neighbours.sort_unstable();
+        let after: Vec<_> = bfs_values(&graph, 31)?
+            .into_iter()
+            .map(|(id, value)| ((id - 1) / 3, value))
+            .collect();
+        let collision = graph.relabel(|id| id / 1000);
+        if before == after
+            && graph.neighbours(142)? == vec![142]
+            && pinned_neighbours == vec![61, 106]
+            && graph.hot_stats().pinned_vertices == 1
+            && graph.hot_stats().hits > 0
+            && matches!(collision, Err(GraphError::VertexAlreadyExist(id)) if id == "0")
+            && graph.vertex_value(271).map(String::as_str) == Some("e") {
+            Ok(())
+        } else {
+            bail!("relabelled bfs {:?} differs from {:?}", after, before)
+        }
+    }
+
+    #[test]
+    fn compact_serialization() -> Result<()> {
+        let mut graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        graph.delete_vertex(47)?;
+        let new_ids = graph.compact();
+
+        let mut writer = BufWriter::new(Vec::new());
+        graph.serialize(&mut writer)?;
+        let tgf = String::from_utf8(writer.into_inner()?)?;
+        let referenced_ids: HashSet<_> = tgf.lines()
+            .flat_map(|line| line.split(' ').take(2))
+            .filter_map(|id| id.parse::<DefaultGraphIdType>().ok())
+            .collect();
+        let reloaded = Graph::deserialize(tgf.as_bytes())?;
+        if new_ids == HashMap::from([(10, 0), (20, 1), (35, 2), (90, 3)])
+            && referenced_ids == HashSet::from([0, 1, 2, 3])
+            && reloaded == graph
+            && graph.neighbours(2)?.len() == 3 {
+            Ok(())
+        } else {
+            bail!("compacted graph still references old ids:\n{}", tgf)
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use super::*;
+
+/// Ошибки разбора с номерами строк (с единицы)
+pub type LineErrors = Vec<(usize, GraphError)>;
+
+impl Graph<String, String> {
+    /// Нестрогая десериализация - единственный способ чтения, который может потерять часть входных данных.
+    /// Строки с ошибками пропускаются, ошибки собираются в порядке появления, граф строится из всех остальных строк.
+    /// Ошибка чтения останавливает разбор: граф содержит строки, прочитанные до неё.
+    /// Граф не возвращается, только если чтение не удалось уже на первой строке.
+    /// Строгие `deserialize*` при любой ошибке не возвращают графа вовсе
+    pub fn deserialize_resilient<BR: BufRead>(reader: BR) -> (Option<Graph<String, String>>, Vec<GraphError>) {
+        let (graph, errors) = Graph::deserialize_diagnostics(reader);
+        (graph, errors.into_iter().map(|(_, error)| error).collect())
+    }
+
+    /// То же, что `deserialize_resilient`, но каждая ошибка сопровождается номером строки (с единицы)
+    pub fn deserialize_diagnostics<BR: BufRead>(reader: BR) -> (Option<Graph<String, String>>, LineErrors) {
+        let options = DeserializeOptions::default();
+        let mut graph = Graph::new(GraphType::Undirected);
+        let mut scan_state = ScanState::Vertex;
+        let mut errors = Vec::new();
+
+        for (line_index, line) in reader.lines().enumerate() {
+            let line_no = line_index + 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    errors.push((line_no, GraphError::from(error)));
+                    if line_index == 0 {
+                        return (None, errors);
+                    }
+                    break;
+                }
+            };
+            if let Err(error) = graph.deserialize_line(&mut scan_state, line.trim(), Some(line_no), &options) {
+                errors.push((line_no, error));
+            }
+        }
+
+        (Some(graph), errors)
+    }
+
+    /// Мягкая десериализация: пустые строки пропускаются молча, строки с ошибками - с предупреждением.
+    /// В отличие от `deserialize_resilient`, ошибка чтения прерывает загрузку целиком
+    pub fn deserialize_lenient<BR: BufRead>(reader: BR) -> Result<(Graph<String, String>, LineErrors), GraphError> {
+        let options = DeserializeOptions::default();
+        let mut graph = Graph::new(GraphType::Undirected);
+        let mut scan_state = ScanState::Vertex;
+        let mut warnings = Vec::new();
+
+        for (line_index, line) in reader.lines().enumerate() {
+            let line_no = line_index + 1;
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Err(error) = graph.deserialize_line(&mut scan_state, line, Some(line_no), &options) {
+                warnings.push((line_no, error));
+            }
+        }
+
+        Ok((graph, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{self, BufReader, Read};
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const TGF_GRAPH: &str = "1 January\n2 March\n3 April\n#\n1 2 spring\n2 3\n3 1 winter\n";
+
+    /// Отдаёт первые `fail_at` байт данных, затем возвращает ошибку чтения
+    struct FailingReader {
+        data: &'static [u8],
+        fail_at: usize,
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.fail_at == 0 {
+                return Err(io::Error::new(io::ErrorKind::ConnectionReset, "reader failed"));
+            }
+            // По одному байту, чтобы ошибка возникала ровно в заданном месте
+            let Some((&byte, rest)) = self.data.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = byte;
+            self.data = rest;
+            self.fail_at -= 1;
+            Ok(1)
+        }
+    }
+
+    fn failing_reader(fail_at: usize) -> BufReader<FailingReader> {
+        BufReader::new(FailingReader { data: TGF_GRAPH.as_bytes(), fail_at })
+    }
+
+    fn is_connection_reset(error: &GraphError) -> bool {
+        matches!(error, GraphError::SerializeGraph(error) if error.kind() == io::ErrorKind::ConnectionReset)
+    }
+
+    #[test]
+    fn io_failure_points() -> Result<()> {
+        let full_graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        for fail_at in 0..TGF_GRAPH.len() {
+            match Graph::deserialize(failing_reader(fail_at)) {
+                Err(error) if is_connection_reset(&error) => {}
+                result => bail!("strict deserialize at byte {}: {:?}", fail_at, result.map(|graph| graph.edges().len())),
+            }
+
+            let (graph, errors) = Graph::deserialize_resilient(failing_reader(fail_at));
+            let complete_lines = TGF_GRAPH[..fail_at].matches('\n').count();
+            let graph_is_prefix = match &graph {
+                None => complete_lines == 0,
+                Some(graph) => graph.vertex_count() <= full_graph.vertex_count()
+                    && graph.edge_count() <= full_graph.edge_count()
+                    && graph.vertex_count() + graph.edge_count() <= complete_lines,
+            };
+            if !(graph_is_prefix && matches!(errors.as_slice(), [error] if is_connection_reset(error))) {
+                bail!("resilient deserialize at byte {}: {:?}", fail_at, errors)
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bad_line_points() -> Result<()> {
+        let lines: Vec<_> = TGF_GRAPH.lines().collect();
+        for bad_index in (0..lines.len()).filter(|&index| lines[index] != "#") {
+            let mut bad_lines = lines.clone();
+            bad_lines[bad_index] = "x y z";
+            let tgf = bad_lines.join("\n");
+
+            if Graph::deserialize(tgf.as_bytes()).is_ok() {
+                bail!("strict deserialize accepted bad line {}", bad_index + 1)
+            }
+            let (graph, errors) = Graph::deserialize_resilient(tgf.as_bytes());
+            let Some(graph) = graph else {
+                bail!("resilient deserialize lost the graph for bad line {}", bad_index + 1)
+            };
+            // Пропущенная вершина тянет за собой два инцидентных ребра
+            let expected_errors = if bad_index < 3 { 3 } else { 1 };
+            let expected_size = if bad_index < 3 { (2, 1) } else { (3, 2) };
+            if errors.len() != expected_errors
+                || !matches!(errors[0], GraphError::WrongVertexIdType(_))
+                || (graph.vertex_count(), graph.edge_count()) != expected_size {
+                bail!("bad line {}: {:?}", bad_index + 1, errors)
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn diagnostics_line_numbers() -> Result<()> {
+        let (graph, errors) = Graph::deserialize_diagnostics("1 a\nx\n2 b\n#\n1 2\n1 9\n".as_bytes());
+        let lines: Vec<_> = errors.iter().map(|(line_no, _)| *line_no).collect();
+        if graph.is_some_and(|graph| graph.edge_count() == 1)
+            && lines == vec![2, 6]
+            && matches!(errors[1].1, GraphError::VertexNotFound(ref id) if id == "9") {
+            Ok(())
+        } else {
+            bail!("wrong diagnostics: {:?}", errors)
+        }
+    }
+
+    #[test]
+    fn strict_line_number() -> Result<()> {
+        match Graph::deserialize("1 a\n2 b\nfoo bar\n#\n1 2\n".as_bytes()) {
+            Err(GraphError::ParseLine { line_no: 3, source }) if matches!(*source, GraphError::WrongVertexIdType(_)) => Ok(()),
+            result => bail!("bad vertex on line 3 not reported: {:?}", result.map(|graph| graph.vertex_count())),
+        }
+    }
+
+    #[test]
+    fn lenient_skips_bad_and_blank_lines() -> Result<()> {
+        let (graph, warnings) = Graph::deserialize_lenient("1 a\n\nfoo bar\n2 b\n#\n\n1 2\n2 7\n".as_bytes())?;
+        let lines: Vec<_> = warnings.iter().map(|(line_no, _)| *line_no).collect();
+        if lines == vec![3, 8]
+            && matches!(warnings[0].1, GraphError::WrongVertexIdType(_))
+            && graph.vertex_count() == 2
+            && graph.has_edge(1, 2) {
+            Ok(())
+        } else {
+            bail!("wrong lenient warnings: {:?}", warnings)
+        }
+    }
+
+    #[test]
+    fn strict_and_resilient_agree_on_valid_input() -> Result<()> {
+        let strict_graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let (resilient_graph, errors) = Graph::deserialize_resilient(TGF_GRAPH.as_bytes());
+        if errors.is_empty() && resilient_graph.as_ref() == Some(&strict_graph) {
+            Ok(())
+        } else {
+            bail!("strict and resilient results differ on valid input: {:?}", errors)
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::{BTreeSet, HashMap};
+use super::*;
+
+/// Какие значения переименовывать
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewriteTargets {
+    Vertices,
+    Edges,
+    Both,
+}
+
+/// Результат переименования значений
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RewriteReport {
+    pub rewritten_vertex_values: usize,
+    pub rewritten_edge_values: usize,
+    /// Значения, которые есть в графе, но отсутствуют в словаре
+    pub unmapped_values: BTreeSet<String>,
+}
+
+impl<ID: GraphId> Graph<String, String, ID> {
+    /// Заменяет значения вершин и (или) рёбер по словарю. Значения, отсутствующие в словаре, не меняются
+    pub fn rewrite_values(&mut self, map: &HashMap<String, String>, targets: RewriteTargets) -> RewriteReport {
+        let mut report = RewriteReport::default();
+        let mut changed_vertices = self.changed_vertices.as_mut();
+
+        if let RewriteTargets::Vertices | RewriteTargets::Both = targets {
+            for vertex in self.vertices.values_mut() {
+                let Some(value) = vertex.value.as_mut() else {
+                    continue;
+                };
+                match map.get(value) {
+                    Some(new_value) => {
+                        *value = new_value.clone();
+                        report.rewritten_vertex_values += 1;
+                        if let Some(changed_vertices) = changed_vertices.as_mut() {
+                            changed_vertices.insert(vertex.id.clone());
+                        }
+                        if let Some(value_index) = self.value_index.as_mut() {
+                            value_index.mark_pending(&vertex.id);
+                        }
+                    }
+                    None => {
+                        report.unmapped_values.insert(value.clone());
+                    }
+                }
+            }
+            if let Some(value_index) = self.value_index.as_mut() {
+                value_index.refresh(&self.vertices);
+            }
+        }
+
+        if let RewriteTargets::Edges | RewriteTargets::Both = targets {
+            // Зеркальные копии рёбер должны получить то же новое значение, что и основное направление
+            let mut replaced_values = HashMap::new();
+            for (vertex_id, edge_direction) in self.vertices.values_mut()
+                .flat_map(|vertex| vertex.edge_directions.iter_mut().map(|edge_direction| (&vertex.id, edge_direction)))
+                .filter(|(_, edge_direction)| edge_direction.is_own()) {
+                let Some(value) = edge_direction.value.as_ref() else {
+                    continue;
+                };
+                match map.get(value) {
+                    Some(new_value) => {
+                        let new_value = Arc::new(Some(new_value.clone()));
+                        let old_value = std::mem::replace(&mut edge_direction.value, Arc::clone(&new_value));
+                        replaced_values.insert(Arc::as_ptr(&old_value), (old_value, new_value));
+                        report.rewritten_edge_values += 1;
+                        if let Some(changed_vertices) = changed_vertices.as_mut() {
+                            changed_vertices.insert(vertex_id.clone());
+                            changed_vertices.insert(edge_direction.to_vertex_id.clone());
+                        }
+                    }
+                    None => {
+                        report.unmapped_values.insert(value.clone());
+                    }
+                }
+            }
+            for edge_direction in self.vertices.values_mut().flat_map(|vertex| vertex.edge_directions.iter_mut()) {
+                if let Some((_, new_value)) = replaced_values.get(&Arc::as_ptr(&edge_direction.value)) {
+                    edge_direction.value = Arc::clone(new_value);
+                }
+            }
+        }
+
+        if report.rewritten_vertex_values + report.rewritten_edge_values > 0 {
+            self.generation += 1;
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const TGF_GRAPH: &str = "1 January
+2 March
+3 April
+#
+1 2 winter
+3 2 spring
+1 3 winter";
+
+    fn dictionary() -> HashMap<String, String> {
+        HashMap::from([
+            ("January".to_owned(), "Январь".to_owned()),
+            ("March".to_owned(), "Март".to_owned()),
+            ("winter".to_owned(), "зима".to_owned()),
+        ])
+    }
+
+    #[test]
+    fn rewrite_vertices_only() -> Result<()> {
+        let mut graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let report = graph.rewrite_values(&dictionary(), RewriteTargets::Vertices);
+        let edge_values: BTreeSet<_> = graph.edges()
+            .into_iter()
+            .filter_map(|(_, _, value)| value.cloned())
+            .collect();
+        if report.rewritten_vertex_values == 2
+            && report.rewritten_edge_values == 0
+            && report.unmapped_values == BTreeSet::from(["April".to_owned()])
+            && graph.vertices[&1].value.as_deref() == Some("Январь")
+            && graph.vertices[&3].value.as_deref() == Some("April")
+            && edge_values == BTreeSet::from(["spring".to_owned(), "winter".to_owned()]) {
+            Ok(())
+        } else {
+            bail!("wrong vertices-only rewrite: {:?}", report)
+        }
+    }
+
+    #[test]
+    fn rewrite_both() -> Result<()> {
+        let mut graph = Graph::deserialize(TGF_GRAPH.as_bytes())?;
+        let report = graph.rewrite_values(&dictionary(), RewriteTargets::Both);
+        let mirror_values: Vec<_> = graph.vertices[&2].edge_directions
+            .iter()
+            .filter_map(|edge_direction| edge_direction.value.as_ref().clone())
+            .collect();
+        if report.rewritten_vertex_values == 2
+            && report.rewritten_edge_values == 2
+            && report.unmapped_values == BTreeSet::from(["April".to_owned(), "spring".to_owned()])
+            && mirror_values.contains(&"зима".to_owned())
+            && mirror_values.contains(&"spring".to_owned()) {
+            Ok(())
+        } else {
+            bail!("wrong rewrite of both vertices and edges: {:?}", report)
+        }
+    }
+}
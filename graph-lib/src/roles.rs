@@ -0,0 +1,237 @@
+use std::fmt;
+use crate::distribution::DegreeKind;
+use super::*;
+
+/// Начало секции ролей вершин после рёбер TGF-файла, строки секции - `<id> <role>`
+pub(crate) const ROLES_MARKER: &str = "!roles";
+
+/// Роль вершины в графе-конвейере
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VertexRole {
+    /// Вершина без входящих рёбер
+    Source,
+    /// Вершина без исходящих рёбер
+    Sink,
+    /// Вершина, лежащая на пути от источника к стоку
+    Internal,
+}
+
+impl VertexRole {
+    fn name(self) -> &'static str {
+        match self {
+            VertexRole::Source => "source",
+            VertexRole::Sink => "sink",
+            VertexRole::Internal => "internal",
+        }
+    }
+}
+
+impl Display for VertexRole {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.name())
+    }
+}
+
+impl FromStr for VertexRole {
+    type Err = GraphError;
+
+    fn from_str(name: &str) -> Result<VertexRole, GraphError> {
+        [VertexRole::Source, VertexRole::Sink, VertexRole::Internal]
+            .into_iter()
+            .find(|role| role.name() == name)
+            .ok_or_else(|| GraphError::WrongRoleLine(name.to_owned()))
+    }
+}
+
+/// Нарушение ролей, найденное `Graph::validate_roles`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoleViolation<ID = DefaultGraphIdType> {
+    /// Роли проверяются только в ориентированном графе
+    UndirectedGraph,
+    SourceWithIncomingEdges { id: ID, in_degree: usize },
+    SinkWithOutgoingEdges { id: ID, out_degree: usize },
+    /// Внутренняя вершина не лежит ни на одном пути от источника к стоку
+    InternalOffPath(ID),
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Назначает вершине роль, возвращая предыдущую. Роль удаляется вместе с вершиной
+    pub fn set_role(&mut self, vertex_id: ID, role: VertexRole) -> Result<Option<VertexRole>, GraphError> {
+        if !self.contains_vertex(&vertex_id) {
+            return Err(GraphError::VertexNotFound(vertex_id.to_string()));
+        }
+        self.mark_changed(&vertex_id);
+        Ok(self.roles.insert(vertex_id, role))
+    }
+
+    /// Снимает роль с вершины, возвращая её
+    pub fn clear_role(&mut self, vertex_id: ID) -> Option<VertexRole> {
+        self.mark_changed(&vertex_id);
+        self.roles.remove(&vertex_id)
+    }
+
+    pub fn role(&self, vertex_id: ID) -> Option<VertexRole> {
+        self.roles.get(&vertex_id).copied()
+    }
+
+    /// Все вершины с ролями в произвольном порядке
+    pub fn roles(&self) -> Vec<(ID, VertexRole)> {
+        self.roles.iter()
+            .map(|(id, &role)| (id.clone(), role))
+            .collect()
+    }
+
+    /// Проверяет роли ориентированного графа: у источников нет входящих рёбер, у стоков - исходящих,
+    /// каждая внутренняя вершина достижима из какого-либо источника и из неё достижим какой-либо сток.
+    /// Порядок нарушений не определён
+    pub fn validate_roles(&self) -> Vec<RoleViolation<ID>> {
+        if let GraphType::Undirected = self.r#type {
+            return vec![RoleViolation::UndirectedGraph];
+        }
+        let in_degrees = self.degrees(DegreeKind::In);
+        let ids_with_role = |expected_role| self.roles.iter()
+            .filter(move |(_, &role)| role == expected_role)
+            .map(|(id, _)| id);
+        let reachable_from_sources = self.reachable_ids(ids_with_role(VertexRole::Source), false);
+        let reaching_sinks = self.reachable_ids(ids_with_role(VertexRole::Sink), true);
+
+        let mut violations = Vec::new();
+        for (id, role) in &self.roles {
+            let violation = match role {
+                VertexRole::Source => Some(in_degrees[id])
+                    .filter(|&in_degree| in_degree > 0)
+                    .map(|in_degree| RoleViolation::SourceWithIncomingEdges { id: id.clone(), in_degree }),
+                VertexRole::Sink => Some(self.vertices[id].edge_directions.len())
+                    .filter(|&out_degree| out_degree > 0)
+                    .map(|out_degree| RoleViolation::SinkWithOutgoingEdges { id: id.clone(), out_degree }),
+                VertexRole::Internal => (!reachable_from_sources.contains(id) || !reaching_sinks.contains(id))
+                    .then(|| RoleViolation::InternalOffPath(id.clone())),
+            };
+            violations.extend(violation);
+        }
+        violations
+    }
+
+    /// Вершины, достижимые из начальных (`backward` - вершины, из которых достижимы начальные)
+    fn reachable_ids<'a, I: Iterator<Item = &'a ID>>(&'a self, start_ids: I, backward: bool) -> HashSet<ID> {
+        let mut reachable_ids: HashSet<_> = start_ids.cloned().collect();
+        let mut pending_ids: Vec<_> = reachable_ids.iter().cloned().collect();
+        while let Some(id) = pending_ids.pop() {
+            let next_ids = if backward {
+                self.predecessor_ids(&id)
+            } else {
+                self.vertices[&id].edge_directions
+                    .iter()
+                    .map(|edge_direction| edge_direction.to_vertex_id.clone())
+                    .collect()
+            };
+            for next_id in next_ids {
+                if reachable_ids.insert(next_id.clone()) {
+                    pending_ids.push(next_id);
+                }
+            }
+        }
+        reachable_ids
+    }
+}
+
+impl<VT, ET, ID: GraphId + FromStr> Graph<VT, ET, ID> {
+    /// Применяет строку секции ролей `<id> <role>`
+    pub(crate) fn apply_role_line(&mut self, line: &str) -> Result<(), GraphError> {
+        let (id, role) = Graph::<VT, ET, ID>::parse_role_line(line)?;
+        self.set_role(id, role)?;
+        Ok(())
+    }
+
+    /// Разбирает строку секции ролей `<id> <role>`
+    pub(crate) fn parse_role_line(line: &str) -> Result<(ID, VertexRole), GraphError> {
+        let (id, role) = line.split_once(DATA_DELIMITER)
+            .ok_or_else(|| GraphError::WrongRoleLine(line.to_owned()))?;
+        let id = id.parse::<ID>()
+            .map_err(|_| GraphError::WrongVertexIdType(line.to_owned()))?;
+        let role = role.trim().parse()
+            .map_err(|_| GraphError::WrongRoleLine(line.to_owned()))?;
+        Ok((id, role))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufWriter;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Конвейер 1 -> 2 -> 3 -> 5 и 1 -> 4 -> 5: источник 1, сток 5
+    fn pipeline() -> Result<Graph<String, String>> {
+        let mut graph = Graph::new(GraphType::Directed);
+        for id in 1..=6 {
+            graph.add_vertex(Vertex::new(id, Some(format!("step{}", id))))?;
+        }
+        for (from_id, to_id) in [(1, 2), (2, 3), (3, 5), (1, 4), (4, 5)] {
+            graph.add_edge(from_id, to_id, None)?;
+        }
+        graph.set_role(1, VertexRole::Source)?;
+        graph.set_role(5, VertexRole::Sink)?;
+        for id in 2..=4 {
+            graph.set_role(id, VertexRole::Internal)?;
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn valid_pipeline() -> Result<()> {
+        let graph = pipeline()?;
+        let violations = graph.validate_roles();
+        if violations.is_empty() && graph.roles().len() == 5 {
+            Ok(())
+        } else {
+            bail!("valid pipeline flagged: {:?}", violations)
+        }
+    }
+
+    #[test]
+    fn role_violations() -> Result<()> {
+        let mut graph = pipeline()?;
+        graph.add_edge(3, 1, None)?;
+        graph.set_role(6, VertexRole::Internal)?;
+        let mut violations = graph.validate_roles();
+        violations.sort_by_key(|violation| format!("{:?}", violation));
+        graph.delete_vertex(6)?;
+        if violations == vec![
+            RoleViolation::InternalOffPath(6),
+            RoleViolation::SourceWithIncomingEdges { id: 1, in_degree: 1 },
+        ] && graph.role(6).is_none()
+            && graph.roles().len() == 5 {
+            Ok(())
+        } else {
+            bail!("wrong role violations: {:?}", violations)
+        }
+    }
+
+    #[test]
+    fn roles_round_trip() -> Result<()> {
+        let graph = pipeline()?;
+        let mut writer = BufWriter::new(Vec::new());
+        graph.serialize(&mut writer)?;
+        let tgf = String::from_utf8(writer.into_inner()?)?;
+        let reloaded = Graph::deserialize(tgf.as_bytes())?;
+        let mut roles = reloaded.roles();
+        roles.sort_by_key(|&(id, _)| id);
+        let wrong_role = Graph::deserialize("1\n#\n!roles\n1 producer".as_bytes());
+        if roles == vec![
+            (1, VertexRole::Source),
+            (2, VertexRole::Internal),
+            (3, VertexRole::Internal),
+            (4, VertexRole::Internal),
+            (5, VertexRole::Sink),
+        ] && reloaded.edge_count() == 5
+            && matches!(wrong_role, Err(GraphError::ParseLine { line_no: 4, source }) if matches!(*source, GraphError::WrongRoleLine(_))) {
+            Ok(())
+        } else {
+            bail!("roles lost in round trip:\n{}", tgf)
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::reachability::strongly_connected_components;
+use super::*;
+
+/// Компоненты сильной связности, см. `Graph::strongly_connected_components`. Каждая компонента идёт после
+/// всех компонент, достижимых из неё, вершины компоненты - по возрастанию идентификатора
+pub type StronglyConnectedComponents<ID = DefaultGraphIdType> = Vec<Vec<ID>>;
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Компоненты сильной связности (алгоритм Тарьяна без рекурсии): вершины, попарно достижимые друг из друга.
+    /// Для графа зависимостей зависимости перечисляются раньше зависящих от них вершин.
+    /// В неориентированном графе компоненты совпадают с компонентами связности
+    pub fn strongly_connected_components(&self) -> StronglyConnectedComponents<ID> {
+        self.group_components(strongly_connected_components)
+    }
+
+    /// Те же компоненты алгоритмом Косарайю: два обхода в глубину без рекурсии, по рёбрам графа и по обратным рёбрам.
+    /// Глубина обхода ограничена только памятью, как и у `strongly_connected_components`. Компоненты, не достижимые
+    /// друг из друга, могут идти в другом порядке
+    pub fn scc_kosaraju(&self) -> StronglyConnectedComponents<ID> {
+        self.group_components(kosaraju)
+    }
+
+    /// Вершины по компонентам, найденным `find_components` по индексам вершин в порядке возрастания идентификатора
+    fn group_components<F>(&self, find_components: F) -> StronglyConnectedComponents<ID>
+    where
+        F: Fn(&[Vec<usize>]) -> (Vec<usize>, usize),
+    {
+        let mut ids: Vec<_> = self.vertices.keys().collect();
+        ids.sort_unstable();
+        let (vertex_components, component_count) = find_components(&self.index_adjacency(&ids));
+        let mut components = vec![Vec::new(); component_count];
+        for (id, component) in ids.into_iter().zip(vertex_components) {
+            components[component].push(id.clone());
+        }
+        components
+    }
+}
+
+/// Компоненты сильной связности алгоритмом Косарайю: номер компоненты каждой вершины и число компонент.
+/// Нумерация как у `strongly_connected_components`: компоненты, достижимые из данной, имеют меньшие номера
+fn kosaraju(adjacency: &[Vec<usize>]) -> (Vec<usize>, usize) {
+    let vertex_count = adjacency.len();
+    let mut visited = vec![false; vertex_count];
+    // Вершины в порядке завершения обхода
+    let mut finished = Vec::with_capacity(vertex_count);
+    // Вершина и номер следующего просматриваемого ребра
+    let mut call_stack = Vec::new();
+    for root in 0..vertex_count {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        call_stack.push((root, 0));
+        while let Some(&mut (index, ref mut edge_position)) = call_stack.last_mut() {
+            if let Some(&neighbour_index) = adjacency[index].get(*edge_position) {
+                *edge_position += 1;
+                if !visited[neighbour_index] {
+                    visited[neighbour_index] = true;
+                    call_stack.push((neighbour_index, 0));
+                }
+            } else {
+                call_stack.pop();
+                finished.push(index);
+            }
+        }
+    }
+
+    let mut reversed = vec![Vec::new(); vertex_count];
+    for (index, neighbours) in adjacency.iter().enumerate() {
+        for &neighbour_index in neighbours {
+            reversed[neighbour_index].push(index);
+        }
+    }
+    // Обход обратных рёбер от последних завершённых вершин находит компоненты от истоков к стокам
+    let mut components = vec![usize::MAX; vertex_count];
+    let mut component_count = 0;
+    let mut stack = Vec::new();
+    for &root in finished.iter().rev() {
+        if components[root] != usize::MAX {
+            continue;
+        }
+        components[root] = component_count;
+        stack.push(root);
+        while let Some(index) = stack.pop() {
+            for &neighbour_index in &reversed[index] {
+                if components[neighbour_index] == usize::MAX {
+                    components[neighbour_index] = component_count;
+                    stack.push(neighbour_index);
+                }
+            }
+        }
+        component_count += 1;
+    }
+    for component in &mut components {
+        *component = component_count - 1 - *component;
+    }
+    (components, component_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Каждое ребро ведёт в ту же или более раннюю компоненту
+    fn dependencies_first(graph: &Graph<(), ()>, components: &StronglyConnectedComponents) -> bool {
+        let positions: HashMap<_, _> = components.iter()
+            .enumerate()
+            .flat_map(|(position, component)| component.iter().map(move |&id| (id, position)))
+            .collect();
+        positions.len() == graph.vertex_count()
+            && graph.edges().into_iter().all(|(from_id, to_id, _)| positions[&to_id] <= positions[&from_id])
+    }
+
+    #[test]
+    fn dependency_cycles() -> Result<()> {
+        let graph = Graph::deserialize_with_options(
+            "1 app\n2 core\n3 io\n4 net\n5 tls\n6 log\n#\n1 2\n2 3\n3 2\n1 4\n4 5\n5 4\n4 3\n3 6\n".as_bytes(),
+            DeserializeOptions { directed: true, ..DeserializeOptions::default() },
+        )?;
+        let components = graph.strongly_connected_components();
+        let undirected_components = Graph::<(), ()>::path(3).strongly_connected_components();
+        let position = |id| components.iter().position(|component| component.contains(&id));
+        if components.len() == 4
+            && components.contains(&vec![2, 3])
+            && components.contains(&vec![4, 5])
+            && position(6) < position(2)
+            && position(2) < position(4)
+            && position(4) < position(1)
+            && graph.scc_kosaraju() == components
+            && undirected_components == vec![vec![0, 1, 2]] {
+            Ok(())
+        } else {
+            bail!("wrong strongly connected components {:?}", components)
+        }
+    }
+
+    #[test]
+    fn kosaraju_matches_tarjan() -> Result<()> {
+        for seed in 1..=5 {
+            let graph = Graph::<(), ()>::gnp_random(60, 0.03, GraphType::Directed, seed)?;
+            let tarjan = graph.strongly_connected_components();
+            let kosaraju = graph.scc_kosaraju();
+            let mut tarjan_sorted = tarjan.clone();
+            let mut kosaraju_sorted = kosaraju.clone();
+            tarjan_sorted.sort_unstable();
+            kosaraju_sorted.sort_unstable();
+            if tarjan_sorted != kosaraju_sorted || !dependencies_first(&graph, &tarjan) || !dependencies_first(&graph, &kosaraju) {
+                bail!("components differ for seed {}: {:?} and {:?}", seed, tarjan, kosaraju)
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn kosaraju_deep_cycle() -> Result<()> {
+        const CYCLE_LEN: u32 = 200_000;
+        let mut graph = Graph::<(), ()>::with_capacity(GraphType::Directed, CYCLE_LEN as usize);
+        for id in 0..CYCLE_LEN {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for id in 0..CYCLE_LEN {
+            graph.add_edge(id, (id + 1) % CYCLE_LEN, None)?;
+        }
+        graph.add_vertex(Vertex::new(CYCLE_LEN, None))?;
+        graph.add_edge(CYCLE_LEN, 0, None)?;
+        let components = graph.scc_kosaraju();
+        if components.len() == 2 && components[0].len() == CYCLE_LEN as usize && components[1] == vec![CYCLE_LEN] {
+            Ok(())
+        } else {
+            bail!("deep cycle split into {} components", components.len())
+        }
+    }
+}
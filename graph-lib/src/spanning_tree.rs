@@ -0,0 +1,234 @@
+use std::collections::{BinaryHeap, HashMap};
+use crate::distances::QueueEntry;
+use crate::utils::UnionFind;
+use super::*;
+
+/// Ребро остовного дерева: концы ребра и его вес
+pub type WeightedEdge<ID = DefaultGraphIdType> = (ID, ID, f64);
+
+/// Ребро остовного дерева со ссылками на концы и значение ребра в исходном графе
+type SpanningEdge<'a, ET, ID> = (&'a ID, &'a ID, f64, &'a Option<ET>);
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Минимальное остовное дерево неориентированного графа (алгоритм Краскала).
+    /// Для несвязного графа возвращается минимальный остовный лес. Рёбра без значения весят 1.0.
+    /// Рёбра равного веса рассматриваются в порядке идентификаторов концов, поэтому результат детерминирован
+    pub fn minimum_spanning_tree<W: Fn(&ET) -> f64>(&self, weight: W) -> Result<Vec<WeightedEdge<ID>>, GraphError> {
+        Ok(self.spanning_edges(weight)?
+            .into_iter()
+            .map(|(from_id, to_id, edge_weight, _)| (from_id.clone(), to_id.clone(), edge_weight))
+            .collect())
+    }
+
+    /// Рёбра минимального остовного леса со значениями, концы ребра - по возрастанию
+    fn spanning_edges<W: Fn(&ET) -> f64>(&self, weight: W) -> Result<Vec<SpanningEdge<'_, ET, ID>>, GraphError> {
+        if let GraphType::Directed = self.r#type {
+            return Err(GraphError::WrongGraphType { expected: GraphType::Undirected.name(), actual: self.r#type.name() });
+        }
+
+        let indices: HashMap<&ID, usize> = self.vertices.keys()
+            .enumerate()
+            .map(|(index, id)| (id, index))
+            .collect();
+        let mut edges: Vec<_> = self.logical_edges()
+            .map(|(from_id, edge_direction)| {
+                let value = edge_direction.value.as_ref();
+                let edge_weight = value.as_ref().map_or(DEFAULT_EDGE_COST, &weight);
+                let to_id = &edge_direction.to_vertex_id;
+                (from_id.min(to_id), from_id.max(to_id), edge_weight, value)
+            })
+            .collect();
+        edges.sort_by(|first, second| first.2.total_cmp(&second.2)
+            .then_with(|| first.0.cmp(second.0))
+            .then_with(|| first.1.cmp(second.1)));
+
+        let mut components = UnionFind::new(indices.len());
+        let mut tree = Vec::with_capacity(indices.len().saturating_sub(1));
+        for edge in edges {
+            if components.union(indices[edge.0], indices[edge.1]) {
+                tree.push(edge);
+            }
+        }
+
+        Ok(tree)
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Минимальное остовное дерево компоненты связности `start_id` (алгоритм Прима с очередью с приоритетом,
+    /// O(m log n)), выгоднее `minimum_spanning_tree` на плотных графах. Вес ребра - `weight` от его значения,
+    /// как в `dijkstra`, рёбра без значения весят 1.0. Результат - родитель каждой вершины дерева, кроме `start_id`.
+    /// Ориентированный граф - ошибка `WrongGraphType`
+    pub fn mst_prim<W: Fn(&ET) -> f64>(&self, start_id: ID, weight: W) -> Result<HashMap<ID, ID>, GraphError> {
+        if let GraphType::Directed = self.r#type {
+            return Err(GraphError::WrongGraphType { expected: GraphType::Undirected.name(), actual: self.r#type.name() });
+        }
+        let (ids, adjacency) = self.weighted_adjacency(&weight);
+        let start = ids.iter()
+            .position(|&id| *id == start_id)
+            .ok_or_else(|| GraphError::VertexNotFound(start_id.to_string()))?;
+
+        // Вес самого лёгкого ребра от дерева до вершины и его конец в дереве
+        let mut edge_weights = vec![f64::INFINITY; ids.len()];
+        let mut parents = vec![None; ids.len()];
+        let mut in_tree = vec![false; ids.len()];
+        edge_weights[start] = 0.0;
+        let mut queue = BinaryHeap::from([QueueEntry { distance: 0.0, index: start }]);
+        while let Some(QueueEntry { index, .. }) = queue.pop() {
+            // Устаревшая запись: вершина уже добавлена в дерево более лёгким ребром
+            if in_tree[index] {
+                continue;
+            }
+            in_tree[index] = true;
+            for &(neighbour_index, edge_weight) in &adjacency[index] {
+                if !in_tree[neighbour_index] && edge_weight < edge_weights[neighbour_index] {
+                    edge_weights[neighbour_index] = edge_weight;
+                    parents[neighbour_index] = Some(index);
+                    queue.push(QueueEntry { distance: edge_weight, index: neighbour_index });
+                }
+            }
+        }
+
+        Ok(parents.into_iter()
+            .enumerate()
+            .filter_map(|(index, parent)| Some((ids[index].clone(), ids[parent?].clone())))
+            .collect())
+    }
+}
+
+impl<VT: Clone, ET: Clone, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Минимальное остовное дерево (лес), см. `minimum_spanning_tree`, в виде графа: все вершины графа
+    /// и только рёбра дерева с их значениями. Режим мультиграфа, ограничения, роли и атрибуты вершин
+    /// и оставшихся рёбер сохраняются
+    pub fn minimum_spanning_tree_graph<W: Fn(&ET) -> f64>(&self, weight: W) -> Result<Graph<VT, ET, ID>, GraphError> {
+        let mut graph = Graph {
+            multigraph: self.multigraph,
+            limits: self.limits,
+            ..Graph::new(self.r#type)
+        };
+        for (id, vertex) in &self.vertices {
+            graph.vertices.insert(id.clone(), Vertex::new(id.clone(), vertex.value.clone()));
+        }
+        graph.roles = self.roles.clone();
+        for (from_id, to_id, _, value) in self.spanning_edges(weight)? {
+            graph.add_edge(from_id.clone(), to_id.clone(), value.clone())
+                .expect("spanning tree edges connect graph vertices and fit the same limits");
+        }
+        graph.attributes = self.attributes.retained_in(&graph);
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn weighted_graph(vertex_count: u32, edges: &[(u32, u32, f64)]) -> Result<Graph<(), f64>> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for id in 1..=vertex_count {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for &(from_id, to_id, weight) in edges {
+            graph.add_edge(from_id, to_id, Some(weight))?;
+        }
+        Ok(graph)
+    }
+
+    fn total_weight(tree: &[WeightedEdge]) -> f64 {
+        tree.iter().map(|&(_, _, weight)| weight).sum()
+    }
+
+    #[test]
+    fn known_mst_weight() -> Result<()> {
+        let graph = weighted_graph(5, &[
+            (1, 2, 2.0), (1, 4, 6.0), (2, 3, 3.0), (2, 4, 8.0),
+            (2, 5, 5.0), (3, 5, 7.0), (4, 5, 9.0),
+        ])?;
+        let tree = graph.minimum_spanning_tree(|&weight| weight)?;
+        if tree.len() == 4 && total_weight(&tree) == 16.0 {
+            Ok(())
+        } else {
+            bail!("wrong minimum spanning tree {:?}", tree)
+        }
+    }
+
+    #[test]
+    fn equal_weights_tie_breaking() -> Result<()> {
+        // Квадрат с диагональю: все рёбра одного веса, дерево выбирается по идентификаторам концов
+        let graph = weighted_graph(4, &[(2, 1, 1.0), (3, 2, 1.0), (4, 3, 1.0), (1, 4, 1.0), (1, 3, 1.0)])?;
+        let tree = graph.minimum_spanning_tree(|&weight| weight)?;
+        if tree == vec![(1, 2, 1.0), (1, 3, 1.0), (1, 4, 1.0)] {
+            Ok(())
+        } else {
+            bail!("equal weight edges chosen out of id order: {:?}", tree)
+        }
+    }
+
+    #[test]
+    fn spanning_tree_graph() -> Result<()> {
+        let mut graph = Graph::<String, String>::deserialize("1 a\n2 b\n3 c\n4 d\n#\n1 2 4\n2 3 1\n1 3 2\n3 4 7\n".as_bytes())?;
+        graph.set_edge_attr(1, 2, "kind", "road".to_owned())?;
+        graph.set_edge_attr(3, 4, "kind", "bridge".to_owned())?;
+        let tree = graph.minimum_spanning_tree_graph(|value| value.parse().unwrap_or(f64::INFINITY))?;
+        let mut edges = tree.edges();
+        edges.sort_unstable();
+        if edges == vec![(1, 3, Some(&"2".to_owned())), (2, 3, Some(&"1".to_owned())), (3, 4, Some(&"7".to_owned()))]
+            && tree.vertex_value(4) == Some(&"d".to_owned())
+            && tree.edge_attr(1, 2, "kind").is_none()
+            && tree.edge_attr(3, 4, "kind") == Some("bridge")
+            && graph.edge_count() == 4 {
+            Ok(())
+        } else {
+            bail!("wrong spanning tree graph {:?}", edges)
+        }
+    }
+
+    #[test]
+    fn prim_matches_kruskal() -> Result<()> {
+        let mut graph = weighted_graph(7, &[
+            (1, 2, 7.0), (1, 4, 5.0), (2, 3, 8.0), (2, 4, 9.0), (2, 5, 7.5), (3, 5, 5.5),
+            (4, 5, 15.0), (4, 6, 6.0), (5, 6, 8.5), (5, 7, 9.5), (6, 7, 11.0),
+        ])?;
+        graph.add_vertex(Vertex::new(8, None))?;
+        let parents = graph.mst_prim(4, |&weight| weight)?;
+        let mut prim_edges: Vec<_> = parents.iter()
+            .map(|(&id, &parent_id)| (id.min(parent_id), id.max(parent_id)))
+            .collect();
+        prim_edges.sort_unstable();
+        let mut kruskal_edges: Vec<_> = graph.minimum_spanning_tree(|&weight| weight)?
+            .into_iter()
+            .map(|(from_id, to_id, _)| (from_id, to_id))
+            .collect();
+        kruskal_edges.sort_unstable();
+        if prim_edges == kruskal_edges
+            && !parents.contains_key(&4)
+            && parents[&1] == 4
+            && graph.mst_prim(8, |&weight| weight)?.is_empty()
+            && matches!(graph.mst_prim(9, |&weight| weight), Err(GraphError::VertexNotFound(_)))
+            && Graph::<(), f64>::new(GraphType::Directed).mst_prim(1, |&weight| weight).is_err() {
+            Ok(())
+        } else {
+            bail!("Prim tree {:?} differs from Kruskal tree {:?}", prim_edges, kruskal_edges)
+        }
+    }
+
+    #[test]
+    fn spanning_forest() -> Result<()> {
+        let mut graph = weighted_graph(6, &[(1, 2, 1.0), (2, 3, 2.0), (1, 3, 3.0), (4, 5, 4.0)])?;
+        graph.add_edge(5, 6, None)?;
+        graph.add_edge(6, 6, Some(0.0))?;
+        let tree = graph.minimum_spanning_tree(|&weight| weight)?;
+        let directed_result = Graph::<(), f64>::new(GraphType::Directed).minimum_spanning_tree(|&weight| weight);
+        if tree.len() == 4
+            && total_weight(&tree) == 8.0
+            && directed_result == Err(GraphError::WrongGraphType { expected: "undirected", actual: "directed" }) {
+            Ok(())
+        } else {
+            bail!("wrong minimum spanning forest {:?}", tree)
+        }
+    }
+}
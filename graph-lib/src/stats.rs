@@ -0,0 +1,90 @@
+use std::fmt;
+use super::*;
+
+/// Сводка по графу, см. `Graph::stats`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub vertices: usize,
+    /// Число рёбер, ребро неориентированного графа считается один раз
+    pub edges: usize,
+    pub self_loops: usize,
+    /// Вершины без рёбер. Вершина с одной лишь петлёй изолированной не считается
+    pub isolated_vertices: usize,
+    /// Наименьшая, наибольшая и средняя степень без учёта направления, как в `Graph::degree`.
+    /// У пустого графа все три равны нулю
+    pub min_degree: usize,
+    pub max_degree: usize,
+    pub average_degree: f64,
+    /// Компоненты связности без учёта направления рёбер
+    pub components: usize,
+    pub largest_component: usize,
+    /// Нет ли в ориентированном графе циклов (включая петли). `None` для неориентированного графа
+    pub acyclic: Option<bool>,
+}
+
+impl Display for GraphStats {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(formatter, "vertices: {}", self.vertices)?;
+        writeln!(formatter, "edges: {}", self.edges)?;
+        writeln!(formatter, "self-loops: {}", self.self_loops)?;
+        writeln!(formatter, "isolated vertices: {}", self.isolated_vertices)?;
+        writeln!(formatter, "degree: min {}, max {}, average {:.2}", self.min_degree, self.max_degree, self.average_degree)?;
+        writeln!(formatter, "components: {}", self.components)?;
+        writeln!(formatter, "largest component: {}", self.largest_component)?;
+        if let Some(acyclic) = self.acyclic {
+            writeln!(formatter, "dag: {}", if acyclic { "yes" } else { "no" })?;
+        }
+        Ok(())
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Сводка по графу: размеры, петли, изолированные вершины, степени, компоненты связности
+    /// и, для ориентированного графа, отсутствие циклов
+    pub fn stats(&self) -> GraphStats {
+        let degree_stats = self.degree_stats(DegreeKind::Undirected);
+        let self_loops = self.logical_edges()
+            .filter(|(from_id, edge_direction)| **from_id == edge_direction.to_vertex_id)
+            .count();
+        let components = self.connected_components();
+
+        GraphStats {
+            vertices: self.vertices.len(),
+            edges: self.edge_count,
+            self_loops,
+            isolated_vertices: degree_stats.histogram.get(&0).copied().unwrap_or(0),
+            min_degree: degree_stats.min,
+            max_degree: degree_stats.max,
+            average_degree: degree_stats.mean,
+            components: components.len(),
+            largest_component: components.first().map_or(0, Vec::len),
+            acyclic: match self.r#type {
+                GraphType::Directed => Some(self.is_acyclic()),
+                GraphType::Undirected => None,
+            },
+        }
+    }
+
+    /// Алгоритм Кана: граф ацикличен, если удаление вершин без входящих рёбер удаляет все вершины
+    fn is_acyclic(&self) -> bool {
+        let mut in_degrees = self.degrees(DegreeKind::In);
+        let mut queue: VecDeque<&ID> = in_degrees.iter()
+            .filter(|(_, &in_degree)| in_degree == 0)
+            .map(|(&id, _)| id)
+            .collect();
+        let mut removed = 0;
+        while let Some(id) = queue.pop_front() {
+            removed += 1;
+            for edge_direction in &self.vertices[id].edge_directions {
+                let to_id = &edge_direction.to_vertex_id;
+                if let Some(in_degree) = in_degrees.get_mut(to_id) {
+                    *in_degree -= 1;
+                    if *in_degree == 0 {
+                        queue.push_back(to_id);
+                    }
+                }
+            }
+        }
+        removed == self.vertices.len()
+    }
+}
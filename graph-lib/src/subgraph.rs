@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+use super::*;
+
+impl<VT: Clone, ET: Clone, ID: GraphId> Graph<VT, ET, ID> {
+    /// Порождённый подграф: перечисленные вершины и рёбра, оба конца которых среди них.
+    /// Идентификаторы, которых нет в графе, пропускаются. Тип графа, режим мультиграфа, ограничения, роли вершин и атрибуты сохраняются
+    pub fn subgraph(&self, vertex_ids: &HashSet<ID>) -> Graph<VT, ET, ID> {
+        self.subgraph_filter(|id, _| vertex_ids.contains(&id))
+    }
+
+    /// Порождённый подграф на вершинах, для которых `keep` вернул `true`
+    pub fn subgraph_filter<F>(&self, keep: F) -> Graph<VT, ET, ID>
+    where
+        F: Fn(ID, Option<&VT>) -> bool,
+    {
+        let mut graph = Graph {
+            multigraph: self.multigraph,
+            dag: self.dag,
+            limits: self.limits,
+            ..Graph::new(self.r#type)
+        };
+        for (id, vertex) in &self.vertices {
+            if keep(id.clone(), vertex.value.as_ref()) {
+                graph.vertices.insert(id.clone(), Vertex::new(id.clone(), vertex.value.clone()));
+                if let Some(&role) = self.roles.get(id) {
+                    graph.roles.insert(id.clone(), role);
+                }
+            }
+        }
+        // Зеркальные направления не копируются, а создаются заново при добавлении основного
+        for (from_id, edge_direction) in self.logical_edges() {
+            if graph.contains_vertex(from_id) && graph.contains_vertex(&edge_direction.to_vertex_id) {
+                graph.add_edge(from_id.clone(), edge_direction.to_vertex_id.clone(), edge_direction.value.as_ref().clone())
+                    .expect("subgraph edges connect kept vertices and fit the same limits");
+            }
+        }
+        graph.attributes = self.attributes.retained_in(&graph);
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    #[test]
+    fn induced_triangle_edge() -> Result<()> {
+        let graph = Graph::deserialize("1 a\n2 b\n3 c\n#\n1 2 x\n2 3 y\n3 1 z".as_bytes())?;
+        let subgraph = graph.subgraph(&HashSet::from([1, 3, 7]));
+        let filtered = graph.subgraph_filter(|_, value| value.map(String::as_str) != Some("a"));
+        let mirror_values: Vec<_> = subgraph.vertices[&1].edge_directions
+            .iter()
+            .map(|edge_direction| (edge_direction.r#type, edge_direction.value.as_ref().clone()))
+            .collect();
+        if subgraph.vertex_count() == 2
+            && subgraph.edge_count() == 1
+            && matches!(mirror_values.as_slice(), [(EdgeDirectionType::Weak, Some(value))] if value == "z")
+            && filtered.edges() == vec![(2, 3, Some(&"y".to_owned()))] {
+            Ok(())
+        } else {
+            bail!("wrong induced subgraph: {:?}", subgraph.edges())
+        }
+    }
+}
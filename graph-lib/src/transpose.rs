@@ -0,0 +1,95 @@
+use super::*;
+
+impl<VT: Clone, ET: Clone, ID: GraphId> Graph<VT, ET, ID> {
+    /// Граф с обращёнными рёбрами. Неориентированный граф копируется без изменений.
+    /// Тип графа, режим мультиграфа и ограничения сохраняются, источники и стоки меняются ролями,
+    /// атрибуты рёбер переходят к обращённым рёбрам
+    pub fn transpose(&self) -> Graph<VT, ET, ID> {
+        let mut graph = Graph {
+            multigraph: self.multigraph,
+            dag: self.dag,
+            limits: self.limits,
+            ..Graph::new(self.r#type)
+        };
+        for (id, vertex) in &self.vertices {
+            graph.vertices.insert(id.clone(), Vertex::new(id.clone(), vertex.value.clone()));
+        }
+        // Источники обращённого ориентированного графа - бывшие стоки
+        graph.roles = self.roles.iter()
+            .map(|(id, &role)| match (self.r#type, role) {
+                (GraphType::Directed, VertexRole::Source) => (id.clone(), VertexRole::Sink),
+                (GraphType::Directed, VertexRole::Sink) => (id.clone(), VertexRole::Source),
+                _ => (id.clone(), role),
+            })
+            .collect();
+        graph.attributes = match self.r#type {
+            GraphType::Directed => self.attributes.reversed(),
+            GraphType::Undirected => self.attributes.clone(),
+        };
+        for (from_id, edge_direction) in self.logical_edges() {
+            let to_id = edge_direction.to_vertex_id.clone();
+            let value = edge_direction.value.as_ref().clone();
+            let result = match self.r#type {
+                GraphType::Directed => graph.add_edge(to_id, from_id.clone(), value),
+                GraphType::Undirected => graph.add_edge(from_id.clone(), to_id, value),
+            };
+            result.expect("transposed edges connect existing vertices and fit the same limits");
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn directed_graph() -> Result<Graph<String, u32>> {
+        let mut graph = Graph::new_multigraph(GraphType::Directed);
+        for (id, value) in [(1, "a"), (2, "b"), (3, "c")] {
+            graph.add_vertex(Vertex::new(id, Some(value.to_owned())))?;
+        }
+        graph.add_edge(1, 2, Some(12))?;
+        graph.add_edge(1, 2, Some(21))?;
+        graph.add_edge(2, 3, None)?;
+        graph.add_edge(3, 3, Some(33))?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn transpose_directed() -> Result<()> {
+        let graph = directed_graph()?;
+        let transposed = graph.transpose();
+        let all_strong = transposed.vertices.values()
+            .flat_map(|vertex| &vertex.edge_directions)
+            .all(EdgeDirection::is_own);
+        let mut neighbours = transposed.neighbours(3)?;
+        neighbours.sort_unstable();
+        if all_strong
+            && transposed != graph
+            && transposed.transpose() == graph
+            && transposed.neighbours(2)? == vec![1, 1]
+            && neighbours == vec![2, 3]
+            && transposed.neighbours(1)?.is_empty()
+            && transposed.predecessors(1)? == vec![2]
+            && transposed.vertex_value(3).map(String::as_str) == Some("c")
+            && transposed.edge_count() == graph.edge_count() {
+            Ok(())
+        } else {
+            bail!("wrong transposed graph")
+        }
+    }
+
+    #[test]
+    fn transpose_undirected() -> Result<()> {
+        let graph = Graph::<String, String>::deserialize("1 a\n2 b\n3\n#\n1 2 x\n3 2\n".as_bytes())?;
+        if graph.transpose() == graph {
+            Ok(())
+        } else {
+            bail!("transposed undirected graph differs from original")
+        }
+    }
+}
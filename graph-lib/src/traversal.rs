@@ -0,0 +1,187 @@
+use std::collections::hash_map;
+use super::*;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum TraversalOrder {
+    BreadthFirst,
+    DepthFirst,
+}
+
+/// Общее ядро обходов. Вершина считается посещённой, когда извлекается из очереди (или стека),
+/// поэтому порядок обхода в глубину совпадает с рекурсивным.
+/// Состояние обхода хранится в самом итераторе, граф заимствуется только на чтение
+pub(crate) struct Traversal<'a, VT, ET, ID> {
+    graph: &'a Graph<VT, ET, ID>,
+    order: TraversalOrder,
+    pending_vertices: VecDeque<&'a Vertex<VT, ET, ID>>,
+    visited_vertices: HashSet<&'a ID>,
+    /// Вершины, с которых начинается обход следующей компоненты, когда текущая исчерпана
+    next_component_starts: Option<hash_map::Values<'a, ID, Vertex<VT, ET, ID>>>,
+}
+
+impl<'a, VT, ET, ID: GraphId> Traversal<'a, VT, ET, ID> {
+    pub(crate) fn new(
+        graph: &'a Graph<VT, ET, ID>,
+        start_id: ID,
+        order: TraversalOrder,
+    ) -> Result<Traversal<'a, VT, ET, ID>, GraphError> {
+        let start_vertex = graph.vertices.get(&start_id)
+            .ok_or_else(|| GraphError::VertexNotFound(start_id.to_string()))?;
+        let mut traversal = Traversal::all_components(graph, order);
+        traversal.next_component_starts = None;
+        traversal.pending_vertices.push_back(start_vertex);
+        Ok(traversal)
+    }
+
+    pub(crate) fn all_components(graph: &'a Graph<VT, ET, ID>, order: TraversalOrder) -> Traversal<'a, VT, ET, ID> {
+        Traversal {
+            graph,
+            order,
+            pending_vertices: VecDeque::new(),
+            visited_vertices: HashSet::new(),
+            next_component_starts: Some(graph.vertices.values()),
+        }
+    }
+
+    fn next_pending_vertex(&mut self) -> Option<&'a Vertex<VT, ET, ID>> {
+        let vertex = match self.order {
+            TraversalOrder::BreadthFirst => self.pending_vertices.pop_front(),
+            TraversalOrder::DepthFirst => self.pending_vertices.pop_back(),
+        };
+        vertex.or_else(|| self.next_component_starts.as_mut()?.next())
+    }
+
+    fn push_neighbours<I: DoubleEndedIterator<Item = &'a ID>>(&mut self, neighbour_ids: I) {
+        let graph = self.graph;
+        let visited_vertices = &self.visited_vertices;
+        let neighbours = neighbour_ids
+            .filter(|&neighbour_id| !visited_vertices.contains(neighbour_id))
+            .filter_map(|neighbour_id| graph.vertices.get(neighbour_id));
+        match self.order {
+            TraversalOrder::BreadthFirst => self.pending_vertices.extend(neighbours),
+            // Соседи кладутся в стек в обратном порядке, чтобы первым посещался первый сосед
+            TraversalOrder::DepthFirst => self.pending_vertices.extend(neighbours.rev()),
+        }
+    }
+}
+
+impl<'a, VT, ET, ID: GraphId> Iterator for Traversal<'a, VT, ET, ID> {
+    type Item = &'a Vertex<VT, ET, ID>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_vertex = loop {
+            let vertex = self.next_pending_vertex()?;
+            if self.visited_vertices.insert(&vertex.id) {
+                break vertex;
+            }
+        };
+
+        match self.graph.hot.lookup(&current_vertex.id) {
+            Some(neighbour_ids) => self.push_neighbours(neighbour_ids.iter()),
+            None => self.push_neighbours(current_vertex.edge_directions
+                .iter()
+                .map(|edge_direction| &edge_direction.to_vertex_id)),
+        }
+
+        Some(current_vertex)
+    }
+}
+
+/// Ленивый обход в ширину: идентификатор и значение вершины в порядке посещения
+pub struct BfsIter<'a, VT, ET, ID = DefaultGraphIdType> {
+    traversal: Traversal<'a, VT, ET, ID>,
+}
+
+impl<'a, VT, ET, ID: GraphId> Iterator for BfsIter<'a, VT, ET, ID> {
+    type Item = (ID, Option<&'a VT>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.traversal.next()
+            .map(|vertex| (vertex.id.clone(), vertex.value.as_ref()))
+    }
+}
+
+impl<VT, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Ленивый обход в ширину из `start_id`. Итератор владеет очередью и множеством посещённых вершин,
+    /// поэтому по одному графу можно одновременно вести несколько обходов
+    pub fn bfs_iter(&self, start_id: ID) -> Result<BfsIter<'_, VT, ET, ID>, GraphError> {
+        Ok(BfsIter { traversal: Traversal::new(self, start_id, TraversalOrder::BreadthFirst)? })
+    }
+
+    /// Ленивый обход в ширину всего графа: когда текущая компонента связности исчерпана,
+    /// обход продолжается с первой непосещённой вершины. Каждая вершина выдаётся ровно один раз
+    pub fn bfs_iter_all(&self) -> BfsIter<'_, VT, ET, ID> {
+        BfsIter { traversal: Traversal::all_components(self, TraversalOrder::BreadthFirst) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn two_components() -> Result<Graph<String, ()>> {
+        let mut graph = Graph::new(GraphType::Undirected);
+        for (id, value) in [(1, "a"), (2, "b"), (3, "c"), (4, "d"), (5, "e")] {
+            graph.add_vertex(Vertex::new(id, Some(value.to_owned())))?;
+        }
+        graph.add_edge(1, 2, None)?;
+        graph.add_edge(2, 3, None)?;
+        graph.add_edge(4, 5, None)?;
+        Ok(graph)
+    }
+
+    #[test]
+    fn bfs_iter_same_order_as_bfs() -> Result<()> {
+        let graph = two_components()?;
+        let bfs_ids: Vec<_> = graph.bfs(2)?
+            .iter()
+            .map(|(id, _, _)| *id)
+            .collect();
+        let iter_ids: Vec<_> = graph.bfs_iter(2)?
+            .map(|(id, _)| id)
+            .collect();
+        let found = graph.bfs_iter(1)?
+            .find(|(_, value)| value.map(String::as_str) == Some("c"));
+        if bfs_ids == iter_ids && iter_ids.len() == 3 && found.map(|(id, _)| id) == Some(3) {
+            Ok(())
+        } else {
+            bail!("bfs_iter order {:?} differs from bfs order {:?}", iter_ids, bfs_ids)
+        }
+    }
+
+    #[test]
+    fn concurrent_bfs_iters() -> Result<()> {
+        let graph = two_components()?;
+        let pairs: Vec<_> = graph.bfs_iter(1)?
+            .zip(graph.bfs_iter(3)?)
+            .map(|((first_id, _), (second_id, _))| (first_id, second_id))
+            .collect();
+        if pairs == vec![(1, 3), (2, 2), (3, 1)] {
+            Ok(())
+        } else {
+            bail!("independent traversals interfere: {:?}", pairs)
+        }
+    }
+
+    #[test]
+    fn bfs_iter_all_components() -> Result<()> {
+        let graph = two_components()?;
+        let mut ids: Vec<_> = graph.bfs_iter_all()
+            .map(|(id, _)| id)
+            .collect();
+        // Компонента {1, 2, 3} должна быть выдана целиком до или после компоненты {4, 5}
+        let component_switches = ids.windows(2)
+            .filter(|pair| (pair[0] <= 3) != (pair[1] <= 3))
+            .count();
+        ids.sort_unstable();
+        if ids == vec![1, 2, 3, 4, 5] && component_switches == 1 {
+            Ok(())
+        } else {
+            bail!("bfs_iter_all must visit every vertex once: {:?}", ids)
+        }
+    }
+}
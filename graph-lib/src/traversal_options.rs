@@ -0,0 +1,187 @@
+use std::cmp::Reverse;
+use crate::distribution::DegreeKind;
+use super::*;
+
+/// Порядок, в котором обход берёт соседей вершины
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NeighbourOrder {
+    /// В порядке добавления рёбер, как у `bfs` и `dfs`
+    #[default]
+    InsertionOrder,
+    /// По возрастанию идентификатора: результат обхода не зависит от порядка добавления рёбер
+    ById,
+    /// По убыванию степени (без учёта направления), при равной степени - по идентификатору
+    ByDegreeDesc,
+}
+
+/// Фильтр рёбер обхода: начальная вершина, конечная вершина и значение ребра
+pub type EdgeFilter<'f, ET, ID = DefaultGraphIdType> = Box<dyn Fn(ID, ID, Option<&ET>) -> bool + 'f>;
+
+/// Параметры `Graph::bfs_with_options` и `Graph::dfs_with_options`. По умолчанию обход совпадает с `bfs` и `dfs`
+pub struct TraversalOptions<'f, ET, ID = DefaultGraphIdType> {
+    pub neighbour_order: NeighbourOrder,
+    /// Наибольшая глубина посещаемых вершин, у начальной вершины глубина 0. `None` - без ограничения
+    pub max_depth: Option<usize>,
+    /// Обход идёт только по рёбрам, для которых фильтр вернул `true`. Ребро неориентированного графа
+    /// передаётся от текущей вершины к соседу. `None` - по всем рёбрам
+    pub edge_filter: Option<EdgeFilter<'f, ET, ID>>,
+}
+
+impl<ET, ID> Default for TraversalOptions<'_, ET, ID> {
+    fn default() -> Self {
+        TraversalOptions {
+            neighbour_order: NeighbourOrder::InsertionOrder,
+            max_depth: None,
+            edge_filter: None,
+        }
+    }
+}
+
+/// Список из идентификатора вершины, значения вершины, соседей по рёбрам, прошедшим фильтр,
+/// в порядке обхода и глубины вершины
+pub type TraversalResult<'a, VT, ID = DefaultGraphIdType> = Vec<(ID, Option<&'a VT>, Vec<ID>, usize)>;
+
+impl<VT, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Обход в ширину с параметрами: порядок соседей, ограничение глубины и фильтр рёбер.
+    /// Глубина вершины - число рёбер кратчайшего пути до неё по рёбрам, прошедшим фильтр
+    pub fn bfs_with_options(&self, start_id: ID, options: &TraversalOptions<'_, ET, ID>) -> Result<TraversalResult<'_, VT, ID>, GraphError> {
+        self.traverse_with_options(start_id, TraversalOrder::BreadthFirst, options)
+    }
+
+    /// Обход в глубину с параметрами, см. `bfs_with_options`. Глубина вершины - её глубина в дереве обхода,
+    /// поэтому при ограничении глубины вершина, впервые встреченная слишком глубоко, не посещается,
+    /// даже если до неё есть более короткий путь
+    pub fn dfs_with_options(&self, start_id: ID, options: &TraversalOptions<'_, ET, ID>) -> Result<TraversalResult<'_, VT, ID>, GraphError> {
+        self.traverse_with_options(start_id, TraversalOrder::DepthFirst, options)
+    }
+
+    fn traverse_with_options(
+        &self,
+        start_id: ID,
+        order: TraversalOrder,
+        options: &TraversalOptions<'_, ET, ID>,
+    ) -> Result<TraversalResult<'_, VT, ID>, GraphError> {
+        let start_vertex = self.vertices.get(&start_id)
+            .ok_or_else(|| GraphError::VertexNotFound(start_id.to_string()))?;
+        let degrees = (options.neighbour_order == NeighbourOrder::ByDegreeDesc)
+            .then(|| self.degrees(DegreeKind::Undirected));
+
+        let mut result = Vec::new();
+        let mut visited_vertices = HashSet::new();
+        let mut pending_vertices = VecDeque::from([(start_vertex, 0)]);
+        loop {
+            let next = match order {
+                TraversalOrder::BreadthFirst => pending_vertices.pop_front(),
+                TraversalOrder::DepthFirst => pending_vertices.pop_back(),
+            };
+            let Some((vertex, depth)) = next else {
+                break;
+            };
+            if !visited_vertices.insert(&vertex.id) {
+                continue;
+            }
+
+            let mut neighbour_ids: Vec<_> = vertex.edge_directions
+                .iter()
+                .filter(|edge_direction| options.edge_filter.as_ref().is_none_or(|edge_filter| edge_filter(
+                    vertex.id.clone(),
+                    edge_direction.to_vertex_id.clone(),
+                    edge_direction.value.as_ref().as_ref(),
+                )))
+                .map(|edge_direction| &edge_direction.to_vertex_id)
+                .collect();
+            match (options.neighbour_order, &degrees) {
+                (NeighbourOrder::ById, _) => neighbour_ids.sort(),
+                (NeighbourOrder::ByDegreeDesc, Some(degrees)) => neighbour_ids.sort_by_key(|&id| (Reverse(degrees[id]), id)),
+                _ => {}
+            }
+
+            if options.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                let neighbours = neighbour_ids.iter()
+                    .filter(|&&neighbour_id| !visited_vertices.contains(neighbour_id))
+                    .map(|&neighbour_id| (&self.vertices[neighbour_id], depth + 1));
+                match order {
+                    TraversalOrder::BreadthFirst => pending_vertices.extend(neighbours),
+                    // Соседи кладутся в стек в обратном порядке, чтобы первым посещался первый сосед
+                    TraversalOrder::DepthFirst => pending_vertices.extend(neighbours.rev()),
+                }
+            }
+            result.push((vertex.id.clone(), vertex.value.as_ref(), neighbour_ids.into_iter().cloned().collect(), depth));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    /// Рёбра добавлены не по порядку идентификаторов: 1 - 4, 1 - 3, 1 - 2, 2 - 5, 3 - 5, 3 - 6, 5 - 7
+    fn dependencies() -> Result<Graph<(), String>> {
+        let mut graph = Graph::new(GraphType::Directed);
+        for id in 1..=7 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        for (from_id, to_id, kind) in [(1, 4, "soft"), (1, 3, "hard"), (1, 2, "hard"), (2, 5, "hard"), (3, 5, "soft"), (3, 6, "hard"), (5, 7, "hard")] {
+            graph.add_edge(from_id, to_id, Some(kind.to_owned()))?;
+        }
+        Ok(graph)
+    }
+
+    fn ids_and_depths(result: &TraversalResult<'_, ()>) -> Vec<(u32, usize)> {
+        result.iter().map(|&(id, _, _, depth)| (id, depth)).collect()
+    }
+
+    #[test]
+    fn default_options_match_bfs_and_dfs() -> Result<()> {
+        let graph = dependencies()?;
+        let options = TraversalOptions::default();
+        let bfs: Vec<_> = graph.bfs(1)?.into_iter().map(|(id, _, neighbour_ids)| (id, neighbour_ids)).collect();
+        let dfs: Vec<_> = graph.dfs(1)?.into_iter().map(|(id, _, neighbour_ids)| (id, neighbour_ids)).collect();
+        let bfs_with_options: Vec<_> = graph.bfs_with_options(1, &options)?.into_iter().map(|(id, _, neighbour_ids, _)| (id, neighbour_ids)).collect();
+        let dfs_with_options: Vec<_> = graph.dfs_with_options(1, &options)?.into_iter().map(|(id, _, neighbour_ids, _)| (id, neighbour_ids)).collect();
+        if bfs == bfs_with_options && dfs == dfs_with_options && graph.bfs_with_options(8, &options).is_err() {
+            Ok(())
+        } else {
+            bail!("default options changed traversal: {:?}", bfs_with_options)
+        }
+    }
+
+    #[test]
+    fn ordered_neighbours_and_depths() -> Result<()> {
+        let graph = dependencies()?;
+        let by_id = TraversalOptions { neighbour_order: NeighbourOrder::ById, ..TraversalOptions::default() };
+        let by_degree = TraversalOptions { neighbour_order: NeighbourOrder::ByDegreeDesc, max_depth: Some(1), ..TraversalOptions::default() };
+        let bfs = graph.bfs_with_options(1, &by_id)?;
+        let dfs = graph.dfs_with_options(1, &by_id)?;
+        let shallow = graph.bfs_with_options(1, &by_degree)?;
+        if ids_and_depths(&bfs) == vec![(1, 0), (2, 1), (3, 1), (4, 1), (5, 2), (6, 2), (7, 3)]
+            && bfs[0].2 == vec![2, 3, 4]
+            && ids_and_depths(&dfs) == vec![(1, 0), (2, 1), (5, 2), (7, 3), (3, 1), (6, 2), (4, 1)]
+            && ids_and_depths(&shallow) == vec![(1, 0), (3, 1), (2, 1), (4, 1)] {
+            Ok(())
+        } else {
+            bail!("wrong traversal order: {:?}", ids_and_depths(&bfs))
+        }
+    }
+
+    #[test]
+    fn filtered_edges() -> Result<()> {
+        let graph = dependencies()?;
+        let hard_only = TraversalOptions {
+            neighbour_order: NeighbourOrder::ById,
+            edge_filter: Some(Box::new(|_, _, kind: Option<&String>| kind.is_some_and(|kind| kind == "hard"))),
+            ..TraversalOptions::default()
+        };
+        let result = graph.bfs_with_options(1, &hard_only)?;
+        if ids_and_depths(&result) == vec![(1, 0), (2, 1), (3, 1), (5, 2), (6, 2), (7, 3)] && result[2].2 == vec![6] {
+            Ok(())
+        } else {
+            bail!("soft edges followed: {:?}", ids_and_depths(&result))
+        }
+    }
+}
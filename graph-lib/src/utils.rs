@@ -1,5 +1,74 @@
-pub(crate) fn remove_from_vec<T, VC: FnMut(&T) -> bool>(data: &mut Vec<T>, value_comparator: VC) {
-    if let Some(position) = data.iter().position(value_comparator) {
-        data.remove(position);
+/// Система непересекающихся множеств над индексами 0..len со сжатием путей и объединением по рангу
+pub(crate) struct UnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+        }
     }
-}
\ No newline at end of file
+
+    pub(crate) fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = index;
+        while self.parents[current] != root {
+            let parent = self.parents[current];
+            self.parents[current] = root;
+            current = parent;
+        }
+        root
+    }
+
+    /// Объединяет множества, возвращая `false`, если элементы уже были в одном множестве
+    pub(crate) fn union(&mut self, first: usize, second: usize) -> bool {
+        let (first_root, second_root) = (self.find(first), self.find(second));
+        if first_root == second_root {
+            return false;
+        }
+        match self.ranks[first_root].cmp(&self.ranks[second_root]) {
+            std::cmp::Ordering::Less => self.parents[first_root] = second_root,
+            std::cmp::Ordering::Greater => self.parents[second_root] = first_root,
+            std::cmp::Ordering::Equal => {
+                self.parents[second_root] = first_root;
+                self.ranks[first_root] += 1;
+            }
+        }
+        true
+    }
+}
+
+/// Генератор псевдослучайных чисел SplitMix64: детерминирован при заданном зерне и не требует зависимостей
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+
+    /// Случайное число в диапазоне 0..bound
+    pub(crate) fn next_below(&mut self, bound: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+
+    /// Случайное число в диапазоне [0, 1) из старших 53 бит
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,164 @@
+use super::*;
+
+/// Отображение значения вершины в её идентификатор, см. `Graph::add_vertex_by_value`
+#[derive(Debug, Clone)]
+pub(crate) struct ValueIds<ID> {
+    pub(crate) ids: HashMap<String, ID>,
+    /// Хеш значения, из которого выводится идентификатор новой вершины
+    hash: fn(&str) -> u64,
+}
+
+/// FNV-1a: в отличие от хешей стандартной библиотеки, не меняется между версиями компилятора,
+/// поэтому идентификаторы, выведенные из значений, стабильны
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+impl<ET> Graph<String, ET> {
+    /// Включает идентификацию вершин по значению, заново строя отображение по всем вершинам графа.
+    /// Из вершин с одинаковым значением значению соответствует вершина с наименьшим идентификатором.
+    /// Значения, изменённые через `set_vertex_value` и подобные методы, учитываются только после повторного вызова
+    pub fn enable_value_ids(&mut self) {
+        self.enable_value_ids_with_hasher(fnv1a);
+    }
+
+    fn enable_value_ids_with_hasher(&mut self, hash: fn(&str) -> u64) {
+        let mut ids: HashMap<String, DefaultGraphIdType> = HashMap::new();
+        for vertex in self.vertices.values() {
+            if let Some(value) = &vertex.value {
+                let id = ids.entry(value.clone()).or_insert(vertex.id);
+                *id = (*id).min(vertex.id);
+            }
+        }
+        self.value_ids = Some(ValueIds { ids, hash });
+    }
+
+    /// Идентификатор вершины, добавленной по значению `value`
+    pub fn id_of(&self, value: &str) -> Option<DefaultGraphIdType> {
+        let id = *self.value_ids.as_ref()?.ids.get(value)?;
+        // Вершина могла быть удалена или получить другое значение через методы по идентификатору
+        (self.vertex_value(id).map(String::as_str) == Some(value)).then_some(id)
+    }
+
+    /// Возвращает идентификатор вершины со значением `value`, добавляя её, если такой вершины нет.
+    /// Идентификатор новой вершины выводится из хеша значения; если он занят, берётся следующий свободный.
+    /// Первый вызов включает идентификацию по значению (`enable_value_ids`)
+    pub fn add_vertex_by_value(&mut self, value: String) -> Result<DefaultGraphIdType, GraphError> {
+        if self.value_ids.is_none() {
+            self.enable_value_ids();
+        }
+        if let Some(id) = self.id_of(&value) {
+            return Ok(id);
+        }
+
+        let hash = self.value_ids.as_ref().map_or_else(|| fnv1a(&value), |value_ids| (value_ids.hash)(&value));
+        let mut id = (hash ^ (hash >> 32)) as DefaultGraphIdType;
+        while self.contains_vertex(&id) {
+            id = id.wrapping_add(1);
+        }
+        self.add_vertex(Vertex::new(id, Some(value.clone())))?;
+        if let Some(value_ids) = self.value_ids.as_mut() {
+            value_ids.ids.insert(value, id);
+        }
+        Ok(id)
+    }
+
+    /// Добавляет ребро между вершинами со значениями `from_value` и `to_value`, создавая недостающие вершины.
+    /// Возвращает идентификаторы концов ребра. Если ребро добавить нельзя, созданные вершины остаются в графе
+    pub fn add_edge_by_values(
+        &mut self,
+        from_value: String,
+        to_value: String,
+        value: Option<ET>,
+    ) -> Result<(DefaultGraphIdType, DefaultGraphIdType), GraphError> {
+        let from_id = self.add_vertex_by_value(from_value)?;
+        let to_id = self.add_vertex_by_value(to_value)?;
+        self.add_edge(from_id, to_id, value)?;
+        Ok((from_id, to_id))
+    }
+}
+
+impl Graph<String, String> {
+    /// Десериализация с включённой идентификацией вершин по значению
+    pub fn deserialize_with_value_ids<BR: BufRead>(reader: BR) -> Result<Graph<String, String>, GraphError> {
+        let mut graph = Graph::deserialize(reader)?;
+        graph.enable_value_ids();
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    fn colliding_hash(_: &str) -> u64 {
+        7
+    }
+
+    #[test]
+    fn same_value_same_id() -> Result<()> {
+        let mut graph = Graph::<String, String>::new(GraphType::Directed);
+        let first_id = graph.add_vertex_by_value("Moscow".to_owned())?;
+        let second_id = graph.add_vertex_by_value("Moscow".to_owned())?;
+
+        let mut reloaded = Graph::deserialize_with_value_ids(format!("{} Moscow\n#\n", first_id).as_bytes())?;
+        if first_id == second_id
+            && graph.vertex_count() == 1
+            && graph.id_of("Moscow") == Some(first_id)
+            && graph.id_of("Kazan").is_none()
+            && reloaded.id_of("Moscow") == Some(first_id)
+            && reloaded.add_vertex_by_value("Moscow".to_owned())? == first_id {
+            Ok(())
+        } else {
+            bail!("same value got different ids")
+        }
+    }
+
+    #[test]
+    fn distinct_values_under_hash_collision() -> Result<()> {
+        let mut graph = Graph::<String, String>::new(GraphType::Undirected);
+        graph.add_vertex(Vertex::new(8, Some("taken".to_owned())))?;
+        graph.enable_value_ids_with_hasher(colliding_hash);
+        let ids = ["a", "b", "c"]
+            .into_iter()
+            .map(|value| graph.add_vertex_by_value(value.to_owned()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let repeated_ids = ["c", "b", "a", "taken"]
+            .into_iter()
+            .map(|value| graph.add_vertex_by_value(value.to_owned()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if ids == vec![7, 9, 10] && repeated_ids == vec![10, 9, 7, 8] && graph.vertex_count() == 4 {
+            Ok(())
+        } else {
+            bail!("colliding values got ids {:?}, then {:?}", ids, repeated_ids)
+        }
+    }
+
+    #[test]
+    fn edges_by_value_with_id_api() -> Result<()> {
+        let mut graph = Graph::<String, String>::new(GraphType::Directed);
+        let (moscow_id, kazan_id) = graph.add_edge_by_values("Moscow".to_owned(), "Kazan".to_owned(), Some("M7".to_owned()))?;
+        graph.add_vertex(Vertex::new(1, Some("Tver".to_owned())))?;
+        graph.add_edge(1, moscow_id, None)?;
+        let unknown_tver = graph.id_of("Tver");
+        graph.enable_value_ids();
+        let (_, tver_id) = graph.add_edge_by_values("Kazan".to_owned(), "Tver".to_owned(), None)?;
+        graph.delete_vertex(kazan_id)?;
+        let kazan_after_delete = graph.id_of("Kazan");
+
+        if unknown_tver.is_none()
+            && tver_id == 1
+            && kazan_after_delete.is_none()
+            && graph.shortest_path(1, moscow_id)? == Some(vec![1, moscow_id])
+            && graph.vertex_value(moscow_id).map(String::as_str) == Some("Moscow")
+            && graph.vertex_count() == 2 {
+            Ok(())
+        } else {
+            bail!("edges by value disagree with id based methods")
+        }
+    }
+}
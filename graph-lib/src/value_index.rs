@@ -0,0 +1,174 @@
+use std::{
+    hash::{DefaultHasher, Hasher},
+    mem,
+};
+use super::*;
+
+/// Обратный индекс значений вершин, см. `Graph::with_value_index`. Вершины хранятся по хешу значения,
+/// поэтому индекс обновляется методами графа без ограничений на тип значения: нужна только функция хеширования
+#[derive(Debug, Clone)]
+pub(crate) struct ValueIndex<VT, ID> {
+    ids: HashMap<u64, Vec<ID>>,
+    hashes: HashMap<ID, u64>,
+    /// Вершины, значение которых могло измениться после последнего обновления индекса. Значение меняется
+    /// уже после `Graph::mark_changed` (например, через ссылку из `vertex_value_mut`), поэтому такие вершины
+    /// переносятся в индекс при следующем изменении графа, а до этого проверяются при поиске напрямую
+    pending_ids: Vec<ID>,
+    hash: fn(&VT) -> u64,
+}
+
+fn hash_value<VT: Hash>(value: &VT) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<VT, ID: GraphId> ValueIndex<VT, ID> {
+    fn build<ET>(hash: fn(&VT) -> u64, vertices: &HashMap<ID, Vertex<VT, ET, ID>>) -> ValueIndex<VT, ID> {
+        let mut value_index = ValueIndex {
+            ids: HashMap::new(),
+            hashes: HashMap::with_capacity(vertices.len()),
+            pending_ids: vertices.keys().cloned().collect(),
+            hash,
+        };
+        value_index.refresh(vertices);
+        value_index
+    }
+
+    /// Индекс того же графа после замены идентификаторов, см. `Graph::relabel`
+    pub(crate) fn rebuilt<ET>(&self, vertices: &HashMap<ID, Vertex<VT, ET, ID>>) -> ValueIndex<VT, ID> {
+        ValueIndex::build(self.hash, vertices)
+    }
+
+    pub(crate) fn mark_pending(&mut self, vertex_id: &ID) {
+        self.pending_ids.push(vertex_id.clone());
+    }
+
+    /// Переносит в индекс текущие значения отложенных вершин, удалённые вершины исключаются из индекса
+    pub(crate) fn refresh<ET>(&mut self, vertices: &HashMap<ID, Vertex<VT, ET, ID>>) {
+        for id in mem::take(&mut self.pending_ids) {
+            if let Some(hash) = self.hashes.remove(&id) {
+                if let Some(ids) = self.ids.get_mut(&hash) {
+                    ids.retain(|indexed_id| *indexed_id != id);
+                    if ids.is_empty() {
+                        self.ids.remove(&hash);
+                    }
+                }
+            }
+            if let Some(value) = vertices.get(&id).and_then(|vertex| vertex.value.as_ref()) {
+                let hash = (self.hash)(value);
+                self.ids.entry(hash).or_default().push(id.clone());
+                self.hashes.insert(id, hash);
+            }
+        }
+    }
+
+    /// Вершины, у которых может быть значение `value`: с тем же хешем и отложенные
+    fn candidates(&self, value: &VT) -> impl Iterator<Item = &ID> {
+        self.ids.get(&(self.hash)(value))
+            .into_iter()
+            .flatten()
+            .chain(&self.pending_ids)
+    }
+}
+
+impl<VT: Eq + Hash, ET, ID: GraphId> Graph<VT, ET, ID> {
+    /// Включает поддерживаемый обратный индекс значений вершин для `vertex_by_value` и `vertices_by_value`.
+    /// Индекс строится по всем вершинам графа и дальше обновляется при добавлении и удалении вершин
+    /// и изменении значений любыми методами графа, включая `vertex_value_mut`
+    pub fn with_value_index(mut self) -> Graph<VT, ET, ID> {
+        self.value_index = Some(ValueIndex::build(hash_value::<VT>, &self.vertices));
+        self
+    }
+}
+
+impl<VT: PartialEq, ET, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Вершины, значение которых удовлетворяет `predicate`, по возрастанию идентификатора.
+    /// Просматривает все вершины, вершины без значения не подходят
+    pub fn find_vertices_by_value<P: Fn(&VT) -> bool>(&self, predicate: P) -> Vec<ID> {
+        let mut ids: Vec<_> = self.vertices.values()
+            .filter(|vertex| vertex.value.as_ref().is_some_and(&predicate))
+            .map(|vertex| vertex.id.clone())
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Все вершины со значением `value` по возрастанию идентификатора. С индексом (`with_value_index`)
+    /// просматриваются только вершины с тем же хешем значения, без индекса - все вершины
+    pub fn vertices_by_value(&self, value: &VT) -> Vec<ID> {
+        let Some(value_index) = &self.value_index else {
+            return self.find_vertices_by_value(|vertex_value| vertex_value == value);
+        };
+        let mut ids: Vec<_> = value_index.candidates(value)
+            .filter(|&id| self.vertices.get(id).is_some_and(|vertex| vertex.value.as_ref() == Some(value)))
+            .cloned()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Вершина со значением `value`, из нескольких таких вершин - с наименьшим идентификатором,
+    /// см. `vertices_by_value`
+    pub fn vertex_by_value(&self, value: &VT) -> Option<ID> {
+        self.vertices_by_value(value).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const MONTHS_TGF: &str = "1 January\n2 March\n3 April\n4 May\n5 December\n#\n1 2\n3 2\n4 3\n";
+
+    #[test]
+    fn deserialized_vertex_by_value() -> Result<()> {
+        let graph = Graph::deserialize(MONTHS_TGF.as_bytes())?.with_value_index();
+        let unindexed = Graph::deserialize(MONTHS_TGF.as_bytes())?;
+        let spring = graph.find_vertices_by_value(|value| ["March", "April", "May"].contains(&value.as_str()));
+        if graph.vertex_by_value(&"May".to_string()) == Some(4)
+            && unindexed.vertex_by_value(&"May".to_string()) == Some(4)
+            && graph.vertex_by_value(&"June".to_string()).is_none()
+            && spring == vec![2, 3, 4] {
+            Ok(())
+        } else {
+            bail!("wrong vertex by value, spring months {:?}", spring)
+        }
+    }
+
+    #[test]
+    fn index_follows_mutations() -> Result<()> {
+        let mut graph = Graph::deserialize(MONTHS_TGF.as_bytes())?.with_value_index();
+        graph.add_vertex(Vertex::new(6, Some("May".to_owned())))?;
+        let duplicates = graph.vertices_by_value(&"May".to_owned());
+        graph.delete_vertex(4)?;
+        let after_delete = graph.vertex_by_value(&"May".to_owned());
+        graph.set_vertex_value(1, Some("June".to_owned()))?;
+        if let Some(value) = graph.vertex_value_mut(2) {
+            *value = "July".to_owned();
+        }
+        let mutated = (graph.vertex_by_value(&"June".to_owned()), graph.vertex_by_value(&"July".to_owned()));
+        graph.upsert_vertex(Vertex::new(3, Some("July".to_owned())))?;
+        graph.relabel(|id| id * 10)?;
+        graph.rewrite_values(&HashMap::from([("December".to_owned(), "Winter".to_owned())]), RewriteTargets::Vertices);
+
+        if duplicates == vec![4, 6]
+            && after_delete == Some(6)
+            && mutated == (Some(1), Some(2))
+            && graph.vertex_by_value(&"January".to_owned()).is_none()
+            && graph.vertex_by_value(&"March".to_owned()).is_none()
+            && graph.vertices_by_value(&"July".to_owned()) == vec![20, 30]
+            && graph.vertex_by_value(&"May".to_owned()) == Some(60)
+            && graph.vertex_by_value(&"Winter".to_owned()) == Some(50)
+            && graph.vertex_by_value(&"December".to_owned()).is_none() {
+            Ok(())
+        } else {
+            bail!("index out of date: {:?}", graph.vertices_by_value(&"July".to_owned()))
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::*;
+
+/// Числовой вес значения ребра для алгоритмов на взвешенных графах. Методы `*_weighted` принимают веса
+/// из значений рёбер, реализующих этот трейт, вместо функции веса. Рёбра без значения весят 1.0
+pub trait EdgeWeight {
+    fn weight(&self) -> f64;
+}
+
+macro_rules! impl_edge_weight {
+    ($($numeric_type:ty),*) => {
+        $(
+            impl EdgeWeight for $numeric_type {
+                fn weight(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_edge_weight!(f64, f32, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<VT, ET: EdgeWeight, ID: GraphId> Graph<VT, ET, ID> {
+    /// Путь наименьшего веса алгоритмом Дейкстры (A* без эвристики), см. `astar`
+    pub fn shortest_path_weighted(&self, from_id: ID, to_id: ID) -> Result<Option<AstarPath<ID>>, GraphError> {
+        self.astar(from_id, to_id, EdgeWeight::weight, |_| 0.0)
+    }
+
+    /// Кратчайшие пути из одной вершины по весам рёбер, см. `dijkstra`
+    pub fn dijkstra_weighted(&self, start_id: ID) -> Result<ShortestPaths<ID>, GraphError> {
+        self.dijkstra(start_id, EdgeWeight::weight)
+    }
+
+    /// Кратчайшие пути из одной вершины при отрицательных весах рёбер, см. `bellman_ford`
+    pub fn bellman_ford_weighted(&self, start_id: ID) -> Result<ShortestPaths<ID>, GraphError> {
+        self.bellman_ford(start_id, EdgeWeight::weight)
+    }
+
+    /// Длины кратчайших путей между всеми парами вершин, см. `all_pairs_shortest_paths`
+    pub fn all_pairs_shortest_paths_weighted(&self) -> HashMap<(ID, ID), f64> {
+        self.all_pairs_shortest_paths(EdgeWeight::weight)
+    }
+
+    /// Минимальное остовное дерево компоненты `start_id` алгоритмом Прима по весам рёбер, см. `mst_prim`
+    pub fn mst_prim_weighted(&self, start_id: ID) -> Result<HashMap<ID, ID>, GraphError> {
+        self.mst_prim(start_id, EdgeWeight::weight)
+    }
+
+    /// Наибольший поток, пропускные способности рёбер - их веса, см. `max_flow`
+    pub fn max_flow_weighted(&self, source_id: ID, sink_id: ID, algorithm: FlowAlgorithm) -> Result<f64, GraphError> {
+        self.max_flow(source_id, sink_id, EdgeWeight::weight, algorithm)
+    }
+}
+
+impl<VT, ET: EdgeWeight, ID: GraphId + Ord> Graph<VT, ET, ID> {
+    /// Минимальное остовное дерево по весам рёбер, см. `minimum_spanning_tree`
+    pub fn minimum_spanning_tree_weighted(&self) -> Result<Vec<WeightedEdge<ID>>, GraphError> {
+        self.minimum_spanning_tree(EdgeWeight::weight)
+    }
+
+    /// Минимальное остовное дерево по весам рёбер в виде графа, см. `minimum_spanning_tree_graph`
+    pub fn minimum_spanning_tree_graph_weighted(&self) -> Result<Graph<VT, ET, ID>, GraphError>
+    where
+        VT: Clone,
+        ET: Clone,
+    {
+        self.minimum_spanning_tree_graph(EdgeWeight::weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufWriter;
+    use anyhow::{
+        Result,
+        bail,
+    };
+
+    const WEIGHTED_TGF: &str = "1 a\n2 b\n3 c\n4 d\n#\n1 2 0.1\n2 3 0.2\n1 3 0.35\n3 4 1e300\n";
+
+    #[test]
+    fn numeric_edge_values_as_weights() -> Result<()> {
+        let mut graph = Graph::<(), u32>::new(GraphType::Directed);
+        for id in 1..=3 {
+            graph.add_vertex(Vertex::new(id, None))?;
+        }
+        graph.add_edge(1, 2, Some(2))?;
+        graph.add_edge(2, 3, Some(2))?;
+        graph.add_edge(1, 3, Some(5))?;
+        if graph.shortest_path_weighted(1, 3)? == Some((4.0, vec![1, 2, 3]))
+            && graph.all_pairs_shortest_paths_weighted()[&(1, 3)] == 4.0
+            && graph.dijkstra_weighted(1)?.path_to(3) == Some(vec![1, 2, 3])
+            && graph.bellman_ford_weighted(1)?.distances[&3] == 4.0
+            && graph.max_flow_weighted(1, 3, FlowAlgorithm::Dinic)? == 7.0
+            && (-1.5f32).weight() == -1.5 {
+            Ok(())
+        } else {
+            bail!("wrong weighted path {:?}", graph.shortest_path_weighted(1, 3)?)
+        }
+    }
+
+    #[test]
+    fn weighted_tgf_round_trip() -> Result<()> {
+        let graph = Graph::deserialize_weighted(WEIGHTED_TGF.as_bytes())?;
+        let mut buf_writer = BufWriter::new(Vec::new());
+        graph.serialize_weighted(&mut buf_writer)?;
+        let reloaded = Graph::deserialize_weighted(buf_writer.into_inner().map_err(|error| error.into_error())?.as_slice())?;
+        let mut edges = reloaded.edges();
+        edges.sort_by_key(|&(from_id, to_id, _)| (from_id.min(to_id), from_id.max(to_id)));
+        if graph.shortest_path_weighted(1, 3)? == Some((0.1 + 0.2, vec![1, 2, 3]))
+            && graph.minimum_spanning_tree_weighted()?.len() == 3
+            && edges == vec![(1, 2, Some(&0.1)), (1, 3, Some(&0.35)), (2, 3, Some(&0.2)), (3, 4, Some(&1e300))]
+            && reloaded.diff(&graph)?.is_empty() {
+            Ok(())
+        } else {
+            bail!("weights changed after round trip: {:?}", edges)
+        }
+    }
+
+    #[test]
+    fn non_numeric_weight_reports_line() -> Result<()> {
+        let result = Graph::deserialize_weighted("1\n2\n3\n#\n1 2 3\n2 3 heavy\n".as_bytes());
+        let expected = GraphError::ParseLine { line_no: 6, source: Box::new(GraphError::WrongEdgeWeight("2 3 heavy".to_owned())) };
+        match result {
+            Err(error) if error == expected => Ok(()),
+            _ => bail!("non-numeric weight not reported: {:?}", result.map(|graph| graph.edge_count())),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Прежние пути из корня библиотеки остаются рабочими и указывают на те же типы, что и пути модулей
+
+use std::marker::PhantomData;
+use graph_lib::prelude::*;
+
+/// Компилируется, только если оба пути указывают на один тип
+fn same_type<T>(_: PhantomData<T>, _: PhantomData<T>) {}
+
+#[test]
+fn root_paths_match_module_paths() {
+    same_type(PhantomData::<graph_lib::Graph<String, String>>, PhantomData::<Graph<String, String>>);
+    same_type(PhantomData::<graph_lib::GraphType>, PhantomData::<GraphType>);
+    same_type(PhantomData::<graph_lib::Vertex<(), ()>>, PhantomData::<Vertex<(), ()>>);
+    same_type(PhantomData::<graph_lib::GraphError>, PhantomData::<graph_lib::errors::GraphError>);
+    same_type(PhantomData::<graph_lib::GraphBuilder<(), ()>>, PhantomData::<GraphBuilder<(), ()>>);
+    same_type(PhantomData::<graph_lib::DefaultGraphIdType>, PhantomData::<DefaultGraphIdType>);
+    same_type(PhantomData::<graph_lib::VertexOrder>, PhantomData::<graph_lib::formats::VertexOrder>);
+    same_type(PhantomData::<graph_lib::DeserializeOptions>, PhantomData::<graph_lib::formats::DeserializeOptions>);
+    same_type(PhantomData::<graph_lib::LineErrors>, PhantomData::<graph_lib::formats::LineErrors>);
+    same_type(PhantomData::<graph_lib::UpdateStrategy>, PhantomData::<graph_lib::formats::UpdateStrategy>);
+    same_type(PhantomData::<graph_lib::GraphStats>, PhantomData::<graph_lib::metrics::GraphStats>);
+    same_type(PhantomData::<graph_lib::DegreeKind>, PhantomData::<graph_lib::metrics::DegreeKind>);
+    same_type(PhantomData::<graph_lib::MemoryEstimate>, PhantomData::<graph_lib::metrics::MemoryEstimate>);
+    same_type(PhantomData::<graph_lib::AstarPath<u32>>, PhantomData::<graph_lib::algo::AstarPath<u32>>);
+    same_type(PhantomData::<graph_lib::MutationPlan>, PhantomData::<graph_lib::algo::MutationPlan>);
+    same_type(PhantomData::<graph_lib::AlignmentReport>, PhantomData::<graph_lib::algo::AlignmentReport>);
+    same_type(PhantomData::<graph_lib::RewriteTargets>, PhantomData::<graph_lib::algo::RewriteTargets>);
+
+    let graph = Graph::deserialize("1 a\n2 b\n#\n1 2".as_bytes()).unwrap();
+    let stats: graph_lib::GraphStats = graph.stats();
+    assert_eq!(stats.edges, 1);
+    assert_eq!(graph_lib::algo::normalized_levenshtein("a", "b"), graph_lib::normalized_levenshtein("a", "b"));
+}
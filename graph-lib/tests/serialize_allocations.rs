@@ -0,0 +1,81 @@
+//! Подсчёт выделений памяти при сериализации. Отдельный тестовый исполняемый файл, т.к. глобальный
+//! аллокатор считает выделения всего процесса
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::BufWriter,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use graph_lib::{Graph, GraphType, Vertex, VertexOrder};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const VERTEX_COUNT: u32 = 1_000;
+const EDGES_PER_VERTEX: u32 = 100;
+/// Допустимое число выделений на 1000 строк вывода
+const ALLOCATIONS_PER_THOUSAND_LINES: usize = 5;
+
+fn graph() -> Graph<String, String> {
+    let mut graph = Graph::new(GraphType::Directed);
+    for id in 0..VERTEX_COUNT {
+        graph.add_vertex(Vertex::new(id, Some(format!("vertex {}", id % 17)))).unwrap();
+    }
+    for from_id in 0..VERTEX_COUNT {
+        for step in 0..EDGES_PER_VERTEX {
+            let to_id = (from_id + 1 + step * 7) % VERTEX_COUNT;
+            let value = (step % 3 != 0).then(|| format!("edge {}", step));
+            graph.add_edge(from_id, to_id, value).unwrap();
+        }
+    }
+    graph
+}
+
+/// Число выделений памяти при записи графа в заранее выделенный буфер и сам вывод
+fn count_allocations<F: Fn(&mut BufWriter<Vec<u8>>)>(serialize: F) -> (usize, String) {
+    let mut buf_writer = BufWriter::with_capacity(1 << 16, Vec::with_capacity(1 << 24));
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    serialize(&mut buf_writer);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    (allocations, String::from_utf8(buf_writer.into_inner().unwrap()).unwrap())
+}
+
+#[test]
+fn serialization_allocates_per_graph_not_per_line() {
+    let graph = graph();
+    let (allocations, tgf) = count_allocations(|buf_writer| graph.serialize(buf_writer).unwrap());
+    let lines = tgf.lines().count();
+    assert_eq!(lines, (VERTEX_COUNT + 1 + VERTEX_COUNT * EDGES_PER_VERTEX) as usize);
+    assert!(allocations * 1000 <= lines * ALLOCATIONS_PER_THOUSAND_LINES, "{} allocations for {} lines", allocations, lines);
+
+    for order in [VertexOrder::ById, VertexOrder::ByValueThenId, VertexOrder::ByDegreeDescending] {
+        let (allocations, ordered_tgf) = count_allocations(|buf_writer| graph.serialize_ordered(buf_writer, order).unwrap());
+        assert_eq!(ordered_tgf.len(), tgf.len());
+        assert!(allocations * 1000 <= lines * ALLOCATIONS_PER_THOUSAND_LINES, "{} allocations for {} lines in {:?}", allocations, lines, order);
+    }
+
+    let reparsed = Graph::deserialize(tgf.as_bytes()).unwrap();
+    let (_, reparsed_tgf) = count_allocations(|buf_writer| reparsed.serialize_ordered(buf_writer, VertexOrder::ById).unwrap());
+    let (_, ordered_tgf) = count_allocations(|buf_writer| graph.serialize_ordered(buf_writer, VertexOrder::ById).unwrap());
+    assert_eq!(reparsed_tgf, ordered_tgf);
+}